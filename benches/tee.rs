@@ -0,0 +1,58 @@
+use std::hint::black_box;
+
+use bytes::Bytes;
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use image::ImageFormat;
+use tee_morphosis::tee::{Tee, hsl::ddnet_color_to_hsl, parts::EyeType, skin::TEE_SKIN_LAYOUT};
+
+const SKIN: &[u8] = include_bytes!("../.ref/test_skin.png");
+
+fn get_tee() -> Tee {
+    Tee::new(Bytes::from_static(SKIN), ImageFormat::Png).expect("Failed to parse Tee")
+}
+
+fn parse(c: &mut Criterion) {
+    c.bench_function("parse", |b| {
+        b.iter(|| Tee::new(black_box(Bytes::from_static(SKIN)), ImageFormat::Png).unwrap())
+    });
+}
+
+fn hsl_transform(c: &mut Criterion) {
+    let tee = get_tee();
+    let hsl = ddnet_color_to_hsl(1900500);
+    c.bench_function("hsl_transform", |b| {
+        b.iter_batched(
+            || tee.clone(),
+            |mut tee| tee.apply_hsl_to_all(black_box(hsl)),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn compose(c: &mut Criterion) {
+    let tee = get_tee();
+    c.bench_function("compose", |b| {
+        b.iter(|| tee.compose_image(black_box(TEE_SKIN_LAYOUT), EyeType::Happy))
+    });
+}
+
+fn encode(c: &mut Criterion) {
+    let tee = get_tee();
+    let mut group = c.benchmark_group("compose_encode");
+    for format in [ImageFormat::Png, ImageFormat::WebP] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{format:?}")),
+            &format,
+            |b, format| {
+                b.iter(|| {
+                    tee.compose(TEE_SKIN_LAYOUT, EyeType::Happy, *format)
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, hsl_transform, compose, encode);
+criterion_main!(benches);
@@ -0,0 +1,76 @@
+//! # Animation module
+//!
+//! Turns a sequence of frames into an animated image, e.g. the tees parsed by
+//! [`Tee::new_animated`].
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use std::time::Duration;
+//! use tee_morphosis::{
+//!     animation,
+//!     tee::{Tee, parts::EyeType, skin::TEE_SKIN_LAYOUT, uv::TEE_UV_LAYOUT},
+//! };
+//!
+//! let tees = Tee::new_animated(sheet, TEE_UV_LAYOUT, 8)?;
+//! let frames = animation::compose_frames(&tees, TEE_SKIN_LAYOUT, EyeType::Normal);
+//! let gif = animation::encode_gif(&frames, Duration::from_millis(100))?;
+//! ```
+//!
+//! With the `ffmpeg` feature, [video] encodes the same frames into WebM or MP4.
+
+#[cfg(feature = "ffmpeg")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ffmpeg")))]
+pub mod video;
+
+use std::{io::Cursor, time::Duration};
+
+use bytes::Bytes;
+use image::{
+    Delay, Frame, RgbaImage,
+    codecs::gif::{GifEncoder, Repeat},
+};
+use tracing::{debug, error, instrument};
+
+use crate::{
+    error::{Result, TeeError},
+    tee::{Tee, parts::EyeSelection, skin::Skin},
+};
+
+/// Composes every tee with the same skin and eye type, see [`Tee::compose_image`].
+pub fn compose_frames<'a>(
+    tees: &[Tee],
+    skin: Skin,
+    eye_type: impl Into<EyeSelection<'a>>,
+) -> Vec<RgbaImage> {
+    let eye_type = eye_type.into();
+    tees.iter()
+        .map(|tee| tee.compose_image(skin, eye_type))
+        .collect()
+}
+
+/// Encodes frames into an infinitely looping GIF, showing each frame for `frame_delay`.
+#[instrument(level = "debug", skip(frames), fields(frames = frames.len()))]
+pub fn encode_gif(
+    frames: &[RgbaImage],
+    frame_delay: Duration,
+) -> Result<Bytes> {
+    if frames.is_empty() {
+        error!("Cannot encode an animation without frames.");
+        return Err(TeeError::EmptyAnimation);
+    }
+
+    let delay = Delay::from_saturating_duration(frame_delay);
+    let mut buf = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(Cursor::new(&mut buf));
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder.encode_frames(
+            frames
+                .iter()
+                .map(|frame| Frame::from_parts(frame.clone(), 0, 0, delay)),
+        )?;
+    }
+    debug!(size = buf.len(), "Encoded animation.");
+    Ok(Bytes::from(buf))
+}
@@ -0,0 +1,200 @@
+//! # Video module
+//!
+//! Encodes frame sequences into WebM or MP4 for platforms preferring video over GIF.
+//! Encoding is done by an `ffmpeg` executable, which must be installed separately.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::animation::{
+//!     compose_frames,
+//!     video::{VideoCodec, VideoOptions, encode_video},
+//! };
+//!
+//! let frames = compose_frames(&tees, TEE_SKIN_LAYOUT, EyeType::Normal);
+//! let options = VideoOptions::new(VideoCodec::WebM)
+//!     .with_frame_rate(30)
+//!     .with_bitrate(500);
+//! let webm = encode_video(&frames, &options)?;
+//! ```
+
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use bytes::Bytes;
+use image::RgbaImage;
+use tracing::{debug, error, instrument, trace};
+
+use crate::error::{Result, TeeError};
+
+/// Container and codec of an encoded video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VideoCodec {
+    /// VP9 in WebM, keeps transparency
+    WebM,
+    /// H.264 in fragmented MP4, transparent pixels become black
+    Mp4,
+}
+
+impl VideoCodec {
+    /// Returns the MIME type of the container.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            VideoCodec::WebM => "video/webm",
+            VideoCodec::Mp4 => "video/mp4",
+        }
+    }
+
+    /// Returns the file extension of the container.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            VideoCodec::WebM => "webm",
+            VideoCodec::Mp4 => "mp4",
+        }
+    }
+
+    fn args(&self) -> &'static [&'static str] {
+        match self {
+            VideoCodec::WebM => &["-c:v", "libvpx-vp9", "-pix_fmt", "yuva420p", "-f", "webm"],
+            // yuv420p needs even dimensions, and a seekable output is not available on a pipe
+            VideoCodec::Mp4 => &[
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+                "-c:v",
+                "libx264",
+                "-pix_fmt",
+                "yuv420p",
+                "-movflags",
+                "frag_keyframe+empty_moov",
+                "-f",
+                "mp4",
+            ],
+        }
+    }
+}
+
+/// Options of [encode_video].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VideoOptions {
+    /// Container and codec
+    pub codec: VideoCodec,
+    /// Frames per second
+    pub frame_rate: u32,
+    /// Target bitrate in kbit/s, `None` leaves it to the encoder
+    pub bitrate: Option<u32>,
+    /// The ffmpeg executable, looked up in `PATH` by default
+    pub ffmpeg: PathBuf,
+}
+
+impl VideoOptions {
+    /// Creates options for 25 frames per second with the default bitrate.
+    pub fn new(codec: VideoCodec) -> Self {
+        Self {
+            codec,
+            frame_rate: 25,
+            bitrate: None,
+            ffmpeg: PathBuf::from("ffmpeg"),
+        }
+    }
+
+    /// Sets the frames per second, at least one.
+    pub fn with_frame_rate(
+        mut self,
+        frame_rate: u32,
+    ) -> Self {
+        self.frame_rate = frame_rate.max(1);
+        self
+    }
+
+    /// Sets the target bitrate in kbit/s.
+    pub fn with_bitrate(
+        mut self,
+        kbits: u32,
+    ) -> Self {
+        self.bitrate = Some(kbits);
+        self
+    }
+
+    /// Sets the ffmpeg executable.
+    pub fn with_ffmpeg(
+        mut self,
+        path: impl Into<PathBuf>,
+    ) -> Self {
+        self.ffmpeg = path.into();
+        self
+    }
+}
+
+/// Encodes frames into a video by piping them through ffmpeg.
+///
+/// # Returns
+///
+/// A `Result` which is `Ok(Bytes)` with the encoded video, `Err(TeeError::EmptyAnimation)`
+/// without frames, `Err(TeeError::InvalidDimensions)` if the frames differ in size,
+/// `Err(TeeError::Process)` if ffmpeg could not be run and `Err(TeeError::Ffmpeg)` if it
+/// failed.
+#[instrument(level = "debug", skip(frames), fields(frames = frames.len()))]
+pub fn encode_video(
+    frames: &[RgbaImage],
+    options: &VideoOptions,
+) -> Result<Bytes> {
+    let Some(first) = frames.first() else {
+        error!("Cannot encode a video without frames.");
+        return Err(TeeError::EmptyAnimation);
+    };
+    let size = first.dimensions();
+    if let Some(frame) = frames.iter().find(|frame| frame.dimensions() != size) {
+        return Err(TeeError::InvalidDimensions {
+            expected: size,
+            found: frame.dimensions(),
+        });
+    }
+
+    let mut command = Command::new(&options.ffmpeg);
+    command
+        .args(["-hide_banner", "-loglevel", "error", "-f", "rawvideo"])
+        .args(["-pix_fmt", "rgba", "-s", &format!("{}x{}", size.0, size.1)])
+        .args(["-r", &options.frame_rate.to_string(), "-i", "pipe:0"])
+        .args(options.codec.args());
+    if let Some(kbits) = options.bitrate {
+        command.args(["-b:v", &format!("{kbits}k")]);
+    }
+    command
+        .arg("pipe:1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    trace!(?command, "Spawning ffmpeg");
+    let mut child = command.spawn().map_err(TeeError::Process)?;
+
+    // Feed the frames from another thread, ffmpeg blocks once its stdout pipe is full
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let output = std::thread::scope(|scope| {
+        let writer = scope.spawn(move || -> std::io::Result<()> {
+            for frame in frames {
+                stdin.write_all(frame.as_raw())?;
+            }
+            Ok(())
+        });
+        let output = child.wait_with_output();
+        // A broken pipe means ffmpeg exited early, its status tells why
+        let _ = writer.join();
+        output
+    })
+    .map_err(TeeError::Process)?;
+
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        error!(status = ?output.status, %message, "ffmpeg failed");
+        return Err(TeeError::Ffmpeg(if message.is_empty() {
+            output.status.to_string()
+        } else {
+            message
+        }));
+    }
+    debug!(size = output.stdout.len(), "Encoded video.");
+    Ok(Bytes::from(output.stdout))
+}
@@ -0,0 +1,75 @@
+//! # Assets module
+//!
+//! Splitters for the DDNet client asset sheets, built on [`Sheet`]:
+//! `game.png`, `emoticons.png`, `particles.png` and `extras.png`. The country flags are
+//! loaded by [countryflags] from their index or a sheet of flags.
+//!
+//! The sheets are laid out on a grid, so any resolution with square grid cells is
+//! accepted, e.g. both the regular 1024x512 `game.png` and its 2048x1024 HD version.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::assets::game::{GameSheet, Weapon};
+//!
+//! let game = GameSheet::parse(std::fs::read("game.png")?.into())?;
+//! let shotgun = game.weapon(Weapon::Shotgun).body;
+//! ```
+
+pub mod countryflags;
+pub mod emoticons;
+pub mod extras;
+pub mod game;
+pub mod particles;
+
+use bytes::Bytes;
+use image::GenericImageView;
+use tracing::error;
+
+use crate::{
+    error::{Result, TeeError},
+    sheet::{Sheet, SheetLayout},
+    tee::{options::ParseOptions, raw::decode_image, uv::UvPart},
+};
+
+/// A sprite on a grid sheet: name and `(x, y, w, h)` in grid cells.
+type GridSprite = (&'static str, u32, u32, u32, u32);
+
+/// Decodes a grid sheet and splits it into its sprites.
+///
+/// `default_size` is the size of the regular sheet, reported when the decoded
+/// sheet does not have square cells on a `grid` of `(columns, rows)`.
+fn parse_grid_sheet(
+    data: Bytes,
+    grid: (u32, u32),
+    default_size: (u32, u32),
+    sprites: &[GridSprite],
+) -> Result<Sheet> {
+    let image = decode_image(data, None, &ParseOptions::default())?;
+    let (width, height) = image.dimensions();
+
+    let cell = width / grid.0;
+    if cell == 0 || width % grid.0 != 0 || (height != cell * grid.1) {
+        error!(found = ?(width, height), grid = ?grid, "Sheet does not match its grid.");
+        return Err(TeeError::InvalidDimensions {
+            expected: default_size,
+            found: (width, height),
+        });
+    }
+
+    let layout = sprites.iter().fold(
+        SheetLayout::new((width, height)),
+        |layout, &(name, x, y, w, h)| {
+            layout.with_part(
+                name,
+                UvPart {
+                    x: x * cell,
+                    y: y * cell,
+                    w: w * cell,
+                    h: h * cell,
+                },
+            )
+        },
+    );
+    Sheet::from_image(image, layout)
+}
@@ -0,0 +1,232 @@
+//! # Module with the country flags
+//!
+//! DDNet ships its flags as `countryflags/<NAME>.png` next to an `index.txt` mapping
+//! every name to a country code, mostly ISO 3166-1 numeric codes. Players send that code,
+//! and `-1` stands for no country.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::assets::countryflags::CountryFlags;
+//!
+//! let index = std::fs::read_to_string("countryflags/index.txt")?;
+//! let flags = CountryFlags::from_index(&index, |name| {
+//!     std::fs::read(format!("countryflags/{name}.png")).ok().map(Into::into)
+//! })?;
+//! let germany = flags.get_or_default(276);
+//! ```
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use image::{GenericImageView, RgbaImage};
+use tracing::{debug, error, instrument, warn};
+
+use crate::{
+    error::{Result, TeeError},
+    tee::{options::ParseOptions, raw::decode_image},
+};
+
+/// Code of the flag shown for players without a country.
+pub const DEFAULT_COUNTRY: i16 = -1;
+
+/// A named flag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountryFlag {
+    /// Name of the flag, e.g. `"DE"`
+    pub name: String,
+    /// The flag sprite
+    pub image: RgbaImage,
+}
+
+/// Flags addressable by country code.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CountryFlags {
+    flags: HashMap<i16, CountryFlag>,
+}
+
+impl CountryFlags {
+    /// Creates an empty set of flags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every flag of a DDNet `index.txt`, reading the image of a name with `load`.
+    ///
+    /// Names `load` returns `None` for are skipped with a warning, as the client does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index is malformed or a flag can not be decoded.
+    #[instrument(level = "debug", skip_all)]
+    pub fn from_index(
+        index: &str,
+        mut load: impl FnMut(&str) -> Option<Bytes>,
+    ) -> Result<Self> {
+        let mut flags = Self::new();
+        for (name, code) in parse_index(index)? {
+            match load(&name) {
+                Some(data) => flags.insert(code, name, data)?,
+                None => warn!(name, code, "Flag image is missing, skipping it."),
+            }
+        }
+        debug!(flags = flags.len(), "Loaded country flags");
+        Ok(flags)
+    }
+
+    /// Splits a sheet of equally sized flags, `codes` lists the code of every cell row
+    /// by row on a grid of `(columns, rows)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sheet can not be decoded, does not divide into the grid or
+    /// has fewer cells than codes.
+    #[instrument(level = "debug", skip(data, codes), fields(data_size = data.len()))]
+    pub fn from_sheet(
+        data: Bytes,
+        grid: (u32, u32),
+        codes: &[(i16, &str)],
+    ) -> Result<Self> {
+        let image = decode_image(data, None, &ParseOptions::default())?;
+        let (width, height) = image.dimensions();
+        let (columns, rows) = grid;
+        let cell = (width / columns.max(1), height / rows.max(1));
+        if columns == 0
+            || rows == 0
+            || cell.0 == 0
+            || cell.1 == 0
+            || width % columns != 0
+            || height % rows != 0
+            || codes.len() > (columns * rows) as usize
+        {
+            error!(found = ?(width, height), ?grid, codes = codes.len(), "Flag sheet does not match its grid.");
+            return Err(TeeError::InvalidDimensions {
+                expected: (cell.0 * columns, cell.1 * rows),
+                found: (width, height),
+            });
+        }
+
+        let flags = codes
+            .iter()
+            .enumerate()
+            .map(|(index, &(code, name))| {
+                let (x, y) = (index as u32 % columns, index as u32 / columns);
+                let image = image
+                    .view(x * cell.0, y * cell.1, cell.0, cell.1)
+                    .to_image();
+                let flag = CountryFlag {
+                    name: name.to_string(),
+                    image,
+                };
+                (code, flag)
+            })
+            .collect();
+        Ok(Self { flags })
+    }
+
+    /// Decodes a flag and adds it under `code`, replacing a previous flag of the code.
+    pub fn insert(
+        &mut self,
+        code: i16,
+        name: impl Into<String>,
+        data: Bytes,
+    ) -> Result<()> {
+        let image = decode_image(data, None, &ParseOptions::default())?.to_rgba8();
+        self.flags.insert(
+            code,
+            CountryFlag {
+                name: name.into(),
+                image,
+            },
+        );
+        Ok(())
+    }
+
+    /// Returns the flag of `code`.
+    pub fn get(
+        &self,
+        code: i16,
+    ) -> Option<&CountryFlag> {
+        self.flags.get(&code)
+    }
+
+    /// Returns the flag of `code`, or the [DEFAULT_COUNTRY] flag for unknown codes like
+    /// the scoreboard does.
+    pub fn get_or_default(
+        &self,
+        code: i16,
+    ) -> Option<&CountryFlag> {
+        self.get(code).or_else(|| self.get(DEFAULT_COUNTRY))
+    }
+
+    /// Returns the code of the flag named `name`, ignoring case.
+    pub fn code_of(
+        &self,
+        name: &str,
+    ) -> Option<i16> {
+        self.flags
+            .iter()
+            .find(|(_, flag)| flag.name.eq_ignore_ascii_case(name))
+            .map(|(&code, _)| code)
+    }
+
+    /// Returns the number of flags.
+    pub fn len(&self) -> usize {
+        self.flags.len()
+    }
+
+    /// Returns `true` if there are no flags.
+    pub fn is_empty(&self) -> bool {
+        self.flags.is_empty()
+    }
+}
+
+/// Parses a DDNet `countryflags/index.txt` into names and codes, in file order.
+///
+/// Every flag is a name line followed by a `== <code>` line, lines starting with `#` and
+/// empty lines are ignored.
+///
+/// # Errors
+///
+/// Returns [TeeError::MalformedFlagIndex] for a code without name, a name without code
+/// or a code that is not a number.
+pub fn parse_index(index: &str) -> Result<Vec<(String, i16)>> {
+    let mut flags = Vec::new();
+    let mut name: Option<(usize, &str)> = None;
+    for (number, line) in index
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+    {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let malformed = |reason| TeeError::MalformedFlagIndex {
+            line: number,
+            reason,
+        };
+        match line.strip_prefix("==") {
+            Some(code) => {
+                let (_, flag_name) = name.take().ok_or(malformed("code without name"))?;
+                let code = code.trim().parse().map_err(|_| malformed("invalid code"))?;
+                flags.push((flag_name.to_string(), code));
+            }
+            None => {
+                if let Some((line, _)) = name {
+                    return Err(TeeError::MalformedFlagIndex {
+                        line,
+                        reason: "name without code",
+                    });
+                }
+                name = Some((number, line));
+            }
+        }
+    }
+    match name {
+        Some((line, _)) => Err(TeeError::MalformedFlagIndex {
+            line,
+            reason: "name without code",
+        }),
+        None => Ok(flags),
+    }
+}
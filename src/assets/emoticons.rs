@@ -0,0 +1,129 @@
+//! # Module with the emoticons.png sheet
+//!
+//! A 4x4 grid of emoticons, ordered by their network ids.
+
+use bytes::Bytes;
+use image::RgbaImage;
+use tracing::instrument;
+
+use crate::{
+    assets::{GridSprite, parse_grid_sheet},
+    error::Result,
+};
+
+/// Columns and rows of the emoticons.png grid.
+pub const EMOTICONS_GRID: (u32, u32) = (4, 4);
+
+/// Size of the regular emoticons.png.
+pub const EMOTICONS_SIZE: (u32, u32) = (512, 512);
+
+/// Emoticons, the discriminant is the id sent over the network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum Emoticon {
+    Oop = 0,
+    Exclamation = 1,
+    Hearts = 2,
+    Drop = 3,
+    DotDot = 4,
+    Music = 5,
+    Sorry = 6,
+    Ghost = 7,
+    Sushi = 8,
+    SplatTee = 9,
+    DevilTee = 10,
+    Zomg = 11,
+    Zzz = 12,
+    Wtf = 13,
+    Eyes = 14,
+    Question = 15,
+}
+
+impl Emoticon {
+    /// Every emoticon, ordered by network id.
+    pub const ALL: [Emoticon; 16] = [
+        Emoticon::Oop,
+        Emoticon::Exclamation,
+        Emoticon::Hearts,
+        Emoticon::Drop,
+        Emoticon::DotDot,
+        Emoticon::Music,
+        Emoticon::Sorry,
+        Emoticon::Ghost,
+        Emoticon::Sushi,
+        Emoticon::SplatTee,
+        Emoticon::DevilTee,
+        Emoticon::Zomg,
+        Emoticon::Zzz,
+        Emoticon::Wtf,
+        Emoticon::Eyes,
+        Emoticon::Question,
+    ];
+
+    /// Returns the emoticon with the given network id.
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(id as usize).copied()
+    }
+
+    /// Returns the network id.
+    pub const fn id(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Name of the emoticon sprite.
+    pub const fn name(&self) -> &'static str {
+        EMOTICON_SPRITES[*self as usize].0
+    }
+}
+
+/// Every sprite of emoticons.png, in grid cells.
+pub const EMOTICON_SPRITES: &[GridSprite] = &[
+    ("oop", 0, 0, 1, 1),
+    ("exclamation", 1, 0, 1, 1),
+    ("hearts", 2, 0, 1, 1),
+    ("drop", 3, 0, 1, 1),
+    ("dotdot", 0, 1, 1, 1),
+    ("music", 1, 1, 1, 1),
+    ("sorry", 2, 1, 1, 1),
+    ("ghost", 3, 1, 1, 1),
+    ("sushi", 0, 2, 1, 1),
+    ("splattee", 1, 2, 1, 1),
+    ("deviltee", 2, 2, 1, 1),
+    ("zomg", 3, 2, 1, 1),
+    ("zzz", 0, 3, 1, 1),
+    ("wtf", 1, 3, 1, 1),
+    ("eyes", 2, 3, 1, 1),
+    ("question", 3, 3, 1, 1),
+];
+
+/// The split emoticons.png sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmoticonSheet {
+    sprites: Vec<RgbaImage>,
+}
+
+impl EmoticonSheet {
+    /// Decodes and splits emoticons.png, guessing its format from the data.
+    #[instrument(level = "debug", skip(data), fields(data_size = data.len()))]
+    pub fn parse(data: Bytes) -> Result<Self> {
+        let sheet = parse_grid_sheet(data, EMOTICONS_GRID, EMOTICONS_SIZE, EMOTICON_SPRITES)?;
+        let sprites = Emoticon::ALL
+            .iter()
+            .map(|emoticon| sheet.extract(emoticon.name()))
+            .collect::<Result<_>>()?;
+        Ok(Self { sprites })
+    }
+
+    /// Returns the sprite of an emoticon.
+    pub fn get(
+        &self,
+        emoticon: Emoticon,
+    ) -> &RgbaImage {
+        &self.sprites[emoticon as usize]
+    }
+
+    /// Returns every emoticon with its sprite, ordered by network id.
+    pub fn iter(&self) -> impl Iterator<Item = (Emoticon, &RgbaImage)> {
+        Emoticon::ALL.into_iter().zip(&self.sprites)
+    }
+}
@@ -0,0 +1,83 @@
+//! # Module with the extras.png sheet
+//!
+//! Sprite positions follow the `extras` sprite set of DDNet's `datasrc/content.py`,
+//! a grid of 16x16 cells.
+
+use bytes::Bytes;
+use image::RgbaImage;
+use tracing::instrument;
+
+use crate::{
+    assets::{GridSprite, parse_grid_sheet},
+    error::Result,
+};
+
+/// Columns and rows of the extras.png grid.
+pub const EXTRAS_GRID: (u32, u32) = (16, 16);
+
+/// Size of the regular extras.png.
+pub const EXTRAS_SIZE: (u32, u32) = (512, 512);
+
+/// Sprites of extras.png.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Extra {
+    Snowflake,
+    Sparkle,
+    Pulley,
+    Hectagon,
+}
+
+impl Extra {
+    /// Every extra, in the order of [EXTRA_SPRITES].
+    pub const ALL: [Extra; 4] = [
+        Extra::Snowflake,
+        Extra::Sparkle,
+        Extra::Pulley,
+        Extra::Hectagon,
+    ];
+
+    /// Name of the extra sprite.
+    pub const fn name(&self) -> &'static str {
+        EXTRA_SPRITES[*self as usize].0
+    }
+}
+
+/// Every sprite of extras.png, in grid cells.
+pub const EXTRA_SPRITES: &[GridSprite] = &[
+    ("part_snowflake", 0, 0, 2, 2),
+    ("part_sparkle", 2, 0, 2, 2),
+    ("part_pulley", 4, 0, 2, 2),
+    ("part_hectagon", 6, 0, 2, 2),
+];
+
+/// The split extras.png sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtrasSheet {
+    sprites: Vec<RgbaImage>,
+}
+
+impl ExtrasSheet {
+    /// Decodes and splits extras.png, guessing its format from the data.
+    #[instrument(level = "debug", skip(data), fields(data_size = data.len()))]
+    pub fn parse(data: Bytes) -> Result<Self> {
+        let sheet = parse_grid_sheet(data, EXTRAS_GRID, EXTRAS_SIZE, EXTRA_SPRITES)?;
+        let sprites = Extra::ALL
+            .iter()
+            .map(|extra| sheet.extract(extra.name()))
+            .collect::<Result<_>>()?;
+        Ok(Self { sprites })
+    }
+
+    /// Returns the sprite of an extra.
+    pub fn get(
+        &self,
+        extra: Extra,
+    ) -> &RgbaImage {
+        &self.sprites[extra as usize]
+    }
+
+    /// Returns every extra with its sprite.
+    pub fn iter(&self) -> impl Iterator<Item = (Extra, &RgbaImage)> {
+        Extra::ALL.into_iter().zip(&self.sprites)
+    }
+}
@@ -0,0 +1,238 @@
+//! # Module with the game.png sheet
+//!
+//! Sprite positions follow the `game` sprite set of DDNet's `datasrc/content.py`,
+//! a grid of 32x16 cells.
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use image::RgbaImage;
+use tracing::instrument;
+
+use crate::{
+    assets::{GridSprite, parse_grid_sheet},
+    error::Result,
+};
+
+/// Columns and rows of the game.png grid.
+pub const GAME_GRID: (u32, u32) = (32, 16);
+
+/// Size of the regular game.png.
+pub const GAME_SIZE: (u32, u32) = (1024, 512);
+
+/// Every sprite of game.png, in grid cells.
+pub const GAME_SPRITES: &[GridSprite] = &[
+    ("hook_chain", 2, 0, 1, 1),
+    ("hook_head", 3, 0, 2, 1),
+    ("weapon_hammer_cursor", 0, 0, 2, 2),
+    ("weapon_hammer_body", 2, 1, 4, 3),
+    ("weapon_gun_cursor", 0, 4, 2, 2),
+    ("weapon_gun_body", 2, 4, 4, 2),
+    ("weapon_gun_proj", 6, 4, 2, 2),
+    ("weapon_gun_muzzle1", 8, 4, 3, 2),
+    ("weapon_gun_muzzle2", 12, 4, 3, 2),
+    ("weapon_gun_muzzle3", 16, 4, 3, 2),
+    ("weapon_shotgun_cursor", 0, 6, 2, 2),
+    ("weapon_shotgun_body", 2, 6, 8, 2),
+    ("weapon_shotgun_proj", 10, 6, 2, 2),
+    ("weapon_shotgun_muzzle1", 12, 6, 3, 2),
+    ("weapon_shotgun_muzzle2", 16, 6, 3, 2),
+    ("weapon_shotgun_muzzle3", 20, 6, 3, 2),
+    ("weapon_grenade_cursor", 0, 8, 2, 2),
+    ("weapon_grenade_body", 2, 8, 7, 2),
+    ("weapon_grenade_proj", 10, 8, 2, 2),
+    ("weapon_ninja_cursor", 0, 10, 2, 2),
+    ("weapon_ninja_body", 2, 10, 8, 2),
+    ("weapon_ninja_proj", 10, 10, 2, 2),
+    ("weapon_ninja_muzzle1", 25, 0, 7, 4),
+    ("weapon_ninja_muzzle2", 25, 4, 7, 4),
+    ("weapon_ninja_muzzle3", 25, 8, 7, 4),
+    ("weapon_laser_cursor", 0, 12, 2, 2),
+    ("weapon_laser_body", 2, 12, 7, 3),
+    ("weapon_laser_proj", 10, 12, 2, 2),
+    ("pickup_health", 10, 2, 2, 2),
+    ("pickup_armor", 12, 2, 2, 2),
+    ("flag_blue", 12, 8, 4, 8),
+    ("flag_red", 16, 8, 4, 8),
+    ("health_full", 21, 0, 2, 2),
+    ("health_empty", 23, 0, 2, 2),
+    ("armor_full", 21, 2, 2, 2),
+    ("armor_empty", 23, 2, 2, 2),
+    ("star1", 15, 0, 2, 2),
+    ("star2", 17, 0, 2, 2),
+    ("star3", 19, 0, 2, 2),
+];
+
+/// Weapons of game.png.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Weapon {
+    Hammer,
+    Gun,
+    Shotgun,
+    Grenade,
+    Ninja,
+    Laser,
+}
+
+impl Weapon {
+    /// Every weapon, in the order of the in-game weapon slots.
+    pub const ALL: [Weapon; 6] = [
+        Weapon::Hammer,
+        Weapon::Gun,
+        Weapon::Shotgun,
+        Weapon::Grenade,
+        Weapon::Laser,
+        Weapon::Ninja,
+    ];
+
+    /// Name of the weapon in the sprite names.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Weapon::Hammer => "hammer",
+            Weapon::Gun => "gun",
+            Weapon::Shotgun => "shotgun",
+            Weapon::Grenade => "grenade",
+            Weapon::Ninja => "ninja",
+            Weapon::Laser => "laser",
+        }
+    }
+}
+
+/// Pickups lying around on maps.
+///
+/// Weapon pickups use the weapon body, see [GameSheet::pickup].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pickup {
+    Health,
+    Armor,
+    Weapon(Weapon),
+}
+
+/// Flags of capture the flag modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Flag {
+    Red,
+    Blue,
+}
+
+/// Sprites of a single weapon.
+#[derive(Debug, Clone)]
+pub struct WeaponSprites<'a> {
+    /// The weapon as it is held
+    pub body: &'a RgbaImage,
+    /// The crosshair shown while the weapon is selected
+    pub cursor: &'a RgbaImage,
+    /// The projectile, the hammer has none
+    pub projectile: Option<&'a RgbaImage>,
+    /// Muzzle flash frames, empty for weapons without a flash
+    pub muzzles: Vec<&'a RgbaImage>,
+}
+
+/// The split game.png sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameSheet {
+    sprites: HashMap<String, RgbaImage>,
+}
+
+impl GameSheet {
+    /// Decodes and splits game.png, guessing its format from the data.
+    #[instrument(level = "debug", skip(data), fields(data_size = data.len()))]
+    pub fn parse(data: Bytes) -> Result<Self> {
+        let sheet = parse_grid_sheet(data, GAME_GRID, GAME_SIZE, GAME_SPRITES)?;
+        Ok(Self {
+            sprites: sheet.extract_all()?,
+        })
+    }
+
+    /// Returns a sprite by its name from [GAME_SPRITES].
+    pub fn sprite(
+        &self,
+        name: &str,
+    ) -> Option<&RgbaImage> {
+        self.sprites.get(name)
+    }
+
+    /// Returns every sprite by its name.
+    pub fn sprites(&self) -> &HashMap<String, RgbaImage> {
+        &self.sprites
+    }
+
+    /// Returns the sprites of a weapon.
+    pub fn weapon(
+        &self,
+        weapon: Weapon,
+    ) -> WeaponSprites<'_> {
+        let name = weapon.name();
+        WeaponSprites {
+            body: &self.sprites[&format!("weapon_{name}_body")],
+            cursor: &self.sprites[&format!("weapon_{name}_cursor")],
+            projectile: self.sprite(&format!("weapon_{name}_proj")),
+            muzzles: (1..=3)
+                .filter_map(|frame| self.sprite(&format!("weapon_{name}_muzzle{frame}")))
+                .collect(),
+        }
+    }
+
+    /// Returns the sprite of a pickup.
+    pub fn pickup(
+        &self,
+        pickup: Pickup,
+    ) -> &RgbaImage {
+        match pickup {
+            Pickup::Health => &self.sprites["pickup_health"],
+            Pickup::Armor => &self.sprites["pickup_armor"],
+            Pickup::Weapon(weapon) => self.weapon(weapon).body,
+        }
+    }
+
+    /// Returns the link of the hook chain, tiled along the rope.
+    pub fn hook_chain(&self) -> &RgbaImage {
+        &self.sprites["hook_chain"]
+    }
+
+    /// Returns the head of the hook.
+    pub fn hook_head(&self) -> &RgbaImage {
+        &self.sprites["hook_head"]
+    }
+
+    /// Returns a flag.
+    pub fn flag(
+        &self,
+        flag: Flag,
+    ) -> &RgbaImage {
+        match flag {
+            Flag::Red => &self.sprites["flag_red"],
+            Flag::Blue => &self.sprites["flag_blue"],
+        }
+    }
+
+    /// Returns one of the three stars shown on freeze, `index` is wrapped into `0..3`.
+    pub fn star(
+        &self,
+        index: usize,
+    ) -> &RgbaImage {
+        &self.sprites[&format!("star{}", index % 3 + 1)]
+    }
+
+    /// Returns the full or empty health indicator of the HUD.
+    pub fn health(
+        &self,
+        full: bool,
+    ) -> &RgbaImage {
+        match full {
+            true => &self.sprites["health_full"],
+            false => &self.sprites["health_empty"],
+        }
+    }
+
+    /// Returns the full or empty armor indicator of the HUD.
+    pub fn armor(
+        &self,
+        full: bool,
+    ) -> &RgbaImage {
+        match full {
+            true => &self.sprites["armor_full"],
+            false => &self.sprites["armor_empty"],
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! # Module with the particles.png sheet
+//!
+//! Sprite positions follow the `particles` sprite set of DDNet's
+//! `datasrc/content.py`, a grid of 8x8 cells.
+
+use bytes::Bytes;
+use image::RgbaImage;
+use tracing::instrument;
+
+use crate::{
+    assets::{GridSprite, parse_grid_sheet},
+    error::Result,
+};
+
+/// Columns and rows of the particles.png grid.
+pub const PARTICLES_GRID: (u32, u32) = (8, 8);
+
+/// Size of the regular particles.png.
+pub const PARTICLES_SIZE: (u32, u32) = (256, 256);
+
+/// Particles of particles.png.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Particle {
+    Slice,
+    Ball,
+    Splat01,
+    Splat02,
+    Splat03,
+    Smoke,
+    Shell,
+    Explosion,
+    AirJump,
+    Hit,
+}
+
+impl Particle {
+    /// Every particle, in the order of [PARTICLE_SPRITES].
+    pub const ALL: [Particle; 10] = [
+        Particle::Slice,
+        Particle::Ball,
+        Particle::Splat01,
+        Particle::Splat02,
+        Particle::Splat03,
+        Particle::Smoke,
+        Particle::Shell,
+        Particle::Explosion,
+        Particle::AirJump,
+        Particle::Hit,
+    ];
+
+    /// Name of the particle sprite.
+    pub const fn name(&self) -> &'static str {
+        PARTICLE_SPRITES[*self as usize].0
+    }
+}
+
+/// Every sprite of particles.png, in grid cells.
+pub const PARTICLE_SPRITES: &[GridSprite] = &[
+    ("part_slice", 0, 0, 1, 1),
+    ("part_ball", 1, 0, 1, 1),
+    ("part_splat01", 2, 0, 1, 1),
+    ("part_splat02", 3, 0, 1, 1),
+    ("part_splat03", 4, 0, 1, 1),
+    ("part_smoke", 0, 1, 1, 1),
+    ("part_shell", 0, 2, 2, 2),
+    ("part_expl01", 0, 4, 4, 4),
+    ("part_airjump", 2, 2, 2, 2),
+    ("part_hit01", 4, 1, 2, 2),
+];
+
+/// The split particles.png sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticleSheet {
+    sprites: Vec<RgbaImage>,
+}
+
+impl ParticleSheet {
+    /// Decodes and splits particles.png, guessing its format from the data.
+    #[instrument(level = "debug", skip(data), fields(data_size = data.len()))]
+    pub fn parse(data: Bytes) -> Result<Self> {
+        let sheet = parse_grid_sheet(data, PARTICLES_GRID, PARTICLES_SIZE, PARTICLE_SPRITES)?;
+        let sprites = Particle::ALL
+            .iter()
+            .map(|particle| sheet.extract(particle.name()))
+            .collect::<Result<_>>()?;
+        Ok(Self { sprites })
+    }
+
+    /// Returns the sprite of a particle.
+    pub fn get(
+        &self,
+        particle: Particle,
+    ) -> &RgbaImage {
+        &self.sprites[particle as usize]
+    }
+
+    /// Returns every particle with its sprite.
+    pub fn iter(&self) -> impl Iterator<Item = (Particle, &RgbaImage)> {
+        Particle::ALL.into_iter().zip(&self.sprites)
+    }
+}
@@ -0,0 +1,350 @@
+//! # Cache module
+//!
+//! [`DedupStore`] keeps parsed tees keyed by the hash of their source bytes, so the
+//! same skin hosted at many URLs is parsed and stored only once. [`RenderCache`] keeps
+//! encoded renders, so identical render requests skip composing entirely.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::cache::DedupStore;
+//! use image::ImageFormat;
+//!
+//! let store = DedupStore::new();
+//! let a = store.get_or_parse("https://a.example/default.png", bytes.clone(), ImageFormat::Png)?;
+//! let b = store.get_or_parse("https://b.example/default.png", bytes, ImageFormat::Png)?;
+//! assert!(std::sync::Arc::ptr_eq(&a, &b));
+//! assert_eq!(store.len(), 1);
+//!
+//! let renders = RenderCache::new(64 * 1024 * 1024, Duration::from_secs(600));
+//! let png = renders.get_or_render(&a, TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png, &options)?;
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use image::ImageFormat;
+use tracing::{debug, instrument, trace};
+
+use crate::{
+    error::Result,
+    tee::{Tee, hash::SourceHash, options::ComposeOptions, parts::EyeSelection, skin::Skin},
+};
+
+#[derive(Debug, Default)]
+struct StoreState {
+    keys: HashMap<String, SourceHash>,
+    tees: HashMap<SourceHash, (Arc<Tee>, usize)>,
+}
+
+/// Content-addressed store of parsed tees.
+///
+/// Keys (usually URLs) point to source hashes, and every hash holds a single parsed
+/// [`Tee`]. A tee is dropped once no key points to it anymore. Clones share the
+/// same storage.
+#[derive(Debug, Clone, Default)]
+pub struct DedupStore {
+    state: Arc<Mutex<StoreState>>,
+}
+
+impl DedupStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the tee stored under `key`.
+    pub fn get(
+        &self,
+        key: &str,
+    ) -> Option<Arc<Tee>> {
+        let state = self.lock();
+        let hash = state.keys.get(key)?;
+        state.tees.get(hash).map(|(tee, _)| tee.clone())
+    }
+
+    /// Returns the tee parsed from bytes with the given hash.
+    pub fn get_by_hash(
+        &self,
+        hash: &SourceHash,
+    ) -> Option<Arc<Tee>> {
+        self.lock().tees.get(hash).map(|(tee, _)| tee.clone())
+    }
+
+    /// Stores a tee under `key`.
+    ///
+    /// If a tee with the same [`Tee::source_hash`] is already stored, the stored one is
+    /// kept and returned, and `tee` is dropped.
+    #[instrument(level = "debug", skip(self, key, tee), fields(hash = %tee.source_hash()))]
+    pub fn insert(
+        &self,
+        key: impl Into<String>,
+        tee: Tee,
+    ) -> Arc<Tee> {
+        let mut state = self.lock();
+        let hash = tee.source_hash();
+        let key = key.into();
+
+        if let Some(stored) = link(&mut state, &key, hash) {
+            trace!("Tee with the same source hash is already stored");
+            return stored;
+        }
+        if let Some(previous) = state.keys.insert(key, hash) {
+            release(&mut state, previous);
+        }
+        let tee = Arc::new(tee);
+        state.tees.insert(hash, (tee.clone(), 1));
+        tee
+    }
+
+    /// Returns the tee for `data`, parsing it only if no tee with the same source
+    /// hash is stored yet, and stores it under `key`.
+    #[instrument(level = "debug", skip(self, data), fields(key = %key.as_ref()))]
+    pub fn get_or_parse(
+        &self,
+        key: impl AsRef<str>,
+        data: Bytes,
+        format: ImageFormat,
+    ) -> Result<Arc<Tee>> {
+        let key = key.as_ref();
+        let hash = SourceHash::of(&data);
+
+        if let Some(stored) = link(&mut self.lock(), key, hash) {
+            debug!("Reusing stored tee with the same source hash");
+            return Ok(stored);
+        }
+
+        let tee = Tee::new(data, format)?;
+        Ok(self.insert(key, tee))
+    }
+
+    /// Removes `key`, dropping its tee if no other key points to it.
+    pub fn remove(
+        &self,
+        key: &str,
+    ) -> bool {
+        let mut state = self.lock();
+        match state.keys.remove(key) {
+            Some(hash) => {
+                release(&mut state, hash);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the amount of distinct tees in the store.
+    pub fn len(&self) -> usize {
+        self.lock().tees.len()
+    }
+
+    /// Returns `true` if the store holds no tees.
+    pub fn is_empty(&self) -> bool {
+        self.lock().tees.is_empty()
+    }
+
+    /// Returns the amount of keys pointing into the store.
+    pub fn keys_len(&self) -> usize {
+        self.lock().keys.len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StoreState> {
+        // The state stays consistent even if a holder panicked
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Points `key` to an already stored tee with `hash`, if there is one.
+fn link(
+    state: &mut StoreState,
+    key: &str,
+    hash: SourceHash,
+) -> Option<Arc<Tee>> {
+    let tee = state.tees.get(&hash)?.0.clone();
+    let previous = state.keys.insert(key.to_string(), hash);
+    if previous != Some(hash) {
+        if let Some((_, references)) = state.tees.get_mut(&hash) {
+            *references += 1;
+        }
+        if let Some(previous) = previous {
+            release(state, previous);
+        }
+    }
+    Some(tee)
+}
+
+/// Drops one reference to `hash`, removing the tee once it is unused.
+fn release(
+    state: &mut StoreState,
+    hash: SourceHash,
+) {
+    if let Some((_, references)) = state.tees.get_mut(&hash) {
+        *references -= 1;
+        if *references == 0 {
+            state.tees.remove(&hash);
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RenderState {
+    /// Render, when it was stored and its tick in `used`
+    entries: HashMap<String, (Bytes, Instant, u64)>,
+    /// Keys by last use, least recent first
+    used: BTreeMap<u64, String>,
+    tick: u64,
+    bytes: usize,
+}
+
+impl RenderState {
+    fn remove(
+        &mut self,
+        key: &str,
+    ) -> Option<Bytes> {
+        let (data, _, tick) = self.entries.remove(key)?;
+        self.used.remove(&tick);
+        self.bytes -= data.len();
+        Some(data)
+    }
+}
+
+/// Cache of encoded renders, keyed by [`Tee::compose_etag`].
+///
+/// The key covers the parts of the tee, the skin layout, the eye, the format and the
+/// compose options, so a recolored tee or changed options never hit a stale render.
+/// Renders expire after `ttl`, and the least recently used ones are evicted once all
+/// renders together exceed `max_bytes`. Clones share the same storage.
+#[derive(Debug, Clone)]
+pub struct RenderCache {
+    state: Arc<Mutex<RenderState>>,
+    max_bytes: usize,
+    ttl: Duration,
+}
+
+impl RenderCache {
+    /// Creates an empty cache holding up to `max_bytes` of renders for `ttl` each.
+    pub fn new(
+        max_bytes: usize,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            state: Arc::default(),
+            max_bytes,
+            ttl,
+        }
+    }
+
+    /// Returns the render stored under `key`, unless it expired.
+    pub fn get(
+        &self,
+        key: &str,
+    ) -> Option<Bytes> {
+        let mut state = self.lock();
+        let state = &mut *state;
+        let (data, stored, tick) = state.entries.get_mut(key)?;
+        if stored.elapsed() >= self.ttl {
+            trace!(key, "Cached render expired");
+            state.remove(key);
+            return None;
+        }
+        state.tick += 1;
+        state.used.remove(tick);
+        *tick = state.tick;
+        let data = data.clone();
+        state.used.insert(state.tick, key.to_string());
+        Some(data)
+    }
+
+    /// Stores a render under `key`, evicting the least recently used renders if the
+    /// cache grows too large.
+    ///
+    /// Renders larger than the whole cache are not stored.
+    pub fn insert(
+        &self,
+        key: impl Into<String>,
+        data: Bytes,
+    ) {
+        let key = key.into();
+        let mut state = self.lock();
+        state.remove(&key);
+        if data.len() > self.max_bytes {
+            debug!(size = data.len(), "Render is larger than the cache");
+            return;
+        }
+        while state.bytes + data.len() > self.max_bytes {
+            let Some((_, oldest)) = state.used.pop_first() else {
+                break;
+            };
+            trace!(key = oldest, "Evicting cached render");
+            let (evicted, _, _) = state.entries.remove(&oldest).expect("used keys are stored");
+            state.bytes -= evicted.len();
+        }
+        state.tick += 1;
+        let tick = state.tick;
+        state.bytes += data.len();
+        state.used.insert(tick, key.clone());
+        state.entries.insert(key, (data, Instant::now(), tick));
+    }
+
+    /// Returns the cached render of `tee`, composing and storing it with
+    /// [`Tee::compose_with_options`] on a miss.
+    #[instrument(level = "debug", skip(self, tee, skin, eye_type, options), fields(img_format = ?img_format))]
+    pub fn get_or_render<'a>(
+        &self,
+        tee: &Tee,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        img_format: ImageFormat,
+        options: &ComposeOptions,
+    ) -> Result<Bytes> {
+        let eye_type = eye_type.into();
+        let key = tee.compose_etag(skin, eye_type, img_format, options);
+        if let Some(data) = self.get(&key) {
+            trace!("Reusing cached render");
+            return Ok(data);
+        }
+        let data = tee.compose_with_options(skin, eye_type, img_format, options)?;
+        self.insert(key, data.clone());
+        Ok(data)
+    }
+
+    /// Removes the render stored under `key`.
+    pub fn remove(
+        &self,
+        key: &str,
+    ) -> bool {
+        self.lock().remove(key).is_some()
+    }
+
+    /// Removes all renders.
+    pub fn clear(&self) {
+        *self.lock() = RenderState::default();
+    }
+
+    /// Returns the amount of stored renders, including expired ones not evicted yet.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns `true` if no renders are stored.
+    pub fn is_empty(&self) -> bool {
+        self.lock().entries.is_empty()
+    }
+
+    /// Returns the size of all stored renders in bytes.
+    pub fn bytes(&self) -> usize {
+        self.lock().bytes
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RenderState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,241 @@
+//! # Cards module
+//!
+//! Preset cards for bots, such as rank announcements and player profiles, drawn from a [CardTemplate] and
+//! a struct of the values to show.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::cards::{CardTemplate, RankCard, RankParams};
+//! use tee_morphosis::scene::text::{FontArc, TextStyle};
+//!
+//! let font = FontArc::try_from_vec(std::fs::read("font.ttf")?)?;
+//! let card = RankCard::new(CardTemplate::new(font));
+//! let params = RankParams::new(&tee, "nameless tee", "Kobra 4", 1)
+//!     .with_time(Duration::from_millis(83_450));
+//! std::fs::write("rank.png", card.encode(&params, ImageFormat::Png)?)?;
+//! ```
+
+#[cfg(feature = "templates")]
+#[cfg_attr(docsrs, doc(cfg(feature = "templates")))]
+pub mod layout;
+pub mod profile;
+pub mod rank;
+
+pub use profile::{ProfileCard, ProfileParams};
+pub use rank::{RankCard, RankParams};
+
+/// Space between two lines of text.
+const LINE_SPACING: u32 = 4;
+
+use image::{Rgba, RgbaImage, imageops};
+
+use crate::{
+    scene::{
+        Scene,
+        text::{FontArc, TextStyle, render_text, text_width},
+    },
+    tee::{
+        Tee,
+        parts::EyeSelection,
+        skin::{Size, Skin, TEE_SKIN_LAYOUT},
+    },
+};
+
+/// Fill behind the content of a card.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CardBackground {
+    /// A single color
+    Color(Rgba<u8>),
+    /// A vertical gradient from the top color to the bottom color
+    Gradient(Rgba<u8>, Rgba<u8>),
+    /// An image, scaled and cropped to cover the card
+    Image(RgbaImage),
+}
+
+/// Size, fonts and colors shared by every card.
+#[derive(Debug, Clone)]
+pub struct CardTemplate {
+    /// Size of the card
+    pub size: Size,
+    /// Fill of the card
+    pub background: CardBackground,
+    /// Radius of the corners, outside of them the card is transparent
+    pub radius: u32,
+    /// Space between the edges and the content
+    pub padding: u32,
+    /// Style of the headline
+    pub title: TextStyle,
+    /// Style of every other line
+    pub body: TextStyle,
+    /// Layout the tee is composed with
+    pub skin: Skin,
+}
+
+impl CardTemplate {
+    /// Creates a dark 480x140 template with white text in `font`.
+    pub fn new(font: FontArc) -> Self {
+        Self {
+            size: (480, 140),
+            background: CardBackground::Gradient(Rgba([44, 48, 66, 255]), Rgba([22, 24, 34, 255])),
+            radius: 12,
+            padding: 12,
+            title: TextStyle::new(font.clone(), 20.),
+            body: TextStyle::new(font, 16.).with_color(Rgba([200, 205, 220, 255])),
+            skin: TEE_SKIN_LAYOUT,
+        }
+    }
+
+    /// Sets the size of the card.
+    pub fn with_size(
+        mut self,
+        size: Size,
+    ) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the fill of the card.
+    pub fn with_background(
+        mut self,
+        background: CardBackground,
+    ) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Creates a scene of the card size holding the background, with rounded corners.
+    pub fn scene(&self) -> Scene {
+        background_scene(self.size, &self.background, self.radius)
+    }
+
+    /// Returns the size a tee is scaled to, as high as the card without padding.
+    pub(crate) fn tee_size(&self) -> Size {
+        let height = self.size.1.saturating_sub(2 * self.padding).max(1);
+        let (w, h) = self.skin.output_size();
+        ((w * height / h.max(1)).max(1), height)
+    }
+
+    /// Composes `tee` scaled to [CardTemplate::tee_size] at the left of the card, returns
+    /// where the content right of it starts.
+    pub(crate) fn place_tee(
+        &self,
+        scene: &mut Scene,
+        tee: &Tee,
+        eye: EyeSelection,
+    ) -> u32 {
+        let size = self.tee_size();
+        let mut image = tee.compose_image(self.skin, eye);
+        if image.dimensions() != size {
+            image = imageops::resize(&image, size.0, size.1, imageops::FilterType::Triangle);
+        }
+        scene.add_image(image, (self.padding as i64, self.padding as i64));
+        2 * self.padding + size.0
+    }
+
+    /// Draws `lines` top down from `top` on, each shortened to `max_width`.
+    ///
+    /// Returns the width and top of every drawn line.
+    pub(crate) fn place_lines(
+        &self,
+        scene: &mut Scene,
+        lines: &[(String, &TextStyle)],
+        (left, top): (u32, u32),
+        max_width: u32,
+    ) -> Vec<(u32, u32)> {
+        let mut y = top;
+        let mut placed = Vec::with_capacity(lines.len());
+        for (line, style) in lines {
+            let image = render_text(&ellipsize(line, style, max_width), style);
+            placed.push((image.width(), y));
+            let height = image.height();
+            scene.add_image(image, (left as i64, y as i64));
+            y += height + LINE_SPACING;
+        }
+        placed
+    }
+}
+
+/// Creates a transparent scene of `size` holding `background` with rounded corners.
+fn background_scene(
+    size: Size,
+    background: &CardBackground,
+    radius: u32,
+) -> Scene {
+    let (width, height) = size;
+    let mut fill = match background {
+        CardBackground::Color(color) => RgbaImage::from_pixel(width, height, *color),
+        CardBackground::Gradient(top, bottom) => RgbaImage::from_fn(width, height, |_, y| {
+            let t = y as f32 / (height.max(2) - 1) as f32;
+            Rgba(std::array::from_fn(|i| {
+                (top[i] as f32 + (bottom[i] as f32 - top[i] as f32) * t).round() as u8
+            }))
+        }),
+        CardBackground::Image(image) => cover(image, size),
+    };
+    round_corners(&mut fill, radius);
+
+    let mut scene = Scene::new(size).with_background(Rgba([0, 0, 0, 0]));
+    scene.add_image(fill, (0, 0));
+    scene
+}
+
+/// Shortens `text` with trailing dots until it is at most `max_width` wide.
+fn ellipsize(
+    text: &str,
+    style: &TextStyle,
+    max_width: u32,
+) -> String {
+    if text_width(text, style) <= max_width {
+        return text.to_string();
+    }
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let candidate = format!("{}...", chars.iter().collect::<String>().trim_end());
+        if text_width(&candidate, style) <= max_width {
+            return candidate;
+        }
+    }
+    String::new()
+}
+
+/// Scales `image` to cover `size` keeping its aspect ratio, and crops the overflow
+/// evenly from both sides.
+fn cover(
+    image: &RgbaImage,
+    (width, height): Size,
+) -> RgbaImage {
+    let (w, h) = (image.width().max(1) as f32, image.height().max(1) as f32);
+    let scale = (width as f32 / w).max(height as f32 / h);
+    let scaled_w = ((w * scale).ceil() as u32).max(width);
+    let scaled_h = ((h * scale).ceil() as u32).max(height);
+    let scaled = imageops::resize(image, scaled_w, scaled_h, imageops::FilterType::Triangle);
+    imageops::crop_imm(
+        &scaled,
+        (scaled_w - width) / 2,
+        (scaled_h - height) / 2,
+        width,
+        height,
+    )
+    .to_image()
+}
+
+/// Clears the pixels outside of corners of `radius`, with an antialiased edge.
+fn round_corners(
+    image: &mut RgbaImage,
+    radius: u32,
+) {
+    let (width, height) = image.dimensions();
+    let radius = radius.min(width / 2).min(height / 2) as f32;
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+        let cx = px.clamp(radius, width as f32 - radius);
+        let cy = py.clamp(radius, height as f32 - radius);
+        let distance = ((px - cx).powi(2) + (py - cy).powi(2)).sqrt();
+        let coverage = (radius - distance + 0.5).clamp(0., 1.);
+        if distance > 0. {
+            pixel[3] = (pixel[3] as f32 * coverage) as u8;
+        }
+    }
+}
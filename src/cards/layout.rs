@@ -0,0 +1,448 @@
+//! # Card layout module
+//!
+//! Cards described by a JSON layout loaded at runtime: the size and background, named
+//! text styles and a list of elements drawn in order. Text and element values may hold
+//! `{key}` bindings, filled from [CardData] on every render.
+//!
+//! ```json
+//! {
+//!   "size": [480, 140],
+//!   "background": { "gradient": ["#2c3042", "#161822"] },
+//!   "radius": 12,
+//!   "styles": {
+//!     "title": { "font": "default", "size": 20, "color": "#ffffff" },
+//!     "body": { "font": "default", "size": 16, "color": "#c8cddc" }
+//!   },
+//!   "elements": [
+//!     { "type": "tee", "x": 12, "y": 12, "height": 116, "eye": "happy" },
+//!     { "type": "text", "x": 140, "y": 12, "style": "title", "text": "Rank #{rank} on {map}", "max_width": 270 },
+//!     { "type": "text", "x": 140, "y": 64, "style": "body", "text": "Time: {time}", "when": "time" },
+//!     { "type": "medal", "x": 424, "y": 12, "size": 44, "rank": "{rank}" },
+//!     { "type": "flag", "x": 140, "y": 100, "height": 20, "country": "{country}" }
+//!   ]
+//! }
+//! ```
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::cards::layout::{CardData, CardLayout};
+//!
+//! let fonts = HashMap::from([("default".to_string(), font)]);
+//! let layout = CardLayout::from_json(&std::fs::read_to_string("rank.json")?, &fonts)?;
+//! let data = CardData::new()
+//!     .with_tee(&tee)
+//!     .with("rank", "1")
+//!     .with("map", "Kobra 4");
+//! std::fs::write("rank.png", layout.scene(&data).encode(ImageFormat::Png)?)?;
+//! ```
+
+use std::collections::HashMap;
+
+use image::{Rgba, RgbaImage, imageops};
+use serde::Deserialize;
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    assets::countryflags::CountryFlags,
+    cards::{CardBackground, background_scene, ellipsize, rank::medal, round_corners},
+    error::{Result, TeeError},
+    scene::{
+        Scene,
+        text::{FontArc, TextStyle, render_text},
+    },
+    tee::{
+        Tee,
+        parts::{EyeSelection, EyeType},
+        skin::{Postion, Size, TEE_SKIN_LAYOUT},
+    },
+};
+
+/// A color written as `#rrggbb` or `#rrggbbaa`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+struct HexColor(Rgba<u8>);
+
+impl TryFrom<String> for HexColor {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        let hex = value.strip_prefix('#').unwrap_or(&value);
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+        };
+        match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6 | 8, Some(r), Some(g), Some(b)) => {
+                let a = if hex.len() == 8 { channel(6) } else { Some(255) };
+                a.map(|a| HexColor(Rgba([r, g, b, a])))
+                    .ok_or_else(|| format!("invalid color {value:?}"))
+            }
+            _ => Err(format!("invalid color {value:?}")),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum RawBackground {
+    Color(HexColor),
+    Gradient(HexColor, HexColor),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawStyle {
+    font: String,
+    size: f32,
+    color: HexColor,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawElement {
+    #[serde(default)]
+    when: Option<String>,
+    x: i64,
+    y: i64,
+    #[serde(flatten)]
+    kind: RawKind,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RawKind {
+    Tee {
+        height: u32,
+        #[serde(default)]
+        eye: Option<String>,
+    },
+    Text {
+        style: String,
+        text: String,
+        #[serde(default)]
+        max_width: Option<u32>,
+    },
+    Medal {
+        size: u32,
+        rank: String,
+    },
+    Flag {
+        height: u32,
+        country: String,
+    },
+    Rect {
+        size: Size,
+        color: HexColor,
+        #[serde(default)]
+        radius: u32,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawLayout {
+    size: Size,
+    background: RawBackground,
+    #[serde(default)]
+    radius: u32,
+    #[serde(default)]
+    styles: HashMap<String, RawStyle>,
+    elements: Vec<RawElement>,
+}
+
+/// What an element of a [CardLayout] draws.
+#[derive(Debug, Clone)]
+pub enum ElementKind {
+    /// The tee of [CardData::tee] scaled to `height`, with the eye named by `eye`,
+    /// normal eyes if `None`
+    Tee { height: u32, eye: Option<String> },
+    /// A line of text, shortened with dots to `max_width`
+    Text {
+        style: TextStyle,
+        text: String,
+        max_width: Option<u32>,
+    },
+    /// The medal of the rank `rank` binds to, see [medal]
+    Medal { size: u32, rank: String },
+    /// The flag of the country code `country` binds to, from [CardData::flags]
+    Flag { height: u32, country: String },
+    /// A filled rectangle with rounded corners
+    Rect {
+        size: Size,
+        color: Rgba<u8>,
+        radius: u32,
+    },
+}
+
+/// An element of a [CardLayout].
+#[derive(Debug, Clone)]
+pub struct Element {
+    /// Key that has to be bound for the element to be drawn, always drawn if `None`
+    pub when: Option<String>,
+    /// Top left corner of the element
+    pub position: Postion,
+    /// What the element draws
+    pub kind: ElementKind,
+}
+
+/// Values bound into a [CardLayout].
+#[derive(Debug, Clone, Default)]
+pub struct CardData<'a> {
+    /// The tee drawn by tee elements
+    pub tee: Option<&'a Tee>,
+    /// The flags drawn by flag elements
+    pub flags: Option<&'a CountryFlags>,
+    /// Values of the `{key}` bindings
+    pub values: HashMap<String, String>,
+}
+
+impl<'a> CardData<'a> {
+    /// Creates data without tee, flags and values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the tee.
+    pub fn with_tee(
+        mut self,
+        tee: &'a Tee,
+    ) -> Self {
+        self.tee = Some(tee);
+        self
+    }
+
+    /// Sets the flags.
+    pub fn with_flags(
+        mut self,
+        flags: &'a CountryFlags,
+    ) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    /// Binds `key` to `value`.
+    pub fn with(
+        mut self,
+        key: impl Into<String>,
+        value: impl ToString,
+    ) -> Self {
+        self.values.insert(key.into(), value.to_string());
+        self
+    }
+
+    /// Replaces every `{key}` of `text` by its value, unbound keys by nothing.
+    pub fn bind(
+        &self,
+        text: &str,
+    ) -> String {
+        let mut bound = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            bound.push_str(&rest[..start]);
+            let key = &rest[start + 1..start + end];
+            match self.values.get(key) {
+                Some(value) => bound.push_str(value),
+                None => warn!(key, "Card binding is not set."),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        bound.push_str(rest);
+        bound
+    }
+}
+
+/// A card layout loaded at runtime, see the [module docs](self) for the format.
+#[derive(Debug, Clone)]
+pub struct CardLayout {
+    /// Size of the card
+    pub size: Size,
+    /// Fill of the card
+    pub background: CardBackground,
+    /// Radius of the corners of the card
+    pub radius: u32,
+    /// Elements drawn in order
+    pub elements: Vec<Element>,
+}
+
+impl CardLayout {
+    /// Parses a JSON layout, resolving the font names of its styles in `fonts`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TeeError::Json] for malformed JSON and colors, and [TeeError::Template]
+    /// for styles naming an unknown font and elements naming an unknown style.
+    #[instrument(level = "debug", skip_all)]
+    pub fn from_json(
+        json: &str,
+        fonts: &HashMap<String, FontArc>,
+    ) -> Result<Self> {
+        let raw: RawLayout = serde_json::from_str(json).map_err(TeeError::Json)?;
+
+        let mut styles = HashMap::with_capacity(raw.styles.len());
+        for (name, style) in raw.styles {
+            let font = fonts.get(&style.font).ok_or_else(|| {
+                TeeError::Template(format!("style {name} uses unknown font {}", style.font))
+            })?;
+            let text = TextStyle::new(font.clone(), style.size).with_color(style.color.0);
+            styles.insert(name, text);
+        }
+
+        let elements =
+            raw.elements
+                .into_iter()
+                .map(|element| {
+                    let kind = match element.kind {
+                        RawKind::Tee {
+                            height,
+                            eye,
+                        } => ElementKind::Tee {
+                            height,
+                            eye,
+                        },
+                        RawKind::Text {
+                            style,
+                            text,
+                            max_width,
+                        } => ElementKind::Text {
+                            style: styles.get(&style).cloned().ok_or_else(|| {
+                                TeeError::Template(format!("unknown style {style}"))
+                            })?,
+                            text,
+                            max_width,
+                        },
+                        RawKind::Medal { size, rank } => ElementKind::Medal { size, rank },
+                        RawKind::Flag {
+                            height,
+                            country,
+                        } => ElementKind::Flag {
+                            height,
+                            country,
+                        },
+                        RawKind::Rect {
+                            size,
+                            color,
+                            radius,
+                        } => ElementKind::Rect {
+                            size,
+                            color: color.0,
+                            radius,
+                        },
+                    };
+                    Ok(Element {
+                        when: element.when,
+                        position: (element.x, element.y),
+                        kind,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+        let background = match raw.background {
+            RawBackground::Color(color) => CardBackground::Color(color.0),
+            RawBackground::Gradient(top, bottom) => CardBackground::Gradient(top.0, bottom.0),
+        };
+        debug!(elements = elements.len(), "Loaded card layout");
+        Ok(Self {
+            size: raw.size,
+            background,
+            radius: raw.radius,
+            elements,
+        })
+    }
+
+    /// Draws the elements with `data` bound into them.
+    ///
+    /// Elements whose `when` key is unbound are skipped, as are tee and flag elements
+    /// without a tee or flags, and medals of ranks without a medal.
+    #[instrument(level = "debug", skip_all)]
+    pub fn scene(
+        &self,
+        data: &CardData,
+    ) -> Scene {
+        let mut scene = background_scene(self.size, &self.background, self.radius);
+        for element in &self.elements {
+            let bound = element
+                .when
+                .as_ref()
+                .is_none_or(|key| data.values.contains_key(key));
+            if !bound {
+                continue;
+            }
+            if let Some(image) = draw_element(&element.kind, data) {
+                scene.add_image(image, element.position);
+            }
+        }
+        scene
+    }
+}
+
+/// Draws a single element, `None` if it has nothing to draw.
+fn draw_element(
+    kind: &ElementKind,
+    data: &CardData,
+) -> Option<RgbaImage> {
+    match kind {
+        ElementKind::Tee {
+            height,
+            eye,
+        } => {
+            let tee = data.tee?;
+            let eye = eye.as_ref().map(|eye| data.bind(eye));
+            let selection = match eye.as_deref() {
+                None | Some("") => EyeSelection::Standard(EyeType::Normal),
+                Some(name) => match EyeType::ALL.into_iter().find(|eye| eye.name() == name) {
+                    Some(eye) => EyeSelection::Standard(eye),
+                    None => EyeSelection::Custom(name),
+                },
+            };
+            let image = tee.compose_image(TEE_SKIN_LAYOUT, selection);
+            let height = (*height).max(1);
+            let width = (image.width() * height / image.height().max(1)).max(1);
+            Some(imageops::resize(
+                &image,
+                width,
+                height,
+                imageops::FilterType::Triangle,
+            ))
+        }
+        ElementKind::Text {
+            style,
+            text,
+            max_width,
+        } => {
+            let text = data.bind(text);
+            let text = match max_width {
+                Some(max_width) => ellipsize(&text, style, *max_width),
+                None => text,
+            };
+            Some(render_text(&text, style))
+        }
+        ElementKind::Medal { size, rank } => medal(data.bind(rank).trim().parse().ok()?, *size),
+        ElementKind::Flag {
+            height,
+            country,
+        } => {
+            let code = data.bind(country).trim().parse().unwrap_or(-1);
+            let flag = &data.flags?.get_or_default(code)?.image;
+            let height = (*height).max(1);
+            let width = (flag.width() * height / flag.height().max(1)).max(1);
+            Some(imageops::resize(
+                flag,
+                width,
+                height,
+                imageops::FilterType::Triangle,
+            ))
+        }
+        ElementKind::Rect {
+            size,
+            color,
+            radius,
+        } => {
+            let mut rect = RgbaImage::from_pixel(size.0, size.1, *color);
+            round_corners(&mut rect, *radius);
+            Some(rect)
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! # Profile card module
+//!
+//! Shows a player: the tee, name and flag, points and favorite server.
+
+use bytes::Bytes;
+use image::{ImageFormat, RgbaImage, imageops};
+use tracing::{debug, instrument};
+
+use crate::{
+    assets::countryflags::CountryFlags,
+    cards::{CardTemplate, LINE_SPACING},
+    error::Result,
+    scene::{Scene, text::line_height},
+    tee::{
+        Tee,
+        parts::{EyeSelection, EyeType},
+    },
+};
+
+/// Values shown on a [ProfileCard].
+#[derive(Debug, Clone)]
+pub struct ProfileParams<'a> {
+    /// The tee of the player
+    pub tee: &'a Tee,
+    /// Eyes of the tee
+    pub eye: EyeSelection<'a>,
+    /// Name of the player
+    pub name: String,
+    /// Points of the player
+    pub points: u32,
+    /// Server the player plays on most, not shown if `None`
+    pub favorite_server: Option<String>,
+    /// Flag drawn right of the name, scaled to the name
+    pub flag: Option<RgbaImage>,
+}
+
+impl<'a> ProfileParams<'a> {
+    /// Creates the values of a tee with normal eyes, without server and flag.
+    pub fn new(
+        tee: &'a Tee,
+        name: impl Into<String>,
+        points: u32,
+    ) -> Self {
+        Self {
+            tee,
+            eye: EyeType::Normal.into(),
+            name: name.into(),
+            points,
+            favorite_server: None,
+            flag: None,
+        }
+    }
+
+    /// Sets the eyes of the tee.
+    pub fn with_eye(
+        mut self,
+        eye: impl Into<EyeSelection<'a>>,
+    ) -> Self {
+        self.eye = eye.into();
+        self
+    }
+
+    /// Sets the favorite server.
+    pub fn with_favorite_server(
+        mut self,
+        server: impl Into<String>,
+    ) -> Self {
+        self.favorite_server = Some(server.into());
+        self
+    }
+
+    /// Sets the flag.
+    pub fn with_flag(
+        mut self,
+        flag: RgbaImage,
+    ) -> Self {
+        self.flag = Some(flag);
+        self
+    }
+
+    /// Sets the flag of a country code, see [CountryFlags::get_or_default].
+    pub fn with_country(
+        mut self,
+        flags: &CountryFlags,
+        code: i16,
+    ) -> Self {
+        self.flag = flags.get_or_default(code).map(|flag| flag.image.clone());
+        self
+    }
+}
+
+/// Card template for player profiles.
+#[derive(Debug, Clone)]
+pub struct ProfileCard {
+    /// Look of the card
+    pub template: CardTemplate,
+    /// Label in front of the points
+    pub points_label: String,
+    /// Label in front of the favorite server
+    pub server_label: String,
+}
+
+impl ProfileCard {
+    /// Creates a profile card drawn with `template` and english labels.
+    pub fn new(template: CardTemplate) -> Self {
+        Self {
+            template,
+            points_label: "Points".to_string(),
+            server_label: "Favorite server".to_string(),
+        }
+    }
+
+    /// Sets the labels in front of the points and the favorite server.
+    pub fn with_labels(
+        mut self,
+        points: impl Into<String>,
+        server: impl Into<String>,
+    ) -> Self {
+        self.points_label = points.into();
+        self.server_label = server.into();
+        self
+    }
+
+    /// Lays out the card as a scene.
+    ///
+    /// The tee fills the left side, the name and the lines below it are placed right of
+    /// it and shortened with dots if they do not fit. The flag keeps its aspect ratio.
+    #[instrument(level = "debug", skip_all, fields(name = params.name))]
+    pub fn scene(
+        &self,
+        params: &ProfileParams,
+    ) -> Scene {
+        let template = &self.template;
+        let padding = template.padding;
+        let mut scene = template.scene();
+
+        let left = template.place_tee(&mut scene, params.tee, params.eye);
+        let right = template.size.0.saturating_sub(padding);
+        let title_height = line_height(&template.title);
+        let flag = params.flag.as_ref().map(|flag| {
+            let height = (title_height * 3 / 4).max(1);
+            let width = (flag.width() * height / flag.height().max(1)).max(1);
+            imageops::resize(flag, width, height, imageops::FilterType::Triangle)
+        });
+        let flag_space = flag.as_ref().map_or(0, |flag| flag.width() + padding);
+
+        let mut lines = vec![
+            (params.name.clone(), &template.title),
+            (
+                format!("{}: {}", self.points_label, group_thousands(params.points)),
+                &template.body,
+            ),
+        ];
+        if let Some(server) = &params.favorite_server {
+            lines.push((format!("{}: {server}", self.server_label), &template.body));
+        }
+
+        let max_width = right.saturating_sub(left);
+        // The name leaves room for the flag behind it
+        let name_width = max_width.saturating_sub(flag_space);
+        let (name_width, _) =
+            template.place_lines(&mut scene, &lines[..1], (left, padding), name_width)[0];
+        let top = padding + title_height + LINE_SPACING;
+        template.place_lines(&mut scene, &lines[1..], (left, top), max_width);
+
+        if let Some(flag) = flag {
+            let x = left + name_width + padding / 2;
+            let y = padding + (title_height - flag.height()) / 2;
+            scene.add_image(flag, (x as i64, y as i64));
+        }
+        debug!("Laid out profile card");
+        scene
+    }
+
+    /// Draws the card.
+    pub fn render(
+        &self,
+        params: &ProfileParams,
+    ) -> RgbaImage {
+        self.scene(params).render()
+    }
+
+    /// Draws the card and encodes it.
+    pub fn encode(
+        &self,
+        params: &ProfileParams,
+        format: ImageFormat,
+    ) -> Result<Bytes> {
+        self.scene(params).encode(format)
+    }
+}
+
+/// Formats `value` with a comma between groups of three digits.
+fn group_thousands(value: u32) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
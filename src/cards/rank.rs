@@ -0,0 +1,193 @@
+//! # Rank card module
+//!
+//! Announces a finished race: the tee, a medal for the top three, the rank, map, player
+//! and time.
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use image::{ImageFormat, Rgba, RgbaImage};
+use tracing::{debug, instrument};
+
+use crate::{
+    cards::CardTemplate,
+    error::Result,
+    scene::{Scene, text::line_height},
+    tee::{
+        Tee,
+        parts::{EyeSelection, EyeType},
+    },
+};
+
+/// Values shown on a [RankCard].
+#[derive(Debug, Clone)]
+pub struct RankParams<'a> {
+    /// The tee of the player
+    pub tee: &'a Tee,
+    /// Eyes of the tee
+    pub eye: EyeSelection<'a>,
+    /// Name of the player
+    pub player: String,
+    /// Name of the map
+    pub map: String,
+    /// Rank on the map, starting at `1`
+    pub rank: u32,
+    /// Finish time, not shown if `None`
+    pub time: Option<Duration>,
+}
+
+impl<'a> RankParams<'a> {
+    /// Creates the values of a happy tee without a time.
+    pub fn new(
+        tee: &'a Tee,
+        player: impl Into<String>,
+        map: impl Into<String>,
+        rank: u32,
+    ) -> Self {
+        Self {
+            tee,
+            eye: EyeType::Happy.into(),
+            player: player.into(),
+            map: map.into(),
+            rank,
+            time: None,
+        }
+    }
+
+    /// Sets the eyes of the tee.
+    pub fn with_eye(
+        mut self,
+        eye: impl Into<EyeSelection<'a>>,
+    ) -> Self {
+        self.eye = eye.into();
+        self
+    }
+
+    /// Sets the finish time.
+    pub fn with_time(
+        mut self,
+        time: Duration,
+    ) -> Self {
+        self.time = Some(time);
+        self
+    }
+}
+
+/// Card template for rank announcements, like "Rank #1 on Kobra 4".
+#[derive(Debug, Clone)]
+pub struct RankCard {
+    /// Look of the card
+    pub template: CardTemplate,
+}
+
+impl RankCard {
+    /// Creates a rank card drawn with `template`.
+    pub fn new(template: CardTemplate) -> Self {
+        Self { template }
+    }
+
+    /// Lays out the card as a scene.
+    ///
+    /// The tee fills the left side, the text is placed right of it and shortened with
+    /// dots if it does not fit. Ranks 1 to 3 get a medal in the top right corner.
+    #[instrument(level = "debug", skip_all, fields(rank = params.rank))]
+    pub fn scene(
+        &self,
+        params: &RankParams,
+    ) -> Scene {
+        let template = &self.template;
+        let padding = template.padding;
+        let mut scene = template.scene();
+
+        let left = template.place_tee(&mut scene, params.tee, params.eye);
+        let mut right = template.size.0.saturating_sub(padding);
+        let medal_size = line_height(&template.title) * 2;
+        if let Some(medal) = medal(params.rank, medal_size) {
+            right = right.saturating_sub(medal.width() + padding);
+            scene.add_image(medal, ((right + padding) as i64, padding as i64));
+        }
+
+        let max_width = right.saturating_sub(left);
+        let title = format!("Rank #{} on {}", params.rank, params.map);
+        let mut lines = vec![
+            (title, &template.title),
+            (params.player.clone(), &template.body),
+        ];
+        if let Some(time) = params.time {
+            lines.push((format!("Time: {}", format_race_time(time)), &template.body));
+        }
+
+        template.place_lines(&mut scene, &lines, (left, padding), max_width);
+        debug!("Laid out rank card");
+        scene
+    }
+
+    /// Draws the card.
+    pub fn render(
+        &self,
+        params: &RankParams,
+    ) -> RgbaImage {
+        self.scene(params).render()
+    }
+
+    /// Draws the card and encodes it.
+    pub fn encode(
+        &self,
+        params: &RankParams,
+        format: ImageFormat,
+    ) -> Result<Bytes> {
+        self.scene(params).encode(format)
+    }
+}
+
+/// Formats a race time as DDNet shows it, `MM:SS.cc` or `H:MM:SS.cc` from an hour on.
+pub fn format_race_time(time: Duration) -> String {
+    let centis = time.as_millis() / 10;
+    let (hours, minutes) = (centis / 360_000, centis / 6_000 % 60);
+    let (seconds, centis) = (centis / 100 % 60, centis % 100);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}.{centis:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}.{centis:02}")
+    }
+}
+
+/// Draws a gold, silver or bronze medal for ranks 1 to 3, `None` for other ranks.
+///
+/// The medal is `diameter` wide, its ribbon makes it half as high again.
+pub fn medal(
+    rank: u32,
+    diameter: u32,
+) -> Option<RgbaImage> {
+    let color: [f32; 3] = match rank {
+        1 => [255., 200., 40.],
+        2 => [200., 205., 215.],
+        3 => [205., 127., 50.],
+        _ => return None,
+    };
+    let diameter = diameter.max(4);
+    let ribbon = diameter / 2;
+    let radius = diameter as f32 / 2.;
+    let center = (radius, ribbon as f32 + radius);
+    Some(RgbaImage::from_fn(diameter, diameter + ribbon, |x, y| {
+        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+        let distance = ((px - center.0).powi(2) + (py - center.1).powi(2)).sqrt();
+        let coverage = (radius - distance + 0.5).clamp(0., 1.);
+        if coverage > 0. {
+            // Darker rim, lit from the top left
+            let shade = if distance > radius * 0.78 { 0.72 } else { 1. };
+            let light = 1. + 0.15 * ((center.1 - py) + (center.0 - px)) / diameter as f32;
+            let [r, g, b] = color.map(|c| (c * shade * light).clamp(0., 255.) as u8);
+            Rgba([r, g, b, (coverage * 255.) as u8])
+        } else if (px - center.0).abs() <= radius / 2. && py < center.1 {
+            // Ribbon with a lighter stripe in its middle
+            if (px - center.0).abs() <= radius / 6. {
+                Rgba([240, 240, 245, 255])
+            } else {
+                Rgba([200, 40, 50, 255])
+            }
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    }))
+}
@@ -0,0 +1,108 @@
+//! # Color blindness module
+//!
+//! Simulates how color blind players see a render, so skin authors and server owners
+//! can check that team colors stay apart. The filters use the matrices of
+//! Machado et al. (2009) at full severity, applied in linear RGB.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::{colorblind::ColorBlindness, tee::options::ComposeOptions};
+//!
+//! let options = ComposeOptions::new().with_color_blindness(ColorBlindness::Deuteranopia);
+//! let preview = tee.compose_with_options(TEE_SKIN_LAYOUT, EyeType::Normal, ImageFormat::Png, &options)?;
+//! ```
+
+use std::sync::OnceLock;
+
+use image::RgbaImage;
+
+/// A kind of dichromacy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorBlindness {
+    /// No red cones
+    Protanopia,
+    /// No green cones, the most common kind
+    Deuteranopia,
+    /// No blue cones
+    Tritanopia,
+}
+
+impl ColorBlindness {
+    /// Every kind, e.g. for side by side previews.
+    pub const ALL: [ColorBlindness; 3] = [
+        ColorBlindness::Protanopia,
+        ColorBlindness::Deuteranopia,
+        ColorBlindness::Tritanopia,
+    ];
+
+    /// Returns the matrix applied to linear RGB.
+    pub const fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            ColorBlindness::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            ColorBlindness::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            ColorBlindness::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+
+    /// Filters an image in place, alpha is kept.
+    pub fn apply(
+        &self,
+        img: &mut RgbaImage,
+    ) {
+        let to_linear = linear_table();
+        let m = self.matrix();
+        for pixel in img.pixels_mut() {
+            let [r, g, b, _] = pixel.0;
+            let rgb = [
+                to_linear[r as usize],
+                to_linear[g as usize],
+                to_linear[b as usize],
+            ];
+            for (channel, row) in pixel.0.iter_mut().zip(m) {
+                let linear = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2];
+                *channel = to_srgb(linear);
+            }
+        }
+    }
+
+    /// Returns a filtered copy of an image.
+    pub fn simulate(
+        &self,
+        img: &RgbaImage,
+    ) -> RgbaImage {
+        let mut img = img.clone();
+        self.apply(&mut img);
+        img
+    }
+}
+
+/// sRGB to linear lookup for every 8-bit value.
+fn linear_table() -> &'static [f32; 256] {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        std::array::from_fn(|value| {
+            let c = value as f32 / 255.0;
+            if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        })
+    })
+}
+
+fn to_srgb(linear: f32) -> u8 {
+    let linear = linear.clamp(0.0, 1.0);
+    let c =
+        if linear <= 0.0031308 { linear * 12.92 } else { 1.055 * linear.powf(1.0 / 2.4) - 0.055 };
+    (c * 255.0).round() as u8
+}
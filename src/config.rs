@@ -0,0 +1,328 @@
+//! # Config module
+//!
+//! Reads the knobs of the library level services from `TEE_MORPHOSIS_*` environment
+//! variables, so deployments can tune them without code changes. Unset variables keep
+//! their defaults and malformed ones fail with [`TeeError::Config`] naming the variable.
+//!
+//! | Variable | Value | Default |
+//! |---|---|---|
+//! | `TEE_MORPHOSIS_CACHE_DIR` | directory for persisted renders | none |
+//! | `TEE_MORPHOSIS_TIMEOUT_SECS` | http request timeout in seconds | `10` |
+//! | `TEE_MORPHOSIS_CONNECT_TIMEOUT_SECS` | http connect timeout in seconds | `5` |
+//! | `TEE_MORPHOSIS_DEFAULT_FORMAT` | `png`, `webp` or `gif` | `png` |
+//! | `TEE_MORPHOSIS_MAX_WIDTH` | widest decoded source in pixels | `4096` |
+//! | `TEE_MORPHOSIS_MAX_HEIGHT` | tallest decoded source in pixels | `4096` |
+//! | `TEE_MORPHOSIS_MAX_ALLOC` | bytes a decode may allocate | `134217728` |
+//! | `TEE_MORPHOSIS_USER_AGENT` | user agent of http requests | `tee_morphosis/<version>` |
+//! | `TEE_MORPHOSIS_CONCURRENCY` | renders running at once | one per CPU |
+//! | `TEE_MORPHOSIS_QUEUE` | renders waiting for a slot | 16 per slot |
+//! | `TEE_MORPHOSIS_ALLOWED_HOSTS` | comma separated hosts skins may be fetched from | any |
+//! | `TEE_MORPHOSIS_DENIED_HOSTS` | comma separated hosts skins may not be fetched from | none |
+//! | `TEE_MORPHOSIS_ALLOW_PRIVATE_ADDRESSES` | `true` to fetch from private networks | `false` |
+//! | `TEE_MORPHOSIS_PROXY` | proxy URL, `system` or `none` | `system` with private addresses allowed, `none` otherwise |
+//! | `TEE_MORPHOSIS_DNS_OVERRIDES` | comma separated `host=address` pairs | none |
+//! | `TEE_MORPHOSIS_IP_PREFERENCE` | `any`, `ipv4`, `ipv6`, `ipv4-only` or `ipv6-only` | `any` |
+//!
+//! The proxy, DNS and IP family variables need the `net` feature and are skipped
+//! without it.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::config::Config;
+//!
+//! let config = Config::from_env()?;
+//! let service = config.render_service()?;
+//! let rendered = service.render(config.request(url)).await?;
+//! ```
+
+use std::{path::PathBuf, time::Duration};
+
+use image::ImageFormat;
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    error::{Result, TeeError},
+    tee::{limits::DecodeLimits, options::ParseOptions},
+};
+#[cfg(feature = "net")]
+use crate::{
+    net::{
+        Fetcher,
+        network::{IpPreference, NetworkOptions},
+        policy::UrlPolicy,
+    },
+    service::{RenderRequest, RenderService},
+};
+
+/// Prefix of every variable read by [Config::from_env].
+pub const ENV_PREFIX: &str = "TEE_MORPHOSIS_";
+
+/// Formats [Config::default_format] accepts, the ones this crate can encode.
+const FORMATS: [ImageFormat; 3] = [ImageFormat::Png, ImageFormat::WebP, ImageFormat::Gif];
+
+/// Settings of the library level services, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Directory for persisted renders, e.g. named with
+    /// [render_filename](crate::meta::render_filename)
+    pub cache_dir: Option<PathBuf>,
+    /// Timeout of a whole http request
+    pub timeout: Duration,
+    /// Timeout of establishing an http connection
+    pub connect_timeout: Duration,
+    /// Format of renders that do not ask for one
+    pub default_format: ImageFormat,
+    /// Limits of decoded sources
+    pub limits: DecodeLimits,
+    /// User agent of http requests
+    pub user_agent: String,
+    /// Renders running at once, `None` for one per CPU
+    pub concurrency: Option<usize>,
+    /// Renders waiting for a slot, `None` for 16 per slot
+    pub queue: Option<usize>,
+    /// Hosts skins may be fetched from, empty for any
+    pub allowed_hosts: Vec<String>,
+    /// Hosts skins may not be fetched from
+    pub denied_hosts: Vec<String>,
+    /// Whether skins may be fetched from private, loopback and link-local addresses
+    pub allow_private_addresses: bool,
+    #[cfg(feature = "net")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+    /// Proxy, DNS overrides and IP family of http requests
+    pub network: NetworkOptions,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            cache_dir: None,
+            timeout: Duration::from_secs(10),
+            connect_timeout: Duration::from_secs(5),
+            default_format: ImageFormat::Png,
+            limits: DecodeLimits::DEFAULT,
+            user_agent: concat!("tee_morphosis/", env!("CARGO_PKG_VERSION")).to_string(),
+            concurrency: None,
+            queue: None,
+            allowed_hosts: Vec::new(),
+            denied_hosts: Vec::new(),
+            allow_private_addresses: false,
+            #[cfg(feature = "net")]
+            network: NetworkOptions::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads the configuration from the environment of the process.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Reads the configuration from `(name, value)` pairs like [Config::from_env].
+    ///
+    /// Pairs without [ENV_PREFIX] are skipped, unknown names with the prefix are logged
+    /// and skipped.
+    #[instrument(level = "debug", skip_all)]
+    pub fn from_vars<K, V>(vars: impl IntoIterator<Item = (K, V)>) -> Result<Self>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut config = Self::default();
+        for (key, value) in vars {
+            let (key, value) = (key.as_ref(), value.as_ref().trim());
+            let Some(name) = key.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            match name {
+                "CACHE_DIR" => {
+                    config.cache_dir = (!value.is_empty()).then(|| PathBuf::from(value));
+                }
+                "TIMEOUT_SECS" => config.timeout = parse_duration(key, value)?,
+                "CONNECT_TIMEOUT_SECS" => config.connect_timeout = parse_duration(key, value)?,
+                "DEFAULT_FORMAT" => {
+                    config.default_format = ImageFormat::from_extension(value)
+                        .filter(|format| FORMATS.contains(format))
+                        .ok_or_else(|| invalid(key, "expected png, webp or gif"))?;
+                }
+                "MAX_WIDTH" => config.limits.max_width = parse_positive(key, value)?,
+                "MAX_HEIGHT" => config.limits.max_height = parse_positive(key, value)?,
+                "MAX_ALLOC" => config.limits.max_alloc = parse_positive(key, value)?,
+                "USER_AGENT" => {
+                    if value.is_empty() || value.chars().any(char::is_control) {
+                        return Err(invalid(key, "expected printable text"));
+                    }
+                    config.user_agent = value.to_string();
+                }
+                "CONCURRENCY" => config.concurrency = Some(parse_positive(key, value)?),
+                "QUEUE" => {
+                    config.queue = Some(
+                        value
+                            .parse()
+                            .map_err(|_| invalid(key, "expected a whole number"))?,
+                    );
+                }
+                "ALLOWED_HOSTS" => config.allowed_hosts = parse_list(value),
+                "DENIED_HOSTS" => config.denied_hosts = parse_list(value),
+                "ALLOW_PRIVATE_ADDRESSES" => {
+                    config.allow_private_addresses = match value.to_ascii_lowercase().as_str() {
+                        "true" | "1" | "yes" => true,
+                        "false" | "0" | "no" => false,
+                        _ => return Err(invalid(key, "expected true or false")),
+                    };
+                }
+                #[cfg(feature = "net")]
+                "PROXY" => {
+                    config.network = match value.to_ascii_lowercase().as_str() {
+                        "" => config.network,
+                        "system" => config.network.with_system_proxy(),
+                        "none" => config.network.without_proxy(),
+                        _ => config
+                            .network
+                            .with_proxy(value)
+                            .map_err(|_| invalid(key, "expected a proxy URL, system or none"))?,
+                    };
+                }
+                #[cfg(feature = "net")]
+                "DNS_OVERRIDES" => {
+                    for entry in parse_list(value) {
+                        let (host, address) = entry
+                            .split_once('=')
+                            .and_then(|(host, address)| {
+                                Some((host.trim(), address.trim().parse().ok()?))
+                            })
+                            .filter(|(host, _)| !host.is_empty())
+                            .ok_or_else(|| invalid(key, "expected host=address pairs"))?;
+                        config.network = config.network.with_dns_override(host, address);
+                    }
+                }
+                #[cfg(feature = "net")]
+                "IP_PREFERENCE" => {
+                    let preference = IpPreference::from_name(value).ok_or_else(|| {
+                        invalid(key, "expected any, ipv4, ipv6, ipv4-only or ipv6-only")
+                    })?;
+                    config.network = config.network.with_ip_preference(preference);
+                }
+                _ => warn!(key, "Unknown configuration variable"),
+            }
+        }
+        debug!(?config, "Read configuration");
+        Ok(config)
+    }
+
+    /// Returns parse options enforcing [Config::limits].
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions::new().with_limits(self.limits)
+    }
+
+    #[cfg(feature = "net")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+    /// Builds the URL policy of the configured hosts and private addresses.
+    pub fn url_policy(&self) -> UrlPolicy {
+        let policy = UrlPolicy::new().with_private_addresses(self.allow_private_addresses);
+        let policy = self
+            .allowed_hosts
+            .iter()
+            .fold(policy, |policy, host| policy.with_allowed_host(host));
+        self.denied_hosts
+            .iter()
+            .fold(policy, |policy, host| policy.with_denied_host(host))
+    }
+
+    #[cfg(feature = "net")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+    /// Builds an http client with the timeouts, user agent, [Config::network] and
+    /// [Config::url_policy].
+    pub fn http_client(&self) -> Result<reqwest::Client> {
+        let builder = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+            .user_agent(&self.user_agent);
+        self.network
+            .apply(builder, &self.url_policy())
+            .build()
+            .map_err(TeeError::Reqwest)
+    }
+
+    #[cfg(feature = "net")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+    /// Builds a fetcher using [Config::http_client], [Config::url_policy] and
+    /// [Config::network].
+    pub fn fetcher(&self) -> Result<Fetcher> {
+        Ok(Fetcher::new()
+            .with_url_policy(self.url_policy())
+            .with_network(self.network.clone())
+            .with_client(self.http_client()?))
+    }
+
+    #[cfg(feature = "net")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+    /// Builds a render service using [Config::fetcher] and the configured slots.
+    pub fn render_service(&self) -> Result<RenderService> {
+        let mut service = RenderService::new().with_fetcher(self.fetcher()?);
+        if let Some(concurrency) = self.concurrency {
+            service = service.with_concurrency(concurrency);
+        }
+        if let Some(queue) = self.queue {
+            service = service.with_queue(queue);
+        }
+        Ok(service)
+    }
+
+    #[cfg(feature = "net")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+    /// Creates a request for `url` encoded with [Config::default_format].
+    pub fn request(
+        &self,
+        url: impl Into<String>,
+    ) -> RenderRequest {
+        RenderRequest::new(url).with_format(self.default_format)
+    }
+}
+
+fn invalid(
+    key: &str,
+    reason: &str,
+) -> TeeError {
+    TeeError::Config {
+        key: key.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Splits a comma separated list, dropping empty entries.
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_positive<T>(
+    key: &str,
+    value: &str,
+) -> Result<T>
+where
+    T: std::str::FromStr + Default + PartialEq,
+{
+    value
+        .parse()
+        .ok()
+        .filter(|parsed| *parsed != T::default())
+        .ok_or_else(|| invalid(key, "expected a positive whole number"))
+}
+
+fn parse_duration(
+    key: &str,
+    value: &str,
+) -> Result<Duration> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs > 0.0)
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| invalid(key, "expected a positive amount of seconds"))
+}
@@ -0,0 +1,67 @@
+//! # Contrast module
+//!
+//! Measures how well a render stands out from a background color, so tools can warn
+//! when a skin nearly vanishes against common map backgrounds.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use image::Rgb;
+//! use tee_morphosis::contrast::{LOW_CONTRAST, contrast_score};
+//!
+//! let render = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+//! if contrast_score(&render, Rgb([135, 165, 195])) < LOW_CONTRAST {
+//!     println!("skin is hard to see on the default sky");
+//! }
+//! ```
+
+use image::{Rgb, RgbaImage};
+
+/// Scores below this value are hard to make out in game.
+pub const LOW_CONTRAST: f32 = 1.5;
+
+/// Returns the mean WCAG contrast ratio between the visible pixels of `img` and
+/// `background`, from `1.0` (invisible) to `21.0` (black on white).
+///
+/// Every pixel is blended over the background and weighted by its alpha, so soft edges
+/// and shadows count less than the solid silhouette. Fully transparent images score
+/// `1.0`. Only luminance is compared, hues of equal brightness score low.
+pub fn contrast_score(
+    img: &RgbaImage,
+    background: Rgb<u8>,
+) -> f32 {
+    let background_luminance = relative_luminance(background.0.map(|c| c as f32 / 255.0));
+
+    let (mut weighted, mut weights) = (0.0f64, 0.0f64);
+    for pixel in img.pixels() {
+        let alpha = pixel.0[3] as f32 / 255.0;
+        if alpha == 0.0 {
+            continue;
+        }
+        let blended = std::array::from_fn(|i| {
+            let color = pixel.0[i] as f32 / 255.0;
+            let back = background.0[i] as f32 / 255.0;
+            color * alpha + back * (1.0 - alpha)
+        });
+        let luminance = relative_luminance(blended);
+        let (light, dark) = if luminance > background_luminance {
+            (luminance, background_luminance)
+        } else {
+            (background_luminance, luminance)
+        };
+        weighted += ((light + 0.05) / (dark + 0.05)) as f64 * alpha as f64;
+        weights += alpha as f64;
+    }
+
+    if weights == 0.0 { 1.0 } else { (weighted / weights) as f32 }
+}
+
+/// WCAG relative luminance of an sRGB color with channels in `0.0..=1.0`.
+fn relative_luminance(rgb: [f32; 3]) -> f32 {
+    let [r, g, b] = rgb.map(
+        |c| {
+            if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        },
+    );
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
@@ -0,0 +1,32 @@
+//! # Core module
+//!
+//! The data model without imaging: UV layouts, skin layouts, color conversions and part
+//! arithmetic. Nothing in here uses `std`, an allocator, the `image` crate or any
+//! optional dependency, so embedded tooling and wasm workers that bring their own
+//! imaging can reuse it. Float functions missing from `core` are implemented in a
+//! private math module.
+//!
+//! Disable the default `std` feature to build only this module, the crate is `no_std`
+//! then:
+//!
+//! ```toml
+//! tee_morphosis = { version = "2.0.0", default-features = false }
+//! ```
+//!
+//! The [tee](crate::tee) modules re-export these types, e.g. [crate::tee::uv::UvPart] is
+//! [uv::UvPart].
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::core::{color::{ddnet_color_to_hsl, hsl_to_rgb}, skin::TEE_SKIN_LAYOUT, uv::TEE_UV_LAYOUT};
+//!
+//! let skin = TEE_SKIN_LAYOUT.scaled(4.0);
+//! let (position, size) = skin.place(skin.first_eyes, TEE_UV_LAYOUT.eyes[0].size());
+//! let (r, g, b) = hsl_to_rgb(ddnet_color_to_hsl(65408));
+//! ```
+
+pub mod color;
+mod math;
+pub mod skin;
+pub mod uv;
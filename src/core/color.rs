@@ -0,0 +1,64 @@
+//! # Color math
+
+use crate::core::math::{abs, floor, rem_euclid};
+
+pub type HSL = (f32, f32, f32);
+pub type RGB = (f32, f32, f32);
+
+const DARKEST_LGT: f32 = 0.5;
+
+/// Convert ddnet color format to hsl
+pub fn ddnet_color_to_hsl(color: u32) -> HSL {
+    let h_raw = ((color >> 16) & 0xFF) as f32;
+    let s_raw = ((color >> 8) & 0xFF) as f32;
+    let l_raw = (color & 0xFF) as f32;
+
+    let h = h_raw / 255.0;
+    let s = s_raw / 255.0;
+    let l_compressed = l_raw / 255.0;
+
+    let l = DARKEST_LGT + l_compressed * (1.0 - DARKEST_LGT);
+
+    (h, s, l)
+}
+
+/// Convert hsl for rgb compatibilities
+pub fn hsl_to_rgb((h, s, l): HSL) -> RGB {
+    let h1 = h * 6.0;
+    let c = (1.0 - abs(2.0 * l - 1.0)) * s;
+    let x = c * (1.0 - abs((h1 % 2.0) - 1.0));
+
+    let (r, g, b) = match floor(h1) as i32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        5 | 6 => (c, 0.0, x),
+        _ => (c, 0.0, x),
+    };
+
+    let m = l - (c / 2.0);
+    (r + m, g + m, b + m)
+}
+
+/// Convert rgb to hsl, the inverse of [hsl_to_rgb]
+pub fn rgb_to_hsl((r, g, b): RGB) -> HSL {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+    if d == 0.0 {
+        return (0.0, 0.0, l);
+    }
+
+    let s = d / (1.0 - abs(2.0 * l - 1.0));
+    let h = if max == r {
+        rem_euclid((g - b) / d, 6.0)
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    (h / 6.0, s.clamp(0.0, 1.0), l)
+}
@@ -0,0 +1,30 @@
+//! Float functions of `std` that `core` does not have, exact for the ranges of layouts
+//! and colors.
+
+pub(crate) fn abs(value: f32) -> f32 {
+    if value < 0.0 { -value } else { value }
+}
+
+/// Exact for `|value| < 2^63`, `NaN` becomes `0.0`.
+pub(crate) fn floor(value: f32) -> f32 {
+    let truncated = value as i64 as f32;
+    if truncated > value { truncated - 1.0 } else { truncated }
+}
+
+/// Rounds half away from zero like [f32::round].
+pub(crate) fn round(value: f32) -> f32 {
+    if value < 0.0 {
+        return -round(-value);
+    }
+    let whole = floor(value);
+    if value - whole >= 0.5 { whole + 1.0 } else { whole }
+}
+
+/// Like [f32::rem_euclid] for a positive `divisor`.
+pub(crate) fn rem_euclid(
+    value: f32,
+    divisor: f32,
+) -> f32 {
+    let remainder = value % divisor;
+    if remainder < 0.0 { remainder + divisor } else { remainder }
+}
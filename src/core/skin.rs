@@ -0,0 +1,97 @@
+//! # Skin layout math
+
+use crate::core::{math::round, uv::ContentSize};
+
+pub type Postion = (i64, i64);
+/// Position of a part in layout units, fractional positions are kept until compose time
+pub type SkinPosition = (f32, f32);
+pub type Size = (u32, u32);
+pub type Scale = f32;
+pub type SkinPS = (SkinPosition, Scale);
+
+#[derive(Debug, Clone, Copy)]
+/// Mappings for output
+///
+/// Positions and the container are in layout units, `scale` converts them to output
+/// pixels. Rounding happens once per part when composing, so blown up layouts place
+/// parts exactly where the unscaled layout does.
+pub struct Skin {
+    pub body: SkinPS,
+    pub feet: SkinPS,
+    pub feet_back: SkinPS,
+    pub first_eyes: SkinPS,
+    pub second_eyes: SkinPS,
+
+    pub container: ContentSize,
+    /// Output pixels per layout unit
+    pub scale: Scale,
+}
+
+impl Skin {
+    /// Returns the layout composed `factor` times larger, e.g. to compose at a higher
+    /// resolution.
+    pub fn scaled(
+        &self,
+        factor: f32,
+    ) -> Self {
+        Skin {
+            scale: self.scale * factor,
+            ..*self
+        }
+    }
+
+    /// Returns the size of the composed image in pixels.
+    pub fn output_size(&self) -> Size {
+        (
+            round(self.container.0 as f32 * self.scale) as u32,
+            round(self.container.1 as f32 * self.scale) as u32,
+        )
+    }
+
+    /// Returns the exact, unrounded pixel position of `part`.
+    pub fn origin(
+        &self,
+        ((x, y), _): SkinPS,
+    ) -> (f32, f32) {
+        (x * self.scale, y * self.scale)
+    }
+
+    /// Returns the pixel position and size of a part of `size` placed at `part`.
+    pub fn place(
+        &self,
+        ((x, y), part_scale): SkinPS,
+        size: Size,
+    ) -> (Postion, Size) {
+        (
+            (round(x * self.scale) as i64, round(y * self.scale) as i64),
+            scale(size, part_scale * self.scale),
+        )
+    }
+}
+
+// https://github.com/ddnet/ddnet-discordbot/blob/5c37e4bcc2e97347de30d48a970c75cec3ecddb3/cogs/skindb.py#L179
+
+/// Layout for rasterized skin
+pub const TEE_SKIN_LAYOUT: Skin = {
+    Skin {
+        body: ((16., 0.), 0.66),
+        feet_back: ((8., 30.), 1.),
+        feet: ((24., 30.), 1.),
+        first_eyes: ((39., 18.), 0.8),
+        second_eyes: ((47., 18.), 0.8),
+        //
+        container: (96, 64),
+        scale: 1.,
+    }
+};
+
+#[inline]
+pub fn scale(
+    size: Size,
+    scale: f32,
+) -> Size {
+    (
+        (size.0 as f32 * scale) as u32,
+        (size.1 as f32 * scale) as u32,
+    )
+}
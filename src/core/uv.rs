@@ -0,0 +1,250 @@
+//! # UV layout math
+
+use crate::core::math::round;
+
+pub type ContentSize = (u32, u32);
+
+pub const BODY_SIZE: ContentSize = (96, 96);
+pub const FEET_SIZE: ContentSize = (64, 32);
+pub const EYE_SIZE: ContentSize = (32, 32);
+pub const HAND_SIZE: ContentSize = (32, 32);
+
+/// A rectangle on the source image, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvPart {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+impl UvPart {
+    /// Creates a part at `(x, y)` with the given size.
+    pub const fn new(
+        x: u32,
+        y: u32,
+        (w, h): ContentSize,
+    ) -> Self {
+        Self { x, y, w, h }
+    }
+
+    /// Returns the size of the part.
+    pub const fn size(&self) -> ContentSize {
+        (self.w, self.h)
+    }
+
+    /// Returns the x coordinate right after the part, saturating at `u32::MAX`.
+    pub const fn right(&self) -> u32 {
+        self.x.saturating_add(self.w)
+    }
+
+    /// Returns the y coordinate right below the part, saturating at `u32::MAX`.
+    pub const fn bottom(&self) -> u32 {
+        self.y.saturating_add(self.h)
+    }
+
+    /// Returns `true` if `other` lies entirely inside this part.
+    pub const fn contains(
+        &self,
+        other: &UvPart,
+    ) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.right() <= self.right()
+            && other.bottom() <= self.bottom()
+    }
+
+    /// Returns `true` if both parts share at least one pixel.
+    pub const fn overlaps(
+        &self,
+        other: &UvPart,
+    ) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+
+    /// Returns the part moved by `(dx, dy)`, saturating at `0`.
+    pub const fn translated(
+        &self,
+        dx: i64,
+        dy: i64,
+    ) -> Self {
+        Self {
+            x: offset(self.x, dx),
+            y: offset(self.y, dy),
+            w: self.w,
+            h: self.h,
+        }
+    }
+
+    /// Returns the part with position and size multiplied by `factor`, e.g. for
+    /// high resolution sheets.
+    pub fn scaled(
+        &self,
+        factor: f32,
+    ) -> Self {
+        let scale = |value: u32| round(value as f32 * factor) as u32;
+        Self {
+            x: scale(self.x),
+            y: scale(self.y),
+            w: scale(self.w),
+            h: scale(self.h),
+        }
+    }
+}
+
+const fn offset(
+    value: u32,
+    delta: i64,
+) -> u32 {
+    let moved = (value as i64).saturating_add(delta);
+    if moved < 0 {
+        0
+    } else if moved > u32::MAX as i64 {
+        u32::MAX
+    } else {
+        moved as u32
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Mappings for parsing
+pub struct UV {
+    pub body: UvPart,
+    pub body_shadow: UvPart,
+    pub feet: UvPart,
+    pub feet_shadow: UvPart,
+    pub hand: UvPart,
+    pub hand_shadow: UvPart,
+    /// [Normal, Angry, Pain, Happy, Empty, Surprise]
+    pub eyes: [UvPart; 6],
+    pub container: ContentSize,
+}
+
+impl UV {
+    /// Names of the parts, in the order of [UV::parts].
+    pub const PART_NAMES: [&'static str; 12] = [
+        "body",
+        "body_shadow",
+        "feet",
+        "feet_shadow",
+        "hand",
+        "hand_shadow",
+        "eye_normal",
+        "eye_angry",
+        "eye_pain",
+        "eye_happy",
+        "eye_empty",
+        "eye_surprise",
+    ];
+
+    /// Returns every part with its name from [UV::PART_NAMES].
+    pub fn parts(&self) -> [(&'static str, UvPart); 12] {
+        let parts = [
+            self.body,
+            self.body_shadow,
+            self.feet,
+            self.feet_shadow,
+            self.hand,
+            self.hand_shadow,
+            self.eyes[0],
+            self.eyes[1],
+            self.eyes[2],
+            self.eyes[3],
+            self.eyes[4],
+            self.eyes[5],
+        ];
+        core::array::from_fn(|index| (Self::PART_NAMES[index], parts[index]))
+    }
+}
+
+/// Describe position and size of each part of Tee on the image (256x128).
+pub const TEE_UV_LAYOUT: UV = {
+    const BODY_END_X: u32 = BODY_SIZE.0;
+    const HANDS_AND_FEET_START_X: u32 = BODY_SIZE.0 * 2;
+    const HAND_SHADOW_START_X: u32 = HANDS_AND_FEET_START_X + HAND_SIZE.0;
+    const FEET_START_Y: u32 = HAND_SIZE.1;
+    const FEET_SHADOW_START_Y: u32 = FEET_START_Y + FEET_SIZE.1;
+    const EYES_START_X: u32 = 64;
+    const EYES_START_Y: u32 = BODY_SIZE.1;
+
+    UV {
+        container: (256, 128),
+        body: UvPart {
+            x: 0,
+            y: 0,
+            w: BODY_SIZE.0,
+            h: BODY_SIZE.1,
+        },
+        body_shadow: UvPart {
+            x: BODY_END_X,
+            y: 0,
+            w: BODY_SIZE.0,
+            h: BODY_SIZE.1,
+        },
+        feet: UvPart {
+            x: HANDS_AND_FEET_START_X,
+            y: FEET_START_Y,
+            w: FEET_SIZE.0,
+            h: FEET_SIZE.1,
+        },
+        feet_shadow: UvPart {
+            x: HANDS_AND_FEET_START_X,
+            y: FEET_SHADOW_START_Y,
+            w: FEET_SIZE.0,
+            h: FEET_SIZE.1,
+        },
+        hand: UvPart {
+            x: HANDS_AND_FEET_START_X,
+            y: 0,
+            w: HAND_SIZE.0,
+            h: HAND_SIZE.1,
+        },
+        hand_shadow: UvPart {
+            x: HAND_SHADOW_START_X,
+            y: 0,
+            w: HAND_SIZE.0,
+            h: HAND_SIZE.1,
+        },
+        eyes: [
+            UvPart {
+                x: EYES_START_X,
+                y: EYES_START_Y,
+                w: EYE_SIZE.0,
+                h: EYE_SIZE.1,
+            }, // Normal
+            UvPart {
+                x: EYES_START_X + EYE_SIZE.0,
+                y: EYES_START_Y,
+                w: EYE_SIZE.0,
+                h: EYE_SIZE.1,
+            }, // Angry
+            UvPart {
+                x: EYES_START_X + EYE_SIZE.0 * 2,
+                y: EYES_START_Y,
+                w: EYE_SIZE.0,
+                h: EYE_SIZE.1,
+            }, // Pain
+            UvPart {
+                x: EYES_START_X + EYE_SIZE.0 * 3,
+                y: EYES_START_Y,
+                w: EYE_SIZE.0,
+                h: EYE_SIZE.1,
+            }, // Happy
+            UvPart {
+                x: EYES_START_X + EYE_SIZE.0 * 4,
+                y: EYES_START_Y,
+                w: EYE_SIZE.0,
+                h: EYE_SIZE.1,
+            }, // Empty
+            UvPart {
+                x: EYES_START_X + EYE_SIZE.0 * 5,
+                y: EYES_START_Y,
+                w: EYE_SIZE.0,
+                h: EYE_SIZE.1,
+            }, // Surprise
+        ],
+    }
+};
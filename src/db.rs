@@ -0,0 +1,497 @@
+//! # Skin database module
+//!
+//! Thin typed client for community skin databases.
+//!
+//! The client downloads the database index once per call and filters it locally,
+//! returning [`SkinEntry`] values whose [`SkinEntry::url`] can be passed straight to
+//! [`Tee::new_from_url`].
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::db::SkinDbClient;
+//!
+//! let client = SkinDbClient::new();
+//! let entries = client.search("default").await?;
+//! let tee = entries[0].fetch().await?;
+//! ```
+//!
+//! Mirrors are tried in order when the source fails, see [`SkinDbClient::with_mirror`]:
+//!
+//! ```rust,ignore
+//! let client = SkinDbClient::new()
+//!     .with_mirror(SkinDbSource::from_asset_url("https://skins.example.com/{name}.png"))
+//!     .with_mirror(SkinDbSource::TEEDATA);
+//! let tee = client.fetch("santa_limekitty").await?;
+//! ```
+
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use tracing::{debug, error, instrument, trace, warn};
+
+use crate::{
+    error::{Result, TeeError},
+    net::{Fetcher, read_image},
+    skin_name,
+    tee::Tee,
+    telemetry,
+};
+
+/// Placeholder replaced with the skin name in [`SkinDbSource::asset_url`].
+pub const NAME_PLACEHOLDER: &str = "{name}";
+
+/// Describes where a skin database keeps its index and its skin images.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkinDbSource {
+    /// URL of the JSON index listing every skin, if the database publishes one
+    pub index_url: Option<Cow<'static, str>>,
+    /// URL template of a skin image, `{name}` is replaced with the skin name
+    pub asset_url: Cow<'static, str>,
+}
+
+impl SkinDbSource {
+    /// The official DDNet skin database.
+    pub const DDNET: SkinDbSource = SkinDbSource {
+        index_url: Some(Cow::Borrowed("https://ddnet.org/skins/skin/skins.json")),
+        asset_url: Cow::Borrowed("https://ddnet.org/skins/skin/{name}.png"),
+    };
+
+    /// The teedata.net skin database.
+    ///
+    /// **note**: teedata does not publish an index, so only [`SkinDbClient::download_url`]
+    /// is available for this source.
+    pub const TEEDATA: SkinDbSource = SkinDbSource {
+        index_url: None,
+        asset_url: Cow::Borrowed("https://teedata.net/databasev2/skins/{name}/{name}.png"),
+    };
+
+    /// Creates a custom source from an index URL and an asset URL template.
+    pub fn custom(
+        index_url: impl Into<Cow<'static, str>>,
+        asset_url: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            index_url: Some(index_url.into()),
+            asset_url: asset_url.into(),
+        }
+    }
+
+    /// Creates a source without index from an asset URL template, e.g. a mirror only
+    /// hosting the images.
+    pub fn from_asset_url(asset_url: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            index_url: None,
+            asset_url: asset_url.into(),
+        }
+    }
+
+    /// Builds the download URL of a skin by its name, [normalized](skin_name::normalize)
+    /// and [encoded](skin_name::encode) for the path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TeeError::UnknownSkin] for names nothing is left of after normalizing,
+    /// such as `..`, which would otherwise climb out of the skins directory.
+    pub fn url_for(
+        &self,
+        name: &str,
+    ) -> Result<String> {
+        let normalized =
+            skin_name::normalize(name).ok_or_else(|| TeeError::UnknownSkin(name.to_string()))?;
+        Ok(self
+            .asset_url
+            .replace(NAME_PLACEHOLDER, &skin_name::encode(&normalized)))
+    }
+}
+
+impl Default for SkinDbSource {
+    fn default() -> Self {
+        Self::DDNET
+    }
+}
+
+/// A single skin listed in a skin database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkinEntry {
+    /// Name of the skin
+    pub name: String,
+    /// Author of the skin, empty if unknown
+    pub creator: String,
+    /// License of the skin, empty if unknown
+    pub license: String,
+    /// Download URL of the skin image
+    pub url: String,
+}
+
+impl SkinEntry {
+    /// Downloads and parses the skin with the default UV layout.
+    ///
+    /// Shortcut for [`Tee::new_from_url`] with [`SkinEntry::url`]. Use
+    /// [`SkinDbClient::fetch`] to respect the rate limits of the client.
+    pub async fn fetch(&self) -> Result<Tee> {
+        Tee::new_from_url(&self.url).await
+    }
+}
+
+/// Raw entry of the index as it is stored by the database.
+#[derive(Debug, Deserialize)]
+struct RawSkinEntry {
+    name: String,
+    #[serde(default)]
+    creator: String,
+    #[serde(default)]
+    license: String,
+}
+
+/// The index is either a bare array or wrapped into a `skins` object.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawIndex {
+    Wrapped { skins: Vec<RawSkinEntry> },
+    Flat(Vec<RawSkinEntry>),
+}
+
+/// Health of a source as seen by a [`SkinDbClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MirrorStatus {
+    /// Asset URL template of the source
+    pub asset_url: Cow<'static, str>,
+    /// Requests that failed in a row
+    pub consecutive_failures: u32,
+    /// Whether the source is tried in order, unhealthy sources are only tried after
+    /// every healthy one failed
+    pub healthy: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Health {
+    failures: u32,
+    down_until: Option<Instant>,
+}
+
+/// Failure counts of the source and the mirrors, shared between clones.
+#[derive(Debug, Clone)]
+struct MirrorHealth {
+    state: Arc<Mutex<Vec<Health>>>,
+    threshold: u32,
+    cooldown: Duration,
+}
+
+impl Default for MirrorHealth {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(vec![Health::default()])),
+            threshold: 3,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+impl MirrorHealth {
+    /// Returns the indices of `count` sources, healthy ones first, each group in order.
+    fn order(
+        &self,
+        count: usize,
+    ) -> Vec<usize> {
+        let now = Instant::now();
+        let state = self.lock();
+        let (mut healthy, down): (Vec<usize>, Vec<usize>) =
+            (0..count).partition(|&index| state[index].down_until.is_none_or(|until| until <= now));
+        healthy.extend(down);
+        healthy
+    }
+
+    fn record(
+        &self,
+        index: usize,
+        ok: bool,
+    ) {
+        let mut state = self.lock();
+        let health = &mut state[index];
+        if ok {
+            *health = Health::default();
+            return;
+        }
+        health.failures += 1;
+        if health.failures >= self.threshold {
+            health.down_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Health>> {
+        // The counters stay consistent even if a holder panicked
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Client for a community skin database.
+#[derive(Debug, Clone, Default)]
+pub struct SkinDbClient {
+    fetcher: Fetcher,
+    source: SkinDbSource,
+    mirrors: Vec<SkinDbSource>,
+    health: MirrorHealth,
+}
+
+impl SkinDbClient {
+    /// Creates a client for the DDNet skin database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a client for a specific source.
+    pub fn with_source(source: SkinDbSource) -> Self {
+        Self {
+            source,
+            ..Self::default()
+        }
+    }
+
+    /// Adds a mirror, tried after the source and the mirrors added before it.
+    ///
+    /// A source is unhealthy after 3 requests failing in a row and only tried once every
+    /// healthy one failed, until 30 seconds passed, see [`SkinDbClient::with_mirror_health`].
+    pub fn with_mirror(
+        mut self,
+        mirror: SkinDbSource,
+    ) -> Self {
+        self.mirrors.push(mirror);
+        // Not shared with the clones this one was made from, their sources differ
+        self.health.state = Arc::new(Mutex::new(vec![Health::default(); self.mirrors.len() + 1]));
+        self
+    }
+
+    /// Sets after how many failures in a row a source is unhealthy and for how long.
+    pub fn with_mirror_health(
+        mut self,
+        threshold: u32,
+        cooldown: Duration,
+    ) -> Self {
+        self.health.threshold = threshold.max(1);
+        self.health.cooldown = cooldown;
+        self
+    }
+
+    /// Replaces the fetcher, e.g. to share a connection pool and rate limits.
+    pub fn with_fetcher(
+        mut self,
+        fetcher: Fetcher,
+    ) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+
+    /// Returns the fetcher used by this client.
+    pub fn fetcher(&self) -> &Fetcher {
+        &self.fetcher
+    }
+
+    /// Returns the source used by this client.
+    pub fn source(&self) -> &SkinDbSource {
+        &self.source
+    }
+
+    /// Returns the mirrors in the order they are tried.
+    pub fn mirrors(&self) -> &[SkinDbSource] {
+        &self.mirrors
+    }
+
+    /// Returns the health of the source followed by the mirrors.
+    pub fn mirror_status(&self) -> Vec<MirrorStatus> {
+        let now = Instant::now();
+        let state = self.health.lock();
+        self.sources()
+            .zip(state.iter())
+            .map(|(source, health)| MirrorStatus {
+                asset_url: source.asset_url.clone(),
+                consecutive_failures: health.failures,
+                healthy: health.down_until.is_none_or(|until| until <= now),
+            })
+            .collect()
+    }
+
+    /// Returns the source followed by the mirrors.
+    fn sources(&self) -> impl Iterator<Item = &SkinDbSource> {
+        std::iter::once(&self.source).chain(&self.mirrors)
+    }
+
+    /// Runs `attempt` on the sources `usable` accepts, healthy ones first, until one
+    /// succeeds.
+    ///
+    /// Connection, timeout and server errors count against the health of a source,
+    /// other errors, e.g. a skin missing on one mirror, only move on to the next one.
+    async fn failover<T, F, Fut>(
+        &self,
+        usable: impl Fn(&SkinDbSource) -> bool,
+        attempt: F,
+    ) -> Option<Result<T>>
+    where
+        F: Fn(SkinDbSource) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let sources: Vec<&SkinDbSource> = self.sources().collect();
+        let mut last = None;
+        for index in self.health.order(sources.len()) {
+            let source = sources[index];
+            if !usable(source) {
+                continue;
+            }
+            match attempt(source.clone()).await {
+                Ok(value) => {
+                    self.health.record(index, true);
+                    return Some(Ok(value));
+                }
+                Err(e) => {
+                    warn!(source = %telemetry::url(&source.asset_url), error = %e, "Skin source failed.");
+                    let unhealthy = match &e {
+                        TeeError::Reqwest(e) => !e.status().is_some_and(|s| s.is_client_error()),
+                        _ => false,
+                    };
+                    if unhealthy {
+                        self.health.record(index, false);
+                    }
+                    last = Some(Err(e));
+                }
+            }
+        }
+        last
+    }
+
+    /// Returns the download URL of a skin by its name.
+    ///
+    /// Does not check that the skin exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [SkinDbSource::url_for].
+    pub fn download_url(
+        &self,
+        name: &str,
+    ) -> Result<String> {
+        self.source.url_for(name)
+    }
+
+    /// Downloads and parses a skin by its name with the default UV layout, from the
+    /// first source that has it.
+    ///
+    /// Names rejected by [SkinDbSource::url_for] fail without a request.
+    pub async fn fetch(
+        &self,
+        name: &str,
+    ) -> Result<Tee> {
+        self.source.url_for(name)?;
+        self.failover(
+            |_| true,
+            |source| async move {
+                let url = source.url_for(name)?;
+                let response = self.fetcher.get(&url).await?;
+                // Outages answer with server errors, they count against the source
+                let response = if response.status().is_server_error() {
+                    response.error_for_status().map_err(TeeError::Reqwest)?
+                } else {
+                    response
+                };
+                let (bytes, format) = read_image(&url, response).await?;
+                tokio::task::spawn_blocking(move || Tee::new(bytes, format))
+                    .await
+                    .map_err(TeeError::Join)?
+            },
+        )
+        .await
+        .expect("the source is always usable")
+    }
+
+    /// Downloads the whole index of the database, from the first source publishing one.
+    ///
+    /// The URLs of the entries point at the source the index was read from.
+    #[instrument(level = "debug", skip(self), fields(source = ?self.source.index_url.as_deref().map(telemetry::url)))]
+    pub async fn entries(&self) -> Result<Vec<SkinEntry>> {
+        self.failover(
+            |source| source.index_url.is_some(),
+            |source| async move {
+                let index_url = source.index_url.as_deref().unwrap_or_default();
+                trace!("Fetching skin database index");
+                let bytes = self
+                    .fetcher
+                    .get(index_url)
+                    .await?
+                    .error_for_status()
+                    .map_err(TeeError::Reqwest)?
+                    .bytes()
+                    .await
+                    .map_err(TeeError::Reqwest)?;
+                entries_from_json(&source, &bytes)
+            },
+        )
+        .await
+        .unwrap_or_else(|| {
+            error!("Skin database does not publish an index.");
+            Err(TeeError::DbIndexUnavailable)
+        })
+    }
+
+    /// Parses an already downloaded index, e.g. one cached on disk.
+    ///
+    /// Entries whose names [SkinDbSource::url_for] rejects are skipped.
+    pub fn entries_from_json(
+        &self,
+        json: &[u8],
+    ) -> Result<Vec<SkinEntry>> {
+        entries_from_json(&self.source, json)
+    }
+
+    /// Searches skins whose name contains `query`, ignoring case.
+    pub async fn search(
+        &self,
+        query: &str,
+    ) -> Result<Vec<SkinEntry>> {
+        let query = query.to_lowercase();
+        let mut entries = self.entries().await?;
+        entries.retain(|entry| entry.name.to_lowercase().contains(&query));
+        Ok(entries)
+    }
+
+    /// Lists skins made by `author`, ignoring case.
+    pub async fn by_author(
+        &self,
+        author: &str,
+    ) -> Result<Vec<SkinEntry>> {
+        let mut entries = self.entries().await?;
+        entries.retain(|entry| entry.creator.eq_ignore_ascii_case(author));
+        Ok(entries)
+    }
+}
+
+/// Parses an index of `source`.
+fn entries_from_json(
+    source: &SkinDbSource,
+    json: &[u8],
+) -> Result<Vec<SkinEntry>> {
+    let raw = match serde_json::from_slice(json).map_err(TeeError::Json)? {
+        RawIndex::Wrapped { skins } | RawIndex::Flat(skins) => skins,
+    };
+    debug!(entries = raw.len(), "Parsed skin database index.");
+
+    Ok(raw
+        .into_iter()
+        .filter_map(|entry| {
+            let url = source
+                .url_for(&entry.name)
+                .inspect_err(|_| warn!(name = entry.name, "Skipping skin with an invalid name."))
+                .ok()?;
+            Some(SkinEntry {
+                url,
+                name: entry.name,
+                creator: entry.creator,
+                license: entry.license,
+            })
+        })
+        .collect())
+}
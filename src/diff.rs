@@ -0,0 +1,100 @@
+//! # Diff module
+//!
+//! Compares two renders pixel by pixel, for regression tests of downstream renderers
+//! and for skin maintainers checking what an edit changed. [`compare_images`] counts
+//! the differing pixels and draws them red onto a faded copy of the first image.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::diff::compare_images;
+//!
+//! let diff = compare_images(&expected, &tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal));
+//! if !diff.within(2) {
+//!     std::fs::write("diff.png", diff.diff_png()?)?;
+//!     panic!("{} pixels changed, by up to {}", diff.differing_pixels, diff.max_delta);
+//! }
+//! ```
+
+use bytes::Bytes;
+use image::{ImageFormat, Rgba, RgbaImage};
+use tracing::{debug, instrument};
+
+use crate::{error::Result, tee::raw::encode_image};
+
+/// Result of [compare_images].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageDiff {
+    /// Number of pixels that differ in any channel
+    pub differing_pixels: usize,
+    /// Largest difference of a single channel, `0` for identical images
+    pub max_delta: u8,
+    /// The first image faded to gray, with differing pixels drawn red, more opaque the
+    /// larger the difference
+    pub diff_image: RgbaImage,
+}
+
+impl ImageDiff {
+    /// Returns whether the images are identical.
+    pub fn is_identical(&self) -> bool {
+        self.differing_pixels == 0
+    }
+
+    /// Returns whether no channel differs by more than `tolerance`.
+    pub fn within(
+        &self,
+        tolerance: u8,
+    ) -> bool {
+        self.max_delta <= tolerance
+    }
+
+    /// Encodes [ImageDiff::diff_image] as PNG.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [encode_image].
+    pub fn diff_png(&self) -> Result<Bytes> {
+        encode_image(&self.diff_image, ImageFormat::Png)
+    }
+}
+
+/// Compares `a` and `b` pixel by pixel.
+///
+/// Images of different sizes are compared on the larger size, pixels outside an image
+/// count as transparent. Fully transparent pixels are equal whatever their color
+/// channels hold, since they look the same.
+#[instrument(level = "debug", skip_all, fields(a = ?a.dimensions(), b = ?b.dimensions()))]
+pub fn compare_images(
+    a: &RgbaImage,
+    b: &RgbaImage,
+) -> ImageDiff {
+    let width = a.width().max(b.width());
+    let height = a.height().max(b.height());
+    let transparent = Rgba([0, 0, 0, 0]);
+    let pixel = |image: &RgbaImage, x, y| *image.get_pixel_checked(x, y).unwrap_or(&transparent);
+
+    let mut differing_pixels = 0;
+    let mut max_delta = 0;
+    let diff_image = RgbaImage::from_fn(width, height, |x, y| {
+        let (pa, pb) = (pixel(a, x, y), pixel(b, x, y));
+        let delta = if pa[3] == 0 && pb[3] == 0 {
+            0
+        } else {
+            (0..4).map(|c| pa[c].abs_diff(pb[c])).max().unwrap_or(0)
+        };
+        if delta == 0 {
+            let [r, g, b, alpha] = pa.0;
+            let gray = ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8;
+            return Rgba([gray, gray, gray, alpha / 4]);
+        }
+        differing_pixels += 1;
+        max_delta = max_delta.max(delta);
+        Rgba([255, 0, 0, 64 + (delta as u32 * 191 / 255) as u8])
+    });
+    debug!(differing_pixels, max_delta, "Compared images");
+    ImageDiff {
+        differing_pixels,
+        max_delta,
+        diff_image,
+    }
+}
@@ -1,18 +1,63 @@
 //! # Error Module
 
+use crate::locale::{Hint, Locale};
+
 pub type Result<T> = std::result::Result<T, TeeError>;
 
+/// Errors of the crate.
+///
+/// New variants are added as features grow, so matches need a wildcard arm.
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum TeeError {
     #[cfg(feature = "net")]
-    #[error("Got error then work with url context: {0}")]
+    #[error("Got error then work with url context: {}", crate::telemetry::request_error(.0))]
     Reqwest(reqwest::Error),
     #[cfg(feature = "net")]
     #[error("Got error then using task in async context: {0}")]
     Join(tokio::task::JoinError),
     #[cfg(feature = "net")]
-    #[error("Req does not contains any img content type: {0}")]
+    #[error("Req does not contains any img content type: {}", crate::telemetry::url(.0))]
     ReqWithOutContentType(String),
+    #[cfg(any(feature = "net", feature = "templates"))]
+    #[error("Got error then parsing json: {0}")]
+    Json(serde_json::Error),
+    #[cfg(feature = "net")]
+    #[error("Skin database does not publish an index")]
+    DbIndexUnavailable,
+    #[cfg(feature = "net")]
+    #[error("Render service queue is full")]
+    ServiceOverloaded,
+    #[cfg(feature = "net")]
+    #[error("URL is not allowed: {}", crate::telemetry::url(.0))]
+    UrlNotAllowed(String),
+    #[cfg(feature = "net")]
+    #[error("Invalid rate limit of {0} requests per second, it must be positive and finite")]
+    InvalidRateLimit(f64),
+    #[cfg(feature = "net")]
+    #[error("Address {address} of {host} is not allowed")]
+    AddressNotAllowed {
+        host: String,
+        address: std::net::IpAddr,
+    },
+    #[cfg(feature = "watch")]
+    #[error("Could not watch the asset directory: {0}")]
+    Watch(std::io::Error),
+    #[cfg(feature = "web")]
+    #[error("Upload of {size} bytes exceeds the limit of {max} bytes")]
+    UploadTooLarge { size: usize, max: usize },
+    #[cfg(feature = "web")]
+    #[error("Malformed multipart upload: {0}")]
+    MalformedUpload(&'static str),
+    #[cfg(feature = "web")]
+    #[error("Upload has no field named {0}")]
+    MissingUploadField(String),
+    #[cfg(feature = "ffmpeg")]
+    #[error("Could not run ffmpeg: {0}")]
+    Process(std::io::Error),
+    #[cfg(feature = "ffmpeg")]
+    #[error("ffmpeg failed: {0}")]
+    Ffmpeg(String),
 
     // Добавить в src/error.rs
     #[error("Invalid builder configuration. Provide either data+format or url")]
@@ -25,7 +70,7 @@ pub enum TeeError {
         "The requested part {part:?} is outside the image bounds (width: {width}, height: {height})"
     )]
     OutOfBounds {
-        part: crate::tee::uv::UVPart,
+        part: crate::tee::uv::UvPart,
         width: u32,
         height: u32,
     },
@@ -35,4 +80,132 @@ pub enum TeeError {
         expected: (u32, u32),
         found: (u32, u32),
     },
+
+    #[error("Frame {index} is out of range, the source has {frames} frame(s).")]
+    FrameOutOfRange { index: usize, frames: usize },
+
+    #[error("The animation has no frames")]
+    EmptyAnimation,
+
+    #[error("The sheet layout has no part named {0}")]
+    UnknownSheetPart(String),
+
+    #[error("The UV layout is missing the {0} part")]
+    MissingUvPart(&'static str),
+
+    #[error("The UV layout parts {first} and {second} overlap")]
+    OverlappingUvParts {
+        first: &'static str,
+        second: &'static str,
+    },
+
+    #[error("Malformed image container: {0}")]
+    MalformedContainer(&'static str),
+
+    #[error("The skin was rejected by the content checker: {0}")]
+    ContentRejected(String),
+
+    #[error("Unknown skin: {0}")]
+    UnknownSkin(String),
+
+    #[error("Self test failed: {0}")]
+    SelfTest(String),
+
+    #[error("Invalid configuration {key}: {reason}")]
+    Config { key: String, reason: String },
+
+    #[error("Deadline exceeded before {stage}")]
+    DeadlineExceeded { stage: &'static str },
+
+    #[error("Invalid card layout: {0}")]
+    Template(String),
+
+    #[error("Font file is not a TrueType or OpenType font")]
+    InvalidFont,
+
+    #[error("Malformed data URL: {0}")]
+    MalformedDataUrl(&'static str),
+
+    #[error("Malformed country flag index at line {line}: {reason}")]
+    MalformedFlagIndex { line: usize, reason: &'static str },
+
+    #[error("Malformed UV preset at line {line}: {reason}")]
+    MalformedUvPreset { line: usize, reason: &'static str },
+}
+
+impl TeeError {
+    /// Returns an actionable hint on how to fix the error, or `None` if the error says
+    /// all there is to say.
+    ///
+    /// The hints are short sentences, so bots can forward them to their users instead
+    /// of the debug output.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tee_morphosis::error::TeeError;
+    ///
+    /// let error = TeeError::InvalidDimensions {
+    ///     expected: (256, 128),
+    ///     found: (512, 256),
+    /// };
+    /// assert!(error.suggestion().unwrap().contains("high resolution"));
+    /// ```
+    pub fn suggestion(&self) -> Option<&'static str> {
+        self.hint().map(|hint| hint.text(Locale::English))
+    }
+
+    /// Picks the hint of [TeeError::suggestion].
+    pub(crate) fn hint(&self) -> Option<Hint> {
+        use image::{ImageError, ImageFormat, error::ImageFormatHint};
+
+        match self {
+            #[cfg(feature = "net")]
+            TeeError::Reqwest(e) if e.is_timeout() || e.is_connect() => Some(Hint::ServerDown),
+            #[cfg(feature = "net")]
+            TeeError::ReqWithOutContentType(_) => Some(Hint::NotAnImageLink),
+            #[cfg(feature = "net")]
+            TeeError::ServiceOverloaded => Some(Hint::Busy),
+            #[cfg(feature = "net")]
+            TeeError::UrlNotAllowed(_) | TeeError::AddressNotAllowed { .. } => {
+                Some(Hint::ForbiddenLink)
+            }
+            TeeError::Image(ImageError::Unsupported(e))
+                if e.format_hint() == ImageFormatHint::Exact(ImageFormat::Jpeg) =>
+            {
+                Some(Hint::Jpeg)
+            }
+            TeeError::Image(ImageError::Unsupported(_)) => Some(Hint::UnsupportedFormat),
+            TeeError::Image(ImageError::Limits(_)) => Some(Hint::TooLarge),
+            TeeError::Image(ImageError::Decoding(_))
+            | TeeError::MalformedContainer(_)
+            | TeeError::MalformedDataUrl(_) => Some(Hint::Damaged),
+            #[cfg(feature = "web")]
+            TeeError::UploadTooLarge { .. } => Some(Hint::TooLarge),
+            TeeError::InvalidDimensions {
+                expected,
+                found,
+            } => {
+                let scaled = found.0 > expected.0
+                    && found.0.is_multiple_of(expected.0)
+                    && found.0 as u64 * expected.1 as u64 == found.1 as u64 * expected.0 as u64;
+                if scaled {
+                    Some(Hint::HighResolution)
+                } else if found.0 < found.1 {
+                    Some(Hint::Upright)
+                } else {
+                    Some(Hint::NotASkin)
+                }
+            }
+            TeeError::OutOfBounds { .. } => Some(Hint::CutOff),
+            TeeError::FrameOutOfRange { .. } => Some(Hint::MissingFrame),
+            TeeError::MissingUvPart(_) | TeeError::OverlappingUvParts { .. } => {
+                Some(Hint::UvLayout)
+            }
+            TeeError::ContentRejected(_) => Some(Hint::Rejected),
+            TeeError::UnknownSkin(_) => Some(Hint::Spelling),
+            TeeError::DeadlineExceeded { .. } => Some(Hint::TooSlow),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,98 @@
+//! # Size estimation module
+//!
+//! Predicts the size of an encoded image without encoding it, so services can pick a
+//! format that fits platform upload limits without encode-retry loops. The estimate
+//! uses the entropy of the pixel differences the encoders predict from and is usually
+//! within a factor of two of the real size for renders, large single color areas are
+//! overestimated.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::estimate::estimate_encoded_size;
+//!
+//! const DISCORD_LIMIT: u64 = 8 * 1024 * 1024;
+//! let format = [ImageFormat::Png, ImageFormat::WebP]
+//!     .into_iter()
+//!     .find(|format| estimate_encoded_size(&banner, *format, &options) <= DISCORD_LIMIT);
+//! ```
+
+use image::{ImageFormat, RgbaImage};
+use tracing::{instrument, trace};
+
+use crate::tee::options::ComposeOptions;
+
+/// Returns the estimated size in bytes of `img` encoded with `format`, including the
+/// metadata of `options`.
+///
+/// Formats this crate can not encode are estimated as uncompressed pixels.
+#[instrument(level = "debug", skip(img, options), fields(size = ?img.dimensions()))]
+pub fn estimate_encoded_size(
+    img: &RgbaImage,
+    format: ImageFormat,
+    options: &ComposeOptions,
+) -> u64 {
+    let pixels = img.width() as u64 * img.height() as u64;
+    let bits = residual_entropy(img);
+    trace!(bits, "Measured residual entropy");
+    // Factors measured against the encoders of the image crate on composed tees
+    let bytes = pixels as f64 * 4.0 * bits / 8.0;
+    let data = match format {
+        // Deflate can not shrink data by more than about 1:1000, which solid areas reach
+        ImageFormat::Png => (bytes * 0.8) as u64 + pixels * 4 / 1000 + 64,
+        ImageFormat::WebP => (bytes * 0.6) as u64 + 32,
+        // Palette indices compress independently of the color channels
+        ImageFormat::Gif => (pixels as f64 * bits * 0.15) as u64 + 800,
+        _ => pixels * 4,
+    };
+    data + metadata_size(format, options)
+}
+
+/// Bits of order-0 entropy per channel byte of the differences to the left neighbour.
+fn residual_entropy(img: &RgbaImage) -> f64 {
+    let mut histogram = [0u64; 256];
+    for row in img.rows() {
+        let mut left = [0u8; 4];
+        for pixel in row {
+            // Fully transparent pixels are flattened by encoders that care about size
+            let current = if pixel.0[3] == 0 { [0; 4] } else { pixel.0 };
+            for c in 0..4 {
+                histogram[current[c].wrapping_sub(left[c]) as usize] += 1;
+            }
+            left = current;
+        }
+    }
+    let total: u64 = histogram.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    histogram
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Bytes added by [RenderMeta::embed](crate::meta::RenderMeta::embed).
+fn metadata_size(
+    format: ImageFormat,
+    options: &ComposeOptions,
+) -> u64 {
+    let Some(meta) = &options.metadata else {
+        return 0;
+    };
+    match format {
+        // One text chunk per entry: length, type, key, separator and crc
+        ImageFormat::Png => meta
+            .entries()
+            .iter()
+            .map(|(key, value)| 13 + key.len() as u64 + value.len() as u64)
+            .sum(),
+        // VP8X and XMP chunk headers with the packet
+        ImageFormat::WebP => 26 + meta.to_xmp().len() as u64,
+        _ => 0,
+    }
+}
@@ -0,0 +1,171 @@
+//! # ETag module
+//!
+//! Strong entity tags for serving renders over HTTP. Tags are derived from the inputs of a
+//! render rather than its bytes, so a handler can answer a conditional request with
+//! `304 Not Modified` before composing anything.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::etag::is_not_modified;
+//!
+//! let etag = tee.compose_etag(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::WebP, &options);
+//! if is_not_modified(request_headers.get("if-none-match"), &etag) {
+//!     return StatusCode::NOT_MODIFIED;
+//! }
+//! let image = tee.compose_tagged(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::WebP, &options)?;
+//! // image.etag() == etag
+//! ```
+
+use std::{collections::HashMap, sync::Arc};
+
+use bytes::Bytes;
+use image::{ImageFormat, Rgb, RgbaImage};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use tracing::{debug, instrument};
+
+use crate::{contrast::contrast_score, error::Result, meta::RenderMeta, tee::raw::encode_image};
+
+/// An encoded render together with its entity tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComposedImage {
+    /// Encoded image data
+    pub data: Bytes,
+    /// Format of [ComposedImage::data]
+    pub format: ImageFormat,
+    etag: String,
+    canvas: Arc<RgbaImage>,
+    meta: Option<RenderMeta>,
+}
+
+impl ComposedImage {
+    pub(crate) fn new(
+        data: Bytes,
+        format: ImageFormat,
+        etag: String,
+        canvas: RgbaImage,
+        meta: Option<RenderMeta>,
+    ) -> Self {
+        Self {
+            data,
+            format,
+            etag,
+            canvas: Arc::new(canvas),
+            meta,
+        }
+    }
+
+    /// Returns the strong entity tag, quoted as sent in the `ETag` header.
+    pub fn etag(&self) -> &str {
+        &self.etag
+    }
+
+    /// Returns the MIME type for the `Content-Type` header.
+    pub fn content_type(&self) -> &'static str {
+        self.format.to_mime_type()
+    }
+
+    /// Returns the composed canvas the image was encoded from.
+    pub fn canvas(&self) -> &RgbaImage {
+        &self.canvas
+    }
+
+    /// Scores the visibility of the image against `background`, see [contrast_score].
+    pub fn contrast_score(
+        &self,
+        background: Rgb<u8>,
+    ) -> Result<f32> {
+        Ok(contrast_score(&self.canvas, background))
+    }
+
+    /// Encodes the composed canvas into several formats in parallel, without composing it
+    /// again.
+    ///
+    /// The own format reuses [ComposedImage::data] and metadata is embedded like in the
+    /// original render. Entity tags are per format, use
+    /// [Tee::compose_etag](crate::tee::Tee::compose_etag) for the other ones.
+    #[instrument(level = "debug", skip(self), fields(own = ?self.format))]
+    pub fn encode_all(
+        &self,
+        formats: &[ImageFormat],
+    ) -> Result<HashMap<ImageFormat, Bytes>> {
+        let encoded: HashMap<ImageFormat, Bytes> = formats
+            .par_iter()
+            .map(|&format| {
+                if format == self.format {
+                    return Ok((format, self.data.clone()));
+                }
+                let data = encode_image(&self.canvas, format)?;
+                let data = match &self.meta {
+                    Some(meta) => meta.embed(data, format)?,
+                    None => data,
+                };
+                Ok((format, data))
+            })
+            .collect::<Result<_>>()?;
+        debug!(formats = encoded.len(), "Encoded composed image");
+        Ok(encoded)
+    }
+
+    /// Checks an `If-None-Match` header value against this image, see [is_not_modified].
+    pub fn is_not_modified<S: AsRef<str>>(
+        &self,
+        if_none_match: Option<S>,
+    ) -> bool {
+        is_not_modified(if_none_match, &self.etag)
+    }
+}
+
+/// Checks whether an `If-None-Match` header value matches `etag`.
+///
+/// `*` matches anything, lists are split on commas and compared weakly as RFC 9110
+/// requires, so `W/"abc"` matches `"abc"`. A missing header never matches.
+pub fn is_not_modified<S: AsRef<str>>(
+    if_none_match: Option<S>,
+    etag: &str,
+) -> bool {
+    let Some(header) = if_none_match else {
+        return false;
+    };
+    let header = header.as_ref().trim();
+    if header == "*" {
+        return true;
+    }
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    header.split(',').any(|tag| opaque(tag) == etag)
+}
+
+/// Incrementally builds a strong entity tag.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ETagHasher(Sha256);
+
+impl ETagHasher {
+    /// Adds a length prefixed field.
+    pub(crate) fn field(
+        &mut self,
+        bytes: &[u8],
+    ) -> &mut Self {
+        self.0.update((bytes.len() as u64).to_le_bytes());
+        self.0.update(bytes);
+        self
+    }
+
+    /// Adds an image with its dimensions.
+    pub(crate) fn image(
+        &mut self,
+        image: &RgbaImage,
+    ) -> &mut Self {
+        self.0.update(image.width().to_le_bytes());
+        self.0.update(image.height().to_le_bytes());
+        self.field(image.as_raw())
+    }
+
+    /// Returns the quoted tag.
+    pub(crate) fn finish(self) -> String {
+        let digest = self.0.finalize();
+        let hex: String = digest[..16].iter().map(|b| format!("{b:02x}")).collect();
+        format!("\"{hex}\"")
+    }
+}
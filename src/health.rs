@@ -0,0 +1,115 @@
+//! # Health module
+//!
+//! [`self_test`] runs a bundled skin through the whole image stack, so services can back
+//! readiness probes with a render instead of assuming codecs and the rayon pool work in
+//! their container.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! let report = tee_morphosis::self_test()?;
+//! println!("ready in {:?} with {} threads", report.total(), report.threads);
+//! ```
+
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use image::{GenericImageView, ImageFormat};
+use rayon::prelude::*;
+use tracing::{debug, error, instrument};
+
+use crate::{
+    error::{Result, TeeError},
+    tee::{Tee, parts::EyeType, raw::encode_image, skin::TEE_SKIN_LAYOUT},
+};
+
+/// Skin run through [self_test].
+const SELF_TEST_SKIN: &[u8] = include_bytes!("../.ref/test_skin.png");
+
+/// Formats encoded and decoded again by [self_test].
+pub const SELF_TEST_FORMATS: [ImageFormat; 3] =
+    [ImageFormat::Png, ImageFormat::WebP, ImageFormat::Gif];
+
+/// Timings of a passed [self_test].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Time spent decoding and splitting the skin
+    pub parse: Duration,
+    /// Time spent composing the tee
+    pub compose: Duration,
+    /// Time spent encoding and decoding again, per format of [SELF_TEST_FORMATS]
+    pub encode: Vec<(ImageFormat, Duration)>,
+    /// Time spent composing every eye on the rayon pool
+    pub parallel: Duration,
+    /// Threads of the rayon pool
+    pub threads: usize,
+}
+
+impl SelfTestReport {
+    /// Returns the total time of the self test.
+    pub fn total(&self) -> Duration {
+        self.parse
+            + self.compose
+            + self.encode.iter().map(|(_, time)| *time).sum::<Duration>()
+            + self.parallel
+    }
+}
+
+/// Parses, composes and encodes a bundled skin and checks the results.
+///
+/// # Returns
+///
+/// A `Result` which is `Ok(SelfTestReport)` with the time of every stage, or
+/// `Err(TeeError)` with the first stage that failed.
+#[instrument(level = "debug")]
+pub fn self_test() -> Result<SelfTestReport> {
+    let start = Instant::now();
+    let tee = Tee::new(Bytes::from_static(SELF_TEST_SKIN), ImageFormat::Png)?;
+    let parse = start.elapsed();
+
+    let start = Instant::now();
+    let canvas = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy);
+    let compose = start.elapsed();
+    if canvas.pixels().all(|pixel| pixel.0[3] == 0) {
+        return Err(fail("the composed tee is empty"));
+    }
+
+    let mut encode = Vec::with_capacity(SELF_TEST_FORMATS.len());
+    for format in SELF_TEST_FORMATS {
+        let start = Instant::now();
+        let data = encode_image(&canvas, format)?;
+        let decoded = image::load_from_memory_with_format(&data, format)?;
+        encode.push((format, start.elapsed()));
+        if decoded.dimensions() != canvas.dimensions() {
+            return Err(fail(&format!(
+                "{} round trip changed the size",
+                format.to_mime_type()
+            )));
+        }
+    }
+
+    let start = Instant::now();
+    let renders: Vec<_> = EyeType::ALL
+        .par_iter()
+        .map(|eye| tee.compose_image(TEE_SKIN_LAYOUT, *eye))
+        .collect();
+    let parallel = start.elapsed();
+    if renders.len() != EyeType::ALL.len() {
+        return Err(fail("the rayon pool lost renders"));
+    }
+
+    let report = SelfTestReport {
+        parse,
+        compose,
+        encode,
+        parallel,
+        threads: rayon::current_num_threads(),
+    };
+    debug!(total = ?report.total(), threads = report.threads, "Self test passed");
+    Ok(report)
+}
+
+fn fail(reason: &str) -> TeeError {
+    error!(reason, "Self test failed");
+    TeeError::SelfTest(reason.to_string())
+}
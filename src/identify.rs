@@ -0,0 +1,206 @@
+//! # Identify module
+//!
+//! **Experimental**: guesses which skin is shown on a crop of an in-game screenshot.
+//!
+//! Skins are registered in a [`SkinIndex`] as small fingerprints of their composed
+//! render. A crop is normalized the same way (background removed, trimmed, resized)
+//! and compared against every fingerprint. Results are only as good as the crop:
+//! it should show a single, unobstructed tee with the default eyes.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::identify::{SkinIndex, identify_from_render};
+//!
+//! let mut index = SkinIndex::new();
+//! index.insert("default", &default_tee);
+//! index.insert("santa", &santa_tee);
+//!
+//! let crop = image::open("screenshot_crop.png")?.to_rgba8();
+//! let candidates = identify_from_render(&crop, &index);
+//! println!("best guess: {}", candidates[0].name);
+//! ```
+
+use image::{Rgba, RgbaImage, imageops};
+use tracing::{debug, instrument};
+
+use crate::tee::{Tee, parts::EyeType, skin::TEE_SKIN_LAYOUT};
+
+/// Size fingerprints are resized to, matching the aspect of [TEE_SKIN_LAYOUT].
+pub const FINGERPRINT_SIZE: (u32, u32) = (24, 16);
+
+/// Maximum distance per channel for a pixel to count as background.
+const BACKGROUND_TOLERANCE: u8 = 24;
+
+/// Reduced render of a skin used for matching.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fingerprint(RgbaImage);
+
+impl Fingerprint {
+    /// Fingerprints an image with a transparent background, e.g. a composed tee.
+    pub fn of_render(render: &RgbaImage) -> Self {
+        Self(normalize(render))
+    }
+
+    /// Fingerprints a screenshot crop, removing its background first.
+    ///
+    /// The background color is taken from the corners of the crop.
+    pub fn of_screenshot(crop: &RgbaImage) -> Self {
+        let mut crop = crop.clone();
+        remove_background(&mut crop);
+        Self(normalize(&crop))
+    }
+
+    /// Returns the similarity of two fingerprints, from `0.0` to `1.0`.
+    pub fn similarity(
+        &self,
+        other: &Fingerprint,
+    ) -> f32 {
+        let mut distance = 0.0;
+        for (a, b) in self.0.pixels().zip(other.0.pixels()) {
+            let (a_visible, b_visible) = (a.0[3] >= 128, b.0[3] >= 128);
+            distance += match (a_visible, b_visible) {
+                (true, true) => {
+                    (0..3)
+                        .map(|channel| a.0[channel].abs_diff(b.0[channel]) as f32)
+                        .sum::<f32>()
+                        / (3.0 * 255.0)
+                }
+                (false, false) => 0.0,
+                _ => 1.0,
+            };
+        }
+        let pixels = (FINGERPRINT_SIZE.0 * FINGERPRINT_SIZE.1) as f32;
+        1.0 - distance / pixels
+    }
+}
+
+/// A skin matched by [identify_from_render].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// Name the skin was registered with
+    pub name: String,
+    /// Similarity from `0.0` to `1.0`
+    pub score: f32,
+}
+
+/// Fingerprints of known skins.
+#[derive(Debug, Clone, Default)]
+pub struct SkinIndex {
+    entries: Vec<(String, Fingerprint)>,
+}
+
+impl SkinIndex {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a skin, composed with [TEE_SKIN_LAYOUT] and the normal eyes.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        tee: &Tee,
+    ) {
+        let render = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        self.insert_fingerprint(name, Fingerprint::of_render(&render));
+    }
+
+    /// Registers an already computed fingerprint.
+    pub fn insert_fingerprint(
+        &mut self,
+        name: impl Into<String>,
+        fingerprint: Fingerprint,
+    ) {
+        self.entries.push((name.into(), fingerprint));
+    }
+
+    /// Returns the amount of registered skins.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no skin is registered.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Matches a screenshot crop against the index, best candidates first.
+#[instrument(level = "debug", skip(crop, index), fields(crop = ?crop.dimensions(), skins = index.len()))]
+pub fn identify_from_render(
+    crop: &RgbaImage,
+    index: &SkinIndex,
+) -> Vec<Candidate> {
+    let fingerprint = Fingerprint::of_screenshot(crop);
+    let mut candidates: Vec<_> = index
+        .entries
+        .iter()
+        .map(|(name, known)| Candidate {
+            name: name.clone(),
+            score: fingerprint.similarity(known),
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    debug!(best = ?candidates.first(), "Identified skin candidates");
+    candidates
+}
+
+/// Makes every pixel close to the corner color transparent.
+fn remove_background(img: &mut RgbaImage) {
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 {
+        return;
+    }
+    let corners = [
+        *img.get_pixel(0, 0),
+        *img.get_pixel(width - 1, 0),
+        *img.get_pixel(0, height - 1),
+        *img.get_pixel(width - 1, height - 1),
+    ];
+    let background = Rgba(std::array::from_fn(|channel| {
+        let mut values = corners.map(|pixel| pixel.0[channel]);
+        values.sort_unstable();
+        // Median of the corners, robust against one corner touching the tee
+        ((values[1] as u16 + values[2] as u16) / 2) as u8
+    }));
+
+    for pixel in img.pixels_mut() {
+        let close = (0..3).all(|channel| {
+            pixel.0[channel].abs_diff(background.0[channel]) <= BACKGROUND_TOLERANCE
+        });
+        if close {
+            *pixel = Rgba([0, 0, 0, 0]);
+        }
+    }
+}
+
+/// Trims transparent borders and resizes to [FINGERPRINT_SIZE].
+fn normalize(img: &RgbaImage) -> RgbaImage {
+    if img.width() == 0 || img.height() == 0 {
+        return RgbaImage::new(FINGERPRINT_SIZE.0, FINGERPRINT_SIZE.1);
+    }
+    let visible = img
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| pixel.0[3] >= 128)
+        .map(|(x, y, _)| (x, y));
+    let bounds = visible.fold(None, |bounds, (x, y)| match bounds {
+        None => Some((x, y, x, y)),
+        Some((left, top, right, bottom)) => {
+            Some((left.min(x), top.min(y), right.max(x), bottom.max(y)))
+        }
+    });
+
+    let trimmed = match bounds {
+        Some((left, top, right, bottom)) => {
+            imageops::crop_imm(img, left, top, right - left + 1, bottom - top + 1).to_image()
+        }
+        None => img.clone(),
+    };
+    imageops::resize(
+        &trimmed,
+        FINGERPRINT_SIZE.0,
+        FINGERPRINT_SIZE.1,
+        imageops::FilterType::Triangle,
+    )
+}
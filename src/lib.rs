@@ -1,4 +1,5 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 //! # tee_morphosis
 //!
@@ -18,15 +19,156 @@
 //!     `Tee` parts are drawn. It also contains the placement information (`SkinPS`)
 //!     for where to draw each part on the canvas.
 //!
+//! *   **[`sheet::Sheet`]**: Splits any asset sheet into named parts described by a
+//!     [`sheet::SheetLayout`]. `Tee` is parsed through it as well, and [assets]
+//!     splits the client asset sheets such as `game.png`.
+//!
+//! *   **[`core`]**: The layout and color math of the types above, free of `std` and of
+//!     the imaging stack.
+//!
+//! ## Animated sources
+//!
+//! Animated sources (GIF, APNG, animated WebP) are parsed from their first frame, see
+//! [tee::options::ParseOptions::frame] to pick another one. Sheets of stacked frames
+//! are parsed with [Tee::new_animated] and encoded back with [animation].
+//!
+//! ## Telemetry
+//!
+//! Spans and events are emitted at `debug` level and below, see [telemetry] for
+//! redacting URLs from them.
+//!
 //! ## available features:
-//! - `net`: include tokio for [Tee::new_from_url]
+//! - `std` (default): everything outside [`core`] and the dependencies it needs.
+//!   Without it the crate is `no_std` and only [`core`] is left, every other feature
+//!   enables `std`
+//! - `net`: include tokio for [Tee::new_from_url], the skin database client in [db] and
+//!   the [service::RenderService] pipeline
+//! - `text`: include ab_glyph for drawing text into a [scene::Scene] and the cards of
+//!   `cards`
+//! - `templates`: include serde_json for card layouts loaded at runtime, see
+//!   `cards::layout`
+//! - `moderation`: include the bundled [moderation::ContentChecker] heuristics
+//! - `watch`: poll an asset directory and reload changed skins, fonts, UV presets and
+//!   card templates, see `watch`
+//! - `web`: read skins from `multipart/form-data` uploads of web services, see `web`
+//! - `testing`: include proptest for the layout invariants and strategies of `testing`
+//! - `gpu`: include wgpu for blending layers and scenes on the GPU, see
+//!   [tee::compositor::CompositorBackend] and [scene::Scene]
+//! - `ffmpeg`: encode animations into WebM and MP4 with an installed ffmpeg, see
+//!   `animation::video`
 
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod animation;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod assets;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod cache;
+#[cfg(feature = "text")]
+#[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+pub mod cards;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod colorblind;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod config;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod contrast;
+pub mod core;
+#[cfg(feature = "net")]
+#[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+pub mod db;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod diff;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 pub mod error;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod estimate;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod etag;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod health;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod identify;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod locale;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod lottie;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod meta;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod moderation;
+#[cfg(feature = "net")]
+#[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+pub mod net;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod pack;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod plan;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod scene;
+#[cfg(feature = "net")]
+#[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+pub mod service;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod sheet;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod skin_name;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 pub mod tee;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod telemetry;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+#[cfg(feature = "watch")]
+#[cfg_attr(docsrs, doc(cfg(feature = "watch")))]
+pub mod watch;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod watermark;
+#[cfg(feature = "web")]
+#[cfg_attr(docsrs, doc(cfg(feature = "web")))]
+pub mod web;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod worker;
+
+#[cfg(feature = "std")]
+pub use health::self_test;
+
+/// Version of the render output, bumped whenever the pixels of a render can change
+/// between crate releases, e.g. after filter tweaks or blending fixes.
+///
+/// It is part of [RenderMeta](meta::RenderMeta), of composed ETags and therefore of
+/// [RenderCache](cache::RenderCache) keys, and of [render_filename](meta::render_filename),
+/// so caches keyed by them drop stale renders after an upgrade.
+pub const RENDER_VERSION: u32 = 1;
 
-#[cfg(doc)]
+#[cfg(all(doc, feature = "std"))]
 use tee::Tee;
-#[cfg(doc)]
+#[cfg(all(doc, feature = "std"))]
 use tee::skin::Skin;
-#[cfg(doc)]
+#[cfg(all(doc, feature = "std"))]
 use tee::uv::UV;
@@ -0,0 +1,445 @@
+//! # Locale module
+//!
+//! Error messages and [suggestions](TeeError::suggestion) in the languages of the larger
+//! DDNet communities, for bots showing them to their users. English is the
+//! [Display](std::fmt::Display) output of [TeeError], the other languages are
+//! translated from it.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::locale::Locale;
+//!
+//! let locale = Locale::from_tag(&interaction.locale).unwrap_or_default();
+//! if let Err(e) = Tee::new(data, ImageFormat::Png) {
+//!     reply(e.localized(locale), e.suggestion_in(locale));
+//! }
+//! ```
+
+use crate::error::TeeError;
+
+/// Language of user facing messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    /// English
+    #[default]
+    English,
+    /// Russian
+    Russian,
+    /// German
+    German,
+}
+
+impl Locale {
+    /// Every supported language.
+    pub const ALL: [Locale; 3] = [Locale::English, Locale::Russian, Locale::German];
+
+    /// Parses a language tag like `"ru"`, `"de-AT"` or `"en_US"`, ignoring the region.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::English),
+            "ru" => Some(Locale::Russian),
+            "de" => Some(Locale::German),
+            _ => None,
+        }
+    }
+
+    /// Returns the two letter code of the language, e.g. `"ru"`.
+    pub const fn tag(&self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::Russian => "ru",
+            Locale::German => "de",
+        }
+    }
+}
+
+/// Hints returned by [TeeError::suggestion].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Hint {
+    #[cfg(feature = "net")]
+    ServerDown,
+    #[cfg(feature = "net")]
+    NotAnImageLink,
+    #[cfg(feature = "net")]
+    Busy,
+    #[cfg(feature = "net")]
+    ForbiddenLink,
+    Jpeg,
+    UnsupportedFormat,
+    TooLarge,
+    Damaged,
+    HighResolution,
+    Upright,
+    NotASkin,
+    CutOff,
+    MissingFrame,
+    UvLayout,
+    Rejected,
+    Spelling,
+    TooSlow,
+}
+
+impl Hint {
+    /// Returns the hint in `locale`.
+    pub(crate) fn text(
+        self,
+        locale: Locale,
+    ) -> &'static str {
+        let [en, ru, de] = match self {
+            #[cfg(feature = "net")]
+            Hint::ServerDown => [
+                "The skin server did not respond, try again later.",
+                "Сервер скинов не ответил, попробуйте позже.",
+                "Der Skin-Server antwortet nicht, versuche es später erneut.",
+            ],
+            #[cfg(feature = "net")]
+            Hint::NotAnImageLink => [
+                "The link does not point to an image, link the PNG file itself.",
+                "Ссылка ведёт не на изображение, укажите ссылку на сам PNG-файл.",
+                "Der Link zeigt auf kein Bild, verlinke die PNG-Datei selbst.",
+            ],
+            #[cfg(feature = "net")]
+            Hint::Busy => [
+                "The renderer is busy, try again in a few seconds.",
+                "Рендерер занят, попробуйте через несколько секунд.",
+                "Der Renderer ist ausgelastet, versuche es in ein paar Sekunden erneut.",
+            ],
+            #[cfg(feature = "net")]
+            Hint::ForbiddenLink => [
+                "Skins can not be loaded from this link, upload the file or use a skin database.",
+                "Скины нельзя загружать по этой ссылке, загрузите файл или используйте базу скинов.",
+                "Von diesem Link können keine Skins geladen werden, lade die Datei hoch oder nutze eine Skin-Datenbank.",
+            ],
+            Hint::Jpeg => [
+                "JPEG can not store transparency, save the skin as PNG.",
+                "JPEG не поддерживает прозрачность, сохраните скин в PNG.",
+                "JPEG kann keine Transparenz speichern, speichere den Skin als PNG.",
+            ],
+            Hint::UnsupportedFormat => [
+                "The image format is not supported, save the skin as PNG.",
+                "Формат изображения не поддерживается, сохраните скин в PNG.",
+                "Das Bildformat wird nicht unterstützt, speichere den Skin als PNG.",
+            ],
+            Hint::TooLarge => [
+                "The image is too large, skins are 256x128 pixels or a multiple of it.",
+                "Изображение слишком большое, скины имеют размер 256x128 пикселей или кратный ему.",
+                "Das Bild ist zu groß, Skins sind 256x128 Pixel groß oder ein Vielfaches davon.",
+            ],
+            Hint::Damaged => [
+                "The image is damaged, export it as PNG again.",
+                "Изображение повреждено, экспортируйте его в PNG заново.",
+                "Das Bild ist beschädigt, exportiere es erneut als PNG.",
+            ],
+            Hint::HighResolution => [
+                "The image is a high resolution skin, parse it with a scaled UV layout.",
+                "Это скин высокого разрешения, разбирайте его с масштабированной UV-разметкой.",
+                "Das Bild ist ein hochauflösender Skin, lies es mit einem skalierten UV-Layout ein.",
+            ],
+            Hint::Upright => [
+                "The image is upright, skins are twice as wide as they are high.",
+                "Изображение вертикальное, ширина скина вдвое больше высоты.",
+                "Das Bild ist hochkant, Skins sind doppelt so breit wie hoch.",
+            ],
+            Hint::NotASkin => [
+                "The image is not a skin, skins are 256x128 pixels or a multiple of it.",
+                "Изображение не является скином, скины имеют размер 256x128 пикселей или кратный ему.",
+                "Das Bild ist kein Skin, Skins sind 256x128 Pixel groß oder ein Vielfaches davon.",
+            ],
+            Hint::CutOff => [
+                "The image is cut off, check its size or parse it with ExtractPolicy::Clamp.",
+                "Изображение обрезано, проверьте его размер или разбирайте с ExtractPolicy::Clamp.",
+                "Das Bild ist abgeschnitten, prüfe seine Größe oder lies es mit ExtractPolicy::Clamp ein.",
+            ],
+            Hint::MissingFrame => [
+                "Pick a frame the animation has.",
+                "Выберите кадр, который есть в анимации.",
+                "Wähle ein Bild, das die Animation enthält.",
+            ],
+            Hint::UvLayout => [
+                "Place every part of the UV layout once, without overlapping another.",
+                "Разместите каждую часть UV-разметки один раз, без наложений.",
+                "Platziere jeden Teil des UV-Layouts genau einmal, ohne Überlappungen.",
+            ],
+            Hint::Rejected => [
+                "The skin is not allowed here, pick another one.",
+                "Этот скин здесь запрещён, выберите другой.",
+                "Dieser Skin ist hier nicht erlaubt, wähle einen anderen.",
+            ],
+            Hint::Spelling => [
+                "Check the spelling of the skin name.",
+                "Проверьте написание названия скина.",
+                "Prüfe die Schreibweise des Skin-Namens.",
+            ],
+            Hint::TooSlow => [
+                "Rendering took too long, try again or ask for a smaller image.",
+                "Рендер занял слишком много времени, попробуйте снова или запросите изображение меньше.",
+                "Das Rendern hat zu lange gedauert, versuche es erneut oder fordere ein kleineres Bild an.",
+            ],
+        };
+        match locale {
+            Locale::English => en,
+            Locale::Russian => ru,
+            Locale::German => de,
+        }
+    }
+}
+
+impl TeeError {
+    /// Returns the message of the error in `locale`, the [Display](std::fmt::Display)
+    /// output for [Locale::English].
+    ///
+    /// Errors of other crates wrapped by the error, like decoding errors, keep their
+    /// English text.
+    pub fn localized(
+        &self,
+        locale: Locale,
+    ) -> String {
+        let pick = |ru: String, de: String| match locale {
+            Locale::Russian => ru,
+            _ => de,
+        };
+        match (locale, self) {
+            (Locale::English, _) => self.to_string(),
+            #[cfg(feature = "net")]
+            (_, TeeError::Reqwest(e)) => {
+                let e = crate::telemetry::request_error(e);
+                pick(
+                    format!("Ошибка при загрузке по ссылке: {e}"),
+                    format!("Fehler beim Laden der URL: {e}"),
+                )
+            }
+            #[cfg(feature = "net")]
+            (_, TeeError::Join(e)) => pick(
+                format!("Ошибка фоновой задачи: {e}"),
+                format!("Fehler in einer Hintergrundaufgabe: {e}"),
+            ),
+            #[cfg(feature = "net")]
+            (_, TeeError::ReqWithOutContentType(url)) => {
+                let url = crate::telemetry::url(url);
+                pick(
+                    format!("Ответ не содержит изображения: {url}"),
+                    format!("Die Antwort enthält kein Bild: {url}"),
+                )
+            }
+            #[cfg(any(feature = "net", feature = "templates"))]
+            (_, TeeError::Json(e)) => pick(
+                format!("Ошибка при разборе JSON: {e}"),
+                format!("Fehler beim Lesen von JSON: {e}"),
+            ),
+            #[cfg(feature = "net")]
+            (_, TeeError::DbIndexUnavailable) => pick(
+                "База скинов не публикует индекс".to_string(),
+                "Die Skin-Datenbank veröffentlicht keinen Index".to_string(),
+            ),
+            #[cfg(feature = "net")]
+            (_, TeeError::ServiceOverloaded) => pick(
+                "Очередь рендера переполнена".to_string(),
+                "Die Render-Warteschlange ist voll".to_string(),
+            ),
+            #[cfg(feature = "net")]
+            (_, TeeError::InvalidRateLimit(rate)) => pick(
+                format!(
+                    "Неверный лимит в {rate} запросов в секунду, он должен быть положительным и конечным"
+                ),
+                format!(
+                    "Ungültiges Limit von {rate} Anfragen pro Sekunde, es muss positiv und endlich sein"
+                ),
+            ),
+            #[cfg(feature = "net")]
+            (_, TeeError::UrlNotAllowed(url)) => {
+                let url = crate::telemetry::url(url);
+                pick(
+                    format!("Ссылка не разрешена: {url}"),
+                    format!("Die URL ist nicht erlaubt: {url}"),
+                )
+            }
+            #[cfg(feature = "net")]
+            (
+                _,
+                TeeError::AddressNotAllowed {
+                    host,
+                    address,
+                },
+            ) => pick(
+                format!("Адрес {address} хоста {host} не разрешён"),
+                format!("Die Adresse {address} von {host} ist nicht erlaubt"),
+            ),
+            #[cfg(feature = "watch")]
+            (_, TeeError::Watch(e)) => pick(
+                format!("Не удалось отслеживать папку ресурсов: {e}"),
+                format!("Der Asset-Ordner kann nicht überwacht werden: {e}"),
+            ),
+            #[cfg(feature = "web")]
+            (_, TeeError::UploadTooLarge { size, max }) => pick(
+                format!("Загрузка размером {size} байт превышает лимит в {max} байт"),
+                format!("Der Upload mit {size} Bytes überschreitet das Limit von {max} Bytes"),
+            ),
+            #[cfg(feature = "web")]
+            (_, TeeError::MalformedUpload(reason)) => pick(
+                format!("Неверная multipart-загрузка: {reason}"),
+                format!("Fehlerhafter Multipart-Upload: {reason}"),
+            ),
+            #[cfg(feature = "web")]
+            (_, TeeError::MissingUploadField(name)) => pick(
+                format!("В загрузке нет поля {name}"),
+                format!("Der Upload enthält kein Feld {name}"),
+            ),
+            #[cfg(feature = "ffmpeg")]
+            (_, TeeError::Process(e)) => pick(
+                format!("Не удалось запустить ffmpeg: {e}"),
+                format!("ffmpeg konnte nicht gestartet werden: {e}"),
+            ),
+            #[cfg(feature = "ffmpeg")]
+            (_, TeeError::Ffmpeg(output)) => pick(
+                format!("Ошибка ffmpeg: {output}"),
+                format!("ffmpeg ist fehlgeschlagen: {output}"),
+            ),
+            (_, TeeError::InvalidBuilderConfiguration) => pick(
+                "Неверная настройка сборщика. Укажите данные и формат или ссылку".to_string(),
+                "Ungültige Builder-Konfiguration. Gib Daten und Format oder eine URL an"
+                    .to_string(),
+            ),
+            (_, TeeError::Image(e)) => pick(
+                format!("Ошибка обработки изображения: {e}"),
+                format!("Fehler beim Verarbeiten des Bildes: {e}"),
+            ),
+            (
+                _,
+                TeeError::OutOfBounds {
+                    part,
+                    width,
+                    height,
+                },
+            ) => pick(
+                format!(
+                    "Часть {}x{} в точке ({}, {}) выходит за границы изображения {width}x{height}",
+                    part.w, part.h, part.x, part.y
+                ),
+                format!(
+                    "Der Teil {}x{} bei ({}, {}) liegt außerhalb des Bildes {width}x{height}",
+                    part.w, part.h, part.x, part.y
+                ),
+            ),
+            (
+                _,
+                TeeError::InvalidDimensions {
+                    expected,
+                    found,
+                },
+            ) => pick(
+                format!(
+                    "Неверный размер изображения. Ожидалось {}x{}, получено {}x{}.",
+                    expected.0, expected.1, found.0, found.1
+                ),
+                format!(
+                    "Ungültige Bildgröße. Erwartet {}x{}, gefunden {}x{}.",
+                    expected.0, expected.1, found.0, found.1
+                ),
+            ),
+            (
+                _,
+                TeeError::FrameOutOfRange {
+                    index,
+                    frames,
+                },
+            ) => pick(
+                format!("Кадра {index} нет, в источнике кадров: {frames}."),
+                format!("Bild {index} existiert nicht, die Quelle hat {frames} Bild(er)."),
+            ),
+            (_, TeeError::EmptyAnimation) => pick(
+                "В анимации нет кадров".to_string(),
+                "Die Animation hat keine Bilder".to_string(),
+            ),
+            (_, TeeError::UnknownSheetPart(name)) => pick(
+                format!("В разметке листа нет части {name}"),
+                format!("Das Sheet-Layout hat keinen Teil namens {name}"),
+            ),
+            (_, TeeError::MissingUvPart(name)) => pick(
+                format!("В UV-разметке нет части {name}"),
+                format!("Dem UV-Layout fehlt der Teil {name}"),
+            ),
+            (
+                _,
+                TeeError::OverlappingUvParts {
+                    first,
+                    second,
+                },
+            ) => pick(
+                format!("Части UV-разметки {first} и {second} накладываются"),
+                format!("Die UV-Teile {first} und {second} überlappen sich"),
+            ),
+            (_, TeeError::MalformedContainer(reason)) => pick(
+                format!("Повреждённый файл изображения: {reason}"),
+                format!("Beschädigte Bilddatei: {reason}"),
+            ),
+            (_, TeeError::ContentRejected(reason)) => pick(
+                format!("Скин отклонён проверкой содержимого: {reason}"),
+                format!("Der Skin wurde von der Inhaltsprüfung abgelehnt: {reason}"),
+            ),
+            (_, TeeError::UnknownSkin(name)) => pick(
+                format!("Неизвестный скин: {name}"),
+                format!("Unbekannter Skin: {name}"),
+            ),
+            (_, TeeError::SelfTest(reason)) => pick(
+                format!("Самопроверка не пройдена: {reason}"),
+                format!("Selbsttest fehlgeschlagen: {reason}"),
+            ),
+            (
+                _,
+                TeeError::Config {
+                    key,
+                    reason,
+                },
+            ) => pick(
+                format!("Неверная настройка {key}: {reason}"),
+                format!("Ungültige Konfiguration {key}: {reason}"),
+            ),
+            (_, TeeError::DeadlineExceeded { stage }) => pick(
+                format!("Время истекло до этапа {stage}"),
+                format!("Zeitlimit vor {stage} überschritten"),
+            ),
+            (_, TeeError::Template(reason)) => pick(
+                format!("Неверный шаблон карточки: {reason}"),
+                format!("Ungültiges Kartenlayout: {reason}"),
+            ),
+            (_, TeeError::InvalidFont) => pick(
+                "Файл шрифта не является шрифтом TrueType или OpenType".to_string(),
+                "Die Schriftdatei ist keine TrueType- oder OpenType-Schrift".to_string(),
+            ),
+            (_, TeeError::MalformedDataUrl(reason)) => pick(
+                format!("Неверный data URL: {reason}"),
+                format!("Fehlerhafte Data-URL: {reason}"),
+            ),
+            (
+                _,
+                TeeError::MalformedFlagIndex {
+                    line,
+                    reason,
+                },
+            ) => pick(
+                format!("Ошибка в индексе флагов в строке {line}: {reason}"),
+                format!("Fehlerhafter Flaggenindex in Zeile {line}: {reason}"),
+            ),
+            (
+                _,
+                TeeError::MalformedUvPreset {
+                    line,
+                    reason,
+                },
+            ) => pick(
+                format!("Ошибка в UV-пресете в строке {line}: {reason}"),
+                format!("Fehlerhaftes UV-Preset in Zeile {line}: {reason}"),
+            ),
+        }
+    }
+
+    /// Returns the [suggestion](TeeError::suggestion) in `locale`.
+    pub fn suggestion_in(
+        &self,
+        locale: Locale,
+    ) -> Option<&'static str> {
+        self.hint().map(|hint| hint.text(locale))
+    }
+}
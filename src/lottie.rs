@@ -0,0 +1,308 @@
+//! # Lottie module
+//!
+//! Exports the layers of a composed tee as a [Lottie](https://lottiefiles.github.io/lottie-docs/)
+//! animation, so web frontends can play eye swaps and bouncing feet with a Lottie player
+//! instead of downloading a GIF per state. Every layer is an image layer with its part
+//! embedded as a PNG data URI, already resized to its size on the skin.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::lottie::{LottieOptions, to_lottie};
+//!
+//! let options = LottieOptions::new()
+//!     .with_eyes([EyeType::Normal, EyeType::Blink, EyeType::Normal])
+//!     .with_foot_bounce(4);
+//! let json = to_lottie(&tee, TEE_SKIN_LAYOUT, &options)?;
+//! std::fs::write("tee.json", json)?;
+//! ```
+
+use image::{ImageFormat, RgbaImage, imageops};
+use tracing::{debug, instrument, trace};
+
+use crate::{
+    error::Result,
+    tee::{
+        Tee,
+        data_url::encode_base64,
+        parts::EyeType,
+        raw::encode_image,
+        skin::{Skin, SkinPS},
+        uv::UvPart,
+    },
+};
+
+/// Lottie format version written into the export.
+pub const LOTTIE_VERSION: &str = "5.7.4";
+
+/// Options of [to_lottie].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LottieOptions {
+    /// Frames per second
+    pub frame_rate: u32,
+    /// Length of the animation in frames
+    pub frames: u32,
+    /// Eyes shown one after another, each for an equal share of the animation
+    pub eyes: Vec<EyeType>,
+    /// How many pixels the feet rise halfway through the animation, `0` keeps them still
+    pub foot_bounce: u32,
+}
+
+impl Default for LottieOptions {
+    fn default() -> Self {
+        Self {
+            frame_rate: 30,
+            frames: 60,
+            eyes: vec![EyeType::Normal],
+            foot_bounce: 0,
+        }
+    }
+}
+
+impl LottieOptions {
+    /// Creates options for a still tee with normal eyes, two seconds at 30 frames per
+    /// second.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the frames per second, at least one.
+    pub fn with_frame_rate(
+        mut self,
+        frame_rate: u32,
+    ) -> Self {
+        self.frame_rate = frame_rate.max(1);
+        self
+    }
+
+    /// Sets the length of the animation in frames, at least one.
+    pub fn with_frames(
+        mut self,
+        frames: u32,
+    ) -> Self {
+        self.frames = frames.max(1);
+        self
+    }
+
+    /// Sets the sequence of eyes, an empty sequence shows normal eyes.
+    pub fn with_eyes(
+        mut self,
+        eyes: impl IntoIterator<Item = EyeType>,
+    ) -> Self {
+        self.eyes = eyes.into_iter().collect();
+        if self.eyes.is_empty() {
+            self.eyes.push(EyeType::Normal);
+        }
+        self
+    }
+
+    /// Sets how many pixels the feet rise.
+    pub fn with_foot_bounce(
+        mut self,
+        pixels: u32,
+    ) -> Self {
+        self.foot_bounce = pixels;
+        self
+    }
+}
+
+/// A layer of the export, in compose order.
+struct LottieLayer {
+    name: String,
+    image: RgbaImage,
+    position: (i64, i64),
+    /// Frames the layer is visible in
+    range: (u32, u32),
+    bounce: bool,
+}
+
+/// Exports a tee composed onto `skin` as Lottie JSON.
+///
+/// Layers are stacked like [`Tee::compose_image`] draws them, so a still export looks
+/// like the composed image.
+///
+/// # Returns
+///
+/// A `Result` which is `Ok(String)` with the JSON document, or `Err(TeeError)` if a part
+/// could not be encoded.
+#[instrument(level = "debug", skip(tee, skin), fields(skin_container = ?skin.container))]
+pub fn to_lottie(
+    tee: &Tee,
+    skin: Skin,
+    options: &LottieOptions,
+) -> Result<String> {
+    let frames = options.frames.max(1);
+    let uv = &tee.used_uv;
+    let mut layers = Vec::new();
+    let mut push = |name: String,
+                    image: &RgbaImage,
+                    placement: SkinPS,
+                    part: UvPart,
+                    range: (u32, u32),
+                    bounce: bool| {
+        let (position, (w, h)) = skin.place(placement, (part.w, part.h));
+        layers.push(LottieLayer {
+            name,
+            image: imageops::resize(image, w, h, imageops::FilterType::Triangle),
+            position,
+            range,
+            bounce,
+        });
+    };
+
+    // Same order as compose_layers
+    let all = (0, frames);
+    let bounce = options.foot_bounce > 0;
+    push(
+        "body_shadow".into(),
+        &tee.body.shadow,
+        skin.body,
+        uv.body_shadow,
+        all,
+        false,
+    );
+    push(
+        "feet_back_shadow".into(),
+        &tee.feet.shadow,
+        skin.feet_back,
+        uv.feet_shadow,
+        all,
+        bounce,
+    );
+    push(
+        "feet_shadow".into(),
+        &tee.feet.shadow,
+        skin.feet,
+        uv.feet_shadow,
+        all,
+        bounce,
+    );
+    push(
+        "feet_back".into(),
+        &tee.feet.value,
+        skin.feet_back,
+        uv.feet,
+        all,
+        bounce,
+    );
+    push(
+        "body".into(),
+        &tee.body.value,
+        skin.body,
+        uv.body,
+        all,
+        false,
+    );
+
+    let eyes = if options.eyes.is_empty() { &[EyeType::Normal][..] } else { &options.eyes };
+    let count = eyes.len() as u32;
+    for (index, eye_type) in eyes.iter().enumerate() {
+        let index = index as u32;
+        let range = (index * frames / count, (index + 1) * frames / count);
+        let eye = tee.get_eye(*eye_type);
+        let name = format!("eye_{}_{index}", eye_type.name());
+        push(
+            format!("{name}_first"),
+            eye,
+            skin.first_eyes,
+            uv.eyes[0],
+            range,
+            false,
+        );
+        push(
+            format!("{name}_second"),
+            &imageops::flip_horizontal(eye),
+            skin.second_eyes,
+            uv.eyes[0],
+            range,
+            false,
+        );
+    }
+    push(
+        "feet".into(),
+        &tee.feet.value,
+        skin.feet,
+        uv.feet,
+        all,
+        bounce,
+    );
+
+    let mut assets = Vec::with_capacity(layers.len());
+    for layer in &layers {
+        let png = encode_image(&layer.image, ImageFormat::Png)?;
+        assets.push(format!(
+            r#"{{"id":"{}","w":{},"h":{},"u":"","p":"data:image/png;base64,{}","e":1}}"#,
+            layer.name,
+            layer.image.width(),
+            layer.image.height(),
+            encode_base64(&png),
+        ));
+    }
+
+    // Lottie draws the first layer on top
+    let lottie_layers: Vec<String> = layers
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, layer)| {
+            let position = if layer.bounce {
+                bounce_keyframes(layer.position, options.foot_bounce, frames)
+            } else {
+                format!(
+                    r#"{{"a":0,"k":[{},{},0]}}"#,
+                    layer.position.0, layer.position.1
+                )
+            };
+            format!(
+                concat!(
+                    r#"{{"ddd":0,"ind":{},"ty":2,"nm":"{}","refId":"{}","sr":1,"#,
+                    r#""ks":{{"o":{{"a":0,"k":100}},"r":{{"a":0,"k":0}},"p":{},"#,
+                    r#""a":{{"a":0,"k":[0,0,0]}},"s":{{"a":0,"k":[100,100,100]}}}},"#,
+                    r#""ao":0,"ip":{},"op":{},"st":0,"bm":0}}"#
+                ),
+                index + 1,
+                layer.name,
+                layer.name,
+                position,
+                layer.range.0,
+                layer.range.1,
+            )
+        })
+        .collect();
+
+    let (width, height) = skin.output_size();
+    let json = format!(
+        concat!(
+            r#"{{"v":"{}","fr":{},"ip":0,"op":{},"w":{},"h":{},"nm":"tee","ddd":0,"#,
+            r#""assets":[{}],"layers":[{}]}}"#
+        ),
+        LOTTIE_VERSION,
+        options.frame_rate.max(1),
+        frames,
+        width,
+        height,
+        assets.join(","),
+        lottie_layers.join(","),
+    );
+    debug!(
+        layers = layers.len(),
+        size = json.len(),
+        "Exported Lottie animation"
+    );
+    Ok(json)
+}
+
+/// Position keyframes rising by `amplitude` halfway and landing at the end, eased.
+fn bounce_keyframes(
+    (x, y): (i64, i64),
+    amplitude: u32,
+    frames: u32,
+) -> String {
+    trace!(amplitude, frames, "Adding bounce keyframes");
+    let ease = r#""i":{"x":[0.667],"y":[1]},"o":{"x":[0.333],"y":[0]}"#;
+    let top = y - amplitude as i64;
+    format!(
+        r#"{{"a":1,"k":[{{"t":0,"s":[{x},{y},0],{ease}}},{{"t":{},"s":[{x},{top},0],{ease}}},{{"t":{frames},"s":[{x},{y},0]}}]}}"#,
+        frames / 2,
+    )
+}
@@ -0,0 +1,610 @@
+//! # Metadata module
+//!
+//! Embeds how a render was produced into the encoded image, so caches downstream can
+//! identify it without keeping a side table. PNG outputs get `tEXt`/`iTXt` chunks, WebP
+//! outputs get an XMP packet. Other formats are left untouched.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::{meta::RenderMeta, tee::options::ComposeOptions};
+//!
+//! let options = ComposeOptions::new().with_metadata(
+//!     RenderMeta::new()
+//!         .with_skin_name("nameless tee")
+//!         .with_source_url("https://ddnet.org/skins/skin/nameless%20tee.png"),
+//! );
+//! // eye, size and source hash are filled from the render itself
+//! let bytes = tee.compose_with_options(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png, &options)?;
+//! ```
+
+use bytes::Bytes;
+use image::ImageFormat;
+use sha2::{Digest, Sha256};
+use tracing::{instrument, trace};
+
+use crate::{
+    RENDER_VERSION,
+    error::{Result, TeeError},
+    tee::hash::SourceHash,
+};
+
+/// Generator recorded when none is set, `tee_morphosis <version>`.
+pub const GENERATOR: &str = concat!("tee_morphosis ", env!("CARGO_PKG_VERSION"));
+
+/// Prefix of every key written by [RenderMeta], except the generator which uses the
+/// standard `Software` key.
+pub const KEY_PREFIX: &str = "tee_morphosis:";
+
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+const XMP_NAMESPACE: &str = "https://github.com/PulseClient-ddnet/tee-morphosis/ns/1.0/";
+
+/// Describes how a render was produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderMeta {
+    /// Name of the skin
+    pub skin_name: Option<String>,
+    /// Where the skin was fetched from
+    pub source_url: Option<String>,
+    /// Hash of the source image, see [Tee::source_hash](crate::tee::Tee::source_hash)
+    pub source_hash: Option<SourceHash>,
+    /// Body color in ddnet format
+    pub body_color: Option<u32>,
+    /// Feet color in ddnet format
+    pub feet_color: Option<u32>,
+    /// Name of the eye the Tee was composed with
+    pub eye: Option<String>,
+    /// Size of the render in pixels
+    pub size: Option<(u32, u32)>,
+    /// Software that produced the render, [GENERATOR] by default
+    pub generator: String,
+    /// [RENDER_VERSION] of the software that produced the render, the current one by
+    /// default and `None` when read from a render that does not record it
+    pub render_version: Option<u32>,
+}
+
+impl Default for RenderMeta {
+    fn default() -> Self {
+        Self {
+            skin_name: None,
+            source_url: None,
+            source_hash: None,
+            body_color: None,
+            feet_color: None,
+            eye: None,
+            size: None,
+            generator: GENERATOR.to_string(),
+            render_version: Some(RENDER_VERSION),
+        }
+    }
+}
+
+impl RenderMeta {
+    /// Creates empty metadata with the default generator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the skin name.
+    pub fn with_skin_name(
+        mut self,
+        name: impl Into<String>,
+    ) -> Self {
+        self.skin_name = Some(name.into());
+        self
+    }
+
+    /// Sets the source URL.
+    pub fn with_source_url(
+        mut self,
+        url: impl Into<String>,
+    ) -> Self {
+        self.source_url = Some(url.into());
+        self
+    }
+
+    /// Sets the source hash.
+    pub fn with_source_hash(
+        mut self,
+        hash: SourceHash,
+    ) -> Self {
+        self.source_hash = Some(hash);
+        self
+    }
+
+    /// Sets the body and feet colors in ddnet format.
+    pub fn with_colors(
+        mut self,
+        body: u32,
+        feet: u32,
+    ) -> Self {
+        self.body_color = Some(body);
+        self.feet_color = Some(feet);
+        self
+    }
+
+    /// Sets the eye name.
+    pub fn with_eye(
+        mut self,
+        eye: impl Into<String>,
+    ) -> Self {
+        self.eye = Some(eye.into());
+        self
+    }
+
+    /// Sets the render size.
+    pub fn with_size(
+        mut self,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    /// Returns the stored entries as `(key, value)` pairs, generator first.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![("Software".to_string(), self.generator.clone())];
+        let mut push = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                entries.push((format!("{KEY_PREFIX}{key}"), value));
+            }
+        };
+        push("skin", self.skin_name.clone());
+        push("source_url", self.source_url.clone());
+        push("source_hash", self.source_hash.map(|h| h.to_hex()));
+        push("body_color", self.body_color.map(|c| c.to_string()));
+        push("feet_color", self.feet_color.map(|c| c.to_string()));
+        push("eye", self.eye.clone());
+        push("size", self.size.map(|(w, h)| format!("{w}x{h}")));
+        push("render_version", self.render_version.map(|v| v.to_string()));
+        entries
+    }
+
+    /// Returns the metadata as an XMP packet.
+    pub fn to_xmp(&self) -> String {
+        let mut attributes = format!(
+            " xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\" xmlns:tm=\"{XMP_NAMESPACE}\" xmp:CreatorTool=\"{}\"",
+            xml_escape(&self.generator)
+        );
+        for (key, value) in self.entries().into_iter().skip(1) {
+            let key = key.trim_start_matches(KEY_PREFIX);
+            attributes.push_str(&format!(" tm:{key}=\"{}\"", xml_escape(&value)));
+        }
+        format!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\
+             <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\
+             <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+             <rdf:Description rdf:about=\"\"{attributes}/>\
+             </rdf:RDF></x:xmpmeta><?xpacket end=\"w\"?>"
+        )
+    }
+
+    /// Embeds the metadata into an encoded image.
+    ///
+    /// PNG and WebP are supported, any other format is returned unchanged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(Bytes)` with the metadata embedded, or
+    /// `Err(TeeError::MalformedContainer)` if `data` is not a valid container of `format`.
+    #[instrument(level = "debug", skip(self, data), fields(format = ?format))]
+    pub fn embed(
+        &self,
+        data: Bytes,
+        format: ImageFormat,
+    ) -> Result<Bytes> {
+        match format {
+            ImageFormat::Png => self.embed_png(&data),
+            ImageFormat::WebP => self.embed_webp(&data),
+            _ => {
+                trace!("Format does not carry metadata, skipping");
+                Ok(data)
+            }
+        }
+    }
+
+    /// Reads metadata embedded by [RenderMeta::embed] back from PNG bytes.
+    ///
+    /// Both `tEXt` and uncompressed `iTXt` chunks are read, unknown keys are ignored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(Some(RenderMeta))` if the image carries metadata written by
+    /// this crate, `Ok(None)` if it does not, or `Err(TeeError::MalformedContainer)` if
+    /// `data` is not a PNG.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::meta::RenderMeta;
+    ///
+    /// let meta = RenderMeta::from_png(&fs::read("render.png")?)?.expect("not rendered by tee_morphosis");
+    /// let (width, height) = meta.size.unwrap();
+    /// ```
+    #[instrument(level = "debug", skip(data))]
+    pub fn from_png(data: &[u8]) -> Result<Option<Self>> {
+        let mut meta = Self::new();
+        meta.render_version = None;
+        let mut found = false;
+        for chunk in png_chunks(data)? {
+            let payload = &data[chunk.data];
+            let Some((key, value)) = (match &chunk.kind {
+                b"tEXt" => parse_text(payload),
+                b"iTXt" => parse_itxt(payload),
+                _ => None,
+            }) else {
+                continue;
+            };
+            if key == "Software" {
+                found |= value.starts_with("tee_morphosis");
+                meta.generator = value;
+                continue;
+            }
+            let Some(key) = key.strip_prefix(KEY_PREFIX) else {
+                continue;
+            };
+            found = true;
+            match key {
+                "skin" => meta.skin_name = Some(value),
+                "source_url" => meta.source_url = Some(value),
+                "source_hash" => meta.source_hash = SourceHash::from_hex(&value),
+                "body_color" => meta.body_color = value.parse().ok(),
+                "feet_color" => meta.feet_color = value.parse().ok(),
+                "eye" => meta.eye = Some(value),
+                "size" => {
+                    meta.size = value
+                        .split_once('x')
+                        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                }
+                "render_version" => meta.render_version = value.parse().ok(),
+                _ => trace!(key, "Skipping unknown metadata key"),
+            }
+        }
+        Ok(found.then_some(meta))
+    }
+
+    fn embed_png(
+        &self,
+        data: &[u8],
+    ) -> Result<Bytes> {
+        let iend = png_chunks(data)?
+            .into_iter()
+            .find(|chunk| &chunk.kind == b"IEND")
+            .ok_or(TeeError::MalformedContainer("png has no IEND chunk"))?;
+
+        let mut out = Vec::with_capacity(data.len() + 256);
+        out.extend_from_slice(&data[..iend.offset]);
+        for (key, value) in self.entries() {
+            if value.is_ascii() {
+                let mut body = key.into_bytes();
+                body.push(0);
+                body.extend_from_slice(value.as_bytes());
+                write_png_chunk(&mut out, b"tEXt", &body);
+            } else {
+                // keyword, no compression, empty language tag and translated keyword
+                let mut body = key.into_bytes();
+                body.extend_from_slice(&[0, 0, 0, 0, 0]);
+                body.extend_from_slice(value.as_bytes());
+                write_png_chunk(&mut out, b"iTXt", &body);
+            }
+        }
+        out.extend_from_slice(&data[iend.offset..]);
+        Ok(Bytes::from(out))
+    }
+
+    fn embed_webp(
+        &self,
+        data: &[u8],
+    ) -> Result<Bytes> {
+        let chunks = webp_chunks(data)?;
+        let first = chunks
+            .first()
+            .ok_or(TeeError::MalformedContainer("webp has no chunks"))?;
+
+        let mut body = Vec::with_capacity(data.len() + 512);
+        if &first.kind != b"VP8X" {
+            let (width, height, alpha) = webp_canvas(first, data)?;
+            let mut vp8x = vec![if alpha { 0x14 } else { 0x04 }, 0, 0, 0];
+            vp8x.extend_from_slice(&(width - 1).to_le_bytes()[..3]);
+            vp8x.extend_from_slice(&(height - 1).to_le_bytes()[..3]);
+            write_riff_chunk(&mut body, b"VP8X", &vp8x);
+        }
+        for chunk in &chunks {
+            match &chunk.kind {
+                // replaced below
+                b"XMP " => continue,
+                b"VP8X" => {
+                    let mut vp8x = data[chunk.data.clone()].to_vec();
+                    if vp8x.len() < 10 {
+                        return Err(TeeError::MalformedContainer("truncated VP8X chunk"));
+                    }
+                    vp8x[0] |= 0x04;
+                    write_riff_chunk(&mut body, b"VP8X", &vp8x);
+                }
+                _ => body.extend_from_slice(&data[chunk.offset..chunk.end]),
+            }
+        }
+        write_riff_chunk(&mut body, b"XMP ", self.to_xmp().as_bytes());
+
+        let mut out = Vec::with_capacity(body.len() + 12);
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(body.len() as u32 + 4).to_le_bytes());
+        out.extend_from_slice(b"WEBP");
+        out.extend_from_slice(&body);
+        Ok(Bytes::from(out))
+    }
+}
+
+/// The inputs that decide how a render looks, see [render_filename].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RenderRecipe {
+    /// Skin identity, its name or [source hash](crate::tee::Tee::source_hash)
+    pub skin: String,
+    /// Body color in ddnet format, `None` for the original colors
+    pub body_color: Option<u32>,
+    /// Feet color in ddnet format, `None` for the original colors
+    pub feet_color: Option<u32>,
+    /// Name of the eye, see [EyeType::name](crate::tee::parts::EyeType::name)
+    pub eye: String,
+    /// Size of the render in pixels
+    pub size: (u32, u32),
+    /// Output format, picks the file extension
+    pub format: Option<ImageFormat>,
+}
+
+impl RenderRecipe {
+    /// Creates a recipe for the original colors of `skin`.
+    pub fn new(
+        skin: impl Into<String>,
+        eye: impl Into<String>,
+        size: (u32, u32),
+    ) -> Self {
+        Self {
+            skin: skin.into(),
+            body_color: None,
+            feet_color: None,
+            eye: eye.into(),
+            size,
+            format: None,
+        }
+    }
+
+    /// Sets the body and feet colors in ddnet format.
+    pub fn with_colors(
+        mut self,
+        body: u32,
+        feet: u32,
+    ) -> Self {
+        self.body_color = Some(body);
+        self.feet_color = Some(feet);
+        self
+    }
+
+    /// Sets the output format.
+    pub fn with_format(
+        mut self,
+        format: ImageFormat,
+    ) -> Self {
+        self.format = Some(format);
+        self
+    }
+}
+
+impl From<&RenderMeta> for RenderRecipe {
+    /// The skin is identified by the source hash when present, by the name otherwise.
+    fn from(meta: &RenderMeta) -> Self {
+        Self {
+            skin: meta
+                .source_hash
+                .map(|hash| hash.to_hex())
+                .or_else(|| meta.skin_name.clone())
+                .unwrap_or_default(),
+            body_color: meta.body_color,
+            feet_color: meta.feet_color,
+            eye: meta.eye.clone().unwrap_or_default(),
+            size: meta.size.unwrap_or_default(),
+            format: None,
+        }
+    }
+}
+
+/// Returns a stable filename for a render, so caches can be organized by content.
+///
+/// The name is 32 hex characters of a SHA-256 over the recipe and [RENDER_VERSION], followed
+/// by the extension of [RenderRecipe::format] when set. The same recipe gives the same name
+/// across runs, platforms and crate versions sharing a render version.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use tee_morphosis::meta::{RenderRecipe, render_filename};
+///
+/// let recipe = RenderRecipe::new("nameless tee", "happy", (96, 64))
+///     .with_colors(65408, 10223541)
+///     .with_format(ImageFormat::WebP);
+/// let path = cache_dir.join(render_filename(&recipe)); // "<32 hex chars>.webp"
+/// ```
+pub fn render_filename(recipe: &RenderRecipe) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(RENDER_VERSION.to_le_bytes());
+    // every field is length prefixed or fixed size, so no two recipes share an encoding
+    for field in [recipe.skin.as_bytes(), recipe.eye.as_bytes()] {
+        hasher.update((field.len() as u64).to_le_bytes());
+        hasher.update(field);
+    }
+    for color in [recipe.body_color, recipe.feet_color] {
+        match color {
+            Some(color) => {
+                hasher.update([1]);
+                hasher.update(color.to_le_bytes());
+            }
+            None => hasher.update([0]),
+        }
+    }
+    hasher.update(recipe.size.0.to_le_bytes());
+    hasher.update(recipe.size.1.to_le_bytes());
+
+    let digest = hasher.finalize();
+    let mut name: String = digest[..16].iter().map(|b| format!("{b:02x}")).collect();
+    if let Some(extension) = recipe
+        .format
+        .and_then(|format| format.extensions_str().first())
+    {
+        name.push('.');
+        name.push_str(extension);
+    }
+    name
+}
+
+/// A chunk of a PNG or RIFF container.
+struct Chunk {
+    kind: [u8; 4],
+    /// Start of the chunk header
+    offset: usize,
+    /// Range of the chunk payload
+    data: std::ops::Range<usize>,
+    /// End of the chunk including CRC or padding
+    end: usize,
+}
+
+fn png_chunks(data: &[u8]) -> Result<Vec<Chunk>> {
+    if !data.starts_with(PNG_SIGNATURE) {
+        return Err(TeeError::MalformedContainer("missing png signature"));
+    }
+    let mut chunks = Vec::new();
+    let mut offset = PNG_SIGNATURE.len();
+    while offset + 8 <= data.len() {
+        let len = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+        let start = offset + 8;
+        let end = start
+            .checked_add(len)
+            .and_then(|end| end.checked_add(4))
+            .filter(|&end| end <= data.len())
+            .ok_or(TeeError::MalformedContainer("png chunk out of bounds"))?;
+        chunks.push(Chunk {
+            kind: data[offset + 4..start].try_into().unwrap(),
+            offset,
+            data: start..start + len,
+            end,
+        });
+        offset = end;
+    }
+    Ok(chunks)
+}
+
+/// Splits a `tEXt` payload into its keyword and Latin-1 text.
+fn parse_text(payload: &[u8]) -> Option<(String, String)> {
+    let split = payload.iter().position(|&b| b == 0)?;
+    let latin1 = |bytes: &[u8]| bytes.iter().map(|&b| b as char).collect::<String>();
+    Some((latin1(&payload[..split]), latin1(&payload[split + 1..])))
+}
+
+/// Splits an uncompressed `iTXt` payload into its keyword and UTF-8 text.
+fn parse_itxt(payload: &[u8]) -> Option<(String, String)> {
+    let mut fields = payload.splitn(2, |&b| b == 0);
+    let key = String::from_utf8(fields.next()?.to_vec()).ok()?;
+    let rest = fields.next()?;
+    // compressed text is never written by `embed`
+    if rest.first()? != &0 {
+        return None;
+    }
+    // skip the compression method, then the language tag and translated keyword
+    let mut fields = rest.get(2..)?.splitn(3, |&b| b == 0);
+    fields.next()?;
+    fields.next()?;
+    let value = String::from_utf8(fields.next()?.to_vec()).ok()?;
+    Some((key, value))
+}
+
+fn webp_chunks(data: &[u8]) -> Result<Vec<Chunk>> {
+    if data.len() < 12 || &data[..4] != b"RIFF" || &data[8..12] != b"WEBP" {
+        return Err(TeeError::MalformedContainer("missing webp header"));
+    }
+    let mut chunks = Vec::new();
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let len = u32::from_le_bytes(data[offset + 4..offset + 8].try_into().unwrap()) as usize;
+        let start = offset + 8;
+        let payload_end = start
+            .checked_add(len)
+            .filter(|&end| end <= data.len())
+            .ok_or(TeeError::MalformedContainer("webp chunk out of bounds"))?;
+        let end = (payload_end + len % 2).min(data.len());
+        chunks.push(Chunk {
+            kind: data[offset..offset + 4].try_into().unwrap(),
+            offset,
+            data: start..payload_end,
+            end,
+        });
+        offset = end;
+    }
+    Ok(chunks)
+}
+
+/// Reads the canvas size and alpha flag of a simple (`VP8L` or `VP8 `) WebP.
+fn webp_canvas(
+    chunk: &Chunk,
+    data: &[u8],
+) -> Result<(u32, u32, bool)> {
+    let payload = &data[chunk.data.clone()];
+    match &chunk.kind {
+        b"VP8L" if payload.len() >= 5 && payload[0] == 0x2f => {
+            let bits = u32::from_le_bytes(payload[1..5].try_into().unwrap());
+            let width = (bits & 0x3fff) + 1;
+            let height = ((bits >> 14) & 0x3fff) + 1;
+            Ok((width, height, bits >> 28 & 1 == 1))
+        }
+        b"VP8 " if payload.len() >= 10 => {
+            let width = u16::from_le_bytes([payload[6], payload[7]]) as u32 & 0x3fff;
+            let height = u16::from_le_bytes([payload[8], payload[9]]) as u32 & 0x3fff;
+            if width == 0 || height == 0 {
+                return Err(TeeError::MalformedContainer("webp frame without size"));
+            }
+            Ok((width, height, false))
+        }
+        _ => Err(TeeError::MalformedContainer("unsupported webp bitstream")),
+    }
+}
+
+fn write_png_chunk(
+    out: &mut Vec<u8>,
+    kind: &[u8; 4],
+    body: &[u8],
+) {
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(body);
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(body);
+    out.extend_from_slice(&crc.finalize().to_be_bytes());
+}
+
+fn write_riff_chunk(
+    out: &mut Vec<u8>,
+    kind: &[u8; 4],
+    body: &[u8],
+) {
+    out.extend_from_slice(kind);
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
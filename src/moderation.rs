@@ -0,0 +1,99 @@
+//! # Moderation module
+//!
+//! Lets moderation pipelines reject uploads while they are parsed. A [ContentChecker] set
+//! with [ParseOptions::with_content_checker](crate::tee::options::ParseOptions::with_content_checker)
+//! sees the decoded body of every skin parsed with those options, including skins
+//! fetched by URL, and parsing fails with [TeeError::ContentRejected](crate::error::TeeError::ContentRejected)
+//! if it rejects it.
+//!
+//! With the `moderation` feature, [SkinToneHeuristic] is a simple bundled checker.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use tee_morphosis::{moderation::SkinToneHeuristic, tee::options::ParseOptions};
+//!
+//! let options = ParseOptions::new().with_content_checker(Arc::new(SkinToneHeuristic::default()));
+//! let tee = Tee::new_with_options(upload, TEE_UV_LAYOUT, ImageFormat::Png, options)?;
+//! ```
+
+use std::fmt;
+
+use image::RgbaImage;
+
+/// Decision of a [ContentChecker].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    /// The skin may be used
+    Accept,
+    /// The skin is rejected for the given reason
+    Reject(String),
+}
+
+/// Inspects skins while they are parsed.
+pub trait ContentChecker: fmt::Debug + Send + Sync {
+    /// Checks the decoded body of a skin, before any recoloring.
+    fn check(
+        &self,
+        body: &RgbaImage,
+    ) -> Verdict;
+}
+
+/// Rejects bodies mostly covered with skin tones.
+///
+/// Pixels are matched with the classic RGB skin color rule of Kovac et al. (2003), so
+/// peach and orange skins are rejected as well. Meant as a cheap first pass in front of
+/// human review, not as a classifier.
+#[cfg(feature = "moderation")]
+#[cfg_attr(docsrs, doc(cfg(feature = "moderation")))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkinToneHeuristic {
+    /// Largest accepted share of opaque body pixels with a skin tone, from `0.0` to `1.0`
+    pub max_ratio: f32,
+}
+
+#[cfg(feature = "moderation")]
+impl Default for SkinToneHeuristic {
+    fn default() -> Self {
+        Self {
+            max_ratio: 0.6,
+        }
+    }
+}
+
+#[cfg(feature = "moderation")]
+impl SkinToneHeuristic {
+    /// Returns the share of opaque pixels with a skin tone, `0.0` for a transparent image.
+    pub fn ratio(body: &RgbaImage) -> f32 {
+        let (mut opaque, mut skin) = (0u32, 0u32);
+        for pixel in body.pixels() {
+            let [r, g, b, a] = pixel.0;
+            if a < 128 {
+                continue;
+            }
+            opaque += 1;
+            let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+            if r > 95 && g > 40 && b > 20 && max - min > 15 && r.abs_diff(g) > 15 && r > g && r > b
+            {
+                skin += 1;
+            }
+        }
+        if opaque == 0 { 0.0 } else { skin as f32 / opaque as f32 }
+    }
+}
+
+#[cfg(feature = "moderation")]
+impl ContentChecker for SkinToneHeuristic {
+    fn check(
+        &self,
+        body: &RgbaImage,
+    ) -> Verdict {
+        let ratio = Self::ratio(body);
+        if ratio > self.max_ratio {
+            Verdict::Reject(format!("{:.0}% of the body has a skin tone", ratio * 100.0))
+        } else {
+            Verdict::Accept
+        }
+    }
+}
@@ -0,0 +1,621 @@
+//! # Network module
+//!
+//! Everything that goes over the wire passes through a [`Fetcher`]: a shared http
+//! client plus a [`RateLimiter`], so resolving hundreds of skins does not hammer the
+//! skin databases.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::net::{Fetcher, RateLimit, RateLimiter};
+//!
+//! let limiter = RateLimiter::new()
+//!     .with_default_limit(RateLimit::new(10, 5.0)?)
+//!     .with_host_limit("teedata.net", RateLimit::new(2, 1.0)?);
+//! let fetcher = Fetcher::new().with_rate_limiter(limiter);
+//!
+//! let tee = fetcher
+//!     .fetch_tee("https://teedata.net/databasev2/skins/glow_rainbow/glow_rainbow.png")
+//!     .await?;
+//! ```
+//!
+//! Fetchers refuse URLs pointing at private, loopback or link-local addresses, see
+//! [`policy`] for host allowlists and denylists. Services fetching user supplied URLs
+//! can also check or sign every request with a [`RequestHook`]:
+//!
+//! ```rust,ignore
+//! let fetcher = Fetcher::new().with_request_hook(|url, request| {
+//!     match url.host_str() {
+//!         Some("skins.example.com") => Ok(request.bearer_auth(&token)),
+//!         Some("teedata.net") => Ok(request),
+//!         _ => Err(TeeError::UrlNotAllowed(url.to_string())),
+//!     }
+//! });
+//! ```
+
+pub mod network;
+pub mod policy;
+
+use std::{
+    collections::HashMap,
+    error::Error as _,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use image::ImageFormat;
+use tracing::{debug, error, instrument, trace};
+
+use crate::{
+    error::{Result, TeeError},
+    tee::{
+        Tee,
+        options::ParseOptions,
+        uv::{TEE_UV_LAYOUT, UV},
+    },
+    telemetry,
+};
+use network::NetworkOptions;
+use policy::UrlPolicy;
+
+/// Token bucket parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    burst: u32,
+    per_second: f64,
+}
+
+impl RateLimit {
+    /// Creates a limit allowing `burst` requests at once and `per_second` on average.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TeeError::InvalidRateLimit] if `per_second` is not positive and finite.
+    pub fn new(
+        burst: u32,
+        per_second: f64,
+    ) -> Result<Self> {
+        if !per_second.is_finite() || per_second <= 0.0 {
+            return Err(TeeError::InvalidRateLimit(per_second));
+        }
+        Ok(Self {
+            burst: burst.max(1),
+            per_second,
+        })
+    }
+
+    /// Returns the amount of requests that can be issued at once after a quiet period.
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// Returns the amount of requests per second allowed on average.
+    pub fn per_second(&self) -> f64 {
+        self.per_second
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    default: Option<RateLimit>,
+    hosts: HashMap<String, RateLimit>,
+    buckets: HashMap<String, Bucket>,
+}
+
+/// Token bucket rate limiter with per-host limits.
+///
+/// Hosts without a specific limit use the default limit, or are not limited at all
+/// if there is none. Clones share their buckets, so one limiter can be handed to
+/// several fetchers.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    state: Arc<Mutex<LimiterState>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter that does not limit anything until limits are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the limit used for hosts without a specific one.
+    pub fn with_default_limit(
+        self,
+        limit: RateLimit,
+    ) -> Self {
+        self.lock().default = Some(limit);
+        self
+    }
+
+    /// Sets the limit of a specific host, e.g. `teedata.net`.
+    pub fn with_host_limit(
+        self,
+        host: impl Into<String>,
+        limit: RateLimit,
+    ) -> Self {
+        self.lock().hosts.insert(host.into(), limit);
+        self
+    }
+
+    /// Waits until a request to `host` is allowed.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn acquire(
+        &self,
+        host: &str,
+    ) {
+        while let Some(wait) = self.try_acquire(host) {
+            trace!(?wait, "Rate limited, waiting");
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes a token if one is available, otherwise returns how long to wait for it.
+    fn try_acquire(
+        &self,
+        host: &str,
+    ) -> Option<Duration> {
+        let mut state = self.lock();
+        let limit = state.hosts.get(host).or(state.default.as_ref()).copied()?;
+
+        let now = Instant::now();
+        let bucket = state.buckets.entry(host.to_string()).or_insert(Bucket {
+            tokens: limit.burst as f64,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limit.per_second).min(limit.burst as f64);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            let missing = 1.0 - bucket.tokens;
+            Some(Duration::from_secs_f64(missing / limit.per_second))
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LimiterState> {
+        // The state stays consistent even if a holder panicked
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Callback run on every request of a [`Fetcher`] before it is sent.
+///
+/// It gets the parsed URL and the request, and returns the request to send, e.g. with
+/// auth headers attached, or an error to refuse it. Refused requests are not sent and
+/// do not take a token of the rate limiter.
+#[derive(Clone)]
+pub struct RequestHook(Arc<HookFn>);
+
+type HookFn =
+    dyn Fn(&reqwest::Url, reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder> + Send + Sync;
+
+impl RequestHook {
+    /// Wraps `hook`.
+    pub fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&reqwest::Url, reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+
+    /// Runs the hook on a request to `url`.
+    pub fn apply(
+        &self,
+        url: &reqwest::Url,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::RequestBuilder> {
+        (self.0)(url, request)
+    }
+}
+
+impl fmt::Debug for RequestHook {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str("RequestHook")
+    }
+}
+
+/// Validators of a fetched source, sent back to only fetch it again if it changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Validators {
+    /// Value of the `ETag` header, sent back as `If-None-Match`
+    pub etag: Option<String>,
+    /// Value of the `Last-Modified` header, sent back as `If-Modified-Since`
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Creates empty validators, which always fetch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the entity tag, quoted as in the `ETag` header.
+    pub fn with_etag(
+        mut self,
+        etag: impl Into<String>,
+    ) -> Self {
+        self.etag = Some(etag.into());
+        self
+    }
+
+    /// Sets the modification date, as in the `Last-Modified` header.
+    pub fn with_last_modified(
+        mut self,
+        last_modified: impl Into<String>,
+    ) -> Self {
+        self.last_modified = Some(last_modified.into());
+        self
+    }
+
+    /// Returns whether there is no validator, so a fetch is never conditional.
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// Reads the validators of `response`.
+    fn of(response: &reqwest::Response) -> Self {
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        }
+    }
+}
+
+/// Result of [`Fetcher::fetch_if_modified`].
+#[derive(Debug, Clone)]
+pub enum FetchOutcome {
+    /// The source did not change since it was fetched with the validators
+    NotModified,
+    /// The source changed or was fetched unconditionally
+    New {
+        /// The parsed source
+        tee: Box<Tee>,
+        /// Validators of the source, for the next refresh
+        validators: Validators,
+    },
+}
+
+/// Shared http client used for every outbound request of the crate.
+#[derive(Debug, Clone)]
+pub struct Fetcher {
+    client: reqwest::Client,
+    limiter: RateLimiter,
+    hook: Option<RequestHook>,
+    policy: UrlPolicy,
+    network: NetworkOptions,
+}
+
+impl Default for Fetcher {
+    fn default() -> Self {
+        let policy = UrlPolicy::default();
+        let network = NetworkOptions::default();
+        Self {
+            client: network.client(&policy),
+            limiter: RateLimiter::default(),
+            hook: None,
+            policy,
+            network,
+        }
+    }
+}
+
+impl Fetcher {
+    /// Creates a fetcher without rate limits, refusing private addresses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the http client, e.g. to share a connection pool.
+    ///
+    /// The URL policy only checks the addresses of hosts and redirects, and the network
+    /// options only apply, if the client was built with [`NetworkOptions::apply`].
+    pub fn with_client(
+        mut self,
+        client: reqwest::Client,
+    ) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Replaces the rate limiter.
+    pub fn with_rate_limiter(
+        mut self,
+        limiter: RateLimiter,
+    ) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Replaces the URL policy and rebuilds the http client with
+    /// [`NetworkOptions::client`].
+    ///
+    /// Call [`Fetcher::with_client`] afterwards to keep other client settings, with a
+    /// client built with [`NetworkOptions::apply`].
+    pub fn with_url_policy(
+        mut self,
+        policy: UrlPolicy,
+    ) -> Self {
+        self.client = self.network.client(&policy);
+        self.policy = policy;
+        self
+    }
+
+    /// Replaces the proxy, DNS and IP family settings and rebuilds the http client with
+    /// [`NetworkOptions::client`], see [`Fetcher::with_url_policy`].
+    pub fn with_network(
+        mut self,
+        network: NetworkOptions,
+    ) -> Self {
+        self.client = network.client(&self.policy);
+        self.network = network;
+        self
+    }
+
+    /// Runs `hook` on every request before it is sent, see [`RequestHook`].
+    ///
+    /// Redirects are followed by the http client without the hook, use
+    /// [`Fetcher::with_client`] with a redirect policy to check them as well.
+    pub fn with_request_hook<F>(
+        mut self,
+        hook: F,
+    ) -> Self
+    where
+        F: Fn(&reqwest::Url, reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.hook = Some(RequestHook::new(hook));
+        self
+    }
+
+    /// Returns the http client.
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Returns the rate limiter.
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    /// Returns the URL policy.
+    pub fn url_policy(&self) -> &UrlPolicy {
+        &self.policy
+    }
+
+    /// Returns the proxy, DNS and IP family settings.
+    pub fn network(&self) -> &NetworkOptions {
+        &self.network
+    }
+
+    /// Issues a GET request once the rate limiter allows it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TeeError::UrlNotAllowed] and [TeeError::AddressNotAllowed] if the URL
+    /// policy refused the URL, an address of its host or a redirect, and the error of
+    /// the request hook if it refused the request.
+    #[instrument(level = "debug", skip(self, url), fields(url = %telemetry::url(url)))]
+    pub async fn get(
+        &self,
+        url: &str,
+    ) -> Result<reqwest::Response> {
+        self.send(url, &Validators::default()).await
+    }
+
+    /// Issues a GET request conditional on `validators`, see [`Fetcher::get`].
+    async fn send(
+        &self,
+        url: &str,
+        validators: &Validators,
+    ) -> Result<reqwest::Response> {
+        let parsed =
+            reqwest::Url::parse(url).map_err(|_| TeeError::UrlNotAllowed(url.to_string()))?;
+        self.policy.check_url(&parsed).inspect_err(|e| {
+            debug!(error = %e, "URL policy refused the request.");
+        })?;
+        let mut request = self.client.get(parsed.clone());
+        if let Some(etag) = &validators.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+        if let Some(hook) = &self.hook {
+            request = hook.apply(&parsed, request).inspect_err(|e| {
+                debug!(error = %e, "Request hook refused the request.");
+            })?;
+        }
+        self.limiter
+            .acquire(parsed.host_str().unwrap_or_default())
+            .await;
+
+        request.send().await.map_err(|e| {
+            error!(error = %e, "Failed to send request.");
+            refusal(&e).unwrap_or(TeeError::Reqwest(e))
+        })
+    }
+
+    /// Fetches an image from a URL and determines its format.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok((Bytes, ImageFormat))` containing the image data and its format.
+    #[instrument(level = "debug", skip(self, url), fields(url = %telemetry::url(url)))]
+    pub async fn fetch_image(
+        &self,
+        url: &str,
+    ) -> Result<(Bytes, ImageFormat)> {
+        let response = self.get(url).await?;
+        read_image(url, response).await
+    }
+
+    /// Fetches a [`Tee`] from a URL unless it did not change since it was fetched with
+    /// `validators`, and parses it with the default UV layout.
+    ///
+    /// Refresh jobs keep the [`Validators`] of [`FetchOutcome::New`] and pass them to the
+    /// next refresh, so unchanged skins are neither downloaded nor parsed again. Empty
+    /// validators always fetch.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// match fetcher.fetch_if_modified(&url, &entry.validators).await? {
+    ///     FetchOutcome::NotModified => {}
+    ///     FetchOutcome::New { tee, validators } => {
+    ///         *entry = Entry { tee, validators };
+    ///     }
+    /// }
+    /// ```
+    pub async fn fetch_if_modified(
+        &self,
+        url: &str,
+        validators: &Validators,
+    ) -> Result<FetchOutcome> {
+        self.fetch_if_modified_with_options(url, validators, TEE_UV_LAYOUT, ParseOptions::default())
+            .await
+    }
+
+    /// Same as [`Fetcher::fetch_if_modified`] with a custom UV layout and
+    /// [`ParseOptions`].
+    #[instrument(level = "debug", skip(self, url, uv, options), fields(url = %telemetry::url(url)))]
+    pub async fn fetch_if_modified_with_options(
+        &self,
+        url: &str,
+        validators: &Validators,
+        uv: UV,
+        options: ParseOptions,
+    ) -> Result<FetchOutcome> {
+        let response = self.send(url, validators).await?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            debug!("Source is not modified");
+            return Ok(FetchOutcome::NotModified);
+        }
+        let validators = Validators::of(&response);
+        let (bytes, format) = read_image(url, response).await?;
+        let tee =
+            tokio::task::spawn_blocking(move || Tee::new_with_options(bytes, uv, format, options))
+                .await
+                .map_err(TeeError::Join)??;
+        Ok(FetchOutcome::New {
+            tee: Box::new(tee),
+            validators,
+        })
+    }
+
+    /// Fetches a [`Tee`] from a URL and parses it with the default UV layout.
+    pub async fn fetch_tee(
+        &self,
+        url: &str,
+    ) -> Result<Tee> {
+        self.fetch_tee_with_options(url, TEE_UV_LAYOUT, ParseOptions::default())
+            .await
+    }
+
+    /// Fetches a [`Tee`] from a URL and parses it with a custom UV layout.
+    pub async fn fetch_tee_with_uv(
+        &self,
+        url: &str,
+        uv: UV,
+    ) -> Result<Tee> {
+        self.fetch_tee_with_options(url, uv, ParseOptions::default())
+            .await
+    }
+
+    /// Fetches a [`Tee`] from a URL and parses it with a custom UV layout and [`ParseOptions`].
+    pub async fn fetch_tee_with_options(
+        &self,
+        url: &str,
+        uv: UV,
+        options: ParseOptions,
+    ) -> Result<Tee> {
+        let (bytes, format) = self.fetch_image(url).await?;
+        debug!(
+            "Successfully fetched image data, size: {} bytes",
+            bytes.len()
+        );
+
+        tokio::task::spawn_blocking(move || Tee::new_with_options(bytes, uv, format, options))
+            .await
+            .map_err(TeeError::Join)?
+    }
+}
+
+/// Reads the body of `response` to `url` and determines its format.
+pub(crate) async fn read_image(
+    url: &str,
+    response: reqwest::Response,
+) -> Result<(Bytes, ImageFormat)> {
+    // Determine format from Content-Type header
+    let format = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(ImageFormat::from_mime_type)
+        .ok_or_else(|| {
+            error!("'Content-Type' header is missing or invalid.");
+            TeeError::ReqWithOutContentType(url.to_string())
+        })?;
+
+    debug!(determined_format = ?format, "Image format determined from response header.");
+
+    let bytes = response.bytes().await.map_err(|e| {
+        error!(error = %e, "Failed to read bytes from response.");
+        TeeError::Reqwest(e)
+    })?;
+
+    Ok((bytes, format))
+}
+
+/// Returns the refusal of the URL policy that failed a request, if any.
+///
+/// The resolver and the redirect policy can only fail requests with boxed errors, this
+/// digs them out of the error sources.
+fn refusal(error: &reqwest::Error) -> Option<TeeError> {
+    let mut source = error.source();
+    while let Some(e) = source {
+        match e.downcast_ref::<TeeError>() {
+            Some(TeeError::UrlNotAllowed(url)) => {
+                return Some(TeeError::UrlNotAllowed(url.clone()));
+            }
+            Some(TeeError::AddressNotAllowed {
+                host,
+                address,
+            }) => {
+                return Some(TeeError::AddressNotAllowed {
+                    host: host.clone(),
+                    address: *address,
+                });
+            }
+            _ => source = e.source(),
+        }
+    }
+    None
+}
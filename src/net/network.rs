@@ -0,0 +1,323 @@
+//! # Network module
+//!
+//! Connection settings of the http client behind a [`Fetcher`](super::Fetcher): the
+//! proxy, fixed addresses of hosts and which IP family to connect over. Several
+//! community skin hosts are flaky over IPv6, [`IpPreference::PreferV4`] tries their
+//! IPv4 addresses first.
+//!
+//! Fixed addresses and the IP preference are handled by the resolver of the client, so
+//! they are checked by the [`UrlPolicy`] like resolved addresses.
+//!
+//! A proxy resolves the hosts it connects to, so the policy can only refuse private IP
+//! hosts of requests sent through it. Unless a proxy is chosen, the system proxies are
+//! therefore only used by policies allowing private addresses.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::net::{Fetcher, network::{IpPreference, NetworkOptions}};
+//!
+//! let network = NetworkOptions::new()
+//!     .with_proxy("http://proxy.internal:3128")?
+//!     .with_dns_override("skins.example.com", "203.0.113.7".parse()?)
+//!     .with_ip_preference(IpPreference::PreferV4);
+//! let fetcher = Fetcher::new().with_network(network);
+//! ```
+
+use std::{
+    collections::HashMap,
+    io,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use tracing::{debug, warn};
+
+use super::policy::{UrlPolicy, normalize_host};
+use crate::error::{Result, TeeError};
+
+/// Which proxy requests go through.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+enum Proxy {
+    /// [Proxy::System] if the policy allows private addresses, [Proxy::Direct] otherwise
+    #[default]
+    Auto,
+    /// The proxies of the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` variables
+    System,
+    /// No proxy
+    Direct,
+    /// Every request through the proxy at this URL
+    Url(String),
+}
+
+/// IP family to connect over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum IpPreference {
+    /// Addresses in the order they resolved in
+    #[default]
+    Any,
+    /// IPv4 addresses first, IPv6 after a short delay
+    PreferV4,
+    /// IPv6 addresses first, IPv4 after a short delay
+    PreferV6,
+    /// IPv4 addresses only
+    OnlyV4,
+    /// IPv6 addresses only
+    OnlyV6,
+}
+
+impl IpPreference {
+    /// Every preference.
+    pub const ALL: [IpPreference; 5] = [
+        IpPreference::Any,
+        IpPreference::PreferV4,
+        IpPreference::PreferV6,
+        IpPreference::OnlyV4,
+        IpPreference::OnlyV6,
+    ];
+
+    /// Returns the name of the preference, e.g. `"ipv4"` for
+    /// [IpPreference::PreferV4].
+    pub const fn name(&self) -> &'static str {
+        match self {
+            IpPreference::Any => "any",
+            IpPreference::PreferV4 => "ipv4",
+            IpPreference::PreferV6 => "ipv6",
+            IpPreference::OnlyV4 => "ipv4-only",
+            IpPreference::OnlyV6 => "ipv6-only",
+        }
+    }
+
+    /// Returns the preference named `name`, see [IpPreference::name].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|preference| preference.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Orders `addresses` by the preference and drops the ones of excluded families.
+    ///
+    /// The order within a family is kept.
+    pub fn arrange(
+        self,
+        mut addresses: Vec<IpAddr>,
+    ) -> Vec<IpAddr> {
+        match self {
+            IpPreference::Any => {}
+            IpPreference::PreferV4 => addresses.sort_by_key(|address| address.is_ipv6()),
+            IpPreference::PreferV6 => addresses.sort_by_key(|address| address.is_ipv4()),
+            IpPreference::OnlyV4 => addresses.retain(IpAddr::is_ipv4),
+            IpPreference::OnlyV6 => addresses.retain(IpAddr::is_ipv6),
+        }
+        addresses
+    }
+}
+
+/// Connection settings of an http client, see the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkOptions {
+    proxy: Proxy,
+    dns_overrides: HashMap<String, Vec<IpAddr>>,
+    ip_preference: IpPreference,
+}
+
+impl NetworkOptions {
+    /// Resolves every host, and uses the system proxies only with policies allowing
+    /// private addresses, see the [module docs](self).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends every request through the proxy at `url`, e.g. `http://proxy:3128`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TeeError::Reqwest] if `url` is not a proxy URL.
+    pub fn with_proxy(
+        mut self,
+        url: impl Into<String>,
+    ) -> Result<Self> {
+        let url = url.into();
+        reqwest::Proxy::all(&url).map_err(TeeError::Reqwest)?;
+        self.proxy = Proxy::Url(url);
+        Ok(self)
+    }
+
+    /// Uses the proxies of the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` variables,
+    /// whatever the policy.
+    ///
+    /// Hosts of requests sent through them are resolved by the proxy, so a policy
+    /// refusing private addresses can not check them.
+    pub fn with_system_proxy(mut self) -> Self {
+        self.proxy = Proxy::System;
+        self
+    }
+
+    /// Connects directly, ignoring the system proxies.
+    pub fn without_proxy(mut self) -> Self {
+        self.proxy = Proxy::Direct;
+        self
+    }
+
+    /// Connects to `address` for `host` instead of resolving it. Repeated calls for a
+    /// host add addresses.
+    pub fn with_dns_override(
+        mut self,
+        host: impl Into<String>,
+        address: IpAddr,
+    ) -> Self {
+        self.dns_overrides
+            .entry(normalize_host(&host.into()))
+            .or_default()
+            .push(address);
+        self
+    }
+
+    /// Sets the IP family to connect over.
+    pub fn with_ip_preference(
+        mut self,
+        preference: IpPreference,
+    ) -> Self {
+        self.ip_preference = preference;
+        self
+    }
+
+    /// Returns the proxy URL, `None` for the system proxies or a direct connection.
+    pub fn proxy(&self) -> Option<&str> {
+        match &self.proxy {
+            Proxy::Url(url) => Some(url),
+            Proxy::Auto | Proxy::System | Proxy::Direct => None,
+        }
+    }
+
+    /// Returns whether the system proxies are ignored, whatever the policy.
+    pub fn is_direct(&self) -> bool {
+        self.proxy == Proxy::Direct
+    }
+
+    /// Returns whether clients built for `policy` use the system proxies.
+    pub fn uses_system_proxy(
+        &self,
+        policy: &UrlPolicy,
+    ) -> bool {
+        match self.proxy {
+            Proxy::Auto => policy.allows_private_addresses(),
+            Proxy::System => true,
+            Proxy::Direct | Proxy::Url(_) => false,
+        }
+    }
+
+    /// Returns the fixed addresses of `host`, if any.
+    pub fn dns_override(
+        &self,
+        host: &str,
+    ) -> Option<&[IpAddr]> {
+        self.dns_overrides
+            .get(&normalize_host(host))
+            .map(Vec::as_slice)
+    }
+
+    /// Returns the IP family to connect over.
+    pub fn ip_preference(&self) -> IpPreference {
+        self.ip_preference
+    }
+
+    /// Installs the proxy, a resolver applying the DNS overrides and IP preference and
+    /// checking every address with `policy`, and the redirect checks of `policy` on
+    /// `builder`.
+    ///
+    /// Requests sent through a proxy are resolved by the proxy, so only their URLs are
+    /// checked. A warning is logged when such a proxy is set for a policy refusing
+    /// private addresses.
+    pub fn apply(
+        &self,
+        builder: reqwest::ClientBuilder,
+        policy: &UrlPolicy,
+    ) -> reqwest::ClientBuilder {
+        let proxied = matches!(self.proxy, Proxy::System | Proxy::Url(_));
+        if proxied && !policy.allows_private_addresses() {
+            warn!("Hosts are resolved by the proxy, only private IP hosts are refused.");
+        }
+        let builder = match &self.proxy {
+            Proxy::Auto | Proxy::System if self.uses_system_proxy(policy) => builder,
+            Proxy::Auto | Proxy::System | Proxy::Direct => builder.no_proxy(),
+            Proxy::Url(url) => {
+                builder.proxy(reqwest::Proxy::all(url).expect("proxy URL is checked by with_proxy"))
+            }
+        };
+        builder
+            .dns_resolver(Arc::new(Resolver {
+                policy: policy.clone(),
+                overrides: self.dns_overrides.clone(),
+                preference: self.ip_preference,
+            }))
+            .redirect(policy.redirect_policy())
+    }
+
+    /// Builds a default http client with [NetworkOptions::apply].
+    ///
+    /// # Panics
+    ///
+    /// Panics like [reqwest::Client::new] if the TLS backend can not be initialized.
+    pub fn client(
+        &self,
+        policy: &UrlPolicy,
+    ) -> reqwest::Client {
+        self.apply(reqwest::Client::builder(), policy)
+            .build()
+            .expect("failed to build the http client")
+    }
+}
+
+/// Resolver applying the DNS overrides and IP preference, refusing hosts with addresses
+/// the policy does not allow.
+struct Resolver {
+    policy: UrlPolicy,
+    overrides: HashMap<String, Vec<IpAddr>>,
+    preference: IpPreference,
+}
+
+impl Resolve for Resolver {
+    fn resolve(
+        &self,
+        name: Name,
+    ) -> Resolving {
+        let policy = self.policy.clone();
+        let preference = self.preference;
+        let host = name.as_str().to_string();
+        let fixed = self.overrides.get(&normalize_host(&host)).cloned();
+        Box::pin(async move {
+            let resolved = match fixed {
+                Some(addresses) => addresses,
+                None => tokio::net::lookup_host((host.as_str(), 0))
+                    .await?
+                    .map(|address| address.ip())
+                    .collect(),
+            };
+            let addresses = preference.arrange(resolved);
+            if addresses.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "{host} has no address of IP preference {}",
+                        preference.name()
+                    ),
+                )
+                .into());
+            }
+            // One refused address refuses the host, the client may pick any of them
+            for &address in &addresses {
+                policy.check_address(&host, address).inspect_err(|e| {
+                    warn!(error = %e, "Refused resolved address.");
+                })?;
+            }
+            debug!(host, addresses = addresses.len(), "Resolved host");
+            Ok(Box::new(
+                addresses
+                    .into_iter()
+                    .map(|address| SocketAddr::new(address, 0)),
+            ) as Addrs)
+        })
+    }
+}
@@ -0,0 +1,255 @@
+//! # Policy module
+//!
+//! Decides which URLs a [`Fetcher`](super::Fetcher) may request, so services fetching
+//! user supplied URLs can not be pointed at their own network. A [`UrlPolicy`] refuses
+//! URLs that are not http(s), hosts outside its allowlist or inside its denylist, and
+//! hosts resolving to private, loopback or link-local addresses.
+//!
+//! Addresses are checked when connecting, by the resolver [`UrlPolicy::apply`] installs
+//! on the http client, so a host can not pass the check and resolve to another address
+//! for the request. The same client checks every redirect.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::net::{Fetcher, policy::UrlPolicy};
+//!
+//! let policy = UrlPolicy::new()
+//!     .with_allowed_host("teedata.net")
+//!     .with_allowed_host("ddnet.org");
+//! let fetcher = Fetcher::new().with_url_policy(policy);
+//! ```
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use reqwest::{Url, redirect};
+use tracing::warn;
+
+use super::network::NetworkOptions;
+use crate::error::{Result, TeeError};
+
+/// Redirects followed before a request fails, the default of reqwest.
+const MAX_REDIRECTS: usize = 10;
+
+/// Rules for the URLs a fetcher may request, see the [module docs](self).
+///
+/// Host entries match the host itself and its subdomains, `teedata.net` matches
+/// `teedata.net` and `skins.teedata.net`. The denylist wins over the allowlist.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UrlPolicy {
+    allowed_hosts: Vec<String>,
+    denied_hosts: Vec<String>,
+    allow_private: bool,
+}
+
+impl UrlPolicy {
+    /// Allows every public http(s) URL and refuses private addresses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows `host` and its subdomains. Once a host is allowed, all other hosts are
+    /// refused.
+    pub fn with_allowed_host(
+        mut self,
+        host: impl Into<String>,
+    ) -> Self {
+        self.allowed_hosts.push(normalize_host(&host.into()));
+        self
+    }
+
+    /// Refuses `host` and its subdomains.
+    pub fn with_denied_host(
+        mut self,
+        host: impl Into<String>,
+    ) -> Self {
+        self.denied_hosts.push(normalize_host(&host.into()));
+        self
+    }
+
+    /// Allows hosts resolving to private, loopback and link-local addresses, e.g. for a
+    /// skin server in the same network.
+    pub fn with_private_addresses(
+        mut self,
+        allow: bool,
+    ) -> Self {
+        self.allow_private = allow;
+        self
+    }
+
+    /// Returns the allowed hosts, empty if every host is allowed.
+    pub fn allowed_hosts(&self) -> &[String] {
+        &self.allowed_hosts
+    }
+
+    /// Returns the denied hosts.
+    pub fn denied_hosts(&self) -> &[String] {
+        &self.denied_hosts
+    }
+
+    /// Returns whether private addresses are allowed.
+    pub fn allows_private_addresses(&self) -> bool {
+        self.allow_private
+    }
+
+    /// Checks the scheme and host of `url`, and its address if the host is an IP.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TeeError::UrlNotAllowed] for refused schemes and hosts, and
+    /// [TeeError::AddressNotAllowed] for refused IP hosts.
+    pub fn check_url(
+        &self,
+        url: &Url,
+    ) -> Result<()> {
+        let refused = || TeeError::UrlNotAllowed(url.to_string());
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(refused());
+        }
+        let host = normalize_host(url.host_str().ok_or_else(refused)?);
+        if !self.allows_host(&host) {
+            return Err(refused());
+        }
+        // IP hosts are connected to without resolving them
+        match host.trim_start_matches('[').trim_end_matches(']').parse() {
+            Ok(address) => self.check_address(&host, address),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Checks an address `host` resolved to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TeeError::AddressNotAllowed] for non public addresses, unless they are
+    /// allowed.
+    pub fn check_address(
+        &self,
+        host: &str,
+        address: IpAddr,
+    ) -> Result<()> {
+        if self.allow_private || is_public_address(address) {
+            Ok(())
+        } else {
+            Err(TeeError::AddressNotAllowed {
+                host: host.to_string(),
+                address,
+            })
+        }
+    }
+
+    /// Installs a resolver checking every resolved address and a redirect policy
+    /// checking every redirect on `builder`.
+    ///
+    /// The system proxies are used only if private addresses are allowed, see
+    /// [NetworkOptions::uses_system_proxy].
+    ///
+    /// Same as [NetworkOptions::apply] with the default options.
+    pub fn apply(
+        &self,
+        builder: reqwest::ClientBuilder,
+    ) -> reqwest::ClientBuilder {
+        NetworkOptions::default().apply(builder, self)
+    }
+
+    /// Builds a default http client with [UrlPolicy::apply].
+    ///
+    /// # Panics
+    ///
+    /// Panics like [reqwest::Client::new] if the TLS backend can not be initialized.
+    pub fn client(&self) -> reqwest::Client {
+        NetworkOptions::default().client(self)
+    }
+
+    /// Returns a redirect policy checking every redirect.
+    pub(crate) fn redirect_policy(&self) -> redirect::Policy {
+        let policy = self.clone();
+        redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                return attempt.error("too many redirects");
+            }
+            match policy.check_url(attempt.url()) {
+                Ok(()) => attempt.follow(),
+                Err(e) => {
+                    warn!(error = %e, "Refused redirect.");
+                    attempt.error(e)
+                }
+            }
+        })
+    }
+
+    fn allows_host(
+        &self,
+        host: &str,
+    ) -> bool {
+        let matches = |pattern: &String| {
+            host == pattern
+                || host
+                    .strip_suffix(pattern.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        };
+        !self.denied_hosts.iter().any(matches)
+            && (self.allowed_hosts.is_empty() || self.allowed_hosts.iter().any(matches))
+    }
+}
+
+/// Returns whether `address` is reachable on the public internet.
+///
+/// Private, loopback, link-local, shared, unspecified, broadcast, multicast,
+/// documentation and reserved ranges are not public. IPv4 addresses embedded in IPv6,
+/// whether mapped, compatible, NAT64 or 6to4, are checked as IPv4.
+pub fn is_public_address(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // This network, shared address space, IETF protocol assignments, benchmarking
+        // and reserved
+        || a == 0
+        || (a == 100 && (b & 0xc0) == 64)
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (b & 0xfe) == 18)
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_public_v4(v4);
+    }
+    let segments = ip.segments();
+    let octets = ip.octets();
+    // NAT64 well known prefix and deprecated IPv4-compatible addresses, which also
+    // covers `::` and `::1`
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] || segments[..6] == [0; 6] {
+        let [_, _, _, _, _, _, _, _, _, _, _, _, a, b, c, d] = octets;
+        return is_public_v4(Ipv4Addr::new(a, b, c, d));
+    }
+    // 6to4 embeds the IPv4 address of the relay
+    if segments[0] == 0x2002 {
+        let [_, _, a, b, c, d, ..] = octets;
+        return is_public_v4(Ipv4Addr::new(a, b, c, d));
+    }
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Unique local, link-local and documentation
+        || (segments[0] & 0xfe00) == 0xfc00
+        || (segments[0] & 0xffc0) == 0xfe80
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8))
+}
+
+/// Lowercases `host` and drops the trailing dot of fully qualified names.
+pub(crate) fn normalize_host(host: &str) -> String {
+    host.trim().trim_end_matches('.').to_ascii_lowercase()
+}
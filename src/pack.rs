@@ -0,0 +1,184 @@
+//! # Skin pack module
+//!
+//! A [`SkinPack`] is a version of a skin database reduced to skin names and the
+//! [`SourceHash`] of every skin. Diffing two versions tells mirrors which skins were
+//! added, removed or changed, e.g. to write a changelog and re-render only what changed.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::pack::SkinPack;
+//!
+//! let mut old = SkinPack::new();
+//! old.insert_bytes("default", &default_v1);
+//! let mut new = SkinPack::new();
+//! new.insert_bytes("default", &default_v2);
+//! new.insert_bytes("santa", &santa);
+//!
+//! let diff = SkinPack::diff(&old, &new);
+//! assert_eq!(diff.changed, ["default"]);
+//! print!("{diff}");
+//! ```
+
+use std::{collections::BTreeMap, fmt};
+
+use tracing::{debug, instrument};
+
+use crate::tee::{Tee, hash::SourceHash};
+
+/// Skin names of one version of a skin database with the hash of every skin.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SkinPack {
+    skins: BTreeMap<String, SourceHash>,
+}
+
+impl SkinPack {
+    /// Creates an empty pack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a skin by the hash of its source, replacing a skin of the same name.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        hash: SourceHash,
+    ) -> Option<SourceHash> {
+        self.skins.insert(name.into(), hash)
+    }
+
+    /// Adds a skin by its source bytes.
+    pub fn insert_bytes(
+        &mut self,
+        name: impl Into<String>,
+        data: &[u8],
+    ) -> Option<SourceHash> {
+        self.insert(name, SourceHash::of(data))
+    }
+
+    /// Adds a parsed skin by [Tee::source_hash].
+    pub fn insert_tee(
+        &mut self,
+        name: impl Into<String>,
+        tee: &Tee,
+    ) -> Option<SourceHash> {
+        self.insert(name, tee.source_hash())
+    }
+
+    /// Removes a skin, returning its hash.
+    pub fn remove(
+        &mut self,
+        name: &str,
+    ) -> Option<SourceHash> {
+        self.skins.remove(name)
+    }
+
+    /// Returns the hash of a skin.
+    pub fn get(
+        &self,
+        name: &str,
+    ) -> Option<SourceHash> {
+        self.skins.get(name).copied()
+    }
+
+    /// Returns the amount of skins.
+    pub fn len(&self) -> usize {
+        self.skins.len()
+    }
+
+    /// Returns whether the pack has no skins.
+    pub fn is_empty(&self) -> bool {
+        self.skins.is_empty()
+    }
+
+    /// Iterates the skins ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, SourceHash)> {
+        self.skins.iter().map(|(name, hash)| (name.as_str(), *hash))
+    }
+
+    /// Compares two versions of a pack.
+    ///
+    /// Skins are matched by name, a skin whose hash differs is changed. Every list of the
+    /// result is sorted by name.
+    #[instrument(level = "debug", skip_all, fields(old = old.len(), new = new.len()))]
+    pub fn diff(
+        old: &SkinPack,
+        new: &SkinPack,
+    ) -> PackDiff {
+        let mut diff = PackDiff::default();
+        for (name, hash) in &new.skins {
+            match old.skins.get(name) {
+                None => diff.added.push(name.clone()),
+                Some(old_hash) if old_hash != hash => diff.changed.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .skins
+            .keys()
+            .filter(|name| !new.skins.contains_key(*name))
+            .cloned()
+            .collect();
+        debug!(
+            added = diff.added.len(),
+            removed = diff.removed.len(),
+            changed = diff.changed.len(),
+            "Diffed skin packs"
+        );
+        diff
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, SourceHash)> for SkinPack {
+    fn from_iter<I: IntoIterator<Item = (S, SourceHash)>>(iter: I) -> Self {
+        Self {
+            skins: iter
+                .into_iter()
+                .map(|(name, hash)| (name.into(), hash))
+                .collect(),
+        }
+    }
+}
+
+/// Difference between two versions of a [SkinPack], see [SkinPack::diff].
+///
+/// Displays as a changelog with one skin per line, prefixed with `+`, `-` or `~`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackDiff {
+    /// Skins only in the new version
+    pub added: Vec<String>,
+    /// Skins only in the old version
+    pub removed: Vec<String>,
+    /// Skins in both versions with different sources
+    pub changed: Vec<String>,
+}
+
+impl PackDiff {
+    /// Returns whether both versions have the same skins.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Iterates the skins that have to be rendered again, added and changed ones.
+    pub fn outdated(&self) -> impl Iterator<Item = &str> {
+        self.added.iter().chain(&self.changed).map(String::as_str)
+    }
+}
+
+impl fmt::Display for PackDiff {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        for name in &self.added {
+            writeln!(f, "+ {name}")?;
+        }
+        for name in &self.removed {
+            writeln!(f, "- {name}")?;
+        }
+        for name in &self.changed {
+            writeln!(f, "~ {name}")?;
+        }
+        Ok(())
+    }
+}
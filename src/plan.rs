@@ -0,0 +1,169 @@
+//! # Render plan module
+//!
+//! Turns a [`PackDiff`] into the renders a CDN of previews has to refresh: every recipe
+//! of an added or changed skin is rendered once, and the files of removed skins are
+//! listed for deletion. Finished jobs are checkpointed, so an interrupted refresh picks
+//! up where it stopped.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::{pack::SkinPack, plan::RenderPlan};
+//!
+//! let diff = SkinPack::diff(&old, &new);
+//! let mut plan = RenderPlan::from_diff(&diff, &recipes)
+//!     .resume(&std::fs::read_to_string("plan.checkpoint").unwrap_or_default());
+//! while let Some(job) = plan.pending().next().cloned() {
+//!     upload(&job.filename, render(&job.recipe)?)?;
+//!     plan.complete(&job.filename);
+//!     std::fs::write("plan.checkpoint", plan.checkpoint())?;
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use tracing::{debug, instrument};
+
+use crate::{
+    meta::{RenderRecipe, render_filename},
+    pack::PackDiff,
+};
+
+/// Why a job is in a [RenderPlan], in order of priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum JobReason {
+    /// The skin changed, the CDN serves an outdated render
+    Changed,
+    /// The skin is new, the CDN has no render yet
+    Added,
+}
+
+/// A render of a [RenderPlan].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RenderJob {
+    /// What to render
+    pub recipe: RenderRecipe,
+    /// Name of the output, see [render_filename]
+    pub filename: String,
+    /// Why the render is needed
+    pub reason: JobReason,
+}
+
+/// The renders needed to bring a CDN up to date with a new version of a skin pack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderPlan {
+    jobs: Vec<RenderJob>,
+    stale: Vec<String>,
+    done: HashSet<String>,
+}
+
+impl RenderPlan {
+    /// Plans the renders of `recipes` affected by `diff`.
+    ///
+    /// `recipes` are all renders the CDN serves, with [RenderRecipe::skin] set to the skin
+    /// name used in the packs. Recipes of unchanged skins are skipped and duplicates are
+    /// planned once. Jobs of changed skins come first, otherwise jobs keep the order of
+    /// `recipes`.
+    #[instrument(level = "debug", skip_all, fields(recipes = recipes.len()))]
+    pub fn from_diff(
+        diff: &PackDiff,
+        recipes: &[RenderRecipe],
+    ) -> Self {
+        let changed: HashSet<&str> = diff.changed.iter().map(String::as_str).collect();
+        let added: HashSet<&str> = diff.added.iter().map(String::as_str).collect();
+        let removed: HashSet<&str> = diff.removed.iter().map(String::as_str).collect();
+
+        let mut seen = HashSet::new();
+        let mut jobs = Vec::new();
+        let mut stale = Vec::new();
+        for recipe in recipes {
+            let skin = recipe.skin.as_str();
+            let reason = if changed.contains(skin) {
+                JobReason::Changed
+            } else if added.contains(skin) {
+                JobReason::Added
+            } else if removed.contains(skin) {
+                let filename = render_filename(recipe);
+                if seen.insert(filename.clone()) {
+                    stale.push(filename);
+                }
+                continue;
+            } else {
+                continue;
+            };
+            let filename = render_filename(recipe);
+            if seen.insert(filename.clone()) {
+                jobs.push(RenderJob {
+                    recipe: recipe.clone(),
+                    filename,
+                    reason,
+                });
+            }
+        }
+        // stable, so recipe order is kept within a reason
+        jobs.sort_by_key(|job| job.reason);
+        debug!(jobs = jobs.len(), stale = stale.len(), "Planned renders");
+        Self {
+            jobs,
+            stale,
+            done: HashSet::new(),
+        }
+    }
+
+    /// Returns every job in order of priority, finished or not.
+    pub fn jobs(&self) -> &[RenderJob] {
+        &self.jobs
+    }
+
+    /// Returns the files of removed skins, to be deleted from the CDN.
+    pub fn stale(&self) -> &[String] {
+        &self.stale
+    }
+
+    /// Iterates the jobs not completed yet, in order of priority.
+    pub fn pending(&self) -> impl Iterator<Item = &RenderJob> {
+        self.jobs
+            .iter()
+            .filter(|job| !self.done.contains(&job.filename))
+    }
+
+    /// Marks the job writing `filename` as completed.
+    ///
+    /// Returns `false` if the plan has no such job or it was already completed.
+    pub fn complete(
+        &mut self,
+        filename: &str,
+    ) -> bool {
+        self.jobs.iter().any(|job| job.filename == filename)
+            && self.done.insert(filename.to_string())
+    }
+
+    /// Returns whether every job is completed.
+    pub fn is_finished(&self) -> bool {
+        self.pending().next().is_none()
+    }
+
+    /// Returns the completed jobs as text to store between runs, one filename per line.
+    pub fn checkpoint(&self) -> String {
+        self.jobs
+            .iter()
+            .filter(|job| self.done.contains(&job.filename))
+            .map(|job| format!("{}\n", job.filename))
+            .collect()
+    }
+
+    /// Marks the jobs listed in a [RenderPlan::checkpoint] as completed.
+    ///
+    /// Lines naming no job of this plan are ignored, so a checkpoint of an older plan
+    /// only skips renders that are still needed and already done.
+    pub fn resume(
+        mut self,
+        checkpoint: &str,
+    ) -> Self {
+        for line in checkpoint.lines() {
+            self.complete(line.trim());
+        }
+        debug!(done = self.done.len(), "Resumed render plan");
+        self
+    }
+}
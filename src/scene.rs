@@ -0,0 +1,515 @@
+//! # Scene module
+//!
+//! A [`Scene`] is a canvas holding several images (composed tees, text, sprites) at
+//! arbitrary positions. Items are kept until the scene is rendered, so a scene can be
+//! built step by step and rendered into any format at the end.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use image::{ImageFormat, Rgba};
+//! use tee_morphosis::scene::Scene;
+//! use tee_morphosis::tee::{Tee, parts::EyeType, skin::TEE_SKIN_LAYOUT};
+//!
+//! let tee = Tee::new(/* ... */)?;
+//! let mut scene = Scene::new((200, 64)).with_background(Rgba([30, 30, 30, 255]));
+//! scene.add_tee(&tee, TEE_SKIN_LAYOUT, EyeType::Normal, (0, 0));
+//! scene.add_tee(&tee, TEE_SKIN_LAYOUT, EyeType::Happy, (100, 0));
+//! let bytes = scene.encode(ImageFormat::Png)?;
+//! ```
+//!
+//! Scenes can also be kept alive and changed, [`Scene::update`] then redraws only the
+//! regions touched since the last update:
+//!
+//! ```rust,ignore
+//! let mut canvas = scene.render();
+//! scene.move_item(id, (40, 0));
+//! for region in scene.update(&mut canvas) {
+//!     // send only `region` of `canvas` to the clients
+//! }
+//! ```
+
+#[cfg(feature = "text")]
+#[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+pub mod bubble;
+#[cfg(feature = "text")]
+#[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+pub mod comic;
+pub mod flag;
+pub mod gallery;
+pub mod hook;
+#[cfg(feature = "net")]
+#[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+pub mod scoreboard;
+#[cfg(feature = "text")]
+#[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+pub mod text;
+
+use bytes::Bytes;
+use image::{ImageFormat, Rgba, RgbaImage, imageops};
+use tracing::{debug, instrument, trace};
+
+#[cfg(feature = "gpu")]
+use crate::tee::compositor::gpu;
+use crate::{
+    error::Result,
+    tee::{
+        Tee,
+        parts::EyeSelection,
+        raw::encode_image,
+        skin::{Postion, Size, Skin},
+    },
+};
+
+/// Identifier of an item placed into a [`Scene`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ItemId(usize);
+
+/// An image placed into a [`Scene`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneItem {
+    /// The image to draw
+    pub image: RgbaImage,
+    /// Position of the top left corner of the image on the scene canvas
+    pub position: Postion,
+}
+
+/// A rectangle of a [`Scene`] canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Region {
+    /// Left edge
+    pub x: u32,
+    /// Top edge
+    pub y: u32,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+}
+
+impl Region {
+    /// Returns the part of an image at `position` that lies on a canvas of `size`,
+    /// `None` if the image is completely outside.
+    fn clipped(
+        position: Postion,
+        (width, height): Size,
+        canvas: Size,
+    ) -> Option<Self> {
+        let left = position.0.max(0);
+        let top = position.1.max(0);
+        let right = (position.0 + width as i64).min(canvas.0 as i64);
+        let bottom = (position.1 + height as i64).min(canvas.1 as i64);
+        (left < right && top < bottom).then(|| Region {
+            x: left as u32,
+            y: top as u32,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        })
+    }
+
+    /// Returns the overlap of two regions.
+    fn intersection(
+        &self,
+        other: &Region,
+    ) -> Option<Region> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        (left < right && top < bottom).then(|| Region {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+
+    /// Returns the smallest region containing both.
+    fn union(
+        &self,
+        other: &Region,
+    ) -> Region {
+        let left = self.x.min(other.x);
+        let top = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Region {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        }
+    }
+}
+
+/// A canvas with images placed on it.
+///
+/// Items are drawn in the order they were added, the last one is the topmost. Changes
+/// are tracked as dirty regions for [`Scene::update`], they are not part of equality.
+#[derive(Debug, Clone)]
+pub struct Scene {
+    size: Size,
+    background: Rgba<u8>,
+    items: Vec<(ItemId, SceneItem)>,
+    next_id: usize,
+    dirty: Vec<Region>,
+}
+
+impl PartialEq for Scene {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.size == other.size
+            && self.background == other.background
+            && self.items == other.items
+            && self.next_id == other.next_id
+    }
+}
+
+impl Scene {
+    /// Creates an empty scene with a transparent background.
+    pub fn new(size: Size) -> Self {
+        Self {
+            size,
+            background: Rgba([0, 0, 0, 0]),
+            items: Vec::new(),
+            next_id: 0,
+            dirty: Vec::new(),
+        }
+    }
+
+    /// Fills the background of the scene with a color.
+    pub fn with_background(
+        mut self,
+        color: Rgba<u8>,
+    ) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Returns the size of the scene canvas.
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the background color of the scene.
+    pub fn background(&self) -> Rgba<u8> {
+        self.background
+    }
+
+    /// Returns the items of the scene in drawing order.
+    pub fn items(&self) -> impl Iterator<Item = (ItemId, &SceneItem)> {
+        self.items.iter().map(|(id, item)| (*id, item))
+    }
+
+    /// Returns an item by its id.
+    pub fn item(
+        &self,
+        id: ItemId,
+    ) -> Option<&SceneItem> {
+        self.items
+            .iter()
+            .find(|(item_id, _)| *item_id == id)
+            .map(|(_, item)| item)
+    }
+
+    /// Places an image on top of the scene.
+    pub fn add_image(
+        &mut self,
+        image: RgbaImage,
+        position: Postion,
+    ) -> ItemId {
+        let id = ItemId(self.next_id);
+        self.next_id += 1;
+        trace!(?id, ?position, "Adding item to the scene");
+        self.mark_dirty(position, image.dimensions());
+        self.items.push((
+            id,
+            SceneItem {
+                image,
+                position,
+            },
+        ));
+        id
+    }
+
+    /// Composes a tee and places it on top of the scene.
+    pub fn add_tee<'a>(
+        &mut self,
+        tee: &Tee,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        position: Postion,
+    ) -> ItemId {
+        self.add_image(tee.compose_image(skin, eye_type), position)
+    }
+
+    /// Places an emoticon above a tee the way the game does, e.g. a sprite from
+    /// [`EmoticonSheet`](crate::assets::emoticons::EmoticonSheet).
+    ///
+    /// `tee_position` and `tee_size` describe the composed tee the emoticon belongs
+    /// to. The emoticon is as high as the tee and overlaps its top a little.
+    pub fn add_emoticon(
+        &mut self,
+        emoticon: &RgbaImage,
+        tee_position: Postion,
+        tee_size: Size,
+    ) -> ItemId {
+        let side = tee_size.1;
+        let image = imageops::resize(emoticon, side, side, imageops::FilterType::Triangle);
+        // The game draws the emoticon 23/64 of the tee size above the tee center
+        let overlap = (side as i64 * 9) / 64;
+        let position = (
+            tee_position.0 + (tee_size.0 as i64 - side as i64) / 2,
+            tee_position.1 + overlap - side as i64,
+        );
+        self.add_image(image, position)
+    }
+
+    /// Draws all items onto a new canvas.
+    #[instrument(level = "debug", skip(self), fields(size = ?self.size, items = self.items.len()))]
+    pub fn render(&self) -> RgbaImage {
+        let mut canvas = RgbaImage::from_pixel(self.size.0, self.size.1, self.background);
+        for (_, item) in &self.items {
+            imageops::overlay(&mut canvas, &item.image, item.position.0, item.position.1);
+        }
+        debug!("Successfully rendered the scene");
+        canvas
+    }
+
+    /// Draws all items onto a new canvas like [Scene::render], blending them in one pass
+    /// on the GPU.
+    ///
+    /// Pixels may differ from [Scene::render] by one level per channel. Renders like it
+    /// without a usable GPU.
+    #[cfg(feature = "gpu")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gpu")))]
+    #[instrument(level = "debug", skip(self), fields(size = ?self.size, items = self.items.len()))]
+    pub fn render_gpu(&self) -> RgbaImage {
+        let mut canvas = RgbaImage::from_pixel(self.size.0, self.size.1, self.background);
+        let items: Vec<_> = self
+            .items
+            .iter()
+            .map(|(_, item)| (&item.image, item.position))
+            .collect();
+        if !gpu::blend(&mut canvas, &items) {
+            return self.render();
+        }
+        debug!("Successfully rendered the scene on the GPU");
+        canvas
+    }
+
+    /// Moves an item, returns `false` if there is no item with this id.
+    pub fn move_item(
+        &mut self,
+        id: ItemId,
+        position: Postion,
+    ) -> bool {
+        let Some(index) = self.index_of(id) else {
+            return false;
+        };
+        let item = &mut self.items[index].1;
+        let (old, size) = (item.position, item.image.dimensions());
+        item.position = position;
+        trace!(?id, ?old, ?position, "Moving scene item");
+        self.mark_dirty(old, size);
+        self.mark_dirty(position, size);
+        true
+    }
+
+    /// Replaces the image of an item, e.g. to change the eyes of a tee, returns `false`
+    /// if there is no item with this id.
+    pub fn replace_image(
+        &mut self,
+        id: ItemId,
+        image: RgbaImage,
+    ) -> bool {
+        let Some(index) = self.index_of(id) else {
+            return false;
+        };
+        let item = &mut self.items[index].1;
+        let (position, old_size) = (item.position, item.image.dimensions());
+        let new_size = image.dimensions();
+        item.image = image;
+        self.mark_dirty(position, old_size);
+        self.mark_dirty(position, new_size);
+        true
+    }
+
+    /// Removes an item and returns it.
+    pub fn remove_item(
+        &mut self,
+        id: ItemId,
+    ) -> Option<SceneItem> {
+        let index = self.index_of(id)?;
+        let (_, item) = self.items.remove(index);
+        trace!(?id, "Removing scene item");
+        self.mark_dirty(item.position, item.image.dimensions());
+        Some(item)
+    }
+
+    /// Returns the regions changed since the last [`Scene::update`].
+    ///
+    /// Overlapping regions are merged.
+    pub fn dirty_regions(&self) -> &[Region] {
+        &self.dirty
+    }
+
+    /// Redraws the changed regions of a canvas previously rendered from this scene and
+    /// returns them.
+    ///
+    /// Only items overlapping a dirty region are drawn, so moving a single tee on a
+    /// crowded scene costs about as much as drawing that tee twice. A canvas of another
+    /// size is replaced by a full render, which is returned as a single region.
+    #[instrument(level = "debug", skip(self, canvas), fields(size = ?self.size, dirty = self.dirty.len()))]
+    pub fn update(
+        &mut self,
+        canvas: &mut RgbaImage,
+    ) -> Vec<Region> {
+        if canvas.dimensions() != self.size {
+            debug!("Canvas size changed, rendering the whole scene");
+            *canvas = self.render();
+            self.dirty.clear();
+            return vec![Region {
+                x: 0,
+                y: 0,
+                width: self.size.0,
+                height: self.size.1,
+            }];
+        }
+
+        let dirty = std::mem::take(&mut self.dirty);
+        for region in &dirty {
+            for y in region.y..region.y + region.height {
+                for x in region.x..region.x + region.width {
+                    canvas.put_pixel(x, y, self.background);
+                }
+            }
+            for (_, item) in &self.items {
+                let Some(area) = Region::clipped(item.position, item.image.dimensions(), self.size)
+                    .and_then(|area| area.intersection(region))
+                else {
+                    continue;
+                };
+                let part = imageops::crop_imm(
+                    &item.image,
+                    (area.x as i64 - item.position.0) as u32,
+                    (area.y as i64 - item.position.1) as u32,
+                    area.width,
+                    area.height,
+                );
+                imageops::overlay(canvas, &*part, area.x as i64, area.y as i64);
+            }
+        }
+        debug!(regions = dirty.len(), "Updated the scene canvas");
+        dirty
+    }
+
+    /// Adds the visible part of an image area to the dirty regions, merging overlaps.
+    fn mark_dirty(
+        &mut self,
+        position: Postion,
+        size: Size,
+    ) {
+        let Some(mut region) = Region::clipped(position, size, self.size) else {
+            return;
+        };
+        // Merging may make the region overlap others, so repeat until it does not
+        while let Some(index) = self
+            .dirty
+            .iter()
+            .position(|other| other.intersection(&region).is_some())
+        {
+            region = region.union(&self.dirty.swap_remove(index));
+        }
+        self.dirty.push(region);
+    }
+
+    fn index_of(
+        &self,
+        id: ItemId,
+    ) -> Option<usize> {
+        self.items.iter().position(|(item_id, _)| *item_id == id)
+    }
+
+    /// Renders the scene and encodes it with the specified format.
+    pub fn encode(
+        &self,
+        format: ImageFormat,
+    ) -> Result<Bytes> {
+        encode_image(&self.render(), format)
+    }
+
+    /// Splits the scene into tiles no larger than `max_size`, e.g. for chat platforms
+    /// rejecting large images.
+    ///
+    /// Tiles are returned row by row, top left first, and split the scene as evenly as
+    /// possible. Every tile keeps the background and the items overlapping it, shifted
+    /// into tile coordinates, so rendering all tiles side by side gives [Scene::render].
+    /// A scene fitting into `max_size` is returned as a single tile.
+    #[instrument(level = "debug", skip(self), fields(size = ?self.size))]
+    pub fn tiles(
+        &self,
+        max_size: Size,
+    ) -> Vec<Scene> {
+        let (width, height) = self.size;
+        let columns = width.div_ceil(max_size.0.max(1)).max(1);
+        let rows = height.div_ceil(max_size.1.max(1)).max(1);
+        let tile_size = (width.div_ceil(columns), height.div_ceil(rows));
+
+        let mut tiles = Vec::with_capacity((columns * rows) as usize);
+        for row in 0..rows {
+            for column in 0..columns {
+                let x = (column * tile_size.0) as i64;
+                let y = (row * tile_size.1) as i64;
+                let size = (
+                    tile_size.0.min(width - column * tile_size.0),
+                    tile_size.1.min(height - row * tile_size.1),
+                );
+                let items = self
+                    .items
+                    .iter()
+                    .filter(|(_, item)| {
+                        item.position.0 < x + size.0 as i64
+                            && item.position.1 < y + size.1 as i64
+                            && item.position.0 + item.image.width() as i64 > x
+                            && item.position.1 + item.image.height() as i64 > y
+                    })
+                    .map(|(id, item)| {
+                        let position = (item.position.0 - x, item.position.1 - y);
+                        (
+                            *id,
+                            SceneItem {
+                                image: item.image.clone(),
+                                position,
+                            },
+                        )
+                    })
+                    .collect();
+                tiles.push(Scene {
+                    size,
+                    background: self.background,
+                    items,
+                    next_id: self.next_id,
+                    dirty: Vec::new(),
+                });
+            }
+        }
+        debug!(columns, rows, ?tile_size, "Split the scene into tiles");
+        tiles
+    }
+
+    /// Splits the scene with [Scene::tiles] and encodes every tile with the specified
+    /// format, in the same order.
+    pub fn encode_tiles(
+        &self,
+        max_size: Size,
+        format: ImageFormat,
+    ) -> Result<Vec<Bytes>> {
+        self.tiles(max_size)
+            .iter()
+            .map(|tile| tile.encode(format))
+            .collect()
+    }
+}
@@ -0,0 +1,210 @@
+//! # Speech bubble module
+//!
+//! Draws comic style speech bubbles with wrapped text, their tail pointing at the head
+//! of a tee.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::scene::{Scene, bubble::{BubbleStyle, head_anchor}, text::{FontArc, TextStyle}};
+//!
+//! let font = FontArc::try_from_vec(std::fs::read("font.ttf")?)?;
+//! let style = BubbleStyle::new(TextStyle::new(font, 14.0).with_color(Rgba([0, 0, 0, 255])));
+//! let mut scene = Scene::new((320, 200));
+//! let tee = scene.add_tee(&tee, TEE_SKIN_LAYOUT, EyeType::Happy, (40, 120));
+//! let anchor = head_anchor((40, 120), TEE_SKIN_LAYOUT.output_size());
+//! scene.add_speech_bubble(anchor, "gg, that was a close one", &style);
+//! ```
+
+use image::{Rgba, RgbaImage, imageops};
+use tracing::{debug, instrument};
+
+use crate::{
+    scene::{
+        ItemId, Scene,
+        text::{TextStyle, line_height, render_text, text_width},
+    },
+    tee::skin::{Postion, Size},
+};
+
+/// Look of a speech bubble.
+#[derive(Debug, Clone)]
+pub struct BubbleStyle {
+    /// Style of the text inside
+    pub text: TextStyle,
+    /// Width lines are wrapped at, words longer than it get a line of their own
+    pub max_width: u32,
+    /// Space between the text and the border
+    pub padding: u32,
+    /// Radius of the corners
+    pub radius: u32,
+    /// Color inside the bubble
+    pub fill: Rgba<u8>,
+    /// Color of the border
+    pub border: Rgba<u8>,
+    /// Thickness of the border, `0` draws none
+    pub border_width: u32,
+    /// Width of the tail where it leaves the bubble and its length
+    pub tail: Size,
+}
+
+impl BubbleStyle {
+    /// Creates a white bubble with a black border.
+    pub fn new(text: TextStyle) -> Self {
+        Self {
+            text,
+            max_width: 160,
+            padding: 6,
+            radius: 8,
+            fill: Rgba([255, 255, 255, 255]),
+            border: Rgba([0, 0, 0, 255]),
+            border_width: 2,
+            tail: (12, 10),
+        }
+    }
+
+    /// Sets the width lines are wrapped at.
+    pub fn with_max_width(
+        mut self,
+        max_width: u32,
+    ) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Sets the fill and border colors.
+    pub fn with_colors(
+        mut self,
+        fill: Rgba<u8>,
+        border: Rgba<u8>,
+    ) -> Self {
+        self.fill = fill;
+        self.border = border;
+        self
+    }
+}
+
+/// Returns the point a bubble should point at for a composed tee placed at `position`,
+/// a little above the middle of its top edge.
+pub fn head_anchor(
+    position: Postion,
+    size: Size,
+) -> Postion {
+    (
+        position.0 + size.0 as i64 / 2,
+        position.1 + size.1 as i64 / 16,
+    )
+}
+
+impl Scene {
+    /// Places a speech bubble holding `text` above `tee_anchor`, its tail ending at the
+    /// anchor.
+    ///
+    /// The bubble is moved sideways to stay on the scene if it can, the tail still
+    /// points at the anchor. See [head_anchor] for the anchor of a composed tee.
+    #[instrument(level = "debug", skip(self, text, style))]
+    pub fn add_speech_bubble(
+        &mut self,
+        tee_anchor: Postion,
+        text: &str,
+        style: &BubbleStyle,
+    ) -> ItemId {
+        let lines = wrap(text, style);
+        let line_height = line_height(&style.text);
+        let inset = style.padding + style.border_width;
+        let text_width = lines
+            .iter()
+            .map(|line| text_width(line, &style.text))
+            .max()
+            .unwrap_or(0);
+        let width = (text_width + 2 * inset).max(2 * style.radius + style.tail.0 + 2);
+        let body_height = lines.len() as u32 * line_height + 2 * inset;
+
+        let max_x = self.size.0 as i64 - width as i64;
+        let x = (tee_anchor.0 - width as i64 / 2).min(max_x).max(0);
+        let y = tee_anchor.1 - (body_height + style.tail.1) as i64;
+        let tail_x = (tee_anchor.0 - x).clamp(
+            (style.radius + style.tail.0 / 2) as i64,
+            (width - style.radius - style.tail.0 / 2) as i64,
+        ) as u32;
+
+        let mut bubble = draw_bubble((width, body_height), tail_x, style);
+        for (index, line) in lines.iter().enumerate() {
+            let line = render_text(line, &style.text);
+            let line_x = (width - line.width()) / 2;
+            imageops::overlay(
+                &mut bubble,
+                &line,
+                line_x as i64,
+                (inset + index as u32 * line_height) as i64,
+            );
+        }
+        debug!(lines = lines.len(), size = ?bubble.dimensions(), "Drew speech bubble");
+        self.add_image(bubble, (x, y))
+    }
+}
+
+/// Splits `text` into lines no wider than the style allows, at whitespace.
+fn wrap(
+    text: &str,
+    style: &BubbleStyle,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate =
+                if line.is_empty() { word.to_string() } else { format!("{line} {word}") };
+            if line.is_empty() || text_width(&candidate, &style.text) <= style.max_width {
+                line = candidate;
+            } else {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            }
+        }
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// Draws the empty bubble, a rounded rectangle of `body` with the tail below it.
+fn draw_bubble(
+    (width, body_height): Size,
+    tail_x: u32,
+    style: &BubbleStyle,
+) -> RgbaImage {
+    let (tail_width, tail_length) = style.tail;
+    let height = body_height + tail_length;
+    // Whether a pixel lies inside the outline shrunk by `inset`
+    let inside = |x: u32, y: u32, inset: u32| {
+        let (x, y, inset) = (x as f32 + 0.5, y as f32 + 0.5, inset as f32);
+        let radius = (style.radius as f32 - inset).max(0.);
+        let (left, top) = (inset, inset);
+        let (right, bottom) = (width as f32 - inset, body_height as f32 - inset);
+        let in_body = x >= left && x <= right && y >= top && y <= bottom && {
+            // Distance to the nearest corner center, if the pixel lies in a corner
+            let cx = x.clamp(left + radius, right - radius);
+            let cy = y.clamp(top + radius, bottom - radius);
+            (x - cx).powi(2) + (y - cy).powi(2) <= radius * radius
+        };
+        // The shrunk tail reaches into the body, so no border is drawn across its root
+        let depth = y - (body_height as f32 - 1.);
+        let length = tail_length as f32 + 1.;
+        let in_tail = depth >= -inset && depth <= length && {
+            let half = tail_width as f32 / 2. * (1. - depth / length) - inset;
+            (x - tail_x as f32).abs() <= half
+        };
+        in_body || in_tail
+    };
+    RgbaImage::from_fn(width, height, |x, y| {
+        if inside(x, y, style.border_width) {
+            style.fill
+        } else if inside(x, y, 0) {
+            style.border
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    })
+}
@@ -0,0 +1,305 @@
+//! # Comic module
+//!
+//! Builds comic strips: panels of tees with expressions and speech bubbles, laid out in
+//! a grid with borders around every panel.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::scene::{bubble::BubbleStyle, comic::{ComicBuilder, ComicStyle, Panel, PanelTee}};
+//!
+//! let style = ComicStyle::new(BubbleStyle::new(text_style));
+//! let png = ComicBuilder::new(style)
+//!     .panel(Panel::new().with_tee(PanelTee::new(&alice, (20, 90)).saying("did you hook me?")))
+//!     .panel(
+//!         Panel::new()
+//!             .with_tee(PanelTee::new(&bob, (100, 90)).with_expression(Expression::Wink).saying("maybe")),
+//!     )
+//!     .encode(ImageFormat::Png)?;
+//! ```
+
+use bytes::Bytes;
+use image::{ImageFormat, Rgba, RgbaImage};
+use rayon::prelude::*;
+use tracing::{debug, instrument};
+
+use crate::{
+    error::Result,
+    scene::{
+        Scene,
+        bubble::{BubbleStyle, head_anchor},
+    },
+    tee::{
+        Tee,
+        expression::Expression,
+        options::ComposeOptions,
+        parts::EyeType,
+        skin::{Postion, Size, Skin, TEE_SKIN_LAYOUT},
+    },
+};
+
+/// Look and layout of a comic.
+#[derive(Debug, Clone)]
+pub struct ComicStyle {
+    /// Size of every panel, including its border
+    pub panel_size: Size,
+    /// Panels per row
+    pub columns: u32,
+    /// Space around and between panels
+    pub gutter: u32,
+    /// Color of the page behind the panels
+    pub page: Rgba<u8>,
+    /// Default background of the panels
+    pub panel_background: Rgba<u8>,
+    /// Color of the panel borders
+    pub border: Rgba<u8>,
+    /// Thickness of the panel borders, `0` draws none
+    pub border_width: u32,
+    /// Layout every tee is composed with
+    pub skin: Skin,
+    /// Style of the speech bubbles
+    pub bubble: BubbleStyle,
+}
+
+impl ComicStyle {
+    /// Creates a style of 3 panels per row, 200x160 each, on a white page.
+    pub fn new(bubble: BubbleStyle) -> Self {
+        Self {
+            panel_size: (200, 160),
+            columns: 3,
+            gutter: 8,
+            page: Rgba([255, 255, 255, 255]),
+            panel_background: Rgba([235, 240, 250, 255]),
+            border: Rgba([0, 0, 0, 255]),
+            border_width: 3,
+            skin: TEE_SKIN_LAYOUT,
+            bubble,
+        }
+    }
+
+    /// Sets the size of every panel.
+    pub fn with_panel_size(
+        mut self,
+        panel_size: Size,
+    ) -> Self {
+        self.panel_size = panel_size;
+        self
+    }
+
+    /// Sets the amount of panels per row.
+    pub fn with_columns(
+        mut self,
+        columns: u32,
+    ) -> Self {
+        self.columns = columns;
+        self
+    }
+}
+
+/// A tee in a panel.
+#[derive(Debug, Clone)]
+pub struct PanelTee<'a> {
+    /// The tee to draw
+    pub tee: &'a Tee,
+    /// Top left corner of the composed tee in the panel
+    pub position: Postion,
+    /// Eyes, ignored if there is an expression
+    pub eye: EyeType,
+    /// Expression, overrides the eyes
+    pub expression: Option<Expression>,
+    /// Text of a speech bubble above the tee
+    pub line: Option<String>,
+}
+
+impl<'a> PanelTee<'a> {
+    /// Places a tee with normal eyes.
+    pub fn new(
+        tee: &'a Tee,
+        position: Postion,
+    ) -> Self {
+        Self {
+            tee,
+            position,
+            eye: EyeType::Normal,
+            expression: None,
+            line: None,
+        }
+    }
+
+    /// Sets the eyes.
+    pub fn with_eye(
+        mut self,
+        eye: EyeType,
+    ) -> Self {
+        self.eye = eye;
+        self
+    }
+
+    /// Sets the expression.
+    pub fn with_expression(
+        mut self,
+        expression: Expression,
+    ) -> Self {
+        self.expression = Some(expression);
+        self
+    }
+
+    /// Gives the tee a speech bubble.
+    pub fn saying(
+        mut self,
+        line: impl Into<String>,
+    ) -> Self {
+        self.line = Some(line.into());
+        self
+    }
+}
+
+/// A panel of a comic.
+#[derive(Debug, Clone, Default)]
+pub struct Panel<'a> {
+    /// Tees drawn in order, their bubbles are drawn over all tees
+    pub tees: Vec<PanelTee<'a>>,
+    /// Background, [ComicStyle::panel_background] if `None`
+    pub background: Option<Rgba<u8>>,
+}
+
+impl<'a> Panel<'a> {
+    /// Creates an empty panel.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a tee.
+    pub fn with_tee(
+        mut self,
+        tee: PanelTee<'a>,
+    ) -> Self {
+        self.tees.push(tee);
+        self
+    }
+
+    /// Sets the background of this panel.
+    pub fn with_background(
+        mut self,
+        background: Rgba<u8>,
+    ) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// Lays out the panel as a scene of the panel size, without border.
+    pub fn scene(
+        &self,
+        style: &ComicStyle,
+    ) -> Scene {
+        let background = self.background.unwrap_or(style.panel_background);
+        let mut scene = Scene::new(style.panel_size).with_background(background);
+        for panel_tee in &self.tees {
+            let image = match panel_tee.expression {
+                Some(expression) => {
+                    let (skin, options) = expression.apply(style.skin, ComposeOptions::new());
+                    panel_tee
+                        .tee
+                        .compose_image_with_options(skin, expression.eyes(), &options)
+                }
+                None => panel_tee.tee.compose_image(style.skin, panel_tee.eye),
+            };
+            scene.add_image(image, panel_tee.position);
+        }
+        for panel_tee in &self.tees {
+            if let Some(line) = &panel_tee.line {
+                let anchor = head_anchor(panel_tee.position, style.skin.output_size());
+                scene.add_speech_bubble(anchor, line, &style.bubble);
+            }
+        }
+        scene
+    }
+}
+
+/// Collects panels and lays them out into a comic page.
+#[derive(Debug, Clone)]
+pub struct ComicBuilder<'a> {
+    style: ComicStyle,
+    panels: Vec<Panel<'a>>,
+}
+
+impl<'a> ComicBuilder<'a> {
+    /// Creates a comic without panels.
+    pub fn new(style: ComicStyle) -> Self {
+        Self {
+            style,
+            panels: Vec::new(),
+        }
+    }
+
+    /// Appends a panel, panels are read row by row.
+    pub fn panel(
+        mut self,
+        panel: Panel<'a>,
+    ) -> Self {
+        self.panels.push(panel);
+        self
+    }
+
+    /// Renders every panel and places them on the page.
+    ///
+    /// Panels are drawn in parallel and clipped to their size, the last row is left
+    /// aligned. A comic without panels is an empty page of the gutter size.
+    #[instrument(level = "debug", skip(self), fields(panels = self.panels.len()))]
+    pub fn build(&self) -> Scene {
+        let style = &self.style;
+        let columns = style.columns.max(1);
+        let (panel_w, panel_h) = style.panel_size;
+        let count = self.panels.len() as u32;
+        let rows = count.div_ceil(columns);
+        let used_columns = columns.min(count);
+        let size = (
+            style.gutter + used_columns * (panel_w + style.gutter),
+            style.gutter + rows * (panel_h + style.gutter),
+        );
+
+        let panels: Vec<RgbaImage> = self
+            .panels
+            .par_iter()
+            .map(|panel| {
+                let mut image = panel.scene(style).render();
+                draw_border(&mut image, style.border, style.border_width);
+                image
+            })
+            .collect();
+
+        let mut page = Scene::new(size).with_background(style.page);
+        for (index, image) in panels.into_iter().enumerate() {
+            let (column, row) = (index as u32 % columns, index as u32 / columns);
+            let position = (
+                (style.gutter + column * (panel_w + style.gutter)) as i64,
+                (style.gutter + row * (panel_h + style.gutter)) as i64,
+            );
+            page.add_image(image, position);
+        }
+        debug!(?size, rows, "Laid out comic");
+        page
+    }
+
+    /// Builds the page and encodes it.
+    pub fn encode(
+        &self,
+        format: ImageFormat,
+    ) -> Result<Bytes> {
+        self.build().encode(format)
+    }
+}
+
+/// Draws a border of `width` pixels along the edges of `image`.
+fn draw_border(
+    image: &mut RgbaImage,
+    color: Rgba<u8>,
+    width: u32,
+) {
+    let (w, h) = image.dimensions();
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        if x < width || y < width || x + width >= w || y + width >= h {
+            *pixel = color;
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! # Flag module
+//!
+//! Draws a tee carrying a capture the flag flag, with the sprites of [`GameSheet`], and
+//! country flags of [`CountryFlags`] next to tees.
+
+use image::imageops;
+
+use crate::{
+    assets::{
+        countryflags::CountryFlags,
+        game::{Flag, GameSheet},
+    },
+    scene::{ItemId, Scene},
+    tee::{
+        Tee,
+        parts::EyeSelection,
+        skin::{Postion, Skin},
+    },
+};
+
+/// Width of the flag relative to the body, as in game (42 pixels for a 64 pixel tee).
+const FLAG_WIDTH: f32 = 42.0 / 64.0;
+
+impl Scene {
+    /// Composes a tee and places it carrying `flag`.
+    ///
+    /// The flag is anchored at the body center like in game: its pole rises from the
+    /// center and the flag is drawn behind the tee.
+    ///
+    /// Returns the ids of the flag and of the tee.
+    pub fn add_tee_with_flag<'a>(
+        &mut self,
+        tee: &Tee,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        position: Postion,
+        game: &GameSheet,
+        flag: Flag,
+    ) -> (ItemId, ItemId) {
+        let ((body_x, body_y), (body_w, body_h)) =
+            skin.place(skin.body, (tee.used_uv.body.w, tee.used_uv.body.h));
+        let center = (
+            position.0 + body_x + body_w as i64 / 2,
+            position.1 + body_y + body_h as i64 / 2,
+        );
+
+        // The flag is twice as high as wide, centered 3/4 of its width above the body
+        let width = (body_w as f32 * FLAG_WIDTH).round().max(1.0);
+        let height = width * 2.0;
+        let image = imageops::resize(
+            game.flag(flag),
+            width as u32,
+            height as u32,
+            imageops::FilterType::Triangle,
+        );
+        let flag_position = (
+            center.0 - (width / 2.0) as i64,
+            center.1 - (width * 0.75 + height / 2.0) as i64,
+        );
+
+        let flag_id = self.add_image(image, flag_position);
+        let tee_id = self.add_tee(tee, skin, eye_type, position);
+        (flag_id, tee_id)
+    }
+
+    /// Places the flag of a country code scaled to `height`, keeping its aspect ratio.
+    ///
+    /// Unknown codes fall back to the default flag like in the scoreboard, `None` is
+    /// returned if there is none either.
+    pub fn add_country_flag(
+        &mut self,
+        flags: &CountryFlags,
+        code: i16,
+        position: Postion,
+        height: u32,
+    ) -> Option<ItemId> {
+        let flag = &flags.get_or_default(code)?.image;
+        let height = height.max(1);
+        let width = (flag.width() * height / flag.height().max(1)).max(1);
+        let image = imageops::resize(flag, width, height, imageops::FilterType::Triangle);
+        Some(self.add_image(image, position))
+    }
+}
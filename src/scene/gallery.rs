@@ -0,0 +1,217 @@
+//! # Gallery module
+//!
+//! Lays many tees out in a grid, split into pages, e.g. for bot commands answering a
+//! skin search with one image per page.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use image::ImageFormat;
+//! use tee_morphosis::scene::gallery::render_gallery;
+//!
+//! let pages = render_gallery(&tees, 5, (96, 64), ImageFormat::Png)?;
+//! for (index, page) in pages.iter().enumerate() {
+//!     std::fs::write(format!("page_{index}.png"), page)?;
+//! }
+//! ```
+
+use bytes::Bytes;
+use image::{ImageFormat, Rgba, imageops};
+use rayon::prelude::*;
+use tracing::{debug, instrument};
+
+#[cfg(feature = "text")]
+use crate::scene::text::{TextStyle, render_text};
+use crate::{
+    error::Result,
+    scene::Scene,
+    tee::{
+        Tee,
+        parts::EyeType,
+        skin::{Size, Skin, TEE_SKIN_LAYOUT},
+    },
+};
+
+/// A tee in a gallery, with an optional label drawn below it.
+#[derive(Debug, Clone, Copy)]
+pub struct GalleryItem<'a> {
+    /// The tee to draw
+    pub tee: &'a Tee,
+    /// Label, only drawn if [GalleryStyle] has a text style
+    pub label: Option<&'a str>,
+}
+
+impl<'a> From<&'a Tee> for GalleryItem<'a> {
+    fn from(tee: &'a Tee) -> Self {
+        Self {
+            tee,
+            label: None,
+        }
+    }
+}
+
+impl<'a> From<(&'a Tee, &'a str)> for GalleryItem<'a> {
+    fn from((tee, label): (&'a Tee, &'a str)) -> Self {
+        Self {
+            tee,
+            label: Some(label),
+        }
+    }
+}
+
+/// Look of a gallery page.
+#[derive(Debug, Clone)]
+pub struct GalleryStyle {
+    /// Cells per row
+    pub columns: u32,
+    /// Rows per page
+    pub rows: u32,
+    /// Size of a cell, tees are scaled to fit it
+    pub cell_size: Size,
+    /// Space around and between cells
+    pub padding: u32,
+    /// Background color of the pages
+    pub background: Rgba<u8>,
+    /// Layout used to compose every tee
+    pub skin: Skin,
+    /// Eyes of every tee
+    pub eye: EyeType,
+    /// Style of labels, labels are not drawn if `None`
+    #[cfg(feature = "text")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+    pub text: Option<TextStyle>,
+}
+
+impl Default for GalleryStyle {
+    fn default() -> Self {
+        Self {
+            columns: 5,
+            rows: 4,
+            cell_size: (96, 64),
+            padding: 8,
+            background: Rgba([20, 20, 20, 200]),
+            skin: TEE_SKIN_LAYOUT,
+            eye: EyeType::Normal,
+            #[cfg(feature = "text")]
+            text: None,
+        }
+    }
+}
+
+impl GalleryStyle {
+    /// Returns the amount of tees on a full page.
+    pub fn page_len(&self) -> usize {
+        (self.columns.max(1) * self.rows.max(1)) as usize
+    }
+
+    /// Height reserved for a label below every cell.
+    fn label_height(&self) -> u32 {
+        #[cfg(feature = "text")]
+        if let Some(text) = &self.text {
+            return text.size.ceil() as u32 + self.padding / 2;
+        }
+        0
+    }
+}
+
+/// Renders `tees` into pages of `columns` x 4 cells of `cell_size`, with the default
+/// [GalleryStyle] otherwise.
+///
+/// # Returns
+///
+/// One encoded image per page, no pages for no tees.
+pub fn render_gallery(
+    tees: &[Tee],
+    columns: u32,
+    cell_size: Size,
+    format: ImageFormat,
+) -> Result<Vec<Bytes>> {
+    let items: Vec<GalleryItem> = tees.iter().map(GalleryItem::from).collect();
+    let style = GalleryStyle {
+        columns,
+        cell_size,
+        ..GalleryStyle::default()
+    };
+    render_gallery_with_style(&items, &style, format)
+}
+
+/// Renders `items` into pages of the given style and encodes them.
+#[instrument(level = "debug", skip_all, fields(items = items.len(), format = ?format))]
+pub fn render_gallery_with_style(
+    items: &[GalleryItem],
+    style: &GalleryStyle,
+    format: ImageFormat,
+) -> Result<Vec<Bytes>> {
+    gallery_scenes(items, style)
+        .par_iter()
+        .map(|scene| scene.encode(format))
+        .collect()
+}
+
+/// Lays `items` out into one [Scene] per page, in order, row by row.
+#[instrument(level = "debug", skip_all, fields(items = items.len()))]
+pub fn gallery_scenes(
+    items: &[GalleryItem],
+    style: &GalleryStyle,
+) -> Vec<Scene> {
+    let columns = style.columns.max(1);
+    let (cell_w, cell_h) = style.cell_size;
+    let label_height = style.label_height();
+    let step_x = cell_w + style.padding;
+    let step_y = cell_h + label_height + style.padding;
+
+    // Composing dominates, do it for all tees at once
+    let avatars: Vec<_> = items
+        .par_iter()
+        .map(|item| {
+            let avatar = item.tee.compose_image(style.skin, style.eye);
+            let scale = (cell_w as f32 / avatar.width().max(1) as f32)
+                .min(cell_h as f32 / avatar.height().max(1) as f32);
+            let (w, h) = (
+                ((avatar.width() as f32 * scale) as u32).max(1),
+                ((avatar.height() as f32 * scale) as u32).max(1),
+            );
+            if (w, h) == avatar.dimensions() {
+                avatar
+            } else {
+                imageops::resize(&avatar, w, h, imageops::FilterType::Triangle)
+            }
+        })
+        .collect();
+
+    let pages: Vec<Scene> = items
+        .chunks(style.page_len())
+        .zip(avatars.chunks(style.page_len()))
+        .map(|(items, avatars)| {
+            let rows = (items.len() as u32).div_ceil(columns);
+            let used_columns = columns.min(items.len() as u32);
+            let size = (
+                style.padding + step_x * used_columns,
+                style.padding + step_y * rows,
+            );
+            let mut scene = Scene::new(size).with_background(style.background);
+            for (index, (item, avatar)) in items.iter().zip(avatars).enumerate() {
+                let (column, row) = (index as u32 % columns, index as u32 / columns);
+                let x = (style.padding + step_x * column) as i64;
+                let y = (style.padding + step_y * row) as i64;
+                let offset = (
+                    (cell_w - avatar.width()) as i64 / 2,
+                    (cell_h - avatar.height()) as i64 / 2,
+                );
+                scene.add_image(avatar.clone(), (x + offset.0, y + offset.1));
+
+                #[cfg(feature = "text")]
+                if let (Some(text), Some(label)) = (&style.text, item.label) {
+                    let label = render_text(label, text);
+                    let label_x = x + (cell_w as i64 - label.width() as i64) / 2;
+                    scene.add_image(label, (label_x, y + cell_h as i64));
+                }
+                #[cfg(not(feature = "text"))]
+                let _ = item;
+            }
+            scene
+        })
+        .collect();
+    debug!(pages = pages.len(), "Laid out gallery");
+    pages
+}
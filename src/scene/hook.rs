@@ -0,0 +1,143 @@
+//! # Hook module
+//!
+//! Draws the hook of a tee: the chain tiled along a line and the head at its end,
+//! with the sprites of [`GameSheet`].
+
+use image::{Rgba, RgbaImage, imageops};
+use tracing::{debug, instrument};
+
+use crate::{
+    assets::game::GameSheet,
+    scene::{ItemId, Scene, SceneItem},
+    tee::skin::Postion,
+};
+
+/// Length and thickness of a chain link and of the hook head, for a 64 pixel tee.
+const SEGMENT_SIZE: (u32, u32) = (24, 16);
+
+impl Scene {
+    /// Draws a hook from the center of the item `from_tee` to `to`.
+    ///
+    /// The hook is placed right below the tee, so the tee is drawn over the start of
+    /// the chain like in game. Returns `None` if there is no item `from_tee`.
+    pub fn draw_hook(
+        &mut self,
+        game: &GameSheet,
+        from_tee: ItemId,
+        to: Postion,
+    ) -> Option<ItemId> {
+        let index = self.items.iter().position(|(id, _)| *id == from_tee)?;
+        let tee = &self.items[index].1;
+        let from = (
+            tee.position.0 + tee.image.width() as i64 / 2,
+            tee.position.1 + tee.image.height() as i64 / 2,
+        );
+
+        let item = render_hook(game, from, to);
+        let id = ItemId(self.next_id);
+        self.next_id += 1;
+        self.items.insert(index, (id, item));
+        Some(id)
+    }
+
+    /// Draws a hook from `from` to `to` on top of the scene.
+    pub fn draw_hook_between(
+        &mut self,
+        game: &GameSheet,
+        from: Postion,
+        to: Postion,
+    ) -> ItemId {
+        let item = render_hook(game, from, to);
+        self.add_image(item.image, item.position)
+    }
+}
+
+/// Renders the chain and the head into an image covering the whole hook.
+#[instrument(level = "debug", skip(game))]
+fn render_hook(
+    game: &GameSheet,
+    from: Postion,
+    to: Postion,
+) -> SceneItem {
+    let (dx, dy) = ((to.0 - from.0) as f32, (to.1 - from.1) as f32);
+    let length = dx.hypot(dy);
+    let angle = dy.atan2(dx);
+    let direction = if length > 0.0 { (dx / length, dy / length) } else { (1.0, 0.0) };
+
+    let resize = |sprite: &RgbaImage| {
+        imageops::resize(
+            sprite,
+            SEGMENT_SIZE.0,
+            SEGMENT_SIZE.1,
+            imageops::FilterType::Triangle,
+        )
+    };
+    let chain = rotate(&resize(game.hook_chain()), angle);
+    let head = rotate(&resize(game.hook_head()), angle);
+
+    // Every sprite fits into a square with the side of the segment diagonal
+    let margin = (SEGMENT_SIZE.0.max(SEGMENT_SIZE.1) as i64 + 1) / 2 + 1;
+    let origin = (from.0.min(to.0) - margin, from.1.min(to.1) - margin);
+    let mut canvas = RgbaImage::new(
+        ((from.0 - to.0).unsigned_abs() as i64 + margin * 2) as u32,
+        ((from.1 - to.1).unsigned_abs() as i64 + margin * 2) as u32,
+    );
+    let mut draw = |sprite: &RgbaImage, (x, y): (f32, f32)| {
+        imageops::overlay(
+            &mut canvas,
+            sprite,
+            x.round() as i64 - origin.0 - sprite.width() as i64 / 2,
+            y.round() as i64 - origin.1 - sprite.height() as i64 / 2,
+        );
+    };
+
+    // The chain is tiled from the head back to the tee, one link per segment length
+    let step = SEGMENT_SIZE.0 as f32;
+    let mut offset = step;
+    let mut links = 0;
+    while offset < length {
+        draw(
+            &chain,
+            (
+                to.0 as f32 - direction.0 * offset,
+                to.1 as f32 - direction.1 * offset,
+            ),
+        );
+        offset += step;
+        links += 1;
+    }
+    draw(&head, (to.0 as f32, to.1 as f32));
+
+    debug!(links, "Rendered hook");
+    SceneItem {
+        image: canvas,
+        position: origin,
+    }
+}
+
+/// Rotates an image clockwise by `angle` radians around its center.
+///
+/// The result is large enough to hold the whole rotated image.
+fn rotate(
+    img: &RgbaImage,
+    angle: f32,
+) -> RgbaImage {
+    let (sin, cos) = angle.sin_cos();
+    let (width, height) = (img.width() as f32, img.height() as f32);
+    let out_width = (width * cos.abs() + height * sin.abs()).ceil() as u32;
+    let out_height = (width * sin.abs() + height * cos.abs()).ceil() as u32;
+
+    let (center_x, center_y) = (width / 2.0, height / 2.0);
+    let (out_center_x, out_center_y) = (out_width as f32 / 2.0, out_height as f32 / 2.0);
+
+    RgbaImage::from_fn(out_width, out_height, |x, y| {
+        let (rx, ry) = (x as f32 + 0.5 - out_center_x, y as f32 + 0.5 - out_center_y);
+        // Inverse rotation back into the source image
+        let sx = rx * cos + ry * sin + center_x;
+        let sy = -rx * sin + ry * cos + center_y;
+        if sx < 0.0 || sy < 0.0 || sx >= width || sy >= height {
+            return Rgba([0, 0, 0, 0]);
+        }
+        *img.get_pixel(sx as u32, sy as u32)
+    })
+}
@@ -0,0 +1,204 @@
+//! # Scoreboard module
+//!
+//! Renders a list of players, as found in the DDNet server info JSON, into a [`Scene`].
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::db::SkinDbClient;
+//! use tee_morphosis::scene::scoreboard::{PlayerEntry, ScoreboardStyle, render_scoreboard};
+//! use tee_morphosis::tee::parts::EyeType;
+//!
+//! let players: Vec<PlayerEntry> = vec![
+//!     ("nameless tee", "default", None, EyeType::Normal).into(),
+//!     ("brainless tee", "santa_limekitty", Some((1900500, 65280)), EyeType::Happy).into(),
+//! ];
+//! let scene = render_scoreboard(&SkinDbClient::new(), &players, &ScoreboardStyle::default()).await?;
+//! std::fs::write("scoreboard.png", scene.encode(image::ImageFormat::Png)?)?;
+//! ```
+
+use std::collections::HashMap;
+
+use image::{Rgba, imageops};
+use tracing::{debug, instrument, warn};
+
+#[cfg(feature = "text")]
+use crate::scene::text::{TextStyle, render_text};
+use crate::{
+    db::SkinDbClient,
+    error::Result,
+    scene::Scene,
+    tee::{
+        Tee,
+        hsl::ddnet_color_to_hsl,
+        parts::{EyeType, TeePart},
+        skin::{Skin, TEE_SKIN_LAYOUT},
+    },
+};
+
+/// Custom colors of a player in DDNet color format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlayerColors {
+    /// Color of the body and hands
+    pub body: u32,
+    /// Color of the feet
+    pub feet: u32,
+}
+
+/// A single row of the scoreboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerEntry {
+    /// Name of the player
+    pub name: String,
+    /// Name of the skin in the skin database
+    pub skin_name: String,
+    /// Custom colors, `None` if the player does not use them
+    pub colors: Option<PlayerColors>,
+    /// Eyes to render the tee with
+    pub emote: EyeType,
+}
+
+impl<N, S> From<(N, S, Option<(u32, u32)>, EyeType)> for PlayerEntry
+where
+    N: Into<String>,
+    S: Into<String>,
+{
+    fn from((name, skin_name, colors, emote): (N, S, Option<(u32, u32)>, EyeType)) -> Self {
+        Self {
+            name: name.into(),
+            skin_name: skin_name.into(),
+            colors: colors.map(|(body, feet)| PlayerColors { body, feet }),
+            emote,
+        }
+    }
+}
+
+/// Look of the scoreboard.
+#[derive(Debug, Clone)]
+pub struct ScoreboardStyle {
+    /// Width of the scoreboard
+    pub width: u32,
+    /// Height of a single row, tees are scaled to fit it
+    pub row_height: u32,
+    /// Space around rows and between the tee and the name
+    pub padding: u32,
+    /// Background color of the scoreboard
+    pub background: Rgba<u8>,
+    /// Layout used to compose every tee
+    pub skin: Skin,
+    /// Skin used when the skin of a player can not be resolved
+    pub fallback_skin: String,
+    /// Style of player names, names are not drawn if `None`
+    #[cfg(feature = "text")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+    pub text: Option<TextStyle>,
+}
+
+impl Default for ScoreboardStyle {
+    fn default() -> Self {
+        Self {
+            width: 400,
+            row_height: 64,
+            padding: 8,
+            background: Rgba([20, 20, 20, 200]),
+            skin: TEE_SKIN_LAYOUT,
+            fallback_skin: "default".to_string(),
+            #[cfg(feature = "text")]
+            text: None,
+        }
+    }
+}
+
+/// Resolves the skin of every player and draws them into a scene, one row per player.
+///
+/// Each distinct skin is downloaded only once, through the fetcher of `db`. Players whose skin can not be resolved
+/// are drawn with [`ScoreboardStyle::fallback_skin`].
+///
+/// # Errors
+///
+/// Returns an error if the fallback skin is needed but can not be resolved either.
+#[instrument(level = "debug", skip_all, fields(players = players.len()))]
+pub async fn render_scoreboard(
+    db: &SkinDbClient,
+    players: &[PlayerEntry],
+    style: &ScoreboardStyle,
+) -> Result<Scene> {
+    let mut tees: HashMap<&str, Tee> = HashMap::new();
+    for player in players {
+        let name = player.skin_name.as_str();
+        if tees.contains_key(name) {
+            continue;
+        }
+        match db.fetch(name).await {
+            Ok(tee) => {
+                tees.insert(name, tee);
+            }
+            Err(e) => warn!(skin = name, error = %e, "Failed to resolve skin, using fallback."),
+        }
+    }
+
+    let fallback_needed = players
+        .iter()
+        .any(|player| !tees.contains_key(player.skin_name.as_str()));
+    let fallback = if fallback_needed { Some(db.fetch(&style.fallback_skin).await?) } else { None };
+
+    let row_height = style.row_height + style.padding;
+    let height = style.padding + row_height * players.len() as u32;
+    let mut scene = Scene::new((style.width, height)).with_background(style.background);
+
+    for (row, player) in players.iter().enumerate() {
+        let mut tee = match (tees.get(player.skin_name.as_str()), &fallback) {
+            (Some(tee), _) | (None, Some(tee)) => tee.clone(),
+            (None, None) => unreachable!("Fallback is resolved when a skin is missing"),
+        };
+        if let Some(colors) = player.colors {
+            apply_player_colors(&mut tee, colors);
+        }
+
+        let mut avatar = tee.compose_image(style.skin, player.emote);
+        if avatar.height() != style.row_height {
+            let width = avatar.width() * style.row_height / avatar.height().max(1);
+            avatar = imageops::resize(
+                &avatar,
+                width,
+                style.row_height,
+                imageops::FilterType::Triangle,
+            );
+        }
+
+        let y = (style.padding + row_height * row as u32) as i64;
+        #[cfg(feature = "text")]
+        let name_x = (style.padding * 2 + avatar.width()) as i64;
+        scene.add_image(avatar, (style.padding as i64, y));
+
+        #[cfg(feature = "text")]
+        if let Some(text) = &style.text {
+            let label = render_text(&player.name, text);
+            let label_y = y + (style.row_height as i64 - label.height() as i64) / 2;
+            scene.add_image(label, (name_x, label_y));
+        }
+    }
+
+    debug!("Successfully rendered scoreboard");
+    Ok(scene)
+}
+
+/// Applies the custom body and feet colors of a player.
+pub(crate) fn apply_player_colors(
+    tee: &mut Tee,
+    colors: PlayerColors,
+) {
+    tee.apply_hsl_to_parts(
+        ddnet_color_to_hsl(colors.body),
+        &[
+            TeePart::Body,
+            TeePart::BodyShadow,
+            TeePart::Hand,
+            TeePart::HandShadow,
+        ],
+    );
+    tee.apply_hsl_to_parts(
+        ddnet_color_to_hsl(colors.feet),
+        &[TeePart::Feet, TeePart::FeetShadow],
+    );
+}
@@ -0,0 +1,129 @@
+//! # Text module
+//!
+//! Rasterizes single-line text with a user-provided font, so labels can be placed
+//! into a [`Scene`].
+
+pub use ab_glyph::FontArc;
+use ab_glyph::{Font, Glyph, PxScale, ScaleFont, point};
+use image::{Rgba, RgbaImage};
+
+use crate::{
+    scene::{ItemId, Scene},
+    tee::skin::Postion,
+};
+
+/// Font, size and color used to draw text.
+#[derive(Debug, Clone)]
+pub struct TextStyle {
+    /// The font to draw with
+    pub font: FontArc,
+    /// Height of the text in pixels
+    pub size: f32,
+    /// Color of the glyphs
+    pub color: Rgba<u8>,
+}
+
+impl TextStyle {
+    /// Creates a white text style.
+    pub fn new(
+        font: FontArc,
+        size: f32,
+    ) -> Self {
+        Self {
+            font,
+            size,
+            color: Rgba([255, 255, 255, 255]),
+        }
+    }
+
+    /// Sets the color of the glyphs.
+    pub fn with_color(
+        mut self,
+        color: Rgba<u8>,
+    ) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// Lays out a single line of glyphs, returns them with the advance of the line.
+fn layout(
+    text: &str,
+    style: &TextStyle,
+) -> (Vec<Glyph>, f32) {
+    let font = style.font.as_scaled(PxScale::from(style.size));
+
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0f32;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += font.kern(previous, id);
+        }
+        glyphs.push(id.with_scale_and_position(font.scale(), point(caret, font.ascent())));
+        caret += font.h_advance(id);
+        previous = Some(id);
+    }
+    (glyphs, caret)
+}
+
+/// Returns the width [render_text] gives a line of text.
+pub fn text_width(
+    text: &str,
+    style: &TextStyle,
+) -> u32 {
+    layout(text, style).1.ceil().max(1.0) as u32
+}
+
+/// Returns the height of a line of text, the height of every image of [render_text].
+pub fn line_height(style: &TextStyle) -> u32 {
+    let font = style.font.as_scaled(PxScale::from(style.size));
+    font.height().ceil().max(1.0) as u32
+}
+
+/// Rasterizes a single line of text into an image fitting it tightly in width.
+///
+/// The height of the image is the line height of the font at `style.size`.
+pub fn render_text(
+    text: &str,
+    style: &TextStyle,
+) -> RgbaImage {
+    let (glyphs, caret) = layout(text, style);
+    let width = caret.ceil().max(1.0) as u32;
+    let height = line_height(style);
+    let mut canvas = RgbaImage::new(width, height);
+
+    for glyph in glyphs {
+        let Some(outline) = style.font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|x, y, coverage| {
+            let x = bounds.min.x as i32 + x as i32;
+            let y = bounds.min.y as i32 + y as i32;
+            if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+                return;
+            }
+            let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+            let alpha = (coverage.clamp(0.0, 1.0) * style.color[3] as f32) as u8;
+            if alpha > pixel[3] {
+                *pixel = Rgba([style.color[0], style.color[1], style.color[2], alpha]);
+            }
+        });
+    }
+
+    canvas
+}
+
+impl Scene {
+    /// Rasterizes a line of text and places it on top of the scene.
+    pub fn add_text(
+        &mut self,
+        text: &str,
+        style: &TextStyle,
+        position: Postion,
+    ) -> ItemId {
+        self.add_image(render_text(text, style), position)
+    }
+}
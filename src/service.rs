@@ -0,0 +1,776 @@
+//! # Render service module
+//!
+//! [`RenderService`] packages fetching, parsing, recoloring and composing behind a single
+//! [`RenderService::render`] call, with a bound on how many renders run at once and how
+//! many may wait for a slot.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::service::{RenderRequest, RenderService};
+//! use tee_morphosis::scene::scoreboard::PlayerColors;
+//! use tee_morphosis::tee::parts::EyeType;
+//!
+//! let service = RenderService::new().with_concurrency(4).with_queue(64);
+//! let rendered = service
+//!     .render(
+//!         RenderRequest::new("https://ddnet.org/skins/skin/default.png")
+//!             .with_eye(EyeType::Happy)
+//!             .with_colors(PlayerColors { body: 1900500, feet: 65280 }),
+//!     )
+//!     .await?;
+//! debug!(source = rendered.source.name(), cache_hit = rendered.cache_hit, "Rendered avatar");
+//! response.header(CONTENT_TYPE, rendered.content_type()).body(rendered.bytes)
+//! ```
+//!
+//! Popular skins can be loaded at startup, so the first renders do not wait for them:
+//!
+//! ```rust,ignore
+//! let report = service.warmup(["default", "santa_limekitty", "https://example.com/x.png"]).await;
+//! for (skin, error) in &report.failed {
+//!     warn!(skin, %error, "Skin could not be warmed up");
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use image::ImageFormat;
+use tokio::{
+    sync::{OnceCell, Semaphore},
+    task::JoinSet,
+};
+use tracing::{debug, instrument, trace, warn};
+
+use crate::{
+    cache::DedupStore,
+    db::{SkinDbClient, SkinDbSource},
+    error::{Result, TeeError},
+    net::Fetcher,
+    scene::scoreboard::{PlayerColors, apply_player_colors},
+    skin_name,
+    tee::{
+        Tee,
+        options::ComposeOptions,
+        parts::EyeType,
+        skin::{Skin, TEE_SKIN_LAYOUT},
+    },
+    telemetry,
+};
+
+/// A single render handled by a [RenderService].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RenderRequest {
+    /// URL of the skin
+    pub url: String,
+    /// Eyes to render the tee with
+    pub eye: EyeType,
+    /// Custom colors, `None` for the original ones
+    pub colors: Option<PlayerColors>,
+    /// Output format
+    pub format: ImageFormat,
+}
+
+impl RenderRequest {
+    /// Creates a request for the normal eyes and original colors, encoded as PNG.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            eye: EyeType::Normal,
+            colors: None,
+            format: ImageFormat::Png,
+        }
+    }
+
+    /// Sets the eyes.
+    pub fn with_eye(
+        mut self,
+        eye: EyeType,
+    ) -> Self {
+        self.eye = eye;
+        self
+    }
+
+    /// Sets custom colors.
+    pub fn with_colors(
+        mut self,
+        colors: PlayerColors,
+    ) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    /// Sets the output format.
+    pub fn with_format(
+        mut self,
+        format: ImageFormat,
+    ) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+/// A step of a [FallbackPolicy].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fallback {
+    /// The last successful render of the same request
+    LastGood,
+    /// The default skin with the colors and eyes of the request
+    DefaultSkin,
+    /// An identicon of the default skin derived from the skin URL, see [Tee::identicon]
+    Identicon,
+}
+
+/// Fallbacks tried in order when a render fails, see [RenderService::render_or_fallback].
+#[derive(Debug, Clone)]
+pub struct FallbackPolicy {
+    /// Steps tried in order
+    pub steps: Vec<Fallback>,
+    /// Skin of [Fallback::DefaultSkin] and base of [Fallback::Identicon]
+    pub default_skin: Arc<Tee>,
+    /// How many successful renders are kept for [Fallback::LastGood]
+    pub last_good_capacity: usize,
+}
+
+impl FallbackPolicy {
+    /// Creates a policy trying the last good render, then the default skin, then an
+    /// identicon, keeping up to 1024 good renders.
+    pub fn new(default_skin: Arc<Tee>) -> Self {
+        Self {
+            steps: vec![
+                Fallback::LastGood,
+                Fallback::DefaultSkin,
+                Fallback::Identicon,
+            ],
+            default_skin,
+            last_good_capacity: 1024,
+        }
+    }
+
+    /// Sets the steps tried in order.
+    pub fn with_steps(
+        mut self,
+        steps: impl IntoIterator<Item = Fallback>,
+    ) -> Self {
+        self.steps = steps.into_iter().collect();
+        self
+    }
+
+    /// Sets how many successful renders are kept for [Fallback::LastGood].
+    pub fn with_last_good_capacity(
+        mut self,
+        capacity: usize,
+    ) -> Self {
+        self.last_good_capacity = capacity;
+        self
+    }
+}
+
+/// Where the image of a [RenderedTee] comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Provenance {
+    /// The skin at this URL, the one requested
+    Skin(String),
+    /// A step of the [FallbackPolicy], the requested skin could not be rendered
+    Fallback(Fallback),
+}
+
+impl Provenance {
+    /// Returns a short name for logs and headers, `"skin"`, `"last-good"`,
+    /// `"default-skin"` or `"identicon"`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Provenance::Skin(_) => "skin",
+            Provenance::Fallback(Fallback::LastGood) => "last-good",
+            Provenance::Fallback(Fallback::DefaultSkin) => "default-skin",
+            Provenance::Fallback(Fallback::Identicon) => "identicon",
+        }
+    }
+}
+
+/// Time spent in each stage of a [RenderService] render.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderTimings {
+    /// Time spent waiting for a render slot
+    pub queued: Duration,
+    /// Time spent fetching the skin, zero if it was stored
+    pub fetch: Duration,
+    /// Time spent parsing the skin, zero if it was stored
+    pub parse: Duration,
+    /// Time spent recoloring, composing and encoding
+    pub compose: Duration,
+}
+
+impl RenderTimings {
+    /// Returns the total time of the render.
+    pub fn total(&self) -> Duration {
+        self.queued + self.fetch + self.parse + self.compose
+    }
+}
+
+/// Output of [RenderService::render] and [RenderService::render_or_fallback].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedTee {
+    /// The encoded image
+    pub bytes: Bytes,
+    /// Format of the image
+    pub format: ImageFormat,
+    /// Where the image comes from
+    pub source: Provenance,
+    /// Time spent in each stage, fallbacks only count their own render
+    pub timings: RenderTimings,
+    /// Whether nothing had to be fetched, the skin or image was already in memory.
+    /// [Fallback::LastGood] renders are hits, the freshly composed
+    /// [Fallback::DefaultSkin] and [Fallback::Identicon] renders are misses.
+    pub cache_hit: bool,
+}
+
+impl RenderedTee {
+    /// Returns the MIME type of the image, for the `Content-Type` header.
+    pub fn content_type(&self) -> &'static str {
+        self.format.to_mime_type()
+    }
+
+    /// Returns the fallback the image comes from, `None` if the requested skin was
+    /// rendered.
+    pub fn fallback(&self) -> Option<Fallback> {
+        match self.source {
+            Provenance::Skin(_) => None,
+            Provenance::Fallback(step) => Some(step),
+        }
+    }
+}
+
+/// Output of [RenderService::warmup].
+#[derive(Debug, Default)]
+pub struct WarmupReport {
+    /// Skins fetched and stored
+    pub loaded: usize,
+    /// Skins that were already stored
+    pub stored: usize,
+    /// Skins that could not be loaded, with the error
+    pub failed: Vec<(String, TeeError)>,
+}
+
+/// Load of a skin that concurrent misses of its URL wait for.
+type InFlight = Arc<OnceCell<Arc<Tee>>>;
+
+/// Fetch → parse → recolor → compose pipeline with backpressure.
+///
+/// Parsed skins are kept in a [DedupStore], and concurrent misses of a URL wait for a
+/// single fetch, so every URL is fetched once while it stays stored. At most
+/// [RenderService::with_concurrency] renders run at once, at most
+/// [RenderService::with_queue] more wait for a slot, and any further request fails
+/// right away with [TeeError::ServiceOverloaded]. Clones share the store and the limits.
+#[derive(Debug, Clone)]
+pub struct RenderService {
+    fetcher: Fetcher,
+    store: DedupStore,
+    skin: Skin,
+    options: ComposeOptions,
+    permits: Arc<Semaphore>,
+    concurrency: usize,
+    queue: usize,
+    pending: Arc<AtomicUsize>,
+    fallback: Option<FallbackPolicy>,
+    last_good: Arc<Mutex<HashMap<RenderRequest, Bytes>>>,
+    skin_db: Option<SkinDbClient>,
+    in_flight: Arc<Mutex<HashMap<String, InFlight>>>,
+}
+
+impl Default for RenderService {
+    fn default() -> Self {
+        let concurrency = std::thread::available_parallelism().map_or(4, |n| n.get());
+        Self {
+            fetcher: Fetcher::new(),
+            store: DedupStore::new(),
+            skin: TEE_SKIN_LAYOUT,
+            options: ComposeOptions::new(),
+            permits: Arc::new(Semaphore::new(concurrency)),
+            concurrency,
+            queue: concurrency * 16,
+            pending: Arc::new(AtomicUsize::new(0)),
+            fallback: None,
+            last_good: Arc::default(),
+            skin_db: None,
+            in_flight: Arc::default(),
+        }
+    }
+}
+
+impl RenderService {
+    /// Creates a service with one render slot per CPU and a queue of 16 per slot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the fetcher, e.g. to share its rate limiter.
+    pub fn with_fetcher(
+        mut self,
+        fetcher: Fetcher,
+    ) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+
+    /// Replaces the store of parsed skins.
+    pub fn with_store(
+        mut self,
+        store: DedupStore,
+    ) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Sets the skin layout every render is composed with.
+    pub fn with_skin(
+        mut self,
+        skin: Skin,
+    ) -> Self {
+        self.skin = skin;
+        self
+    }
+
+    /// Sets the options every render is composed with.
+    ///
+    /// Unset source URL and colors of [ComposeOptions::metadata] are filled per request.
+    pub fn with_options(
+        mut self,
+        options: ComposeOptions,
+    ) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets how many renders run at once, at least one.
+    pub fn with_concurrency(
+        mut self,
+        concurrency: usize,
+    ) -> Self {
+        self.concurrency = concurrency.max(1);
+        self.permits = Arc::new(Semaphore::new(self.concurrency));
+        self
+    }
+
+    /// Sets how many renders may wait for a free slot.
+    pub fn with_queue(
+        mut self,
+        queue: usize,
+    ) -> Self {
+        self.queue = queue;
+        self
+    }
+
+    /// Sets the fallbacks tried by [RenderService::render_or_fallback].
+    pub fn with_fallback(
+        mut self,
+        policy: FallbackPolicy,
+    ) -> Self {
+        self.fallback = Some(policy);
+        self
+    }
+
+    /// Sets the skin database names are resolved with, see [RenderService::skin_url].
+    /// Names are fetched through the client, so its mirrors are used.
+    pub fn with_skin_db(
+        mut self,
+        client: SkinDbClient,
+    ) -> Self {
+        self.skin_db = Some(client);
+        self
+    }
+
+    /// Returns the URL a skin is stored under, `name_or_url` is returned as is if it is
+    /// a URL, and resolved as a [normalized](skin_name::normalize) skin name with the
+    /// skin database otherwise, [SkinDbSource::DDNET] if none is set.
+    pub fn skin_url(
+        &self,
+        name_or_url: &str,
+    ) -> Option<String> {
+        if name_or_url.contains("://") {
+            return Some(name_or_url.to_string());
+        }
+        match &self.skin_db {
+            Some(client) => client.download_url(name_or_url),
+            None => SkinDbSource::DDNET.url_for(name_or_url),
+        }
+        .ok()
+    }
+
+    /// Fetches, parses and stores skins by name or URL, so later renders of them do not
+    /// wait for the network.
+    ///
+    /// At most [RenderService::with_concurrency] skins are loaded at once. Warmups do
+    /// not take render slots and are not limited by the queue.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn warmup<I>(
+        &self,
+        names_or_urls: I,
+    ) -> WarmupReport
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let mut tasks = JoinSet::new();
+        let mut entries = HashMap::new();
+        for entry in names_or_urls {
+            let entry = entry.as_ref().to_string();
+            let service = self.clone();
+            let permits = permits.clone();
+            let task = tasks.spawn({
+                let entry = entry.clone();
+                async move {
+                    let _permit = permits
+                        .acquire()
+                        .await
+                        .expect("the semaphore is never closed");
+                    service.warm(&entry).await
+                }
+            });
+            entries.insert(task.id(), entry);
+        }
+
+        let mut report = WarmupReport::default();
+        while let Some(joined) = tasks.join_next_with_id().await {
+            let (id, result) = match joined {
+                Ok((id, result)) => (id, result),
+                Err(e) => (e.id(), Err(TeeError::Join(e))),
+            };
+            match result {
+                Ok(true) => report.loaded += 1,
+                Ok(false) => report.stored += 1,
+                Err(error) => {
+                    let entry = entries.remove(&id).unwrap_or_default();
+                    warn!(skin = %telemetry::url(&entry), %error, "Warmup failed");
+                    report.failed.push((entry, error));
+                }
+            }
+        }
+        debug!(
+            loaded = report.loaded,
+            stored = report.stored,
+            failed = report.failed.len(),
+            "Warmed up skins"
+        );
+        report
+    }
+
+    /// Stores the skin `name_or_url`, returning whether it had to be loaded.
+    async fn warm(
+        &self,
+        name_or_url: &str,
+    ) -> Result<bool> {
+        let url = self
+            .skin_url(name_or_url)
+            .ok_or_else(|| TeeError::UnknownSkin(name_or_url.to_string()))?;
+        if self.store.get(&url).is_some() {
+            return Ok(false);
+        }
+        match (&self.skin_db, skin_name::normalize(name_or_url)) {
+            (Some(client), Some(name)) if !name_or_url.contains("://") => {
+                let tee = client.fetch(&name).await?;
+                self.store.insert(url, tee);
+            }
+            _ => {
+                self.tee(&url, &mut RenderTimings::default()).await?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns the store of parsed skins.
+    pub fn store(&self) -> &DedupStore {
+        &self.store
+    }
+
+    /// Returns the amount of renders running or waiting for a slot.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Acquire)
+    }
+
+    /// Renders a request.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(RenderedTee)` with the encoded image,
+    /// `Err(TeeError::ServiceOverloaded)` if the queue is full, or any error of fetching,
+    /// parsing and composing.
+    #[instrument(level = "debug", skip(self, request), fields(url = %telemetry::url(&request.url), eye = ?request.eye))]
+    pub async fn render(
+        &self,
+        request: RenderRequest,
+    ) -> Result<RenderedTee> {
+        let started = Instant::now();
+        let _slot = self.reserve()?;
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .expect("the semaphore is never closed");
+        let mut timings = RenderTimings {
+            queued: started.elapsed(),
+            ..Default::default()
+        };
+        trace!("Acquired a render slot");
+
+        let (tee, cache_hit) = self.tee(&request.url, &mut timings).await?;
+        let mut options = self.options.clone();
+        if let Some(meta) = &mut options.metadata {
+            meta.source_url.get_or_insert_with(|| request.url.clone());
+            if let Some(colors) = request.colors {
+                meta.body_color.get_or_insert(colors.body);
+                meta.feet_color.get_or_insert(colors.feet);
+            }
+        }
+        let skin = self.skin;
+        let format = request.format;
+        let source = Provenance::Skin(request.url.clone());
+
+        let started = Instant::now();
+        let bytes = tokio::task::spawn_blocking(move || {
+            let recolored;
+            let tee = match request.colors {
+                Some(colors) => {
+                    let mut tee = Tee::clone(&tee);
+                    apply_player_colors(&mut tee, colors);
+                    recolored = tee;
+                    &recolored
+                }
+                None => &*tee,
+            };
+            tee.compose_with_options(skin, request.eye, request.format, &options)
+        })
+        .await
+        .map_err(TeeError::Join)??;
+        timings.compose = started.elapsed();
+        Ok(RenderedTee {
+            bytes,
+            format,
+            source,
+            timings,
+            cache_hit,
+        })
+    }
+
+    /// Renders a request like [RenderService::render] and walks the [FallbackPolicy] if
+    /// that fails, so users always get some avatar.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(RenderedTee)` with the first image that could be made, or the
+    /// error of the requested render if every fallback failed or no policy is set.
+    #[instrument(level = "debug", skip(self, request), fields(url = %telemetry::url(&request.url)))]
+    pub async fn render_or_fallback(
+        &self,
+        request: RenderRequest,
+    ) -> Result<RenderedTee> {
+        let error = match self.render(request.clone()).await {
+            Ok(rendered) => {
+                self.remember(&request, &rendered.bytes);
+                return Ok(rendered);
+            }
+            Err(error) => error,
+        };
+        let Some(policy) = &self.fallback else {
+            return Err(error);
+        };
+        warn!(%error, "Render failed, trying fallbacks");
+
+        for &step in &policy.steps {
+            let result = match step {
+                Fallback::LastGood => Ok(self
+                    .last_good
+                    .lock()
+                    .expect("last good renders are never poisoned")
+                    .get(&request)
+                    .map(|bytes| RenderedTee {
+                        bytes: bytes.clone(),
+                        format: request.format,
+                        source: Provenance::Fallback(step),
+                        timings: RenderTimings::default(),
+                        cache_hit: true,
+                    })),
+                Fallback::DefaultSkin | Fallback::Identicon => self
+                    .render_fallback(step, policy.default_skin.clone(), request.clone())
+                    .await
+                    .map(Some),
+            };
+            match result {
+                Ok(Some(rendered)) => {
+                    debug!(?step, "Rendered a fallback");
+                    return Ok(rendered);
+                }
+                Ok(None) => trace!(?step, "Fallback has nothing to offer"),
+                Err(error) => warn!(?step, %error, "Fallback failed"),
+            }
+        }
+        Err(error)
+    }
+
+    /// Composes the default skin or an identicon of it for `request`.
+    async fn render_fallback(
+        &self,
+        step: Fallback,
+        default_skin: Arc<Tee>,
+        request: RenderRequest,
+    ) -> Result<RenderedTee> {
+        let skin = self.skin;
+        let options = self.options.clone();
+        let format = request.format;
+        let started = Instant::now();
+        let bytes = tokio::task::spawn_blocking(move || {
+            if step == Fallback::Identicon {
+                let identicon = Tee::identicon(&request.url, &default_skin);
+                return identicon.tee.compose_with_options(
+                    skin,
+                    identicon.eye,
+                    request.format,
+                    &options,
+                );
+            }
+            let recolored;
+            let tee = match request.colors {
+                Some(colors) => {
+                    let mut tee = Tee::clone(&default_skin);
+                    apply_player_colors(&mut tee, colors);
+                    recolored = tee;
+                    &recolored
+                }
+                None => &*default_skin,
+            };
+            tee.compose_with_options(skin, request.eye, request.format, &options)
+        })
+        .await
+        .map_err(TeeError::Join)??;
+        Ok(RenderedTee {
+            bytes,
+            format,
+            source: Provenance::Fallback(step),
+            timings: RenderTimings {
+                compose: started.elapsed(),
+                ..Default::default()
+            },
+            cache_hit: false,
+        })
+    }
+
+    /// Keeps a successful render for [Fallback::LastGood].
+    fn remember(
+        &self,
+        request: &RenderRequest,
+        bytes: &Bytes,
+    ) {
+        let Some(policy) = &self.fallback else {
+            return;
+        };
+        if !policy.steps.contains(&Fallback::LastGood) || policy.last_good_capacity == 0 {
+            return;
+        }
+        let mut last_good = self
+            .last_good
+            .lock()
+            .expect("last good renders are never poisoned");
+        if last_good.len() >= policy.last_good_capacity && !last_good.contains_key(request) {
+            // any entry will do, the map only bounds memory
+            if let Some(key) = last_good.keys().next().cloned() {
+                last_good.remove(&key);
+            }
+        }
+        last_good.insert(request.clone(), bytes.clone());
+    }
+
+    /// Returns the stored tee for `url` and whether it was stored, fetching and parsing
+    /// it on a miss. The time spent is added to `timings`.
+    ///
+    /// Concurrent misses of a URL share one load. If it fails, the next waiting request
+    /// loads the skin itself.
+    async fn tee(
+        &self,
+        url: &str,
+        timings: &mut RenderTimings,
+    ) -> Result<(Arc<Tee>, bool)> {
+        if let Some(tee) = self.store.get(url) {
+            trace!("Skin is already stored");
+            return Ok((tee, true));
+        }
+        let cell = self
+            .in_flight
+            .lock()
+            .expect("in-flight map poisoned")
+            .entry(url.to_string())
+            .or_default()
+            .clone();
+        let mut loaded = false;
+        let result = {
+            let loaded = &mut loaded;
+            cell.get_or_try_init(|| async move {
+                *loaded = true;
+                self.load(url, timings).await
+            })
+            .await
+            .cloned()
+        };
+        let mut in_flight = self.in_flight.lock().expect("in-flight map poisoned");
+        if in_flight
+            .get(url)
+            .is_some_and(|entry| Arc::ptr_eq(entry, &cell))
+        {
+            in_flight.remove(url);
+        }
+        drop(in_flight);
+        if !loaded {
+            trace!("Skin was loaded by a concurrent request");
+        }
+        Ok((result?, !loaded))
+    }
+
+    /// Fetches and parses the skin at `url` into the store.
+    async fn load(
+        &self,
+        url: &str,
+        timings: &mut RenderTimings,
+    ) -> Result<Arc<Tee>> {
+        let started = Instant::now();
+        let (bytes, format) = self.fetcher.fetch_image(url).await?;
+        timings.fetch = started.elapsed();
+        debug!(size = bytes.len(), "Fetched skin");
+
+        let store = self.store.clone();
+        let url = url.to_string();
+        let started = Instant::now();
+        let tee = tokio::task::spawn_blocking(move || store.get_or_parse(url, bytes, format))
+            .await
+            .map_err(TeeError::Join)??;
+        timings.parse = started.elapsed();
+        Ok(tee)
+    }
+
+    /// Counts a render as pending, failing if the queue is full.
+    fn reserve(&self) -> Result<PendingSlot> {
+        let limit = self.concurrency + self.queue;
+        let pending = self.pending.fetch_add(1, Ordering::AcqRel);
+        let slot = PendingSlot(self.pending.clone());
+        if pending >= limit {
+            warn!(pending, limit, "Render queue is full");
+            return Err(TeeError::ServiceOverloaded);
+        }
+        Ok(slot)
+    }
+}
+
+/// Decrements the pending counter when a render finishes or is cancelled.
+struct PendingSlot(Arc<AtomicUsize>);
+
+impl Drop for PendingSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
@@ -0,0 +1,227 @@
+//! # Sheet module
+//!
+//! Splits any asset sheet into named parts. A [`SheetLayout`] maps names to
+//! rectangles, a [`Sheet`] is a decoded image checked against such a layout.
+//!
+//! [`Tee`](crate::tee::Tee) is parsed through a sheet built from its [`UV`], and the
+//! same engine splits the other DDNet asset sheets.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::{sheet::{Sheet, SheetLayout}, tee::{options::ParseOptions, uv::UvPart}};
+//!
+//! let layout = SheetLayout::new((64, 32))
+//!     .with_part("left", UvPart::new(0, 0, (32, 32)))
+//!     .with_part("right", UvPart::new(32, 0, (32, 32)));
+//! let sheet = Sheet::new(std::fs::read("sheet.png")?.into(), layout, &ParseOptions::default())?;
+//! let parts = sheet.extract_all()?;
+//! ```
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use image::{DynamicImage, GenericImageView, RgbaImage};
+use tracing::{debug, error, instrument};
+
+use crate::{
+    error::{Result, TeeError},
+    tee::{
+        options::ParseOptions,
+        pool::BufferPool,
+        raw::{
+            ExtractPolicy, decode_image, extract_part_pooled, extract_part_with_policy,
+            validate_image_dimensions,
+        },
+        uv::{ContentSize, UV, UvPart},
+    },
+};
+
+/// Named rectangles of an asset sheet.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SheetLayout {
+    /// Size of the whole sheet
+    pub container: ContentSize,
+    /// Parts of the sheet by name
+    pub parts: HashMap<String, UvPart>,
+}
+
+impl SheetLayout {
+    /// Creates a layout for a sheet of the given size without parts.
+    pub fn new(container: ContentSize) -> Self {
+        Self {
+            container,
+            parts: HashMap::new(),
+        }
+    }
+
+    /// Adds a part, replacing a part with the same name.
+    pub fn with_part(
+        mut self,
+        name: impl Into<String>,
+        part: UvPart,
+    ) -> Self {
+        self.parts.insert(name.into(), part);
+        self
+    }
+
+    /// Returns a part by its name.
+    pub fn get(
+        &self,
+        name: &str,
+    ) -> Option<UvPart> {
+        self.parts.get(name).copied()
+    }
+
+    /// Returns the layout with every part and the container multiplied by `factor`,
+    /// e.g. for high resolution sheets.
+    pub fn scaled(
+        &self,
+        factor: f32,
+    ) -> Self {
+        let container = UvPart::new(0, 0, self.container).scaled(factor);
+        Self {
+            container: container.size(),
+            parts: self
+                .parts
+                .iter()
+                .map(|(name, part)| (name.clone(), part.scaled(factor)))
+                .collect(),
+        }
+    }
+}
+
+impl From<UV> for SheetLayout {
+    /// Names the parts after [UV::PART_NAMES].
+    fn from(uv: UV) -> Self {
+        uv.parts()
+            .into_iter()
+            .fold(SheetLayout::new(uv.container), |layout, (name, part)| {
+                layout.with_part(name, part)
+            })
+    }
+}
+
+/// A decoded asset sheet matching its [`SheetLayout`].
+#[derive(Debug, Clone)]
+pub struct Sheet {
+    image: DynamicImage,
+    layout: SheetLayout,
+    policy: ExtractPolicy,
+    pool: Option<BufferPool>,
+}
+
+impl Sheet {
+    /// Decodes a sheet, guessing its format from the data.
+    ///
+    /// The sheet is checked and split according to [ParseOptions::extract_policy], into
+    /// the [ParseOptions::buffer_pool] if set.
+    #[instrument(level = "debug", skip(data, layout, options), fields(data_size = data.len()))]
+    pub fn new(
+        data: Bytes,
+        layout: SheetLayout,
+        options: &ParseOptions,
+    ) -> Result<Self> {
+        let image = decode_image(data, None, options)?;
+        let sheet = Self::from_image_with_policy(image, layout, options.extract_policy)?;
+        Ok(match &options.buffer_pool {
+            Some(pool) => sheet.with_buffer_pool(pool.clone()),
+            None => sheet,
+        })
+    }
+
+    /// Wraps an already decoded sheet, checking its size against the layout.
+    pub fn from_image(
+        image: DynamicImage,
+        layout: SheetLayout,
+    ) -> Result<Self> {
+        Self::from_image_with_policy(image, layout, ExtractPolicy::Strict)
+    }
+
+    /// Wraps an already decoded sheet like [Sheet::from_image].
+    ///
+    /// With [ExtractPolicy::Clamp] the sheet may be smaller than the layout, and parts
+    /// reaching past its edge are padded with transparency.
+    pub fn from_image_with_policy(
+        image: DynamicImage,
+        layout: SheetLayout,
+        policy: ExtractPolicy,
+    ) -> Result<Self> {
+        let (width, height) = image.dimensions();
+        let (max_width, max_height) = layout.container;
+        match policy {
+            ExtractPolicy::Strict => validate_image_dimensions((width, height), layout.container)?,
+            ExtractPolicy::Clamp if width > max_width || height > max_height => {
+                error!(actual = ?(width, height), expected = ?layout.container, "Sheet is larger than its layout.");
+                return Err(TeeError::InvalidDimensions {
+                    expected: layout.container,
+                    found: (width, height),
+                });
+            }
+            ExtractPolicy::Clamp => {}
+        }
+        Ok(Self {
+            image,
+            layout,
+            policy,
+            pool: None,
+        })
+    }
+
+    /// Extracts parts into buffers taken from `pool`.
+    pub fn with_buffer_pool(
+        mut self,
+        pool: BufferPool,
+    ) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Returns the layout of the sheet.
+    pub fn layout(&self) -> &SheetLayout {
+        &self.layout
+    }
+
+    /// Returns the whole decoded sheet.
+    pub fn image(&self) -> &DynamicImage {
+        &self.image
+    }
+
+    /// Extracts a single part by its name.
+    pub fn extract(
+        &self,
+        name: &str,
+    ) -> Result<RgbaImage> {
+        let part = self.layout.get(name).ok_or_else(|| {
+            error!(part = name, "Sheet layout has no such part.");
+            TeeError::UnknownSheetPart(name.to_string())
+        })?;
+        self.extract_part(part)
+    }
+
+    fn extract_part(
+        &self,
+        part: UvPart,
+    ) -> Result<RgbaImage> {
+        match &self.pool {
+            Some(pool) => extract_part_pooled(&self.image, part, self.policy, pool),
+            None => extract_part_with_policy(&self.image, part, self.policy),
+        }
+    }
+
+    /// Extracts every part of the layout.
+    #[instrument(level = "debug", skip(self), fields(parts = self.layout.parts.len()))]
+    pub fn extract_all(&self) -> Result<HashMap<String, RgbaImage>> {
+        let parts = self
+            .layout
+            .parts
+            .iter()
+            .map(|(name, part)| {
+                let image = self.extract_part(*part)?;
+                Ok((name.clone(), image))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        debug!("Successfully extracted all parts of the sheet.");
+        Ok(parts)
+    }
+}
@@ -0,0 +1,76 @@
+//! # Skin name module
+//!
+//! Skin names as the game accepts them: at most [MAX_LENGTH] bytes, without quotes and
+//! slashes, which would break the config and the path of the skin file. Names made of
+//! dots only are refused as well, in URLs they would step out of the skin directory.
+//! Names keep their case, the skin files and the database URLs are case sensitive.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use tee_morphosis::skin_name;
+//!
+//! let name = skin_name::normalize("  nameless tee.png ").unwrap();
+//! assert_eq!(name, "nameless tee");
+//! assert_eq!(skin_name::encode(&name), "nameless%20tee");
+//! ```
+
+/// Longest skin name in bytes, the game stores names in 24 byte buffers.
+pub const MAX_LENGTH: usize = 23;
+
+/// Returns `true` if the game accepts `name` as is.
+pub fn is_valid(name: &str) -> bool {
+    name.len() <= MAX_LENGTH && !name.chars().any(is_forbidden) && !is_dots(name)
+}
+
+/// Turns user input into a name the game accepts, `None` if nothing is left of it.
+///
+/// Surrounding whitespace and a `.png` extension are removed, so file names work too.
+/// Quotes, slashes and control characters are dropped, and the name is cut to
+/// [MAX_LENGTH] bytes without splitting a character. Names made of dots only, such as
+/// `..`, give `None`.
+pub fn normalize(name: &str) -> Option<String> {
+    let name = name.trim();
+    let name = match name.len().checked_sub(4) {
+        Some(stem) if name.is_char_boundary(stem) && name[stem..].eq_ignore_ascii_case(".png") => {
+            &name[..stem]
+        }
+        _ => name,
+    };
+    let mut normalized = String::with_capacity(name.len().min(MAX_LENGTH));
+    for c in name.chars().filter(|&c| !is_forbidden(c)) {
+        if normalized.len() + c.len_utf8() > MAX_LENGTH {
+            break;
+        }
+        normalized.push(c);
+    }
+    let normalized = normalized.trim_end();
+    (!is_dots(normalized)).then(|| normalized.to_string())
+}
+
+/// Percent-encodes `name` for a URL path segment, leaving only unreserved characters.
+///
+/// Dots are kept, so pass names checked by [normalize] or [is_valid]: URL parsers
+/// resolve `.` and `..` segments even when they are percent-encoded.
+pub fn encode(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Returns `true` for empty names and names made of dots only, which are relative path
+/// segments in URLs.
+fn is_dots(name: &str) -> bool {
+    name.bytes().all(|byte| byte == b'.')
+}
+
+/// Characters the game rejects in skin names.
+fn is_forbidden(c: char) -> bool {
+    matches!(c, '"' | '/' | '\\') || c.is_control()
+}
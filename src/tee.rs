@@ -52,26 +52,67 @@
 //! ```
 
 pub mod builder;
+pub mod censor;
+pub mod compositor;
+pub mod data_url;
+pub mod expression;
+pub mod hash;
 pub mod hsl;
+pub mod identicon;
+pub(crate) mod imaging;
+pub mod layer;
+pub mod limits;
+pub mod options;
 pub mod parts;
+pub mod pool;
+pub mod portrait;
+pub mod pose;
+pub mod preview;
+pub mod random;
+pub mod raw;
 pub mod skin;
+pub mod sprite;
+pub mod team;
+pub mod timings;
 pub mod uv;
+pub mod variation;
 
-use std::{collections::HashMap, io::Cursor};
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Instant,
+};
 
 use bytes::Bytes;
-use image::{DynamicImage, GenericImageView, ImageFormat, ImageReader, RgbaImage, imageops};
-use tracing::{debug, error, info, instrument, trace, warn};
+use image::{DynamicImage, GenericImageView, ImageFormat, RgbaImage};
+use rayon::prelude::*;
+use tracing::{Span, debug, field::Empty, instrument, trace, warn};
 
 use crate::{
+    RENDER_VERSION,
     error::{Result, TeeError},
+    etag::{ComposedImage, ETagHasher},
+    meta::RenderMeta,
+    moderation::Verdict,
+    sheet::{Sheet, SheetLayout},
     tee::{
-        hsl::{HSL, img_hsl_transform},
-        parts::{EyeType, EyeTypeData, TeePart, WithShadow},
+        compositor::{CompositorBackend, PlacedLayer, check_deadline, composite},
+        data_url::{DataUrl, decode_base64},
+        hash::SourceHash,
+        hsl::{HSL, img_hsl_transform, img_recolor_dark},
+        imaging::{Backend, Filter, Imaging},
+        layer::{Layer, ZOrder},
+        limits::DecodeLimits,
+        options::{CompatMode, ComposeOptions, FeetStyle, ParseOptions},
+        parts::{AnyPart, EyePair, EyeSelection, EyeType, EyeTypeData, TeePart, WithShadow},
+        raw::{decode_image, encode_image, synthesize_blink, validate_image_dimensions},
         skin::{Skin, SkinPS},
-        uv::{TEE_UV_LAYOUT, UV, UVPart},
+        team::TeamColor,
+        timings::{ComposeReport, ComposeTimings, LayerTiming},
+        uv::{TEE_UV_LAYOUT, UV, UvPart},
     },
 };
+#[cfg(feature = "net")]
+use crate::{net::Fetcher, telemetry};
 
 /// Represents a parsed Tee character, containing all its visual components.
 ///
@@ -88,10 +129,16 @@ pub struct Tee {
     ///
     /// [Normal, Angry, Pain, Happy, Empty, Surprise]
     pub eye: [EyeTypeData; 6],
+    /// [EyeType::Blink], synthesized from the normal eye
+    blink: RgbaImage,
+    /// Extra eyes registered with [Tee::add_custom_eye], by name
+    custom_eyes: BTreeMap<String, RgbaImage>,
     /// The hand parts of the character, including both the main hand and its shadow
     pub hand: WithShadow,
     /// The UV mapping used to extract parts from the source image
     pub used_uv: UV,
+    /// SHA-256 of the source bytes
+    source_hash: SourceHash,
 }
 
 impl Tee {
@@ -116,7 +163,7 @@ impl Tee {
     /// let tee = Tee::new(image_data.into(), ImageFormat::Png)?;
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    #[instrument(level = "info", skip(data), fields(format = ?format))]
+    #[instrument(level = "debug", skip(data), fields(format = ?format))]
     pub fn new(
         data: Bytes,
         format: ImageFormat,
@@ -147,34 +194,267 @@ impl Tee {
     /// let tee = Tee::new_with_uv(image_data.into(), custom_uv, ImageFormat::Png)?;
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    #[instrument(level = "info", skip(data, uv), fields(format = ?format))]
+    #[instrument(level = "debug", skip(data, uv), fields(format = ?format))]
     pub fn new_with_uv(
         data: Bytes,
         uv: UV,
         format: ImageFormat,
     ) -> Result<Self> {
+        Self::new_with_options(data, uv, format, ParseOptions::default())
+    }
+
+    /// Parses a `Tee` struct from raw image data with a custom UV layout and [ParseOptions].
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The raw bytes of an image containing the [Tee] parts.
+    /// * `uv` - A [UV] struct containing the coordinates and dimensions for each part on the source image.
+    /// * `format` - The [ImageFormat] of the input data (e.g., PNG, JPEG).
+    /// * `options` - The [ParseOptions], e.g. decode limits.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(Tee)` on successful parsing, or `Err(TeeError)` on failure.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::tee::{Tee, limits::DecodeLimits, options::ParseOptions, uv::TEE_UV_LAYOUT};
+    /// use image::ImageFormat;
+    ///
+    /// let image_data = std::fs::read("tee_parts.png")?;
+    /// let options = ParseOptions::new().with_limits(DecodeLimits::UNTRUSTED);
+    /// let tee = Tee::new_with_options(image_data.into(), TEE_UV_LAYOUT, ImageFormat::Png, options)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[instrument(level = "debug", skip(data, uv, options), fields(format = ?format))]
+    pub fn new_with_options(
+        data: Bytes,
+        uv: UV,
+        format: ImageFormat,
+        options: ParseOptions,
+    ) -> Result<Self> {
+        let source_hash = SourceHash::of(&data);
         trace!("Starting to decode image with format: {:?}", format);
-        let img = decode_image(data, format)?;
-        let img_dimensions = img.dimensions();
+        let img = decode_image(data, Some(format), &options)?;
+        let tee = Self::from_image(img, uv, source_hash, &options)?;
+        if let Some(checker) = &options.content_checker {
+            if let Verdict::Reject(reason) = checker.check(&tee.body.value) {
+                warn!(%reason, "Content checker rejected the skin");
+                return Err(TeeError::ContentRejected(reason));
+            }
+            trace!("Content checker accepted the skin");
+        }
+        Ok(tee)
+    }
+
+    /// Parses a `Tee` struct from untrusted image data with default [uv]::[TEE_UV_LAYOUT].
+    ///
+    /// The format is guessed from the content, and `limits` are enforced before the
+    /// pixel buffer is allocated, so decompression bombs fail early instead of
+    /// exhausting memory. Use it for arbitrary user uploads.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The raw bytes of an image containing the [Tee] parts.
+    /// * `limits` - The [DecodeLimits] to enforce, see [DecodeLimits::UNTRUSTED].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(Tee)` on successful parsing, or `Err(TeeError)` on failure.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::tee::{Tee, limits::DecodeLimits};
+    ///
+    /// let upload = std::fs::read("upload.png")?;
+    /// let tee = Tee::new_untrusted(upload.into(), DecodeLimits::UNTRUSTED)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[instrument(level = "debug", skip(data), fields(data_size = data.len()))]
+    pub fn new_untrusted(
+        data: Bytes,
+        limits: DecodeLimits,
+    ) -> Result<Self> {
+        let source_hash = SourceHash::of(&data);
+        trace!("Starting to decode untrusted image");
+        let options = ParseOptions::new().with_limits(limits);
+        let img = decode_image(data, None, &options)?;
+        Self::from_image(img, TEE_UV_LAYOUT, source_hash, &options)
+    }
+
+    /// Parses a [Tee] from a `data:` URL, e.g. a canvas export posted by a web frontend.
+    ///
+    /// The format is taken from the media type, or guessed from the content if the URL
+    /// has none. The payload is untrusted and decoded with [DecodeLimits::UNTRUSTED].
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::tee::Tee;
+    ///
+    /// let tee = Tee::new_from_data_url(&request.body.skin)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[instrument(level = "debug", skip(url), fields(url_len = url.len()))]
+    pub fn new_from_data_url(url: &str) -> Result<Self> {
+        let url = DataUrl::parse(url)?;
+        trace!(
+            media_type = url.media_type,
+            data_size = url.data.len(),
+            "Parsed data URL"
+        );
+        match url.format() {
+            Some(format) => Self::new_with_options(
+                url.data,
+                TEE_UV_LAYOUT,
+                format,
+                ParseOptions::new().with_limits(DecodeLimits::UNTRUSTED),
+            ),
+            None if url.media_type.is_empty() => {
+                Self::new_untrusted(url.data, DecodeLimits::UNTRUSTED)
+            }
+            None => {
+                warn!(
+                    media_type = url.media_type,
+                    "Data URL does not hold an image"
+                );
+                Err(TeeError::MalformedDataUrl("media type is not an image"))
+            }
+        }
+    }
 
-        debug!(image_dimensions = ?img_dimensions, "Image decoded successfully.");
+    /// Parses a [Tee] from bare base64, guessing the format from the content.
+    ///
+    /// Like [Tee::new_from_data_url], the payload is decoded with [DecodeLimits::UNTRUSTED].
+    #[instrument(level = "debug", skip(encoded), fields(encoded_len = encoded.len()))]
+    pub fn new_from_base64(encoded: &str) -> Result<Self> {
+        let data = decode_base64(encoded)?;
+        Self::new_untrusted(data, DecodeLimits::UNTRUSTED)
+    }
 
-        validate_image_dimensions(img_dimensions, uv.container)?;
+    /// Parses a vertically stacked sheet of `frame_count` skins into one [Tee] per frame.
+    ///
+    /// Each frame has the size of `uv.container` and the frames are ordered top to
+    /// bottom. The format is guessed from the data. All tees share the
+    /// [source hash](Tee::source_hash) of the whole sheet.
+    ///
+    /// **note**: the sheet is decoded with the default [DecodeLimits], so it can hold
+    /// up to 32 frames of 256x128.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::tee::{Tee, uv::TEE_UV_LAYOUT};
+    ///
+    /// let sheet = std::fs::read("animated_skin.png")?;
+    /// let frames = Tee::new_animated(sheet.into(), TEE_UV_LAYOUT, 8)?;
+    /// assert_eq!(frames.len(), 8);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[instrument(level = "debug", skip(data, uv), fields(data_size = data.len()))]
+    pub fn new_animated(
+        data: Bytes,
+        uv: UV,
+        frame_count: u32,
+    ) -> Result<Vec<Self>> {
+        let source_hash = SourceHash::of(&data);
+        trace!("Starting to decode animated sheet");
+        let img = decode_image(data, None, &ParseOptions::default())?;
 
-        debug!("Extracting all parts from the image.");
-        let body = extract_with_shadow(&img, uv.body, uv.body_shadow)?;
-        let feet = extract_with_shadow(&img, uv.feet, uv.feet_shadow)?;
-        let hand = extract_with_shadow(&img, uv.hand, uv.hand_shadow)?;
-        let eye = extract_all_eyes(&img, &uv.eyes)?;
+        let (width, height) = uv.container;
+        let Some(sheet_height) = height.checked_mul(frame_count) else {
+            // No image is that tall, the sheet can not hold every frame
+            return Err(TeeError::FrameOutOfRange {
+                index: frame_count as usize - 1,
+                frames: (img.height() / height.max(1)) as usize,
+            });
+        };
+        validate_image_dimensions(img.dimensions(), (width, sheet_height))?;
 
-        info!("Successfully parsed all Tee parts from the image.");
-        Ok(Self {
+        (0..frame_count)
+            .map(|frame| {
+                trace!(frame, "Parsing frame of animated sheet");
+                let frame = img.crop_imm(0, frame * height, width, height);
+                Self::from_image(frame, uv, source_hash, &ParseOptions::default())
+            })
+            .collect()
+    }
+
+    /// Extracts all parts from an already decoded image.
+    ///
+    /// The image is split as a [Sheet] with the layout built from `uv`, into buffers of
+    /// the [buffer pool](ParseOptions::buffer_pool) if there is one.
+    fn from_image(
+        img: DynamicImage,
+        uv: UV,
+        source_hash: SourceHash,
+        options: &ParseOptions,
+    ) -> Result<Self> {
+        debug!(image_dimensions = ?img.dimensions(), "Image decoded successfully.");
+        let mut sheet =
+            Sheet::from_image_with_policy(img, SheetLayout::from(uv), options.extract_policy)?;
+        if let Some(pool) = &options.buffer_pool {
+            sheet = sheet.with_buffer_pool(pool.clone());
+        }
+
+        debug!("Extracting all parts from the image.");
+        let [
             body,
+            body_shadow,
             feet,
-            eye,
+            feet_shadow,
             hand,
+            hand_shadow,
+            normal,
+            angry,
+            pain,
+            happy,
+            empty,
+            surprise,
+        ] = UV::PART_NAMES.map(|name| sheet.extract(name));
+        let normal = normal?;
+        let blink = synthesize_blink(&normal);
+
+        let eye = [
+            EyeTypeData::Normal(normal),
+            EyeTypeData::Angry(angry?),
+            EyeTypeData::Pain(pain?),
+            EyeTypeData::Happy(happy?),
+            EyeTypeData::Empty(empty?),
+            EyeTypeData::Surprise(surprise?),
+        ];
+
+        let tee = Self {
+            body: WithShadow {
+                value: body?,
+                shadow: body_shadow?,
+            },
+            feet: WithShadow {
+                value: feet?,
+                shadow: feet_shadow?,
+            },
+            eye,
+            blink,
+            hand: WithShadow {
+                value: hand?,
+                shadow: hand_shadow?,
+            },
             used_uv: uv,
-        })
+            source_hash,
+            custom_eyes: BTreeMap::new(),
+        };
+        let blank_eyes: Vec<_> = EyeType::ALL
+            .into_iter()
+            .filter(|&eye| tee.is_eye_blank(eye))
+            .collect();
+        if !blank_eyes.is_empty() {
+            warn!(?blank_eyes, "Skin has fully transparent eye sprites");
+        }
+
+        debug!("Successfully parsed all Tee parts from the image.");
+        Ok(tee)
     }
 
     #[cfg(feature = "net")]
@@ -203,23 +483,13 @@ impl Tee {
     ///     Ok(())
     /// }
     /// ```
-    #[instrument(level = "info", skip(uv), fields(url = %url))]
+    #[instrument(level = "debug", skip(url, uv), fields(url = %telemetry::url(url)))]
     pub async fn new_from_url_with_uv(
         url: &str,
         uv: UV,
     ) -> Result<Self> {
-        trace!("Fetching image from URL: {}", url);
-        let (bytes, format) = fetch_image_from_url(url).await?;
-
-        info!(
-            "Successfully fetched image data, size: {} bytes",
-            bytes.len()
-        );
-
-        // Use `instrument` to create a new span for the `Tee::new_with_uv` call
-        tokio::task::spawn_blocking(move || Self::new_with_uv(bytes, uv, format))
-            .await
-            .map_err(TeeError::Join)?
+        trace!("Fetching image from URL: {}", telemetry::url(url));
+        Fetcher::new().fetch_tee_with_uv(url, uv).await
     }
 
     #[cfg(feature = "net")]
@@ -246,19 +516,10 @@ impl Tee {
     ///     Ok(())
     /// }
     /// ```
-    #[instrument(level = "info", fields(url = %url))]
+    #[instrument(level = "debug", skip(url), fields(url = %telemetry::url(url)))]
     pub async fn new_from_url(url: &str) -> Result<Self> {
-        trace!("Fetching image from URL: {}", url);
-        let (bytes, format) = fetch_image_from_url(url).await?;
-
-        info!(
-            "Successfully fetched image data, size: {} bytes",
-            bytes.len()
-        );
-
-        tokio::task::spawn_blocking(move || Self::new(bytes, format))
-            .await
-            .map_err(TeeError::Join)?
+        trace!("Fetching image from URL: {}", telemetry::url(url));
+        Fetcher::new().fetch_tee(url).await
     }
 
     /// Applies HSL color transformation to specific parts of the Tee.
@@ -287,27 +548,8 @@ impl Tee {
         parts: &[TeePart],
     ) {
         trace!("Applying HSL transformation to {} parts", parts.len());
-        for part in parts {
-            match part {
-                TeePart::Body => {
-                    img_hsl_transform(&mut self.body.value, hsl);
-                }
-                TeePart::BodyShadow => {
-                    img_hsl_transform(&mut self.body.shadow, hsl);
-                }
-                TeePart::Feet => {
-                    img_hsl_transform(&mut self.feet.value, hsl);
-                }
-                TeePart::FeetShadow => {
-                    img_hsl_transform(&mut self.feet.shadow, hsl);
-                }
-                TeePart::Hand => {
-                    img_hsl_transform(&mut self.hand.value, hsl);
-                }
-                TeePart::HandShadow => {
-                    img_hsl_transform(&mut self.hand.shadow, hsl);
-                }
-            }
+        for &part in parts {
+            img_hsl_transform(self.get_mut(part), hsl);
         }
         debug!("Successfully applied HSL transformation to specified parts");
     }
@@ -350,12 +592,31 @@ impl Tee {
         debug!("Successfully applied HSL transformation to all parts");
     }
 
+    /// Tints the whole Tee with the color of a team, like the game does in team based modes.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::tee::{Tee, team::TeamColor};
+    ///
+    /// let mut tee = Tee::new(/* ... */)?;
+    /// tee.apply_team_color(TeamColor::Red);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[instrument(level = "debug", skip(self))]
+    pub fn apply_team_color(
+        &mut self,
+        team: TeamColor,
+    ) {
+        self.apply_hsl_to_all(team.to_hsl());
+    }
+
     /// Composites the Tee parts onto a base skin image to create a final character portrait.
     ///
     /// # Arguments
     ///
     /// * `skin` - The base `Skin` to draw the Tee parts onto.
-    /// * `eye_type` - The `EyeType` to use for the eyes in the final image.
+    /// * `eye_type` - The `EyeType` or custom eye name to use for the eyes in the final image.
     /// * `img_format` - The desired `ImageFormat` for the output bytes (e.g., PNG, JPEG).
     ///
     /// # Returns
@@ -374,44 +635,513 @@ impl Tee {
     /// std::fs::write("output.png", result)?;
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    #[instrument(level = "info", skip(self, skin), fields(eye_type = ?eye_type, img_format = ?img_format, skin_container = ?skin.container))]
-    pub fn compose(
+    #[instrument(level = "debug", skip(self, skin, eye_type), fields(img_format = ?img_format, skin_container = ?skin.container))]
+    pub fn compose<'a>(
         &self,
         skin: Skin,
-        eye_type: EyeType,
+        eye_type: impl Into<EyeSelection<'a>>,
         img_format: ImageFormat,
     ) -> Result<Bytes> {
         trace!("Starting composition process");
-        let mut canvas = RgbaImage::new(skin.container.0, skin.container.1);
+        let canvas = self.compose_image(skin, eye_type);
+
+        let bytes = encode_image(&canvas, img_format)?;
+        debug!(
+            output_size = bytes.len(),
+            "Successfully composed Tee image."
+        );
+        Ok(bytes)
+    }
+
+    /// Composites the Tee like [`Tee::compose`] and applies [ComposeOptions] to the result.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::{
+    ///     tee::{Tee, options::ComposeOptions, parts::EyeType, skin::TEE_SKIN_LAYOUT},
+    ///     watermark::Watermark,
+    /// };
+    /// use image::ImageFormat;
+    ///
+    /// let tee = Tee::new(/* ... */)?;
+    /// let options = ComposeOptions::new().with_watermark(Watermark::image(logo).with_opacity(0.4));
+    /// let result = tee.compose_with_options(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png, &options)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[instrument(
+        level = "debug",
+        skip(self, skin, eye_type, options),
+        fields(img_format = ?img_format, encode_us = Empty, output_bytes = Empty)
+    )]
+    pub fn compose_with_options<'a>(
+        &self,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        img_format: ImageFormat,
+        options: &ComposeOptions,
+    ) -> Result<Bytes> {
+        let eye_type = eye_type.into();
+        let canvas = self.compose_image_with_options(skin, eye_type, options);
+        let start = Instant::now();
+        let data = encode_image(&canvas, img_format)?;
+        let data = match self.render_meta(eye_type, canvas.dimensions(), options) {
+            Some(meta) => {
+                trace!("Embedding render metadata");
+                meta.embed(data, img_format)?
+            }
+            None => data,
+        };
+        let span = Span::current();
+        span.record("encode_us", start.elapsed().as_micros() as u64);
+        span.record("output_bytes", data.len());
+        Ok(data)
+    }
+
+    /// Returns the metadata of [ComposeOptions] with unset eye, size and source hash
+    /// filled from the render.
+    fn render_meta(
+        &self,
+        eye_type: EyeSelection<'_>,
+        size: (u32, u32),
+        options: &ComposeOptions,
+    ) -> Option<RenderMeta> {
+        let mut meta = options.metadata.clone()?;
+        meta.eye.get_or_insert_with(|| eye_type.name().into_owned());
+        meta.size.get_or_insert(size);
+        meta.source_hash.get_or_insert(self.source_hash);
+        Some(meta)
+    }
+
+    /// Composites the Tee like [`Tee::compose_image`] and applies [ComposeOptions] to the result.
+    pub fn compose_image_with_options<'a>(
+        &self,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        options: &ComposeOptions,
+    ) -> RgbaImage {
+        self.compose_image_until(skin, eye_type.into(), options, None)
+            .expect("composing without a deadline can not fail")
+            .0
+    }
+
+    /// Composites the Tee like [`Tee::compose_with_options`], giving up once `deadline`
+    /// passed.
+    ///
+    /// The deadline is checked before every layer and before encoding, so an
+    /// interactive caller can answer with a cached or smaller render instead of waiting.
+    /// A render whose encoding already started is finished.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(Bytes)` on success, or `Err(TeeError::DeadlineExceeded)`
+    /// naming the stage that was not started in time.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let deadline = Instant::now() + Duration::from_millis(50);
+    /// let data = match tee.compose_with_deadline(skin, EyeType::Happy, ImageFormat::Png, &options, deadline) {
+    ///     Err(TeeError::DeadlineExceeded { .. }) => cached_preview.clone(),
+    ///     result => result?,
+    /// };
+    /// ```
+    #[instrument(level = "debug", skip(self, skin, eye_type, options), fields(img_format = ?img_format))]
+    pub fn compose_with_deadline<'a>(
+        &self,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        img_format: ImageFormat,
+        options: &ComposeOptions,
+        deadline: Instant,
+    ) -> Result<Bytes> {
+        let eye_type = eye_type.into();
+        let (canvas, _) = self.compose_image_until(skin, eye_type, options, Some(deadline))?;
+        check_deadline(Some(deadline), "encoding")?;
+        let data = encode_image(&canvas, img_format)?;
+        match self.render_meta(eye_type, canvas.dimensions(), options) {
+            Some(meta) => meta.embed(data, img_format),
+            None => Ok(data),
+        }
+    }
+
+    /// Composites like [`Tee::compose_image_with_options`], checking `deadline` between
+    /// the stages.
+    fn compose_image_until(
+        &self,
+        skin: Skin,
+        eye_type: EyeSelection<'_>,
+        options: &ComposeOptions,
+        deadline: Option<Instant>,
+    ) -> Result<(RgbaImage, Vec<LayerTiming>)> {
+        let eye_type = match eye_type {
+            EyeSelection::Standard(eye) if options.blank_eye_fallback && self.is_eye_blank(eye) => {
+                warn!(
+                    ?eye,
+                    "Selected eye is blank, falling back to the normal eye"
+                );
+                EyeSelection::Standard(EyeType::Normal)
+            }
+            EyeSelection::Pair(pair)
+                if options.blank_eye_fallback
+                    && (self.is_eye_blank(pair.left) || self.is_eye_blank(pair.right)) =>
+            {
+                warn!(
+                    ?pair,
+                    "Selected eyes are blank, falling back to the normal eye"
+                );
+                let fallback = |eye| if self.is_eye_blank(eye) { EyeType::Normal } else { eye };
+                EyeSelection::Pair(EyePair::new(fallback(pair.left), fallback(pair.right)))
+            }
+            eye_type => eye_type,
+        };
+        let backend = options
+            .backend
+            .unwrap_or_else(|| CompositorBackend::auto(skin.output_size()));
+        let (mut canvas, timings) =
+            self.compose_canvas(skin, eye_type, backend, options, deadline)?;
+        if let Some(watermark) = &options.watermark {
+            check_deadline(deadline, "watermark")?;
+            trace!("Applying watermark");
+            watermark.apply(&mut canvas);
+        }
+        if let Some(kind) = options.color_blindness {
+            check_deadline(deadline, "color blindness filter")?;
+            trace!(?kind, "Simulating color blindness");
+            kind.apply(&mut canvas);
+        }
+        Ok((canvas, timings))
+    }
+
+    #[cfg(feature = "net")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+    /// Composites the Tee like [`Tee::compose_with_options`] on tokio's blocking pool, so
+    /// async handlers don't stall the executor while the image is composed and encoded.
+    ///
+    /// The Tee is cloned into the blocking task.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::tee::{Tee, options::ComposeOptions, parts::EyeType, skin::TEE_SKIN_LAYOUT};
+    /// use image::ImageFormat;
+    ///
+    /// let tee = Tee::new_from_url("https://example.com/tee.png").await?;
+    /// let bytes = tee
+    ///     .compose_async(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::WebP, ComposeOptions::new())
+    ///     .await?;
+    /// ```
+    #[instrument(level = "debug", skip(self, skin, eye_type, options), fields(img_format = ?img_format))]
+    pub async fn compose_async<'a>(
+        &self,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        img_format: ImageFormat,
+        options: ComposeOptions,
+    ) -> Result<Bytes> {
+        // the selection borrows, the task needs owned data
+        let (owned, custom): (EyeSelection<'static>, _) = match eye_type.into() {
+            EyeSelection::Standard(eye) => (EyeSelection::Standard(eye), None),
+            EyeSelection::Pair(pair) => (EyeSelection::Pair(pair), None),
+            EyeSelection::Custom(name) => (
+                EyeSelection::Standard(EyeType::Normal),
+                Some(name.to_owned()),
+            ),
+        };
+        let tee = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let eye = custom.as_deref().map_or(owned, EyeSelection::Custom);
+            tee.compose_with_options(skin, eye, img_format, &options)
+        })
+        .await
+        .map_err(TeeError::Join)?
+    }
 
-        // Define the composition function
-        let mut compose = |layer: &RgbaImage, ((x, y), scale): SkinPS, uv_part: UVPart| {
+    /// Returns the entity tag of a render without composing it.
+    ///
+    /// The tag covers [RENDER_VERSION], [Tee::etag], the skin layout, the eye, the format
+    /// and `options`, and equals [ComposedImage::etag] of the matching [Tee::compose_tagged] call.
+    pub fn compose_etag<'a>(
+        &self,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        img_format: ImageFormat,
+        options: &ComposeOptions,
+    ) -> String {
+        let mut hasher = ETagHasher::default();
+        hasher
+            .field(&RENDER_VERSION.to_le_bytes())
+            .field(self.etag().as_bytes())
+            .field(format!("{skin:?}").as_bytes())
+            .field(eye_type.into().name().as_bytes())
+            .field(img_format.to_mime_type().as_bytes());
+        options.hash_into(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Composites the Tee like [`Tee::compose_with_options`] and tags the result for HTTP
+    /// serving, see [crate::etag].
+    #[instrument(level = "debug", skip(self, skin, eye_type, options), fields(img_format = ?img_format))]
+    pub fn compose_tagged<'a>(
+        &self,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        img_format: ImageFormat,
+        options: &ComposeOptions,
+    ) -> Result<ComposedImage> {
+        let eye_type = eye_type.into();
+        let etag = self.compose_etag(skin, eye_type, img_format, options);
+        let canvas = self.compose_image_with_options(skin, eye_type, options);
+        let meta = self.render_meta(eye_type, canvas.dimensions(), options);
+        let mut data = encode_image(&canvas, img_format)?;
+        if let Some(meta) = &meta {
+            data = meta.embed(data, img_format)?;
+        }
+        Ok(ComposedImage::new(data, img_format, etag, canvas, meta))
+    }
+
+    /// Composites the Tee like [`Tee::compose`] and measures the time spent in each stage.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok((Bytes, ComposeTimings))` containing the final image data
+    /// and the timings on success, or `Err(TeeError)` on failure.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::tee::{Tee, parts::EyeType, skin::TEE_SKIN_LAYOUT};
+    /// use image::ImageFormat;
+    ///
+    /// let tee = Tee::new(/* ... */)?;
+    /// let (_, timings) = tee.compose_timed(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::WebP)?;
+    /// println!("layers: {:?}, encode: {:?}", timings.layers, timings.encode);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[instrument(level = "debug", skip(self, skin, eye_type), fields(img_format = ?img_format))]
+    pub fn compose_timed<'a>(
+        &self,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        img_format: ImageFormat,
+    ) -> Result<(Bytes, ComposeTimings)> {
+        let start = Instant::now();
+        let canvas = self.compose_image(skin, eye_type);
+        let layers = start.elapsed();
+
+        let start = Instant::now();
+        let bytes = encode_image(&canvas, img_format)?;
+        let encode = start.elapsed();
+
+        let timings = ComposeTimings {
+            layers,
+            encode,
+            output_size: bytes.len(),
+        };
+        debug!(?timings, "Successfully composed Tee image.");
+        Ok((bytes, timings))
+    }
+
+    /// Composites the Tee like [`Tee::compose_with_options`] and reports the time spent on
+    /// every layer and on encoding.
+    ///
+    /// The same numbers are recorded as `encode_us` and `output_bytes` on the span of
+    /// this call, and as `resize_us` and `overlay_us` on a debug event per layer.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let (_, report) = tee.compose_with_report(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png, &options)?;
+    /// if let Some(layer) = report.slowest_layer() {
+    ///     println!("{} took {:?}", layer.name, layer.resize + layer.overlay);
+    /// }
+    /// ```
+    #[instrument(
+        level = "debug",
+        skip(self, skin, eye_type, options),
+        fields(img_format = ?img_format, encode_us = Empty, output_bytes = Empty)
+    )]
+    pub fn compose_with_report<'a>(
+        &self,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        img_format: ImageFormat,
+        options: &ComposeOptions,
+    ) -> Result<(Bytes, ComposeReport)> {
+        let eye_type = eye_type.into();
+        let (canvas, layers) = self
+            .compose_image_until(skin, eye_type, options, None)
+            .expect("composing without a deadline can not fail");
+
+        let start = Instant::now();
+        let data = encode_image(&canvas, img_format)?;
+        let data = match self.render_meta(eye_type, canvas.dimensions(), options) {
+            Some(meta) => meta.embed(data, img_format)?,
+            None => data,
+        };
+        let encode = start.elapsed();
+
+        let span = Span::current();
+        span.record("encode_us", encode.as_micros() as u64);
+        span.record("output_bytes", data.len());
+        let report = ComposeReport {
+            layers,
+            encode,
+            output_size: data.len(),
+        };
+        Ok((data, report))
+    }
+
+    /// Composites the Tee parts onto a canvas without encoding it.
+    ///
+    /// Useful when the result is further processed, e.g. placed into a [`Scene`](crate::scene::Scene).
+    ///
+    /// # Arguments
+    ///
+    /// * `skin` - The base `Skin` to draw the Tee parts onto.
+    /// * `eye_type` - The `EyeType` or custom eye name to use for the eyes in the final image.
+    ///
+    /// # Returns
+    ///
+    /// The composed `RgbaImage` with the size of [Skin::output_size].
+    ///
+    /// **note**: an unknown custom eye falls back to [EyeType::Normal]. The compositor
+    /// backend is picked from the output area, see [CompositorBackend::auto].
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::tee::{Tee, parts::EyeType, skin::TEE_SKIN_LAYOUT};
+    ///
+    /// let tee = Tee::new(/* ... */)?;
+    /// let canvas = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[instrument(level = "debug", skip(self, skin, eye_type), fields(skin_container = ?skin.container))]
+    pub fn compose_image<'a>(
+        &self,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+    ) -> RgbaImage {
+        let backend = CompositorBackend::auto(skin.output_size());
+        self.compose_image_with_backend(skin, eye_type.into(), backend)
+    }
+
+    /// Composites the Tee like [`Tee::compose_image`] with a chosen [CompositorBackend].
+    pub fn compose_image_with_backend(
+        &self,
+        skin: Skin,
+        eye_type: EyeSelection<'_>,
+        backend: CompositorBackend,
+    ) -> RgbaImage {
+        self.compose_canvas(skin, eye_type, backend, &ComposeOptions::default(), None)
+            .expect("composing without a deadline can not fail")
+            .0
+    }
+
+    /// Composites the layers with the options that change how they are drawn.
+    fn compose_canvas(
+        &self,
+        skin: Skin,
+        eye_type: EyeSelection<'_>,
+        backend: CompositorBackend,
+        options: &ComposeOptions,
+        deadline: Option<Instant>,
+    ) -> Result<(RgbaImage, Vec<LayerTiming>)> {
+        trace!(?eye_type, ?backend, "Composing image");
+        let (width, height) = skin.output_size();
+        let mut canvas = RgbaImage::new(width, height);
+
+        // Collect the layers, the backend resizes and blends them
+        let mut layers = Vec::new();
+        let mut compose = |name: &'static str, layer: &RgbaImage, part: SkinPS, uv_part: UvPart| {
+            let (position, size) = skin.place(part, (uv_part.w, uv_part.h));
+            let origin = match options.compat {
+                // Fractional positions are resampled instead of rounded
+                CompatMode::Native => skin.origin(part),
+                // Pillow only places at whole pixels
+                CompatMode::DiscordBotPy => (position.0 as f32, position.1 as f32),
+            };
             debug!(
-                "Composing layer at position ({}, {}) with size ({}, {}) and scale {}",
-                x, y, uv_part.w, uv_part.h, scale
-            );
-            let (w, h) = skin::scale((uv_part.w, uv_part.h), scale);
-            imageops::overlay(
-                &mut canvas,
-                &imageops::resize(layer, w, h, imageops::FilterType::Triangle),
-                x,
-                y,
+                "Composing layer at position {:?} with size {:?} and scale {}",
+                origin, size, part.1
             );
+            layers.push(PlacedLayer::at(name, layer.clone(), origin, size));
         };
 
         // Layering order is important for correct appearance
-        self.compose_layers(&mut compose, &skin, eye_type);
-
-        let mut buf = Vec::new();
-        let mut cursor = Cursor::new(&mut buf);
-        debug!(
-            "Writing composed image to buffer in format: {:?}",
-            img_format
+        self.compose_layers(
+            &mut compose,
+            &skin,
+            eye_type,
+            options.feet,
+            &options.layer_order,
+            &options.extra_layers,
         );
-        canvas.write_to(&mut cursor, img_format)?;
+        let timings = composite(&mut canvas, layers, backend, options.compat, deadline)?;
 
-        info!(output_size = buf.len(), "Successfully composed Tee image.");
-        Ok(Bytes::from(buf))
+        Ok((canvas, timings))
+    }
+
+    /// Composites the Tee once at the largest of `sizes` and downscales it for the others.
+    ///
+    /// Every size is the length of the longer side of the output, the aspect ratio of
+    /// [Skin::output_size] is kept. Outputs are encoded in parallel.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(HashMap<u32, Bytes>)` with an encoded image per size, or
+    /// `Err(TeeError)` on failure.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let icons = tee.compose_sizes(TEE_SKIN_LAYOUT, EyeType::Normal, ImageFormat::WebP, &[32, 64, 128, 256])?;
+    /// let small = &icons[&32];
+    /// ```
+    #[instrument(level = "debug", skip(self, skin, eye_type), fields(skin_container = ?skin.container))]
+    pub fn compose_sizes<'a>(
+        &self,
+        skin: Skin,
+        eye_type: impl Into<EyeSelection<'a>>,
+        img_format: ImageFormat,
+        sizes: &[u32],
+    ) -> Result<HashMap<u32, Bytes>> {
+        let Some(&largest) = sizes.iter().max() else {
+            return Ok(HashMap::new());
+        };
+        let output = skin.output_size();
+        let longer_side = output.0.max(output.1).max(1);
+        let fit = |size: u32| {
+            let factor = size as f32 / longer_side as f32;
+            skin::scale(output, factor)
+        };
+
+        let factor = largest as f32 / longer_side as f32;
+        let mut canvas = self.compose_image(skin.scaled(factor), eye_type);
+        // Rounding may miss the requested size by a pixel
+        let target = fit(largest);
+        if canvas.dimensions() != target {
+            canvas = Backend::resize(&canvas, target.0, target.1, Filter::Lanczos3);
+        }
+        trace!(size = ?canvas.dimensions(), "Composed the largest size");
+
+        let canvas = &canvas;
+        sizes
+            .par_iter()
+            .map(|&size| {
+                let (w, h) = fit(size);
+                let data = if (w, h) == canvas.dimensions() {
+                    encode_image(canvas, img_format)?
+                } else {
+                    let resized = Backend::resize(canvas, w.max(1), h.max(1), Filter::Lanczos3);
+                    encode_image(&resized, img_format)?
+                };
+                Ok((size, data))
+            })
+            .collect()
     }
 
     /// Composites the Tee with PNG format.
@@ -436,16 +1166,41 @@ impl Tee {
     /// std::fs::write("output.png", result)?;
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    #[instrument(level = "info", skip(self, skin), fields(skin_container = ?skin.container))]
-    pub fn compose_png(
+    #[instrument(level = "debug", skip(self, skin, eye_type), fields(skin_container = ?skin.container))]
+    pub fn compose_png<'a>(
         &self,
         skin: Skin,
-        eye_type: EyeType,
+        eye_type: impl Into<EyeSelection<'a>>,
     ) -> Result<Bytes> {
         trace!("Composing with default options (happy eyes, PNG format)");
         self.compose(skin, eye_type, ImageFormat::Png)
     }
 
+    /// Returns the SHA-256 of the bytes this Tee was parsed from.
+    ///
+    /// Skins with identical source files share the same hash regardless of where
+    /// they were loaded from, see [`DedupStore`](crate::cache::DedupStore).
+    pub fn source_hash(&self) -> SourceHash {
+        self.source_hash
+    }
+
+    /// Returns a strong entity tag of the current parts, quoted as sent in the `ETag` header.
+    ///
+    /// Unlike [Tee::source_hash] it changes when the parts are recolored or eyes are added.
+    pub fn etag(&self) -> String {
+        let mut hasher = ETagHasher::default();
+        for part in [&self.body, &self.feet, &self.hand] {
+            hasher.image(&part.value).image(&part.shadow);
+        }
+        for eye in EyeType::ALL {
+            hasher.image(self.get_eye(eye));
+        }
+        for (name, image) in &self.custom_eyes {
+            hasher.field(name.as_bytes()).image(image);
+        }
+        hasher.finish()
+    }
+
     /// Retrieves the image for a specific eye type.
     ///
     /// # Arguments
@@ -472,6 +1227,9 @@ impl Tee {
         &self,
         r#type: EyeType,
     ) -> &RgbaImage {
+        if r#type == EyeType::Blink {
+            return &self.blink;
+        }
         let index = r#type.index();
         match (&r#type, &self.eye[index]) {
             (EyeType::Normal, EyeTypeData::Normal(img)) => img,
@@ -489,9 +1247,171 @@ impl Tee {
         }
     }
 
+    /// Returns the sprite of any part or eye.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::tee::{Tee, parts::AnyPart};
+    ///
+    /// let tee = Tee::new(/* ... */)?;
+    /// for part in AnyPart::ALL {
+    ///     println!("{}: {:?}", part.name(), tee.get(part).dimensions());
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn get(
+        &self,
+        part: impl Into<AnyPart>,
+    ) -> &RgbaImage {
+        match part.into() {
+            AnyPart::Body => &self.body.value,
+            AnyPart::BodyShadow => &self.body.shadow,
+            AnyPart::Feet => &self.feet.value,
+            AnyPart::FeetShadow => &self.feet.shadow,
+            AnyPart::Hand => &self.hand.value,
+            AnyPart::HandShadow => &self.hand.shadow,
+            AnyPart::Eye(eye_type) => self.get_eye(eye_type),
+        }
+    }
+
+    /// Returns the sprite of any part or eye, mutably.
+    pub fn get_mut(
+        &mut self,
+        part: impl Into<AnyPart>,
+    ) -> &mut RgbaImage {
+        match part.into() {
+            AnyPart::Body => &mut self.body.value,
+            AnyPart::BodyShadow => &mut self.body.shadow,
+            AnyPart::Feet => &mut self.feet.value,
+            AnyPart::FeetShadow => &mut self.feet.shadow,
+            AnyPart::Hand => &mut self.hand.value,
+            AnyPart::HandShadow => &mut self.hand.shadow,
+            AnyPart::Eye(EyeType::Blink) => &mut self.blink,
+            AnyPart::Eye(eye_type) => self.eye[eye_type.index()].image_mut(),
+        }
+    }
+
+    /// Applies HSL color transformation to a single part or eye.
+    ///
+    /// Eyes are transformed as a whole, use [Tee::set_eye_color] to only recolor the
+    /// pupils.
+    pub fn apply_hsl(
+        &mut self,
+        part: impl Into<AnyPart>,
+        hsl: HSL,
+    ) {
+        let part = part.into();
+        trace!(part = part.name(), ?hsl, "Applying HSL transformation");
+        img_hsl_transform(self.get_mut(part), hsl);
+    }
+
+    /// Returns whether an eye sprite is fully transparent.
+    ///
+    /// Low quality community skins often leave some eyes empty, see
+    /// [ComposeOptions::blank_eye_fallback]. The current sprite is checked, so edits
+    /// through [Tee::get_mut] are taken into account.
+    pub fn is_eye_blank(
+        &self,
+        r#type: EyeType,
+    ) -> bool {
+        self.get_eye(r#type).pixels().all(|pixel| pixel.0[3] == 0)
+    }
+
+    /// Recolors the pupils of every eye, including custom eyes, with `hsl`.
+    ///
+    /// Only the dark pixels of the sprites are painted, see [img_recolor_dark], so
+    /// highlights stay white and the shading of the pupils is kept. Eyes registered later
+    /// are not recolored.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut tee = Tee::new(/* ... */)?;
+    /// tee.set_eye_color(ddnet_color_to_hsl(0x00ff80));
+    /// let canvas = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy);
+    /// ```
+    #[instrument(level = "debug", skip(self))]
+    pub fn set_eye_color(
+        &mut self,
+        hsl: HSL,
+    ) {
+        for eye in &mut self.eye {
+            img_recolor_dark(eye.image_mut(), hsl);
+        }
+        img_recolor_dark(&mut self.blink, hsl);
+        for image in self.custom_eyes.values_mut() {
+            img_recolor_dark(image, hsl);
+        }
+        debug!("Recolored the pupils of all eyes");
+    }
+
+    /// Registers an extra eye under `name`, replacing an eye with the same name.
+    ///
+    /// The eye must have the size of the normal eye of this Tee. Register e.g. dead or
+    /// X-eyes added by community mods and select them with [EyeSelection::Custom].
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use tee_morphosis::tee::{Tee, parts::EyeSelection, skin::TEE_SKIN_LAYOUT};
+    ///
+    /// let mut tee = Tee::new(/* ... */)?;
+    /// tee.add_custom_eye("dead", image::open("dead_eye.png")?.to_rgba8())?;
+    /// let canvas = tee.compose_image(TEE_SKIN_LAYOUT, EyeSelection::Custom("dead"));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[instrument(level = "debug", skip(self, name, image))]
+    pub fn add_custom_eye(
+        &mut self,
+        name: impl Into<String>,
+        image: RgbaImage,
+    ) -> Result<()> {
+        let expected = self.get_eye(EyeType::Normal).dimensions();
+        validate_image_dimensions(image.dimensions(), expected)?;
+        self.custom_eyes.insert(name.into(), image);
+        Ok(())
+    }
+
+    /// Removes a custom eye, returning its image.
+    pub fn remove_custom_eye(
+        &mut self,
+        name: &str,
+    ) -> Option<RgbaImage> {
+        self.custom_eyes.remove(name)
+    }
+
+    /// Returns a custom eye by its name.
+    pub fn custom_eye(
+        &self,
+        name: &str,
+    ) -> Option<&RgbaImage> {
+        self.custom_eyes.get(name)
+    }
+
+    /// Returns the names of all custom eyes, sorted.
+    pub fn custom_eye_names(&self) -> impl Iterator<Item = &str> {
+        self.custom_eyes.keys().map(String::as_str)
+    }
+
+    /// Retrieves the image for a standard or custom eye, `None` for unknown custom eyes.
+    ///
+    /// For an [EyePair] the left eye is returned.
+    pub fn select_eye<'a>(
+        &self,
+        selection: impl Into<EyeSelection<'a>>,
+    ) -> Option<&RgbaImage> {
+        match selection.into() {
+            EyeSelection::Standard(eye_type) => Some(self.get_eye(eye_type)),
+            EyeSelection::Custom(name) => self.custom_eye(name),
+            EyeSelection::Pair(pair) => Some(self.get_eye(pair.left)),
+        }
+    }
+
     /// Returns all parts of the Tee as a HashMap.
     ///
-    /// **note**: does not include eyes. Use [Tee::get_all_eyes] instead
+    /// **note**: does not include eyes. Use [Tee::get_all_eyes] instead, or [Tee::get] with
+    /// [AnyPart::ALL] for every sprite
     ///
     /// # Returns
     ///
@@ -539,249 +1459,106 @@ impl Tee {
     #[instrument(level = "debug", skip(self))]
     pub fn get_all_eyes(&self) -> HashMap<EyeType, &RgbaImage> {
         trace!("Collecting all eye types into a HashMap");
-        let mut eyes = HashMap::new();
-        eyes.insert(EyeType::Normal, self.get_eye(EyeType::Normal));
-        eyes.insert(EyeType::Angry, self.get_eye(EyeType::Angry));
-        eyes.insert(EyeType::Pain, self.get_eye(EyeType::Pain));
-        eyes.insert(EyeType::Happy, self.get_eye(EyeType::Happy));
-        eyes.insert(EyeType::Empty, self.get_eye(EyeType::Empty));
-        eyes.insert(EyeType::Surprise, self.get_eye(EyeType::Surprise));
+        let eyes = EyeType::ALL
+            .into_iter()
+            .map(|eye_type| (eye_type, self.get_eye(eye_type)))
+            .collect();
         debug!("Successfully collected all eye types into a HashMap");
         eyes
     }
 
     // Helper methods for internal use
 
-    /// Composes all layers of the Tee onto the canvas in the correct order.
+    /// Composes the layers of the Tee in `order`, bottom first.
     ///
     /// # Arguments
     ///
-    /// * `compose` - A closure that handles the actual composition of a layer.
+    /// * `compose` - A closure that handles the actual composition of a named layer.
     /// * `skin` - The skin layout to use for positioning.
-    /// * `eye_type` - The eye type to use for the eyes.
+    /// * `eye_type` - The eyes to use, unknown custom eyes fall back to [EyeType::Normal].
+    /// * `feet` - Shading of the feet.
+    /// * `order` - The layers to draw, see [Layer::DEFAULT_ORDER].
+    /// * `extras` - Sprites drawn between the layers, see [ZOrder].
     fn compose_layers<F>(
         &self,
         compose: &mut F,
         skin: &Skin,
-        eye_type: EyeType,
+        eye_type: EyeSelection<'_>,
+        feet: FeetStyle,
+        order: &[Layer],
+        extras: &[(RgbaImage, SkinPS, ZOrder)],
     ) where
-        F: FnMut(&RgbaImage, SkinPS, UVPart),
+        F: FnMut(&'static str, &RgbaImage, SkinPS, UvPart),
     {
-        trace!("Starting to compose layers in order");
-
-        // Layering order is important for correct appearance
-        compose(&self.body.shadow, skin.body, self.used_uv.body_shadow); // body shadow
-        compose(&self.feet.shadow, skin.feet_back, self.used_uv.feet_shadow); // back feet shadow
-        compose(&self.feet.shadow, skin.feet, self.used_uv.feet_shadow); // front feet shadow
-        compose(&self.feet.value, skin.feet_back, self.used_uv.feet); // back feet
-        compose(&self.body.value, skin.body, self.used_uv.body); // body
-
-        let eye = self.get_eye(eye_type);
-        compose(eye, skin.first_eyes, self.used_uv.eyes[0]); // first eye
-        compose(
-            &imageops::flip_horizontal(eye),
-            skin.second_eyes,
-            self.used_uv.eyes[0],
-        ); // second eye (flipped)
-
-        compose(&self.feet.value, skin.feet, self.used_uv.feet); // front feet
+        trace!(?order, "Starting to compose layers in order");
 
-        debug!("Successfully composed all layers");
-    }
-}
-
-/// Extracts a rectangular part from a source image.
-///
-/// # Arguments
-///
-/// * `img` - A reference to the source `DynamicImage`.
-/// * `part` - A `Part` struct defining the coordinates (`x`, `y`) and dimensions (`w`, `h`) of the area to extract.
-///
-/// # Returns
-///
-/// A `Result` which is `Ok(RgbaImage)` containing the extracted part, or `Err(TeeError::OutOfBounds)` if the part's dimensions fall outside the source image.
-///
-/// # Errors
-///
-/// Returns `TeeError::OutOfBounds` if the dimensions provided fall out of bounds.
-///
-/// # Example
-///
-/// ```rust,ignore
-/// use tee_morphosis::tee::extract_part;
-/// use image::DynamicImage;
-/// use tee_morphosis::tee::uv::Part;
-///
-/// let img = image::open("source.png")?;
-/// let part = Part { x: 10, y: 10, w: 50, h: 50 };
-/// let extracted = extract_part(&img, part)?;
-/// # Ok::<(), Box<dyn std::error::Error>>(())
-/// ```
-#[instrument(level = "debug", skip(img), fields(part = ?part))]
-fn extract_part(
-    img: &DynamicImage,
-    part: UVPart,
-) -> Result<RgbaImage> {
-    let (img_width, img_height) = img.dimensions();
-
-    if part.x + part.w > img_width || part.y + part.h > img_height {
-        error!(
-            image_width = img_width,
-            image_height = img_height,
-            "Failed to extract part: out of bounds."
-        );
-        return Err(TeeError::OutOfBounds {
-            part,
-            width: img_width,
-            height: img_height,
-        });
-    }
-
-    trace!(
-        "Extracting part at position ({}, {}) with size ({}, {})",
-        part.x, part.y, part.w, part.h
-    );
-    let cropped_image = img.view(part.x, part.y, part.w, part.h).to_image();
-    Ok(cropped_image)
-}
+        let (first_eye, second_eye) = match eye_type {
+            EyeSelection::Pair(pair) => (self.get_eye(pair.left), self.get_eye(pair.right)),
+            eye_type => {
+                let eye = self.select_eye(eye_type).unwrap_or_else(|| {
+                    warn!(
+                        ?eye_type,
+                        "Unknown custom eye, falling back to the normal eye."
+                    );
+                    self.get_eye(EyeType::Normal)
+                });
+                (eye, eye)
+            }
+        };
+        let uv = &self.used_uv;
+        let compose_extras = |compose: &mut F, z_order: ZOrder| {
+            for (image, placement, _) in extras.iter().filter(|extra| extra.2 == z_order) {
+                compose(
+                    "extra",
+                    image,
+                    *placement,
+                    UvPart::new(0, 0, image.dimensions()),
+                );
+            }
+        };
+        let before_body = order.iter().position(|layer| *layer == Layer::Body);
+        let after_eyes = order.iter().rposition(Layer::is_eye).map(|index| index + 1);
 
-/// Decodes image data from bytes with the specified format.
-///
-/// # Arguments
-///
-/// * `data` - The raw bytes of the image.
-/// * `format` - The format of the image data.
-///
-/// # Returns
-///
-/// A `Result` which is `Ok(DynamicImage)` on successful decoding, or `Err(TeeError)` on failure.
-#[instrument(level = "debug", skip(data), fields(format = ?format, data_size = data.len()))]
-fn decode_image(
-    data: Bytes,
-    format: ImageFormat,
-) -> Result<DynamicImage> {
-    let mut img = ImageReader::new(Cursor::new(data));
-    img.set_format(format);
-    let img = img.decode()?;
-    Ok(img)
-}
+        for (index, layer) in order.iter().enumerate() {
+            if after_eyes == Some(index) {
+                compose_extras(compose, ZOrder::AfterEyes);
+            }
+            if before_body == Some(index) {
+                compose_extras(compose, ZOrder::BeforeBody);
+            }
+            let name = layer.name();
+            match layer {
+                Layer::BodyShadow => compose(name, &self.body.shadow, skin.body, uv.body_shadow),
+                Layer::FeetBackShadow => {
+                    compose(name, &self.feet.shadow, skin.feet_back, uv.feet_shadow)
+                }
+                Layer::FeetShadow => compose(name, &self.feet.shadow, skin.feet, uv.feet_shadow),
+                Layer::FeetBack => compose(
+                    name,
+                    &feet.back_foot(&self.feet.value),
+                    skin.feet_back,
+                    uv.feet,
+                ),
+                Layer::Body => compose(name, &self.body.value, skin.body, uv.body),
+                Layer::FirstEye => compose(name, first_eye, skin.first_eyes, uv.eyes[0]),
+                Layer::SecondEye => compose(
+                    name,
+                    &Backend::flip_horizontal(second_eye),
+                    skin.second_eyes,
+                    uv.eyes[0],
+                ),
+                Layer::Feet => compose(name, &self.feet.value, skin.feet, uv.feet),
+            }
+        }
+        // Extras without their anchor end up on top
+        if after_eyes.is_none_or(|index| index == order.len()) {
+            compose_extras(compose, ZOrder::AfterEyes);
+        }
+        if before_body.is_none() {
+            compose_extras(compose, ZOrder::BeforeBody);
+        }
+        compose_extras(compose, ZOrder::Topmost);
 
-/// Validates that the image dimensions match the expected container dimensions.
-///
-/// # Arguments
-///
-/// * `actual` - The actual dimensions of the image.
-/// * `expected` - The expected dimensions of the image.
-///
-/// # Returns
-///
-/// A `Result` which is `Ok(())` if the dimensions match, or `Err(TeeError::InvalidDimensions)` if they don't.
-#[instrument(level = "debug", fields(actual = ?actual, expected = ?expected))]
-fn validate_image_dimensions(
-    actual: (u32, u32),
-    expected: (u32, u32),
-) -> Result<()> {
-    if actual != expected {
-        error!(
-            expected = ?expected,
-            found = ?actual,
-            "Invalid image dimensions."
-        );
-        return Err(TeeError::InvalidDimensions {
-            expected,
-            found: actual,
-        });
+        debug!("Successfully composed all layers");
     }
-    Ok(())
-}
-
-/// Extracts a part and its shadow from the source image.
-///
-/// # Arguments
-///
-/// * `img` - The source image.
-/// * `part` - The part to extract.
-/// * `shadow_part` - The shadow part to extract.
-///
-/// # Returns
-///
-/// A `Result` which is `Ok(WithShadow)` containing both the part and its shadow.
-#[instrument(level = "debug", skip(img), fields(part = ?part, shadow_part = ?shadow_part))]
-fn extract_with_shadow(
-    img: &DynamicImage,
-    part: UVPart,
-    shadow_part: UVPart,
-) -> Result<WithShadow> {
-    trace!("Extracting part and its shadow");
-    let value = extract_part(img, part)?;
-    let shadow = extract_part(img, shadow_part)?;
-    Ok(WithShadow {
-        value,
-        shadow,
-    })
-}
-
-/// Extracts all eye types from the source image.
-///
-/// # Arguments
-///
-/// * `img` - The source image.
-/// * `eye_parts` - An array of parts for each eye type.
-///
-/// # Returns
-///
-/// A `Result` which is `Ok([EyeTypeData; 6])` containing all eye types.
-#[instrument(level = "debug", skip(img, eye_parts))]
-fn extract_all_eyes(
-    img: &DynamicImage,
-    eye_parts: &[UVPart; 6],
-) -> Result<[EyeTypeData; 6]> {
-    trace!("Extracting all eye types");
-    let eyes = [
-        EyeTypeData::Normal(extract_part(img, eye_parts[0])?),
-        EyeTypeData::Angry(extract_part(img, eye_parts[1])?),
-        EyeTypeData::Pain(extract_part(img, eye_parts[2])?),
-        EyeTypeData::Happy(extract_part(img, eye_parts[3])?),
-        EyeTypeData::Empty(extract_part(img, eye_parts[4])?),
-        EyeTypeData::Surprise(extract_part(img, eye_parts[5])?),
-    ];
-    Ok(eyes)
-}
-
-/// Fetches an image from a URL and determines its format.
-///
-/// # Arguments
-///
-/// * `url` - The URL to fetch the image from.
-///
-/// # Returns
-///
-/// A `Result` which is `Ok((Bytes, ImageFormat))` containing the image data and its format.
-#[cfg(feature = "net")]
-#[instrument(level = "info", fields(url = %url))]
-async fn fetch_image_from_url(url: &str) -> Result<(Bytes, ImageFormat)> {
-    let response = reqwest::get(url).await.map_err(|e| {
-        error!(error = %e, "Failed to send request.");
-        TeeError::Reqwest(e)
-    })?;
-
-    // Determine format from Content-Type header
-    let format = response
-        .headers()
-        .get(reqwest::header::CONTENT_TYPE)
-        .and_then(|value| value.to_str().ok())
-        .and_then(|mime| ImageFormat::from_mime_type(mime))
-        .ok_or_else(|| {
-            error!("'Content-Type' header is missing or invalid.");
-            TeeError::ReqWithOutContentType(url.to_string())
-        })?;
-
-    info!(determined_format = ?format, "Image format determined from response header.");
-
-    let bytes = response.bytes().await.map_err(|e| {
-        error!(error = %e, "Failed to read bytes from response.");
-        TeeError::Reqwest(e)
-    })?;
-
-    Ok((bytes, format))
 }
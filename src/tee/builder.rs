@@ -2,7 +2,8 @@
 
 use crate::error::Result;
 use crate::tee::Tee;
-use crate::tee::uv::UV;
+use crate::tee::options::ParseOptions;
+use crate::tee::uv::{TEE_UV_LAYOUT, UV};
 use bytes::Bytes;
 use image::ImageFormat;
 
@@ -13,6 +14,7 @@ pub struct TeeBuilder {
     #[cfg(feature = "net")]
     url: Option<String>,
     uv: Option<UV>,
+    options: ParseOptions,
 }
 
 impl TeeBuilder {
@@ -47,31 +49,38 @@ impl TeeBuilder {
         self
     }
 
+    pub fn with_options(
+        mut self,
+        options: ParseOptions,
+    ) -> Self {
+        self.options = options;
+        self
+    }
+
     #[cfg(feature = "net")]
     pub async fn build(self) -> Result<Tee> {
         use crate::error::TeeError;
 
-        match (self.data, self.format, self.url, self.uv) {
-            (Some(data), Some(format), _, uv) => match uv {
-                Some(uv) => Tee::new_with_uv(data, uv, format),
-                None => Tee::new(data, format),
-            },
-            (None, None, Some(url), uv) => match uv {
-                Some(uv) => Tee::new_from_url_with_uv(&url, uv).await,
-                None => Tee::new_from_url(&url).await,
-            },
+        use crate::net::Fetcher;
+
+        let uv = self.uv.unwrap_or(TEE_UV_LAYOUT);
+        match (self.data, self.format, self.url) {
+            (Some(data), Some(format), _) => Tee::new_with_options(data, uv, format, self.options),
+            (None, None, Some(url)) => {
+                Fetcher::new()
+                    .fetch_tee_with_options(&url, uv, self.options)
+                    .await
+            }
             _ => Err(TeeError::InvalidBuilderConfiguration),
         }
     }
 
     #[cfg(not(feature = "net"))]
     pub fn build(self) -> Result<Tee> {
-        use crate::tee::TeeError;
-        match (self.data, self.format, self.uv) {
-            (Some(data), Some(format), uv) => match uv {
-                Some(uv) => Tee::new_with_uv(data, uv, format),
-                None => Tee::new(data, format),
-            },
+        use crate::error::TeeError;
+        let uv = self.uv.unwrap_or(TEE_UV_LAYOUT);
+        match (self.data, self.format) {
+            (Some(data), Some(format)) => Tee::new_with_options(data, uv, format, self.options),
             _ => Err(TeeError::InvalidBuilderConfiguration),
         }
     }
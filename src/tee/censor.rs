@@ -0,0 +1,116 @@
+//! # Module with censor filters
+//!
+//! Obscures parts of user uploaded skins, e.g. offensive body art, before they are shown
+//! publicly. Filters only change colors, the silhouette of the part is kept.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::tee::{censor::Censor, parts::TeePart};
+//!
+//! tee.censor_part(TeePart::Body, Censor::Pixelate(12));
+//! ```
+
+use image::{Rgba, RgbaImage, imageops};
+use tracing::{debug, instrument};
+
+use crate::tee::{Tee, parts::TeePart};
+
+/// A filter applied by [Tee::censor_part].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Censor {
+    /// Blocks of the given edge length in pixels, each filled with its average color
+    Pixelate(u32),
+    /// Gaussian blur with the given sigma
+    Blur(f32),
+}
+
+impl Censor {
+    /// Filters an image in place, alpha is kept.
+    pub fn apply(
+        &self,
+        img: &mut RgbaImage,
+    ) {
+        match *self {
+            Censor::Pixelate(block) => pixelate(img, block.max(1)),
+            Censor::Blur(sigma) => blur(img, sigma),
+        }
+    }
+}
+
+impl Tee {
+    /// Obscures a single part with a [Censor] filter.
+    #[instrument(level = "debug", skip(self))]
+    pub fn censor_part(
+        &mut self,
+        part: TeePart,
+        censor: Censor,
+    ) {
+        let img = match part {
+            TeePart::Body => &mut self.body.value,
+            TeePart::BodyShadow => &mut self.body.shadow,
+            TeePart::Feet => &mut self.feet.value,
+            TeePart::FeetShadow => &mut self.feet.shadow,
+            TeePart::Hand => &mut self.hand.value,
+            TeePart::HandShadow => &mut self.hand.shadow,
+        };
+        censor.apply(img);
+        debug!("Censored part");
+    }
+}
+
+fn pixelate(
+    img: &mut RgbaImage,
+    block: u32,
+) {
+    let (width, height) = img.dimensions();
+    for top in (0..height).step_by(block as usize) {
+        for left in (0..width).step_by(block as usize) {
+            let (right, bottom) = ((left + block).min(width), (top + block).min(height));
+            // Weighted by alpha, so transparent pixels do not darken the block
+            let mut sum = [0u64; 4];
+            for y in top..bottom {
+                for x in left..right {
+                    let [r, g, b, a] = img.get_pixel(x, y).0.map(u64::from);
+                    sum[0] += r * a;
+                    sum[1] += g * a;
+                    sum[2] += b * a;
+                    sum[3] += a;
+                }
+            }
+            if sum[3] == 0 {
+                continue;
+            }
+            let color = [0, 1, 2].map(|c| (sum[c] / sum[3]) as u8);
+            for y in top..bottom {
+                for x in left..right {
+                    let pixel = img.get_pixel_mut(x, y);
+                    pixel.0[..3].copy_from_slice(&color);
+                }
+            }
+        }
+    }
+}
+
+fn blur(
+    img: &mut RgbaImage,
+    sigma: f32,
+) {
+    // Blur premultiplied colors, so transparent pixels do not darken the edges
+    let mut premultiplied = img.clone();
+    for pixel in premultiplied.pixels_mut() {
+        let alpha = pixel.0[3] as u32;
+        for c in &mut pixel.0[..3] {
+            *c = (*c as u32 * alpha / 255) as u8;
+        }
+    }
+    let blurred = imageops::blur(&premultiplied, sigma);
+    for (pixel, blurred) in img.pixels_mut().zip(blurred.pixels()) {
+        let Rgba([r, g, b, a]) = *blurred;
+        if a == 0 {
+            continue;
+        }
+        let unpremultiply = |c: u8| (c as u32 * 255 / a as u32).min(255) as u8;
+        pixel.0[..3].copy_from_slice(&[unpremultiply(r), unpremultiply(g), unpremultiply(b)]);
+    }
+}
@@ -0,0 +1,389 @@
+//! # Module with compositor backends
+//!
+//! A render is a stack of resized layers blended onto a canvas. Small renders are
+//! fastest with a plain overlay loop, large ones (banners, 2048px previews) benefit
+//! from cache friendly tiles or from splitting the canvas into bands blended by rayon.
+//! Every CPU backend produces the same pixels. With the `gpu` feature layers can also be
+//! blended on the GPU, whose float rounding may differ by one level per channel.
+//! [CompatMode::DiscordBotPy] swaps the arithmetic for the one of Pillow and always
+//! blends like [CompositorBackend::Simple].
+
+#[cfg(feature = "gpu")]
+pub(crate) mod gpu;
+
+use std::time::{Duration, Instant};
+
+use image::{Pixel, Rgba, RgbaImage};
+use rayon::prelude::*;
+use tracing::{debug, trace, warn};
+
+use crate::{
+    error::{Result, TeeError},
+    tee::{
+        imaging::{Backend, Filter, Imaging, Pillow},
+        options::CompatMode,
+        timings::LayerTiming,
+    },
+};
+
+/// How layers are blended onto the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompositorBackend {
+    /// Overlay every layer onto the whole canvas, one after another
+    Simple,
+    /// Blend all layers tile by tile, so each tile stays in cache
+    Tiled,
+    /// Split the canvas into horizontal bands blended in parallel, and resize the layers in
+    /// parallel
+    Parallel,
+    /// Resize the layers like [CompositorBackend::Simple] and blend them all in one pass
+    /// on the GPU with wgpu. Blends like [CompositorBackend::Simple] without the `gpu`
+    /// feature or a usable GPU, see [CompositorBackend::gpu_available]
+    Gpu,
+}
+
+impl CompositorBackend {
+    /// Largest output area composed with [CompositorBackend::Simple] by [CompositorBackend::auto].
+    pub const SIMPLE_MAX_AREA: u64 = 512 * 512;
+    /// Largest output area composed with [CompositorBackend::Tiled] by [CompositorBackend::auto].
+    pub const TILED_MAX_AREA: u64 = 1024 * 1024;
+
+    /// Edge length of the tiles of [CompositorBackend::Tiled].
+    const TILE: u32 = 128;
+    /// Rows per band of [CompositorBackend::Parallel].
+    const BAND: u32 = 64;
+
+    /// Returns whether [CompositorBackend::Gpu] found a GPU to blend on. The GPU is
+    /// opened on the first call, without the `gpu` feature it is never available.
+    pub fn gpu_available() -> bool {
+        #[cfg(feature = "gpu")]
+        return gpu::is_available();
+        #[cfg(not(feature = "gpu"))]
+        false
+    }
+
+    /// Picks a backend for an output size.
+    pub fn auto((width, height): (u32, u32)) -> Self {
+        let area = width as u64 * height as u64;
+        if area <= Self::SIMPLE_MAX_AREA {
+            CompositorBackend::Simple
+        } else if area <= Self::TILED_MAX_AREA {
+            CompositorBackend::Tiled
+        } else {
+            CompositorBackend::Parallel
+        }
+    }
+}
+
+/// Blends `layers` on the GPU, `false` if there is none or the crate was built without
+/// the `gpu` feature.
+fn blend_on_gpu(
+    canvas: &mut RgbaImage,
+    layers: &[(&RgbaImage, (i64, i64))],
+) -> bool {
+    #[cfg(feature = "gpu")]
+    return gpu::blend(canvas, layers);
+    #[cfg(not(feature = "gpu"))]
+    {
+        let _ = (canvas, layers);
+        false
+    }
+}
+
+/// Fractions of a pixel below this are placed as whole pixels.
+const SUBPIXEL_EPSILON: f32 = 1.0 / 512.0;
+
+/// A layer scheduled for blending, not yet resized.
+pub(crate) struct PlacedLayer {
+    /// Name reported in [LayerTiming]
+    pub name: &'static str,
+    pub image: RgbaImage,
+    pub position: (i64, i64),
+    pub size: (u32, u32),
+    /// Fraction of a pixel the layer lies right of and below `position`, in `0.0..1.0`
+    pub offset: (f32, f32),
+}
+
+impl PlacedLayer {
+    /// Creates a layer placed at a fractional pixel position.
+    pub fn at(
+        name: &'static str,
+        image: RgbaImage,
+        (x, y): (f32, f32),
+        size: (u32, u32),
+    ) -> Self {
+        let snap = |v: f32| {
+            let whole = v.floor();
+            let fraction = v - whole;
+            if fraction < SUBPIXEL_EPSILON {
+                (whole as i64, 0.0)
+            } else if fraction > 1.0 - SUBPIXEL_EPSILON {
+                (whole as i64 + 1, 0.0)
+            } else {
+                (whole as i64, fraction)
+            }
+        };
+        let (x, offset_x) = snap(x);
+        let (y, offset_y) = snap(y);
+        Self {
+            name,
+            image,
+            position: (x, y),
+            size,
+            offset: (offset_x, offset_y),
+        }
+    }
+}
+
+/// Resizes `layers` and blends them onto `canvas` in order, returning the time spent on
+/// every layer.
+///
+/// Fails with `TeeError::DeadlineExceeded` once `deadline` passed. It is checked
+/// between layers, the parallel backend checks it once all layers are resized.
+pub(crate) fn composite(
+    canvas: &mut RgbaImage,
+    layers: Vec<PlacedLayer>,
+    backend: CompositorBackend,
+    compat: CompatMode,
+    deadline: Option<Instant>,
+) -> Result<Vec<LayerTiming>> {
+    let backend = match compat {
+        CompatMode::Native => backend,
+        CompatMode::DiscordBotPy => CompositorBackend::Simple,
+    };
+    trace!(
+        ?backend,
+        ?compat,
+        layers = layers.len(),
+        "Compositing layers"
+    );
+    let resize = |layer: PlacedLayer| {
+        let start = Instant::now();
+        let (w, h) = layer.size;
+        let resized = match compat {
+            CompatMode::Native => Backend::resize(&layer.image, w, h, Filter::Triangle),
+            CompatMode::DiscordBotPy => Pillow::resize(&layer.image, w, h, Filter::Bicubic),
+        };
+        let resized = if layer.offset == (0.0, 0.0) {
+            resized
+        } else {
+            shift_subpixel(&resized, layer.offset)
+        };
+        let timing = LayerTiming {
+            name: layer.name,
+            resize: start.elapsed(),
+            overlay: Duration::ZERO,
+        };
+        (resized, layer.position, timing)
+    };
+
+    let mut timings = Vec::with_capacity(layers.len());
+    match backend {
+        CompositorBackend::Simple => {
+            for layer in layers {
+                check_deadline(deadline, "layer")?;
+                let (image, (x, y), mut timing) = resize(layer);
+                let start = Instant::now();
+                match compat {
+                    CompatMode::Native => Backend::overlay(canvas, &image, x, y),
+                    CompatMode::DiscordBotPy => Pillow::overlay(canvas, &image, x, y),
+                }
+                timing.overlay = start.elapsed();
+                timings.push(timing);
+            }
+        }
+        CompositorBackend::Tiled => {
+            let mut layers = layers
+                .into_iter()
+                .map(|layer| {
+                    check_deadline(deadline, "layer")?;
+                    Ok(resize(layer))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            check_deadline(deadline, "blending")?;
+            let (width, height) = canvas.dimensions();
+            for tile_y in (0..height).step_by(CompositorBackend::TILE as usize) {
+                for tile_x in (0..width).step_by(CompositorBackend::TILE as usize) {
+                    let tile = (
+                        tile_x,
+                        tile_y,
+                        (tile_x + CompositorBackend::TILE).min(width),
+                        (tile_y + CompositorBackend::TILE).min(height),
+                    );
+                    for (image, position, timing) in &mut layers {
+                        let start = Instant::now();
+                        blend_rect(canvas, width, 0, tile, image, *position);
+                        timing.overlay += start.elapsed();
+                    }
+                }
+            }
+            timings.extend(layers.into_iter().map(|(_, _, timing)| timing));
+        }
+        CompositorBackend::Parallel => {
+            let layers: Vec<_> = layers.into_par_iter().map(resize).collect();
+            check_deadline(deadline, "blending")?;
+            let (width, height) = canvas.dimensions();
+            let band_len = (CompositorBackend::BAND * width * 4) as usize;
+            // Blend time per layer, summed over all bands
+            let overlay = canvas
+                .par_chunks_mut(band_len)
+                .enumerate()
+                .map(|(band, rows)| {
+                    let top = band as u32 * CompositorBackend::BAND;
+                    let rect = (0, top, width, (top + CompositorBackend::BAND).min(height));
+                    layers
+                        .iter()
+                        .map(|(image, position, _)| {
+                            let start = Instant::now();
+                            blend_rect(rows, width, top, rect, image, *position);
+                            start.elapsed()
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .reduce(
+                    || vec![Duration::ZERO; layers.len()],
+                    |a, b| a.iter().zip(b).map(|(a, b)| *a + b).collect(),
+                );
+            timings.extend(
+                layers
+                    .into_iter()
+                    .zip(overlay)
+                    .map(|((_, _, timing), overlay)| LayerTiming {
+                        overlay,
+                        ..timing
+                    }),
+            );
+        }
+        CompositorBackend::Gpu => {
+            let mut layers = layers
+                .into_iter()
+                .map(|layer| {
+                    check_deadline(deadline, "layer")?;
+                    Ok(resize(layer))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            check_deadline(deadline, "blending")?;
+            let start = Instant::now();
+            let placed: Vec<_> = layers
+                .iter()
+                .map(|(image, position, _)| (image, *position))
+                .collect();
+            if !blend_on_gpu(canvas, &placed) {
+                for (image, (x, y), _) in &layers {
+                    Backend::overlay(canvas, image, *x, *y);
+                }
+            }
+            // All layers are blended at once, each gets an even share of the time
+            let overlay = start.elapsed() / layers.len().max(1) as u32;
+            for (_, _, timing) in &mut layers {
+                timing.overlay = overlay;
+            }
+            timings.extend(layers.into_iter().map(|(_, _, timing)| timing));
+        }
+    }
+    for timing in &timings {
+        debug!(
+            layer = timing.name,
+            resize_us = timing.resize.as_micros() as u64,
+            overlay_us = timing.overlay.as_micros() as u64,
+            "Composited layer"
+        );
+    }
+    Ok(timings)
+}
+
+/// Fails with `TeeError::DeadlineExceeded` if `deadline` passed before `stage`.
+pub(crate) fn check_deadline(
+    deadline: Option<Instant>,
+    stage: &'static str,
+) -> Result<()> {
+    match deadline {
+        Some(deadline) if Instant::now() >= deadline => {
+            warn!(stage, "Compose deadline exceeded");
+            Err(TeeError::DeadlineExceeded { stage })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Blends the part of `image` placed at `position` that falls into `rect` of the canvas.
+///
+/// `target` holds raw canvas rows of `width` pixels starting at row `offset_y`, `rect` is
+/// `(x0, y0, x1, y1)` in canvas coordinates and must lie inside `target`.
+fn blend_rect(
+    target: &mut [u8],
+    width: u32,
+    offset_y: u32,
+    (x0, y0, x1, y1): (u32, u32, u32, u32),
+    image: &RgbaImage,
+    (x, y): (i64, i64),
+) {
+    let left = (x0 as i64).max(x);
+    let top = (y0 as i64).max(y);
+    let right = (x1 as i64).min(x + image.width() as i64);
+    let bottom = (y1 as i64).min(y + image.height() as i64);
+    for canvas_y in top..bottom {
+        for canvas_x in left..right {
+            let top_pixel: &Rgba<u8> =
+                image.get_pixel((canvas_x - x) as u32, (canvas_y - y) as u32);
+            let index =
+                ((canvas_y as usize - offset_y as usize) * width as usize + canvas_x as usize) * 4;
+            Rgba::from_slice_mut(&mut target[index..index + 4]).blend(top_pixel);
+        }
+    }
+}
+
+/// Resamples `image` moved right and down by a fraction of a pixel with bilinear
+/// weights.
+///
+/// The result grows by a pixel on every axis with an offset, so no coverage is cut off.
+/// Colors are weighted by alpha, so transparent pixels do not darken the edges.
+fn shift_subpixel(
+    image: &RgbaImage,
+    (offset_x, offset_y): (f32, f32),
+) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let grow = |offset: f32| u32::from(offset > 0.0);
+    let mut shifted = RgbaImage::new(width + grow(offset_x), height + grow(offset_y));
+    let sample = |x: i64, y: i64| -> [f32; 4] {
+        if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+            return [0.0; 4];
+        }
+        let [r, g, b, a] = image.get_pixel(x as u32, y as u32).0;
+        let alpha = a as f32 / 255.0;
+        [
+            r as f32 * alpha,
+            g as f32 * alpha,
+            b as f32 * alpha,
+            a as f32,
+        ]
+    };
+    let weights = [
+        (0, 0, (1.0 - offset_x) * (1.0 - offset_y)),
+        (1, 0, offset_x * (1.0 - offset_y)),
+        (0, 1, (1.0 - offset_x) * offset_y),
+        (1, 1, offset_x * offset_y),
+    ];
+    for (x, y, pixel) in shifted.enumerate_pixels_mut() {
+        let mut sum = [0.0f32; 4];
+        for (dx, dy, weight) in weights {
+            if weight == 0.0 {
+                continue;
+            }
+            let source = sample(x as i64 - dx, y as i64 - dy);
+            for (total, channel) in sum.iter_mut().zip(source) {
+                *total += channel * weight;
+            }
+        }
+        let alpha = sum[3] / 255.0;
+        if alpha > 0.0 {
+            *pixel = Rgba([
+                (sum[0] / alpha).round().clamp(0.0, 255.0) as u8,
+                (sum[1] / alpha).round().clamp(0.0, 255.0) as u8,
+                (sum[2] / alpha).round().clamp(0.0, 255.0) as u8,
+                sum[3].round().clamp(0.0, 255.0) as u8,
+            ]);
+        }
+    }
+    shifted
+}
@@ -0,0 +1,71 @@
+// Blends the layers onto the canvas in order, one invocation per canvas pixel. The
+// arithmetic follows `Blend for Rgba<u8>` of the image crate.
+
+struct Params {
+    width: u32,
+    height: u32,
+    count: u32,
+    _padding: u32,
+}
+
+struct Layer {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    // Index of the first pixel of the layer in `pixels`
+    start: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> layers: array<Layer>;
+@group(0) @binding(2) var<storage, read> pixels: array<u32>;
+@group(0) @binding(3) var<storage, read_write> canvas: array<u32>;
+
+fn unpack(pixel: u32) -> vec4<f32> {
+    return vec4<f32>(
+        f32(pixel & 255u),
+        f32((pixel >> 8u) & 255u),
+        f32((pixel >> 16u) & 255u),
+        f32(pixel >> 24u),
+    ) / 255.0;
+}
+
+fn blend(background: u32, foreground: u32) -> u32 {
+    let alpha = foreground >> 24u;
+    if alpha == 0u {
+        return background;
+    }
+    if alpha == 255u {
+        return foreground;
+    }
+    let bg = unpack(background);
+    let fg = unpack(foreground);
+    let alpha_final = bg.a + fg.a - bg.a * fg.a;
+    if alpha_final == 0.0 {
+        return background;
+    }
+    let color = (fg.rgb * fg.a + (bg.rgb * bg.a) * (1.0 - fg.a)) / alpha_final;
+    // Truncates like the casts of the image crate
+    let out = vec4<u32>(vec4<f32>(color, alpha_final) * 255.0);
+    return out.r | (out.g << 8u) | (out.b << 16u) | (out.a << 24u);
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.width || id.y >= params.height {
+        return;
+    }
+    let index = id.y * params.width + id.x;
+    var pixel = canvas[index];
+    for (var i = 0u; i < params.count; i++) {
+        let layer = layers[i];
+        let x = i32(id.x) - layer.x;
+        let y = i32(id.y) - layer.y;
+        if x < 0 || y < 0 || u32(x) >= layer.width || u32(y) >= layer.height {
+            continue;
+        }
+        pixel = blend(pixel, pixels[layer.start + u32(y) * layer.width + u32(x)]);
+    }
+    canvas[index] = pixel;
+}
@@ -0,0 +1,228 @@
+//! # GPU blending module
+//!
+//! Blends layers with a wgpu compute shader, one invocation per canvas pixel. Layers are
+//! uploaded into a single storage buffer, so a scene of hundreds of tees is blended in
+//! one pass. The device is created on first use and shared by the whole process.
+
+use std::sync::{OnceLock, mpsc};
+
+use image::RgbaImage;
+use tracing::{debug, warn};
+use wgpu::util::DeviceExt;
+
+/// Edge length of the workgroups of `blend.wgsl`.
+const WORKGROUP: u32 = 8;
+
+/// Device and pipeline shared by every blend.
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl Gpu {
+    /// Opens the default adapter, `None` if there is none able to run compute shaders.
+    async fn new() -> Option<Self> {
+        let instance = wgpu::Instance::default();
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+            .inspect_err(|e| warn!(error = %e, "No GPU adapter, blending on the CPU"))
+            .ok()?;
+        let name = adapter.get_info().name;
+        if !adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        {
+            warn!(
+                adapter = name,
+                "GPU adapter can not run compute shaders, blending on the CPU"
+            );
+            return None;
+        }
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("tee_morphosis compositor"),
+                required_limits: adapter.limits(),
+                ..Default::default()
+            })
+            .await
+            .inspect_err(|e| warn!(adapter = name, error = %e, "Failed to open the GPU, blending on the CPU"))
+            .ok()?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("blend"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("blend.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("blend"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        debug!(adapter = name, "Opened the GPU compositor");
+        Some(Self {
+            device,
+            queue,
+            pipeline,
+        })
+    }
+}
+
+/// Returns the shared GPU, opening it on the first call.
+fn gpu() -> Option<&'static Gpu> {
+    static GPU: OnceLock<Option<Gpu>> = OnceLock::new();
+    GPU.get_or_init(|| pollster::block_on(Gpu::new())).as_ref()
+}
+
+/// Returns whether a GPU able to blend was found.
+pub(crate) fn is_available() -> bool {
+    gpu().is_some()
+}
+
+/// Blends `layers` onto `canvas` in order, with the arithmetic of `imageops::overlay`
+/// up to float rounding.
+///
+/// Returns `false` and leaves `canvas` untouched if there is no usable GPU or the
+/// layers exceed its buffer limits.
+pub(crate) fn blend(
+    canvas: &mut RgbaImage,
+    layers: &[(&RgbaImage, (i64, i64))],
+) -> bool {
+    let Some(gpu) = gpu() else {
+        return false;
+    };
+    let (width, height) = canvas.dimensions();
+
+    // Layers outside the canvas are skipped, so the positions of the rest fit an i32
+    let mut metadata = Vec::new();
+    let mut pixels = Vec::new();
+    let mut count = 0u32;
+    for &(image, (x, y)) in layers {
+        let (w, h) = image.dimensions();
+        let visible = w > 0
+            && h > 0
+            && x < width as i64
+            && y < height as i64
+            && x + w as i64 > 0
+            && y + h as i64 > 0;
+        if !visible {
+            continue;
+        }
+        let Ok(start) = u32::try_from(pixels.len() / 4) else {
+            return false;
+        };
+        for value in [x as i32 as u32, y as i32 as u32, w, h, start] {
+            metadata.extend_from_slice(&value.to_le_bytes());
+        }
+        pixels.extend_from_slice(image.as_raw());
+        count += 1;
+    }
+    if count == 0 {
+        return true;
+    }
+
+    let limits = gpu.device.limits();
+    let fits = |len: usize| {
+        len as u64 <= limits.max_storage_buffer_binding_size as u64
+            && len as u64 <= limits.max_buffer_size
+    };
+    let groups = (width.div_ceil(WORKGROUP), height.div_ceil(WORKGROUP));
+    if !fits(pixels.len())
+        || !fits(canvas.len())
+        || groups.0 > limits.max_compute_workgroups_per_dimension
+        || groups.1 > limits.max_compute_workgroups_per_dimension
+    {
+        debug!(pixels = pixels.len(), "Layers exceed the GPU limits");
+        return false;
+    }
+
+    let device = &gpu.device;
+    let buffer = |label, contents: &[u8], usage| {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents,
+            usage,
+        })
+    };
+    let params: Vec<u8> = [width, height, count, 0]
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+    let params = buffer("params", &params, wgpu::BufferUsages::UNIFORM);
+    let metadata = buffer("layers", &metadata, wgpu::BufferUsages::STORAGE);
+    let pixels = buffer("pixels", &pixels, wgpu::BufferUsages::STORAGE);
+    let target = buffer(
+        "canvas",
+        canvas.as_raw(),
+        wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+    );
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("readback"),
+        size: canvas.len() as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("blend"),
+        layout: &gpu.pipeline.get_bind_group_layout(0),
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: metadata.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: pixels.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: target.as_entire_binding(),
+            },
+        ],
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("blend"),
+    });
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("blend"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&gpu.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(groups.0, groups.1, 1);
+    }
+    encoder.copy_buffer_to_buffer(&target, 0, &readback, 0, canvas.len() as u64);
+    gpu.queue.submit([encoder.finish()]);
+
+    let slice = readback.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        // The receiver only goes away once this function returned
+        let _ = sender.send(result);
+    });
+    if let Err(e) = device.poll(wgpu::PollType::wait_indefinitely()) {
+        warn!(error = %e, "Failed to wait for the GPU, blending on the CPU");
+        return false;
+    }
+    match receiver.recv() {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            warn!(error = %e, "Failed to read the canvas back from the GPU");
+            return false;
+        }
+        Err(_) => return false,
+    }
+    canvas.copy_from_slice(&slice.get_mapped_range());
+    readback.unmap();
+    debug!(layers = count, "Blended layers on the GPU");
+    true
+}
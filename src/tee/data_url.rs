@@ -0,0 +1,157 @@
+//! # Module with data URLs
+//!
+//! Web frontends post canvas exports as `data:image/png;base64,...` strings, see
+//! [Tee::new_from_data_url](crate::tee::Tee::new_from_data_url). Payloads may also be
+//! percent-encoded, as RFC 2397 allows.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use tee_morphosis::tee::data_url::DataUrl;
+//!
+//! let url = DataUrl::parse("data:image/png;base64,iVBORw0KGgo=")?;
+//! assert_eq!(url.format(), Some(image::ImageFormat::Png));
+//! assert_eq!(&url.data[1..4], b"PNG");
+//! # Ok::<(), tee_morphosis::error::TeeError>(())
+//! ```
+
+use bytes::Bytes;
+use image::ImageFormat;
+
+use crate::error::{Result, TeeError};
+
+/// A parsed `data:` URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUrl {
+    /// Media type without parameters, lowercase, e.g. `"image/png"`, empty if the URL
+    /// has none
+    pub media_type: String,
+    /// The decoded payload
+    pub data: Bytes,
+}
+
+impl DataUrl {
+    /// Parses a `data:[<media type>][;base64],<data>` URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TeeError::MalformedDataUrl] if the scheme or the comma is missing, or the
+    /// payload is not valid base64 or percent-encoding.
+    pub fn parse(url: &str) -> Result<Self> {
+        let url = url.trim();
+        let rest = match url.get(..5) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("data:") => &url[5..],
+            _ => return Err(TeeError::MalformedDataUrl("missing data: scheme")),
+        };
+        let (header, payload) = rest
+            .split_once(',')
+            .ok_or(TeeError::MalformedDataUrl("missing comma"))?;
+        let mut params = header.split(';');
+        let media_type = params
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let is_base64 = params.any(|param| param.trim().eq_ignore_ascii_case("base64"));
+        let data = if is_base64 {
+            decode_base64(&percent_decode(payload)?)?
+        } else {
+            Bytes::from(percent_decode(payload)?)
+        };
+        Ok(Self {
+            media_type,
+            data,
+        })
+    }
+
+    /// Returns the image format named by the media type.
+    pub fn format(&self) -> Option<ImageFormat> {
+        ImageFormat::from_mime_type(&self.media_type)
+    }
+}
+
+/// Decodes standard or URL-safe base64, with or without padding, ignoring whitespace.
+///
+/// # Errors
+///
+/// Returns [TeeError::MalformedDataUrl] for characters outside the alphabet or a
+/// truncated last group.
+pub fn decode_base64(encoded: impl AsRef<[u8]>) -> Result<Bytes> {
+    let encoded = encoded.as_ref();
+    let mut out = Vec::with_capacity(encoded.len() / 4 * 3);
+    let mut group = 0u32;
+    let mut bits = 0;
+    let mut padding = false;
+    for &byte in encoded.iter().filter(|byte| !byte.is_ascii_whitespace()) {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => {
+                padding = true;
+                continue;
+            }
+            _ => return Err(TeeError::MalformedDataUrl("invalid base64 character")),
+        };
+        if padding {
+            return Err(TeeError::MalformedDataUrl("base64 data after padding"));
+        }
+        group = group << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((group >> bits) as u8);
+        }
+    }
+    // A lone character holds 6 bits, less than a byte
+    if bits >= 6 {
+        return Err(TeeError::MalformedDataUrl("truncated base64 data"));
+    }
+    Ok(Bytes::from(out))
+}
+
+/// Encodes `data` as standard base64 with padding.
+pub fn encode_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Replaces `%XX` escapes with their bytes.
+fn percent_decode(text: &str) -> Result<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = bytes
+                .get(index + 1..index + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or(TeeError::MalformedDataUrl("invalid percent escape"))?;
+            out.push(hex);
+            index += 3;
+        } else {
+            out.push(bytes[index]);
+            index += 1;
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,149 @@
+//! # Module with expression presets
+//!
+//! An [Expression] bundles an [EyePair], nudged eye positions and sprites drawn over the
+//! face, so bots can render an expressive tee in one call.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::tee::{expression::Expression, skin::TEE_SKIN_LAYOUT};
+//!
+//! let png = tee.compose_expression(TEE_SKIN_LAYOUT, Expression::Cry, ImageFormat::Png)?;
+//!
+//! // Or combine it with other options
+//! let (skin, options) = Expression::Sleepy.apply(TEE_SKIN_LAYOUT, ComposeOptions::new());
+//! let png = tee.compose_with_options(skin, Expression::Sleepy.eyes(), ImageFormat::Png, &options)?;
+//! ```
+
+use bytes::Bytes;
+use image::{ImageFormat, Rgba, RgbaImage};
+use tracing::instrument;
+
+use crate::{
+    error::Result,
+    tee::{
+        Tee,
+        layer::ZOrder,
+        options::ComposeOptions,
+        parts::{EyePair, EyeType},
+        skin::{Skin, SkinPS, SkinPosition},
+    },
+};
+
+/// Size of the tear sprite in layout units.
+const TEAR_SIZE: (u32, u32) = (6, 9);
+
+/// A preset of eyes, eye offsets and overlay sprites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Expression {
+    /// Happy left eye, closed right eye
+    Wink,
+    /// Eyes in pain, lowered, with a tear below each
+    Cry,
+    /// Surprised eyes at different heights
+    Dizzy,
+    /// Blinking eyes, lowered
+    Sleepy,
+}
+
+impl Expression {
+    /// Every expression.
+    pub const ALL: [Expression; 4] = [
+        Expression::Wink,
+        Expression::Cry,
+        Expression::Dizzy,
+        Expression::Sleepy,
+    ];
+
+    /// Returns the lowercase name of the expression, e.g. `"wink"`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Expression::Wink => "wink",
+            Expression::Cry => "cry",
+            Expression::Dizzy => "dizzy",
+            Expression::Sleepy => "sleepy",
+        }
+    }
+
+    /// Returns the eyes of the expression.
+    pub const fn eyes(&self) -> EyePair {
+        match self {
+            Expression::Wink => EyePair::WINK,
+            Expression::Cry => EyePair::new(EyeType::Pain, EyeType::Pain),
+            Expression::Dizzy => EyePair::new(EyeType::Surprise, EyeType::Surprise),
+            Expression::Sleepy => EyePair::new(EyeType::Blink, EyeType::Blink),
+        }
+    }
+
+    /// Returns how far the left and right eye are moved, in layout units.
+    pub const fn eye_offsets(&self) -> [SkinPosition; 2] {
+        match self {
+            Expression::Wink => [(0., 0.), (0., 0.)],
+            Expression::Cry => [(0., 1.), (0., 1.)],
+            Expression::Dizzy => [(0., -1.5), (0., 1.5)],
+            Expression::Sleepy => [(0., 2.), (0., 2.)],
+        }
+    }
+
+    /// Moves the eyes of `skin` and adds the overlay sprites of the expression to
+    /// `options`.
+    ///
+    /// Compose the returned skin with [Expression::eyes].
+    pub fn apply(
+        &self,
+        skin: Skin,
+        options: ComposeOptions,
+    ) -> (Skin, ComposeOptions) {
+        let [left, right] = self.eye_offsets();
+        let shift = |((x, y), scale): SkinPS, (dx, dy): SkinPosition| ((x + dx, y + dy), scale);
+        let skin = Skin {
+            first_eyes: shift(skin.first_eyes, left),
+            second_eyes: shift(skin.second_eyes, right),
+            ..skin
+        };
+
+        let options = match self {
+            Expression::Cry => [skin.first_eyes, skin.second_eyes].into_iter().fold(
+                options,
+                |options, ((x, y), _)| {
+                    options.with_extra_layer(tear(), ((x + 8., y + 16.), 1.), ZOrder::AfterEyes)
+                },
+            ),
+            _ => options,
+        };
+        (skin, options)
+    }
+}
+
+impl Tee {
+    /// Composites the Tee with an [Expression] and default [ComposeOptions].
+    #[instrument(level = "debug", skip(self, skin), fields(expression = expression.name()))]
+    pub fn compose_expression(
+        &self,
+        skin: Skin,
+        expression: Expression,
+        img_format: ImageFormat,
+    ) -> Result<Bytes> {
+        let (skin, options) = expression.apply(skin, ComposeOptions::default());
+        self.compose_with_options(skin, expression.eyes(), img_format, &options)
+    }
+}
+
+/// Draws a light blue drop, round at the bottom and pointed at the top.
+fn tear() -> RgbaImage {
+    let (width, height) = TEAR_SIZE;
+    let radius = width as f32 / 2.;
+    let center = (radius, height as f32 - radius);
+    RgbaImage::from_fn(width, height, |x, y| {
+        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+        // The drop narrows linearly from the round bottom to the tip
+        let allowed = if py < center.1 {
+            radius * py / center.1
+        } else {
+            (radius * radius - (py - center.1).powi(2)).max(0.).sqrt()
+        };
+        let inside = (allowed - (px - center.0).abs()).clamp(0., 1.);
+        let light = (40. * (1. - py / height as f32)) as u8;
+        Rgba([120 + light, 190 + light / 2, 255, (inside * 220.) as u8])
+    })
+}
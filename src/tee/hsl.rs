@@ -1,46 +1,36 @@
 //! Module for color transforms
-
-pub type HSL = (f32, f32, f32);
-pub type RGB = (f32, f32, f32);
+//!
+//! The conversions live in [crate::core::color] and are re-exported here.
 
 use image::RgbaImage;
 use rayon::iter::{ParallelBridge, ParallelIterator};
 
-const DARKEST_LGT: f32 = 0.5;
-
-/// Convert ddnet color format to hsl
-pub fn ddnet_color_to_hsl(color: u32) -> HSL {
-    let h_raw = ((color >> 16) & 0xFF) as f32;
-    let s_raw = ((color >> 8) & 0xFF) as f32;
-    let l_raw = (color & 0xFF) as f32;
-
-    let h = h_raw / 255.0;
-    let s = s_raw / 255.0;
-    let l_compressed = l_raw / 255.0;
-
-    let l = DARKEST_LGT + l_compressed * (1.0 - DARKEST_LGT);
-
-    (h, s, l)
-}
-
-/// Convert hsl for rgb compatibilities
-fn hsl_to_rgb((h, s, l): HSL) -> RGB {
-    let h1 = h * 6.0;
-    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
-    let x = c * (1.0 - ((h1 % 2.0) - 1.0).abs());
-
-    let (r, g, b) = match h1.floor() as i32 {
-        0 => (c, x, 0.0),
-        1 => (x, c, 0.0),
-        2 => (0.0, c, x),
-        3 => (0.0, x, c),
-        4 => (x, 0.0, c),
-        5 | 6 => (c, 0.0, x),
-        _ => (c, 0.0, x),
-    };
+pub use crate::core::color::*;
 
-    let m = l - (c / 2.0);
-    (r + m, g + m, b + m)
+/// Take img and shift the hsl of every pixel, the hue wraps around and saturation and
+/// lightness are clamped
+pub fn img_hsl_shift(
+    img: &mut RgbaImage,
+    (dh, ds, dl): HSL,
+) {
+    img.pixels_mut().par_bridge().for_each(|pixel| {
+        if pixel[3] == 0 {
+            return;
+        }
+        let (h, s, l) = rgb_to_hsl((
+            pixel[0] as f32 / 255.0,
+            pixel[1] as f32 / 255.0,
+            pixel[2] as f32 / 255.0,
+        ));
+        let (r, g, b) = hsl_to_rgb((
+            (h + dh).rem_euclid(1.0),
+            (s + ds).clamp(0.0, 1.0),
+            (l + dl).clamp(0.0, 1.0),
+        ));
+        pixel[0] = (r * 255.0).round().clamp(0.0, 255.0) as u8;
+        pixel[1] = (g * 255.0).round().clamp(0.0, 255.0) as u8;
+        pixel[2] = (b * 255.0).round().clamp(0.0, 255.0) as u8;
+    });
 }
 
 /// Take img and apply hsl to it
@@ -56,3 +46,37 @@ pub fn img_hsl_transform(
         pixel[2] = ((pixel[2] as f32 / 255.0 * b) * 255.0).clamp(0.0, 255.0) as u8;
     });
 }
+
+/// Pixels at least this light are left untouched by [img_recolor_dark], e.g. the
+/// highlights of pupils.
+pub const DARK_MASK_LIGHTNESS: f32 = 0.5;
+
+/// Take img and paint its dark pixels with hsl, keeping their shading
+///
+/// The darkest pixels get exactly the given color, lighter ones approach white. Pixels
+/// fade out of the mask over the last tenth below [DARK_MASK_LIGHTNESS].
+pub fn img_recolor_dark(
+    img: &mut RgbaImage,
+    (h, s, l): HSL,
+) {
+    img.pixels_mut().par_bridge().for_each(|pixel| {
+        if pixel[3] == 0 {
+            return;
+        }
+        let rgb = (
+            pixel[0] as f32 / 255.0,
+            pixel[1] as f32 / 255.0,
+            pixel[2] as f32 / 255.0,
+        );
+        let (_, _, lightness) = rgb_to_hsl(rgb);
+        let weight = ((DARK_MASK_LIGHTNESS - lightness) / 0.1).clamp(0.0, 1.0);
+        if weight == 0.0 {
+            return;
+        }
+        let (r, g, b) = hsl_to_rgb((h, s, l + lightness * (1.0 - l)));
+        for (channel, (old, new)) in [(0, (rgb.0, r)), (1, (rgb.1, g)), (2, (rgb.2, b))] {
+            let value = old + (new - old) * weight;
+            pixel[channel] = (value * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+    });
+}
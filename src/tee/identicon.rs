@@ -0,0 +1,98 @@
+//! # Module with name based avatars
+//!
+//! Derives stable colors and an eye type from a player name, so every player gets a
+//! recognizable avatar even when their skin can not be resolved.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! let identicon = Tee::identicon("nameless tee", &default_tee);
+//! let png = identicon.compose(TEE_SKIN_LAYOUT, ImageFormat::Png)?;
+//! ```
+
+use bytes::Bytes;
+use image::ImageFormat;
+use sha2::{Digest, Sha256};
+use tracing::{debug, instrument};
+
+use crate::{
+    error::Result,
+    tee::{Tee, hsl::ddnet_color_to_hsl, parts::EyeType, parts::TeePart, skin::Skin},
+};
+
+/// Eye types picked by [Tee::identicon], the ones that read as a face on their own.
+pub const IDENTICON_EYES: [EyeType; 5] = [
+    EyeType::Normal,
+    EyeType::Angry,
+    EyeType::Pain,
+    EyeType::Happy,
+    EyeType::Surprise,
+];
+
+/// A tee recolored from a player name, with the eyes it should be drawn with.
+#[derive(Debug, Clone)]
+pub struct Identicon {
+    /// The recolored tee
+    pub tee: Tee,
+    /// Eyes picked for the name
+    pub eye: EyeType,
+    /// Body and hand color in DDNet color format
+    pub body_color: u32,
+    /// Feet color in DDNet color format
+    pub feet_color: u32,
+}
+
+impl Identicon {
+    /// Composes the tee with its eyes, see [Tee::compose].
+    pub fn compose(
+        &self,
+        skin: Skin,
+        format: ImageFormat,
+    ) -> Result<Bytes> {
+        self.tee.compose(skin, self.eye, format)
+    }
+}
+
+impl Tee {
+    /// Recolors `base` with colors derived from the SHA-256 of `name` and picks one of
+    /// [IDENTICON_EYES].
+    ///
+    /// Colors are applied like custom player colors in game, saturation is kept above
+    /// one half so no name ends up gray.
+    #[instrument(level = "debug", skip(base))]
+    pub fn identicon(
+        name: &str,
+        base: &Tee,
+    ) -> Identicon {
+        let digest = Sha256::digest(name.as_bytes());
+        let color = |bytes: &[u8]| {
+            let saturation = 128 + (bytes[1] as u32 >> 1);
+            (bytes[0] as u32) << 16 | saturation << 8 | bytes[2] as u32
+        };
+        let body_color = color(&digest[0..3]);
+        let feet_color = color(&digest[3..6]);
+        let eye = IDENTICON_EYES[digest[6] as usize % IDENTICON_EYES.len()];
+
+        let mut tee = base.clone();
+        tee.apply_hsl_to_parts(
+            ddnet_color_to_hsl(body_color),
+            &[
+                TeePart::Body,
+                TeePart::BodyShadow,
+                TeePart::Hand,
+                TeePart::HandShadow,
+            ],
+        );
+        tee.apply_hsl_to_parts(
+            ddnet_color_to_hsl(feet_color),
+            &[TeePart::Feet, TeePart::FeetShadow],
+        );
+        debug!(body_color, feet_color, ?eye, "Derived identicon");
+        Identicon {
+            tee,
+            eye,
+            body_color,
+            feet_color,
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! # Imaging module
+//!
+//! The few imaging operations parsing and composing rely on, behind [Imaging] so the
+//! backend doing them can be swapped without touching the public API. [Backend] is the
+//! one selected at compile time, only the `image` crate backend exists so far. [Pillow]
+//! reproduces the arithmetic of the Python imaging library for
+//! [CompatMode::DiscordBotPy](crate::tee::options::CompatMode::DiscordBotPy).
+
+use std::io::Cursor;
+
+use bytes::Bytes;
+use image::{DynamicImage, GenericImageView, ImageFormat, Rgba, RgbaImage, imageops};
+
+use crate::error::Result;
+
+/// Filter used when resizing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Filter {
+    /// Bilinear, used for parts
+    Triangle,
+    /// Sharper, used for whole renders
+    Lanczos3,
+    /// Cubic with `a = -0.5`, the default of Pillow
+    Bicubic,
+}
+
+/// Operations a backend has to provide, all on RGBA8 images.
+pub(crate) trait Imaging {
+    /// Copies a rectangle out of `img`, which must lie inside it.
+    fn crop(
+        img: &DynamicImage,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> RgbaImage;
+
+    fn resize(
+        img: &RgbaImage,
+        width: u32,
+        height: u32,
+        filter: Filter,
+    ) -> RgbaImage;
+
+    /// Blends `top` onto `bottom` at `(x, y)`, clipping whatever lies outside.
+    fn overlay(
+        bottom: &mut RgbaImage,
+        top: &RgbaImage,
+        x: i64,
+        y: i64,
+    );
+
+    /// Copies `top` onto `bottom` at `(x, y)` without blending.
+    fn replace(
+        bottom: &mut RgbaImage,
+        top: &RgbaImage,
+        x: i64,
+        y: i64,
+    );
+
+    fn flip_horizontal(img: &RgbaImage) -> RgbaImage;
+
+    fn encode(
+        img: &RgbaImage,
+        format: ImageFormat,
+    ) -> Result<Bytes>;
+}
+
+/// Backend built on the `image` crate.
+pub(crate) struct ImageCrate;
+
+impl Imaging for ImageCrate {
+    fn crop(
+        img: &DynamicImage,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> RgbaImage {
+        img.view(x, y, width, height).to_image()
+    }
+
+    fn resize(
+        img: &RgbaImage,
+        width: u32,
+        height: u32,
+        filter: Filter,
+    ) -> RgbaImage {
+        let filter = match filter {
+            Filter::Triangle => imageops::FilterType::Triangle,
+            Filter::Lanczos3 => imageops::FilterType::Lanczos3,
+            Filter::Bicubic => imageops::FilterType::CatmullRom,
+        };
+        imageops::resize(img, width, height, filter)
+    }
+
+    fn overlay(
+        bottom: &mut RgbaImage,
+        top: &RgbaImage,
+        x: i64,
+        y: i64,
+    ) {
+        imageops::overlay(bottom, top, x, y);
+    }
+
+    fn replace(
+        bottom: &mut RgbaImage,
+        top: &RgbaImage,
+        x: i64,
+        y: i64,
+    ) {
+        imageops::replace(bottom, top, x, y);
+    }
+
+    fn flip_horizontal(img: &RgbaImage) -> RgbaImage {
+        imageops::flip_horizontal(img)
+    }
+
+    fn encode(
+        img: &RgbaImage,
+        format: ImageFormat,
+    ) -> Result<Bytes> {
+        let mut buf = Vec::new();
+        img.write_to(&mut Cursor::new(&mut buf), format)?;
+        Ok(Bytes::from(buf))
+    }
+}
+
+/// The backend in use, alternative backends select themselves here behind a feature.
+pub(crate) type Backend = ImageCrate;
+
+/// Backend doing what Pillow does, bit for bit, where it matters for composing.
+///
+/// Resizing and blending follow `Resample.c` and `AlphaComposite.c` of Pillow, the other
+/// operations are exact anyway and left to [ImageCrate].
+pub(crate) struct Pillow;
+
+impl Pillow {
+    /// Fractional bits of the resampling weights.
+    const PRECISION_BITS: u32 = 32 - 8 - 2;
+
+    /// Returns the support and the kernel of `filter`.
+    fn kernel(filter: Filter) -> (f64, fn(f64) -> f64) {
+        fn bilinear(x: f64) -> f64 {
+            (1.0 - x.abs()).max(0.0)
+        }
+        fn bicubic(x: f64) -> f64 {
+            const A: f64 = -0.5;
+            let x = x.abs();
+            if x < 1.0 {
+                ((A + 2.0) * x - (A + 3.0)) * x * x + 1.0
+            } else if x < 2.0 {
+                (((x - 5.0) * x + 8.0) * x - 4.0) * A
+            } else {
+                0.0
+            }
+        }
+        fn lanczos(x: f64) -> f64 {
+            let sinc = |x: f64| {
+                if x == 0.0 {
+                    1.0
+                } else {
+                    (x * std::f64::consts::PI).sin() / (x * std::f64::consts::PI)
+                }
+            };
+            if (-3.0..3.0).contains(&x) { sinc(x) * sinc(x / 3.0) } else { 0.0 }
+        }
+        match filter {
+            Filter::Triangle => (1.0, bilinear),
+            Filter::Bicubic => (2.0, bicubic),
+            Filter::Lanczos3 => (3.0, lanczos),
+        }
+    }
+
+    /// Returns the first source pixel and the fixed point weights of every output pixel
+    /// when resampling `in_size` pixels to `out_size`.
+    fn coefficients(
+        in_size: u32,
+        out_size: u32,
+        filter: Filter,
+    ) -> Vec<(usize, Vec<i64>)> {
+        let (support, kernel) = Self::kernel(filter);
+        let scale = in_size as f64 / out_size as f64;
+        let filterscale = scale.max(1.0);
+        let support = support * filterscale;
+        let one = (1i64 << Self::PRECISION_BITS) as f64;
+        (0..out_size)
+            .map(|out| {
+                let center = (out as f64 + 0.5) * scale;
+                // Truncation toward zero like the C casts
+                let min = ((center - support + 0.5) as i64).max(0) as usize;
+                let max = ((center + support + 0.5) as i64).min(in_size as i64) as usize;
+                let weights: Vec<f64> = (min..max)
+                    .map(|x| kernel((x as f64 - center + 0.5) / filterscale))
+                    .collect();
+                let total: f64 = weights.iter().sum();
+                let fixed = weights
+                    .iter()
+                    .map(|&weight| {
+                        let weight = if total != 0.0 { weight / total } else { weight };
+                        if weight < 0.0 {
+                            (-0.5 + weight * one) as i64
+                        } else {
+                            (0.5 + weight * one) as i64
+                        }
+                    })
+                    .collect();
+                (min, fixed)
+            })
+            .collect()
+    }
+
+    /// Resamples the rows of premultiplied `img` to `width` pixels.
+    fn resample_horizontal(
+        img: &RgbaImage,
+        width: u32,
+        filter: Filter,
+    ) -> RgbaImage {
+        let coefficients = Self::coefficients(img.width(), width, filter);
+        RgbaImage::from_fn(width, img.height(), |x, y| {
+            let (min, weights) = &coefficients[x as usize];
+            Self::convolve(
+                weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &weight)| (img.get_pixel((min + i) as u32, y), weight)),
+            )
+        })
+    }
+
+    /// Resamples the columns of premultiplied `img` to `height` pixels.
+    fn resample_vertical(
+        img: &RgbaImage,
+        height: u32,
+        filter: Filter,
+    ) -> RgbaImage {
+        let coefficients = Self::coefficients(img.height(), height, filter);
+        RgbaImage::from_fn(img.width(), height, |x, y| {
+            let (min, weights) = &coefficients[y as usize];
+            Self::convolve(
+                weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &weight)| (img.get_pixel(x, (min + i) as u32), weight)),
+            )
+        })
+    }
+
+    /// Sums weighted pixels in fixed point, rounding and clipping every channel.
+    fn convolve<'a>(pixels: impl Iterator<Item = (&'a Rgba<u8>, i64)>) -> Rgba<u8> {
+        let mut sums = [1i64 << (Self::PRECISION_BITS - 1); 4];
+        for (pixel, weight) in pixels {
+            for (sum, &channel) in sums.iter_mut().zip(&pixel.0) {
+                *sum += channel as i64 * weight;
+            }
+        }
+        Rgba(sums.map(|sum| (sum >> Self::PRECISION_BITS).clamp(0, 255) as u8))
+    }
+}
+
+/// `a * b / 255` rounded, as Pillow computes it.
+fn mul_div_255(
+    a: u32,
+    b: u32,
+) -> u32 {
+    let t = a * b + 128;
+    ((t >> 8) + t) >> 8
+}
+
+impl Imaging for Pillow {
+    fn crop(
+        img: &DynamicImage,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> RgbaImage {
+        ImageCrate::crop(img, x, y, width, height)
+    }
+
+    /// Resizes like `Image.resize`, which works on premultiplied alpha.
+    fn resize(
+        img: &RgbaImage,
+        width: u32,
+        height: u32,
+        filter: Filter,
+    ) -> RgbaImage {
+        if img.dimensions() == (width, height) {
+            return img.clone();
+        }
+        let mut premultiplied = img.clone();
+        for pixel in premultiplied.pixels_mut() {
+            let alpha = pixel[3] as u32;
+            for channel in &mut pixel.0[..3] {
+                *channel = mul_div_255(*channel as u32, alpha) as u8;
+            }
+        }
+        if width != img.width() {
+            premultiplied = Self::resample_horizontal(&premultiplied, width, filter);
+        }
+        if height != img.height() {
+            premultiplied = Self::resample_vertical(&premultiplied, height, filter);
+        }
+        for pixel in premultiplied.pixels_mut() {
+            let alpha = pixel[3] as u32;
+            if alpha != 0 && alpha != 255 {
+                for channel in &mut pixel.0[..3] {
+                    *channel = (255 * *channel as u32 / alpha).min(255) as u8;
+                }
+            }
+        }
+        premultiplied
+    }
+
+    /// Blends like `Image.alpha_composite`.
+    fn overlay(
+        bottom: &mut RgbaImage,
+        top: &RgbaImage,
+        x: i64,
+        y: i64,
+    ) {
+        const PRECISION_BITS: u32 = 7;
+        let shift_for_div_255 = |a: u32| ((a >> 8) + a) >> 8;
+        let left = x.max(0);
+        let upper = y.max(0);
+        let right = (x + top.width() as i64).min(bottom.width() as i64);
+        let lower = (y + top.height() as i64).min(bottom.height() as i64);
+        for canvas_y in upper..lower {
+            for canvas_x in left..right {
+                let src = top
+                    .get_pixel((canvas_x - x) as u32, (canvas_y - y) as u32)
+                    .0;
+                let dst = bottom.get_pixel_mut(canvas_x as u32, canvas_y as u32);
+                if src[3] == 0 {
+                    continue;
+                }
+                let (src_a, dst_a) = (src[3] as u32, dst[3] as u32);
+                let blend = dst_a * (255 - src_a);
+                let out_a255 = src_a * 255 + blend;
+                let coef1 = src_a * 255 * 255 * (1 << PRECISION_BITS) / out_a255;
+                let coef2 = 255 * (1 << PRECISION_BITS) - coef1;
+                for channel in 0..3 {
+                    let mixed = src[channel] as u32 * coef1 + dst[channel] as u32 * coef2;
+                    dst[channel] = (shift_for_div_255(mixed + (0x80 << PRECISION_BITS))
+                        >> PRECISION_BITS) as u8;
+                }
+                dst[3] = shift_for_div_255(out_a255 + 0x80) as u8;
+            }
+        }
+    }
+
+    fn replace(
+        bottom: &mut RgbaImage,
+        top: &RgbaImage,
+        x: i64,
+        y: i64,
+    ) {
+        ImageCrate::replace(bottom, top, x, y);
+    }
+
+    fn flip_horizontal(img: &RgbaImage) -> RgbaImage {
+        ImageCrate::flip_horizontal(img)
+    }
+
+    fn encode(
+        img: &RgbaImage,
+        format: ImageFormat,
+    ) -> Result<Bytes> {
+        ImageCrate::encode(img, format)
+    }
+}
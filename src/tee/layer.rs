@@ -0,0 +1,90 @@
+//! # Module with the layer order of composed tees
+//!
+//! A composed tee is a stack of layers drawn bottom to top. The order is plain data, so
+//! a render can reorder, repeat or leave out layers, e.g. draw the eyes under the body
+//! for a "possessed" look.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::tee::{
+//!     layer::{Layer, ZOrder},
+//!     options::ComposeOptions,
+//! };
+//!
+//! let mut order = Layer::DEFAULT_ORDER.to_vec();
+//! order.retain(|layer| !layer.is_eye());
+//! order.splice(0..0, [Layer::FirstEye, Layer::SecondEye]);
+//! let options = ComposeOptions::new().with_layer_order(order);
+//!
+//! // A hat drawn over everything, placed like the body
+//! let options = ComposeOptions::new().with_extra_layer(hat, ((16., -12.), 0.66), ZOrder::Topmost);
+//! ```
+
+/// Where an extra layer of [ComposeOptions](crate::tee::options::ComposeOptions) is
+/// drawn.
+///
+/// If the anchoring layer is left out of the order, the extra layer is drawn on top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZOrder {
+    /// Right before the first [Layer::Body]
+    BeforeBody,
+    /// Right after the last eye
+    AfterEyes,
+    /// Over every layer
+    Topmost,
+}
+
+/// A layer of a composed tee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layer {
+    /// Shadow of the body
+    BodyShadow,
+    /// Shadow of the back foot
+    FeetBackShadow,
+    /// Shadow of the front foot
+    FeetShadow,
+    /// Back foot, shaded by [FeetStyle](crate::tee::options::FeetStyle)
+    FeetBack,
+    /// Body
+    Body,
+    /// Left eye
+    FirstEye,
+    /// Right eye, the left one flipped
+    SecondEye,
+    /// Front foot
+    Feet,
+}
+
+impl Layer {
+    /// The order the game draws a tee in, bottom first.
+    pub const DEFAULT_ORDER: [Layer; 8] = [
+        Layer::BodyShadow,
+        Layer::FeetBackShadow,
+        Layer::FeetShadow,
+        Layer::FeetBack,
+        Layer::Body,
+        Layer::FirstEye,
+        Layer::SecondEye,
+        Layer::Feet,
+    ];
+
+    /// Returns the name of the layer.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layer::BodyShadow => "body_shadow",
+            Layer::FeetBackShadow => "feet_back_shadow",
+            Layer::FeetShadow => "feet_shadow",
+            Layer::FeetBack => "feet_back",
+            Layer::Body => "body",
+            Layer::FirstEye => "first_eye",
+            Layer::SecondEye => "second_eye",
+            Layer::Feet => "feet",
+        }
+    }
+
+    /// Returns whether the layer is one of the eyes.
+    pub fn is_eye(&self) -> bool {
+        matches!(self, Layer::FirstEye | Layer::SecondEye)
+    }
+}
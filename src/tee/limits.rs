@@ -0,0 +1,52 @@
+//! # Module with decode limits
+
+/// Upper bounds enforced while decoding a source image.
+///
+/// The dimensions are checked against the image header before any pixel buffer is
+/// allocated, so decompression bombs are rejected early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Maximum width of the source image in pixels
+    pub max_width: u32,
+    /// Maximum height of the source image in pixels
+    pub max_height: u32,
+    /// Maximum amount of bytes the decoder may allocate
+    pub max_alloc: u64,
+}
+
+impl DecodeLimits {
+    /// Limits used when parsing from trusted sources, see [`ParseOptions`](crate::tee::options::ParseOptions).
+    ///
+    /// Fits skins up to 4096x4096 while keeping a single decode under 128 MiB.
+    pub const DEFAULT: DecodeLimits = DecodeLimits {
+        max_width: 4096,
+        max_height: 4096,
+        max_alloc: 128 * 1024 * 1024,
+    };
+
+    /// Limits suited for arbitrary user uploads.
+    ///
+    /// Large enough for HD skins (up to 2048x2048), small enough to keep a single
+    /// decode under 32 MiB.
+    pub const UNTRUSTED: DecodeLimits = DecodeLimits {
+        max_width: 2048,
+        max_height: 2048,
+        max_alloc: 32 * 1024 * 1024,
+    };
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl From<DecodeLimits> for image::Limits {
+    fn from(limits: DecodeLimits) -> Self {
+        let mut image_limits = image::Limits::default();
+        image_limits.max_image_width = Some(limits.max_width);
+        image_limits.max_image_height = Some(limits.max_height);
+        image_limits.max_alloc = Some(limits.max_alloc);
+        image_limits
+    }
+}
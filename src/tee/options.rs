@@ -0,0 +1,335 @@
+//! # Module with parse and compose options
+
+use std::{borrow::Cow, sync::Arc};
+
+use image::RgbaImage;
+
+use crate::{
+    colorblind::ColorBlindness,
+    etag::ETagHasher,
+    meta::RenderMeta,
+    moderation::ContentChecker,
+    tee::{
+        compositor::CompositorBackend,
+        layer::{Layer, ZOrder},
+        limits::DecodeLimits,
+        pool::BufferPool,
+        raw::ExtractPolicy,
+        skin::SkinPS,
+    },
+    watermark::Watermark,
+};
+
+/// Options controlling how a source image is decoded and split into parts.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Limits enforced while decoding the source image
+    pub limits: DecodeLimits,
+    /// Frame taken from animated sources (GIF, APNG, animated WebP), `0` is the first one
+    pub frame: usize,
+    /// How parts reaching past the edge of the source are handled
+    pub extract_policy: ExtractPolicy,
+    /// Checker the decoded body is passed to, see [crate::moderation]
+    pub content_checker: Option<Arc<dyn ContentChecker>>,
+    /// Pool parts are extracted into, see [crate::tee::pool]
+    pub buffer_pool: Option<BufferPool>,
+}
+
+impl PartialEq for ParseOptions {
+    /// Checkers and pools are equal if they are the same instance.
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.limits == other.limits
+            && self.frame == other.frame
+            && self.extract_policy == other.extract_policy
+            && match (&self.content_checker, &other.content_checker) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+            && match (&self.buffer_pool, &other.buffer_pool) {
+                (Some(a), Some(b)) => a.same_pool(b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+}
+
+impl ParseOptions {
+    /// Creates options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the decode limits.
+    pub fn with_limits(
+        mut self,
+        limits: DecodeLimits,
+    ) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the frame taken from animated sources.
+    pub fn with_frame(
+        mut self,
+        frame: usize,
+    ) -> Self {
+        self.frame = frame;
+        self
+    }
+
+    /// Sets how parts reaching past the edge of the source are handled.
+    pub fn with_extract_policy(
+        mut self,
+        policy: ExtractPolicy,
+    ) -> Self {
+        self.extract_policy = policy;
+        self
+    }
+
+    /// Sets the checker every parsed body is passed to.
+    pub fn with_content_checker(
+        mut self,
+        checker: Arc<dyn ContentChecker>,
+    ) -> Self {
+        self.content_checker = Some(checker);
+        self
+    }
+
+    /// Sets the pool parts are extracted into.
+    pub fn with_buffer_pool(
+        mut self,
+        pool: BufferPool,
+    ) -> Self {
+        self.buffer_pool = Some(pool);
+        self
+    }
+}
+
+/// Options applied when compositing a Tee, see [Tee::compose_with_options](crate::tee::Tee::compose_with_options).
+#[derive(Debug, Clone)]
+pub struct ComposeOptions {
+    /// Mark drawn over the composed image
+    pub watermark: Option<Watermark>,
+    /// Metadata embedded into the encoded output, see [crate::meta]
+    ///
+    /// Unset eye, size and source hash are filled from the render.
+    pub metadata: Option<RenderMeta>,
+    /// Use the normal eye when the selected one is [blank](crate::tee::Tee::is_eye_blank)
+    pub blank_eye_fallback: bool,
+    /// Filter simulating color blindness, applied last
+    pub color_blindness: Option<ColorBlindness>,
+    /// Compositor backend, picked from the output area when `None`
+    pub backend: Option<CompositorBackend>,
+    /// Resampling and blending arithmetic, see [CompatMode]
+    pub compat: CompatMode,
+    /// Shading of the feet
+    pub feet: FeetStyle,
+    /// Layers drawn bottom first, [Layer::DEFAULT_ORDER] by default
+    pub layer_order: Vec<Layer>,
+    /// Sprites drawn between the layers, placed on the skin like parts
+    ///
+    /// The sprite is scaled like a part of its size, extra layers at the same [ZOrder] are
+    /// drawn in order.
+    pub extra_layers: Vec<(RgbaImage, SkinPS, ZOrder)>,
+}
+
+impl Default for ComposeOptions {
+    fn default() -> Self {
+        Self {
+            watermark: None,
+            metadata: None,
+            blank_eye_fallback: false,
+            color_blindness: None,
+            backend: None,
+            compat: CompatMode::default(),
+            feet: FeetStyle::default(),
+            layer_order: Layer::DEFAULT_ORDER.to_vec(),
+            extra_layers: Vec::new(),
+        }
+    }
+}
+
+impl ComposeOptions {
+    /// Creates options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the watermark.
+    pub fn with_watermark(
+        mut self,
+        watermark: Watermark,
+    ) -> Self {
+        self.watermark = Some(watermark);
+        self
+    }
+    /// Sets the metadata embedded into the encoded output.
+    pub fn with_metadata(
+        mut self,
+        metadata: RenderMeta,
+    ) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+    /// Sets whether blank eyes fall back to the normal eye.
+    pub fn with_blank_eye_fallback(
+        mut self,
+        fallback: bool,
+    ) -> Self {
+        self.blank_eye_fallback = fallback;
+        self
+    }
+
+    /// Sets the color blindness simulated on the output.
+    pub fn with_color_blindness(
+        mut self,
+        kind: ColorBlindness,
+    ) -> Self {
+        self.color_blindness = Some(kind);
+        self
+    }
+
+    /// Forces a compositor backend instead of picking one from the output area.
+    pub fn with_backend(
+        mut self,
+        backend: CompositorBackend,
+    ) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Sets the resampling and blending arithmetic.
+    pub fn with_compat(
+        mut self,
+        compat: CompatMode,
+    ) -> Self {
+        self.compat = compat;
+        self
+    }
+
+    /// Sets the shading of the feet.
+    pub fn with_feet(
+        mut self,
+        feet: FeetStyle,
+    ) -> Self {
+        self.feet = feet;
+        self
+    }
+
+    /// Sets the layers to draw, bottom first.
+    pub fn with_layer_order(
+        mut self,
+        order: impl IntoIterator<Item = Layer>,
+    ) -> Self {
+        self.layer_order = order.into_iter().collect();
+        self
+    }
+
+    /// Adds a sprite drawn at `placement` on the skin, see [ComposeOptions::extra_layers].
+    pub fn with_extra_layer(
+        mut self,
+        image: RgbaImage,
+        placement: SkinPS,
+        z_order: ZOrder,
+    ) -> Self {
+        self.extra_layers.push((image, placement, z_order));
+        self
+    }
+
+    /// Feeds everything that changes the encoded output into an entity tag.
+    pub(crate) fn hash_into(
+        &self,
+        hasher: &mut ETagHasher,
+    ) {
+        match &self.watermark {
+            Some(watermark) => watermark.hash_into(hasher.field(b"watermark")),
+            None => {
+                hasher.field(b"");
+            }
+        }
+        hasher.field(&[self.blank_eye_fallback as u8]);
+        hasher.field(format!("{:?}", self.color_blindness).as_bytes());
+        hasher.field(format!("{:?}", self.compat).as_bytes());
+        hasher.field(&self.feet.darken_back.to_bits().to_be_bytes());
+        let order: Vec<&str> = self.layer_order.iter().map(Layer::name).collect();
+        hasher.field(order.join(",").as_bytes());
+        for (image, placement, z_order) in &self.extra_layers {
+            hasher
+                .field(format!("{:?} {placement:?} {z_order:?}", image.dimensions()).as_bytes())
+                .field(image.as_raw());
+        }
+        match &self.metadata {
+            Some(metadata) => {
+                hasher.field(b"metadata");
+                for (key, value) in metadata.entries() {
+                    hasher.field(key.as_bytes()).field(value.as_bytes());
+                }
+            }
+            None => {
+                hasher.field(b"");
+            }
+        }
+    }
+}
+
+/// Arithmetic used to resize and blend the parts, see [ComposeOptions::compat].
+///
+/// Renders of different modes differ in a few pixel values along the edges of the parts,
+/// the layout is the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CompatMode {
+    /// Bilinear resizing, subpixel placement and the blending of the `image` crate
+    #[default]
+    Native,
+    /// The output of the Python renderer of
+    /// [ddnet-discordbot](https://github.com/ddnet/ddnet-discordbot/blob/5c37e4bcc2e97347de30d48a970c75cec3ecddb3/cogs/skindb.py),
+    /// as closely as its arithmetic is reproduced
+    ///
+    /// Parts are resized with the fixed point bicubic filter of Pillow on premultiplied
+    /// alpha, placed at whole pixels and blended with the integer `Image.alpha_composite`
+    /// of Pillow. The compositor backend is ignored, parts are blended one after another.
+    ///
+    /// Renders have not been checked against renders of the bot yet, so they may still
+    /// differ in a few pixel values. Compare them with a tolerance, e.g.
+    /// [ImageDiff::within](crate::diff::ImageDiff::within).
+    DiscordBotPy,
+}
+
+/// Shading of the feet, see [ComposeOptions::feet].
+///
+/// The game draws the back foot behind the body slightly darker than the front foot,
+/// which gives the tee depth. The default draws both feet with the same brightness.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FeetStyle {
+    /// How much darker the back foot is, from `0.0` (unchanged) to `1.0` (black)
+    pub darken_back: f32,
+}
+
+impl FeetStyle {
+    /// Creates a style darkening the back foot by `darken_back`, clamped to `0.0..=1.0`.
+    pub fn new(darken_back: f32) -> Self {
+        Self {
+            darken_back: darken_back.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Returns the back foot, darkened if the style asks for it.
+    pub(crate) fn back_foot<'a>(
+        &self,
+        feet: &'a RgbaImage,
+    ) -> Cow<'a, RgbaImage> {
+        if self.darken_back <= 0.0 {
+            return Cow::Borrowed(feet);
+        }
+        let factor = 1.0 - self.darken_back.min(1.0);
+        let mut darkened = feet.clone();
+        for pixel in darkened.pixels_mut() {
+            for channel in &mut pixel.0[..3] {
+                *channel = (*channel as f32 * factor).round() as u8;
+            }
+        }
+        Cow::Owned(darkened)
+    }
+}
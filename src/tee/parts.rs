@@ -1,5 +1,7 @@
 //! # Module with `UV` parts
 
+use std::borrow::Cow;
+
 use image::RgbaImage;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -28,6 +30,7 @@ pub struct WithShadow {
 ///
 /// Each variant contains the image data for that specific eye expression.
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum EyeTypeData {
     /// Normal eye expression
     Normal(RgbaImage),
@@ -43,10 +46,49 @@ pub enum EyeTypeData {
     Surprise(RgbaImage),
 }
 
+impl EyeTypeData {
+    /// Returns the image of the eye regardless of its type.
+    pub fn image(&self) -> &RgbaImage {
+        match self {
+            EyeTypeData::Normal(img)
+            | EyeTypeData::Angry(img)
+            | EyeTypeData::Pain(img)
+            | EyeTypeData::Happy(img)
+            | EyeTypeData::Empty(img)
+            | EyeTypeData::Surprise(img) => img,
+        }
+    }
+
+    /// Returns the image of the eye regardless of its type, mutably.
+    pub fn image_mut(&mut self) -> &mut RgbaImage {
+        match self {
+            EyeTypeData::Normal(img)
+            | EyeTypeData::Angry(img)
+            | EyeTypeData::Pain(img)
+            | EyeTypeData::Happy(img)
+            | EyeTypeData::Empty(img)
+            | EyeTypeData::Surprise(img) => img,
+        }
+    }
+
+    /// Returns the image of the eye regardless of its type, by value.
+    pub fn into_image(self) -> RgbaImage {
+        match self {
+            EyeTypeData::Normal(img)
+            | EyeTypeData::Angry(img)
+            | EyeTypeData::Pain(img)
+            | EyeTypeData::Happy(img)
+            | EyeTypeData::Empty(img)
+            | EyeTypeData::Surprise(img) => img,
+        }
+    }
+}
+
 /// An enum to specify the desired eye state for the Tee.
 ///
 /// This enum is used to select which eye expression to use when compositing the final image.
 #[derive(Debug, Clone, Copy, PartialEq, Hash, Eq)]
+#[non_exhaustive]
 pub enum EyeType {
     /// Normal eye expression
     Normal,
@@ -60,12 +102,30 @@ pub enum EyeType {
     Empty,
     /// Surprise eye expression
     Surprise,
+    /// Blink eye expression
+    ///
+    /// The skin sheet has no blink sprite, it is the normal eye squashed vertically
+    /// while parsing.
+    Blink,
 }
 
 impl EyeType {
+    /// Every eye type, ordered by [EyeType::index].
+    pub const ALL: [EyeType; 7] = [
+        EyeType::Normal,
+        EyeType::Angry,
+        EyeType::Pain,
+        EyeType::Happy,
+        EyeType::Empty,
+        EyeType::Surprise,
+        EyeType::Blink,
+    ];
+
     /// Returns the array index corresponding to this eye type.
     ///
     /// This is used to access the appropriate eye image in the `Tee.eye` array.
+    /// [EyeType::Blink] is synthesized and stored apart from it, its index follows the
+    /// array.
     pub const fn index(&self) -> usize {
         match self {
             EyeType::Normal => 0,
@@ -74,6 +134,165 @@ impl EyeType {
             EyeType::Happy => 3,
             EyeType::Empty => 4,
             EyeType::Surprise => 5,
+            EyeType::Blink => 6,
+        }
+    }
+
+    /// Returns the lowercase name of this eye type, e.g. `"happy"`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            EyeType::Normal => "normal",
+            EyeType::Angry => "angry",
+            EyeType::Pain => "pain",
+            EyeType::Happy => "happy",
+            EyeType::Empty => "empty",
+            EyeType::Surprise => "surprise",
+            EyeType::Blink => "blink",
+        }
+    }
+}
+
+/// Any sprite of a Tee, the parts with their shadows and every eye.
+///
+/// Lets generic code walk all sprites in one loop, see [Tee::get](crate::tee::Tee::get).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnyPart {
+    Body,
+    BodyShadow,
+    Feet,
+    FeetShadow,
+    Hand,
+    HandShadow,
+    Eye(EyeType),
+}
+
+impl AnyPart {
+    /// Every sprite, parts first, then the eyes ordered by [EyeType::index].
+    pub const ALL: [AnyPart; 13] = [
+        AnyPart::Body,
+        AnyPart::BodyShadow,
+        AnyPart::Feet,
+        AnyPart::FeetShadow,
+        AnyPart::Hand,
+        AnyPart::HandShadow,
+        AnyPart::Eye(EyeType::Normal),
+        AnyPart::Eye(EyeType::Angry),
+        AnyPart::Eye(EyeType::Pain),
+        AnyPart::Eye(EyeType::Happy),
+        AnyPart::Eye(EyeType::Empty),
+        AnyPart::Eye(EyeType::Surprise),
+        AnyPart::Eye(EyeType::Blink),
+    ];
+
+    /// Returns the name of the sprite as in [UV::PART_NAMES](crate::tee::uv::UV::PART_NAMES),
+    /// e.g. `"feet_shadow"` or `"eye_happy"`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            AnyPart::Body => "body",
+            AnyPart::BodyShadow => "body_shadow",
+            AnyPart::Feet => "feet",
+            AnyPart::FeetShadow => "feet_shadow",
+            AnyPart::Hand => "hand",
+            AnyPart::HandShadow => "hand_shadow",
+            AnyPart::Eye(EyeType::Normal) => "eye_normal",
+            AnyPart::Eye(EyeType::Angry) => "eye_angry",
+            AnyPart::Eye(EyeType::Pain) => "eye_pain",
+            AnyPart::Eye(EyeType::Happy) => "eye_happy",
+            AnyPart::Eye(EyeType::Empty) => "eye_empty",
+            AnyPart::Eye(EyeType::Surprise) => "eye_surprise",
+            AnyPart::Eye(EyeType::Blink) => "eye_blink",
+        }
+    }
+}
+
+impl From<TeePart> for AnyPart {
+    fn from(value: TeePart) -> Self {
+        match value {
+            TeePart::Body => AnyPart::Body,
+            TeePart::BodyShadow => AnyPart::BodyShadow,
+            TeePart::Feet => AnyPart::Feet,
+            TeePart::FeetShadow => AnyPart::FeetShadow,
+            TeePart::Hand => AnyPart::Hand,
+            TeePart::HandShadow => AnyPart::HandShadow,
+        }
+    }
+}
+
+impl From<EyeType> for AnyPart {
+    fn from(value: EyeType) -> Self {
+        AnyPart::Eye(value)
+    }
+}
+
+/// Different eyes for both sides of the face, as seen on the composed image.
+///
+/// The right eye is mirrored like the second eye of a single [EyeType].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EyePair {
+    /// Eye drawn at [Skin::first_eyes](crate::tee::skin::Skin::first_eyes)
+    pub left: EyeType,
+    /// Eye drawn at [Skin::second_eyes](crate::tee::skin::Skin::second_eyes)
+    pub right: EyeType,
+}
+
+impl EyePair {
+    /// A wink, a happy left eye and an empty right one.
+    pub const WINK: EyePair = EyePair::new(EyeType::Happy, EyeType::Empty);
+
+    pub const fn new(
+        left: EyeType,
+        right: EyeType,
+    ) -> Self {
+        Self {
+            left,
+            right,
         }
     }
 }
+
+/// Selects the eyes used when compositing a Tee.
+///
+/// Either one of the canonical [EyeType]s, a custom eye registered with
+/// [Tee::add_custom_eye](crate::tee::Tee::add_custom_eye), or an [EyePair].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EyeSelection<'a> {
+    /// One of the eyes every Tee has
+    Standard(EyeType),
+    /// A custom eye by the name it was registered with
+    Custom(&'a str),
+    /// A different eye on each side
+    Pair(EyePair),
+}
+
+impl<'a> EyeSelection<'a> {
+    /// Returns the name of the selected eye, see [EyeType::name].
+    ///
+    /// Pairs are named like `happy+empty`, left eye first.
+    pub fn name(&self) -> Cow<'a, str> {
+        match *self {
+            EyeSelection::Standard(eye) => Cow::Borrowed(eye.name()),
+            EyeSelection::Custom(name) => Cow::Borrowed(name),
+            EyeSelection::Pair(pair) => {
+                Cow::Owned(format!("{}+{}", pair.left.name(), pair.right.name()))
+            }
+        }
+    }
+}
+
+impl From<EyeType> for EyeSelection<'_> {
+    fn from(value: EyeType) -> Self {
+        EyeSelection::Standard(value)
+    }
+}
+
+impl From<EyePair> for EyeSelection<'_> {
+    fn from(value: EyePair) -> Self {
+        EyeSelection::Pair(value)
+    }
+}
+
+impl<'a> From<&'a str> for EyeSelection<'a> {
+    fn from(value: &'a str) -> Self {
+        EyeSelection::Custom(value)
+    }
+}
@@ -0,0 +1,167 @@
+//! # Module with pooled part buffers
+//!
+//! Parts have the same few sizes on every skin, so services parsing thousands of skins
+//! can hand the buffers of tees they are done with back to a [BufferPool] and have the
+//! next parse extract into them instead of allocating.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::tee::{Tee, options::ParseOptions, pool::BufferPool, uv::TEE_UV_LAYOUT};
+//!
+//! let pool = BufferPool::new(64);
+//! let options = ParseOptions::new().with_buffer_pool(pool.clone());
+//! for data in uploads {
+//!     let tee = Tee::new_with_options(data, TEE_UV_LAYOUT, ImageFormat::Png, options.clone())?;
+//!     let png = tee.compose_png(TEE_SKIN_LAYOUT, EyeType::Normal)?;
+//!     pool.recycle(tee);
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use image::RgbaImage;
+use tracing::trace;
+
+use crate::tee::Tee;
+
+#[derive(Debug, Default)]
+struct PoolState {
+    /// Free buffers by image size
+    free: HashMap<(u32, u32), Vec<Vec<u8>>>,
+    /// Free buffers over all sizes
+    len: usize,
+}
+
+/// A shared pool of RGBA buffers, grouped by image size.
+///
+/// Cloning is cheap, clones share the same buffers.
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+    state: Arc<Mutex<PoolState>>,
+    /// Free buffers kept per size, more are dropped
+    max_per_size: usize,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl BufferPool {
+    /// Creates an empty pool keeping at most `max_per_size` free buffers of every size.
+    pub fn new(max_per_size: usize) -> Self {
+        Self {
+            state: Arc::default(),
+            max_per_size,
+            hits: Arc::default(),
+            misses: Arc::default(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PoolState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Takes a transparent image of the given size, reusing a free buffer if there is one.
+    pub fn take(
+        &self,
+        width: u32,
+        height: u32,
+    ) -> RgbaImage {
+        let buffer = {
+            let mut state = self.lock();
+            let buffer = state.free.get_mut(&(width, height)).and_then(Vec::pop);
+            if buffer.is_some() {
+                state.len -= 1;
+            }
+            buffer
+        };
+        let buffer = match buffer {
+            Some(mut buffer) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                buffer.fill(0);
+                buffer
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                vec![0; width as usize * height as usize * 4]
+            }
+        };
+        RgbaImage::from_raw(width, height, buffer).expect("buffer matches the image size")
+    }
+
+    /// Hands the buffer of `image` back, it is dropped if the pool is full for its size.
+    pub fn give(
+        &self,
+        image: RgbaImage,
+    ) {
+        let size = image.dimensions();
+        let mut state = self.lock();
+        let free = state.free.entry(size).or_default();
+        if free.len() < self.max_per_size {
+            free.push(image.into_raw());
+            state.len += 1;
+        } else {
+            trace!(?size, "Buffer pool is full, dropping buffer");
+        }
+    }
+
+    /// Hands every part of `tee` back, including its custom eyes.
+    pub fn recycle(
+        &self,
+        tee: Tee,
+    ) {
+        let Tee {
+            body,
+            feet,
+            eye,
+            blink,
+            hand,
+            custom_eyes,
+            ..
+        } = tee;
+        for part in [body, feet, hand] {
+            self.give(part.value);
+            self.give(part.shadow);
+        }
+        for eye in eye {
+            self.give(eye.into_image());
+        }
+        self.give(blink);
+        for (_, eye) in custom_eyes {
+            self.give(eye);
+        }
+    }
+
+    /// Returns the amount of free buffers.
+    pub fn len(&self) -> usize {
+        self.lock().len
+    }
+
+    /// Returns `true` if there are no free buffers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if both share the same buffers.
+    pub fn same_pool(
+        &self,
+        other: &BufferPool,
+    ) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+
+    /// Returns how many taken buffers were reused and how many were allocated.
+    pub fn stats(&self) -> (u64, u64) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+}
@@ -0,0 +1,113 @@
+//! # Module with head portraits
+//!
+//! A portrait is the body and eyes of a tee without its feet, cropped tightly to the
+//! head and scaled to a square, the size of an emoji in chat messages.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::tee::parts::EyeType;
+//!
+//! let emoji = tee.compose_portrait(EyeType::Happy, 32, ImageFormat::Png)?;
+//! ```
+
+use bytes::Bytes;
+use image::{ImageFormat, RgbaImage, imageops};
+use tracing::{instrument, trace};
+
+use crate::{
+    error::Result,
+    tee::{
+        Tee,
+        imaging::{Backend, Filter, Imaging},
+        layer::Layer,
+        options::ComposeOptions,
+        parts::EyeSelection,
+        raw::{encode_image, opaque_bounds},
+        skin::TEE_SKIN_LAYOUT,
+    },
+};
+
+/// Layers of a portrait, bottom first.
+pub const PORTRAIT_LAYERS: [Layer; 4] = [
+    Layer::BodyShadow,
+    Layer::Body,
+    Layer::FirstEye,
+    Layer::SecondEye,
+];
+
+impl Tee {
+    /// Composites the body and eyes of the Tee, cropped to the head and scaled to
+    /// `size` x `size` pixels.
+    ///
+    /// The crop follows the visible pixels of the body, so bodies not filling their
+    /// sprite are cropped just as tightly.
+    #[instrument(level = "debug", skip(self, eye_type))]
+    pub fn compose_portrait_image<'a>(
+        &self,
+        eye_type: impl Into<EyeSelection<'a>>,
+        size: u32,
+    ) -> RgbaImage {
+        let skin = TEE_SKIN_LAYOUT;
+        let (_, (body_width, _)) = skin.place(skin.body, self.used_uv.body.size());
+        // The ball covers about four fifths of its sprite, compose large enough that
+        // the crop is only scaled down
+        let factor = (size as f32 * 1.25 / body_width.max(1) as f32).max(1.);
+        let options = ComposeOptions::new().with_layer_order(PORTRAIT_LAYERS);
+        let canvas = self.compose_image_with_options(skin.scaled(factor), eye_type, &options);
+        fit_square(&canvas, size, 0)
+    }
+
+    /// Composites a portrait like [Tee::compose_portrait_image] and encodes it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(Bytes)` with the encoded portrait, or `Err(TeeError)` if
+    /// encoding fails.
+    pub fn compose_portrait<'a>(
+        &self,
+        eye_type: impl Into<EyeSelection<'a>>,
+        size: u32,
+        img_format: ImageFormat,
+    ) -> Result<Bytes> {
+        encode_image(&self.compose_portrait_image(eye_type, size), img_format)
+    }
+}
+
+/// Crops `canvas` to its visible pixels and scales them to span a square of `size`
+/// pixels, centered with at least `padding` transparent pixels on every side.
+pub(crate) fn fit_square(
+    canvas: &RgbaImage,
+    size: u32,
+    padding: u32,
+) -> RgbaImage {
+    let size = size.max(1);
+    let mut square = RgbaImage::new(size, size);
+    let Some((x, y, width, height)) = opaque_bounds(canvas) else {
+        return square;
+    };
+    let inner = size.saturating_sub(padding * 2).max(1);
+    let content = width.max(height);
+    let fit = |side: u32| {
+        ((side as u64 * inner as u64 + content as u64 / 2) / content as u64).max(1) as u32
+    };
+    let (fit_width, fit_height) = (fit(width), fit(height));
+    trace!(
+        canvas_size = ?canvas.dimensions(),
+        x, y, width, height, fit_width, fit_height,
+        "Framing content"
+    );
+
+    // Only the content is resampled, so the padding stays transparent
+    let mut content = imageops::crop_imm(canvas, x, y, width, height).to_image();
+    if (fit_width, fit_height) != (width, height) {
+        content = Backend::resize(&content, fit_width, fit_height, Filter::Lanczos3);
+    }
+    Backend::replace(
+        &mut square,
+        &content,
+        ((size - fit_width) / 2) as i64,
+        ((size - fit_height) / 2) as i64,
+    );
+    square
+}
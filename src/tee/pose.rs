@@ -0,0 +1,148 @@
+//! # Module with pose presets
+//!
+//! A [Pose] approximates a tee seen from another angle with the front facing sprites:
+//! the back view mirrors the body and hides the eyes, the three quarter view moves the
+//! eyes to the side and draws the far foot smaller and closer. Map story renderers use
+//! them for tees facing away from the camera.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::tee::{pose::Pose, skin::TEE_SKIN_LAYOUT};
+//!
+//! let png = tee.compose_pose(TEE_SKIN_LAYOUT, Pose::Back, EyeType::Normal, ImageFormat::Png)?;
+//!
+//! // Or combine it with other options, the back view also needs the mirrored body
+//! let (skin, options) = Pose::ThreeQuarter.apply(TEE_SKIN_LAYOUT, ComposeOptions::new());
+//! let png = tee.compose_with_options(skin, EyeType::Happy, ImageFormat::Png, &options)?;
+//! ```
+
+use std::borrow::Cow;
+
+use bytes::Bytes;
+use image::{ImageFormat, RgbaImage};
+use tracing::instrument;
+
+use crate::{
+    error::Result,
+    tee::{
+        Tee,
+        imaging::{Backend, Imaging},
+        options::ComposeOptions,
+        parts::EyeSelection,
+        skin::{Skin, SkinPS, SkinPosition},
+    },
+};
+
+/// A preset of part placements approximating a view angle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Pose {
+    /// Facing the camera, the layout as is
+    #[default]
+    Front,
+    /// Facing away, mirrored body without eyes
+    Back,
+    /// Turned halfway to the right, eyes moved to the side and the far foot smaller
+    ThreeQuarter,
+}
+
+impl Pose {
+    /// Every pose.
+    pub const ALL: [Pose; 3] = [Pose::Front, Pose::Back, Pose::ThreeQuarter];
+
+    /// Returns the lowercase name of the pose, e.g. `"back"`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Pose::Front => "front",
+            Pose::Back => "back",
+            Pose::ThreeQuarter => "three-quarter",
+        }
+    }
+
+    /// Returns whether the body is drawn mirrored, see [Tee::posed].
+    pub const fn mirrors_body(&self) -> bool {
+        matches!(self, Pose::Back)
+    }
+
+    /// Returns how far the left and right eye are moved, in layout units.
+    pub const fn eye_offsets(&self) -> [SkinPosition; 2] {
+        match self {
+            Pose::Front | Pose::Back => [(0., 0.), (0., 0.)],
+            // The far eye moves less, so the eyes close up
+            Pose::ThreeQuarter => [(6., 0.), (4., 0.)],
+        }
+    }
+
+    /// Moves the eyes and feet of `skin` and drops the eye layers from `options` for
+    /// the back view.
+    ///
+    /// Sprites are left alone, compose the back view with a [posed](Tee::posed) tee.
+    pub fn apply(
+        &self,
+        skin: Skin,
+        mut options: ComposeOptions,
+    ) -> (Skin, ComposeOptions) {
+        let [left, right] = self.eye_offsets();
+        let shift = |((x, y), scale): SkinPS, (dx, dy): SkinPosition| ((x + dx, y + dy), scale);
+        let mut skin = Skin {
+            first_eyes: shift(skin.first_eyes, left),
+            second_eyes: shift(skin.second_eyes, right),
+            ..skin
+        };
+        match self {
+            Pose::Front => {}
+            Pose::Back => options.layer_order.retain(|layer| !layer.is_eye()),
+            Pose::ThreeQuarter => {
+                // The far foot is foreshortened, smaller and tucked towards the near one
+                let ((x, y), scale) = skin.feet_back;
+                skin.feet_back = ((x + 6., y + 1.), scale * 0.85);
+            }
+        }
+        (skin, options)
+    }
+}
+
+impl Tee {
+    /// Returns the Tee with the sprites of `pose`, a clone with the body and its shadow
+    /// mirrored for [Pose::Back] and the Tee itself otherwise.
+    pub fn posed(
+        &self,
+        pose: Pose,
+    ) -> Cow<'_, Tee> {
+        if !pose.mirrors_body() {
+            return Cow::Borrowed(self);
+        }
+        let mut tee = self.clone();
+        tee.body.value = Backend::flip_horizontal(&tee.body.value);
+        tee.body.shadow = Backend::flip_horizontal(&tee.body.shadow);
+        Cow::Owned(tee)
+    }
+
+    /// Composites the Tee in a [Pose] with default [ComposeOptions], the eyes are not
+    /// drawn for [Pose::Back].
+    #[instrument(level = "debug", skip(self, skin, eye_type), fields(pose = pose.name()))]
+    pub fn compose_pose_image<'a>(
+        &self,
+        skin: Skin,
+        pose: Pose,
+        eye_type: impl Into<EyeSelection<'a>>,
+    ) -> RgbaImage {
+        let (skin, options) = pose.apply(skin, ComposeOptions::default());
+        self.posed(pose)
+            .compose_image_with_options(skin, eye_type, &options)
+    }
+
+    /// Composites the Tee in a [Pose] like [Tee::compose_pose_image] and encodes it.
+    #[instrument(level = "debug", skip(self, skin, eye_type), fields(pose = pose.name()))]
+    pub fn compose_pose<'a>(
+        &self,
+        skin: Skin,
+        pose: Pose,
+        eye_type: impl Into<EyeSelection<'a>>,
+        img_format: ImageFormat,
+    ) -> Result<Bytes> {
+        let (skin, options) = pose.apply(skin, ComposeOptions::default());
+        self.posed(pose)
+            .compose_with_options(skin, eye_type, img_format, &options)
+    }
+}
@@ -0,0 +1,137 @@
+//! # Module with single part previews
+//!
+//! A [PartPreview] renders one part of a tee with its shadow, centered on a padded
+//! square, e.g. for the feet of a skin in an editor without any canvas math.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::tee::preview::PartPreview;
+//!
+//! let feet = tee.compose_part_preview(PartPreview::FEET.with_size(128), ImageFormat::Png)?;
+//! let hand = tee.compose_part_preview_image(PartPreview::HAND);
+//! ```
+
+use bytes::Bytes;
+use image::{ImageFormat, RgbaImage};
+use tracing::instrument;
+
+use crate::{
+    error::Result,
+    tee::{
+        Tee,
+        imaging::{Backend, Imaging},
+        parts::WithShadow,
+        portrait::fit_square,
+        raw::encode_image,
+    },
+};
+
+/// A part drawn by a [PartPreview].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PreviewPart {
+    /// The body, without eyes
+    Body,
+    /// A foot
+    Feet,
+    /// A hand
+    Hand,
+}
+
+impl PreviewPart {
+    /// Every part.
+    pub const ALL: [PreviewPart; 3] = [PreviewPart::Body, PreviewPart::Feet, PreviewPart::Hand];
+
+    /// Returns the lowercase name of the part, e.g. `"feet"`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            PreviewPart::Body => "body",
+            PreviewPart::Feet => "feet",
+            PreviewPart::Hand => "hand",
+        }
+    }
+}
+
+/// A preset rendering a single part with its shadow, see the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PartPreview {
+    /// The part to draw
+    pub part: PreviewPart,
+    /// Width and height of the output in pixels
+    pub size: u32,
+    /// Transparent pixels kept between the part and every edge
+    pub padding: u32,
+}
+
+impl PartPreview {
+    /// The feet on a 64 pixel square.
+    pub const FEET: PartPreview = PartPreview::new(PreviewPart::Feet);
+    /// The hand on a 64 pixel square.
+    pub const HAND: PartPreview = PartPreview::new(PreviewPart::Hand);
+
+    /// Creates a preview of `part` on a 64 pixel square with 4 pixels of padding.
+    pub const fn new(part: PreviewPart) -> Self {
+        Self {
+            part,
+            size: 64,
+            padding: 4,
+        }
+    }
+
+    /// Sets the width and height of the output.
+    pub const fn with_size(
+        mut self,
+        size: u32,
+    ) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the padding around the part.
+    pub const fn with_padding(
+        mut self,
+        padding: u32,
+    ) -> Self {
+        self.padding = padding;
+        self
+    }
+}
+
+impl Tee {
+    /// Draws a single part over its shadow, cropped to its visible pixels and centered
+    /// on a square of [PartPreview::size] with [PartPreview::padding].
+    ///
+    /// The longer side of the part spans the square without the padding, so previews
+    /// of different skins line up. A fully transparent part gives an empty square.
+    #[instrument(level = "debug", skip(self), fields(part = preview.part.name()))]
+    pub fn compose_part_preview_image(
+        &self,
+        preview: PartPreview,
+    ) -> RgbaImage {
+        let WithShadow {
+            value,
+            shadow,
+        } = match preview.part {
+            PreviewPart::Body => &self.body,
+            PreviewPart::Feet => &self.feet,
+            PreviewPart::Hand => &self.hand,
+        };
+        let mut canvas = shadow.clone();
+        Backend::overlay(&mut canvas, value, 0, 0);
+        fit_square(&canvas, preview.size, preview.padding)
+    }
+
+    /// Draws a part like [Tee::compose_part_preview_image] and encodes it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(Bytes)` with the encoded preview, or `Err(TeeError)` if
+    /// encoding fails.
+    pub fn compose_part_preview(
+        &self,
+        preview: PartPreview,
+        img_format: ImageFormat,
+    ) -> Result<Bytes> {
+        encode_image(&self.compose_part_preview_image(preview), img_format)
+    }
+}
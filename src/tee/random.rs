@@ -0,0 +1,218 @@
+//! # Module with procedurally generated tees
+//!
+//! Draws a whole skin sheet from a seed, for placeholder avatars of players without a
+//! skin. The same seed always gives the same tee.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::tee::{Tee, hsl::ddnet_color_to_hsl};
+//!
+//! let tee = Tee::random(42, None);
+//! let team = [ddnet_color_to_hsl(65461), ddnet_color_to_hsl(10223541)];
+//! let red_or_blue = Tee::random_with_pattern(player_id, Some(&team), true);
+//! ```
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use tracing::{debug, instrument};
+
+use crate::tee::{
+    Tee,
+    hash::SourceHash,
+    hsl::{HSL, hsl_to_rgb},
+    options::ParseOptions,
+    parts::TeePart,
+    uv::{TEE_UV_LAYOUT, UvPart},
+};
+
+impl Tee {
+    /// Generates a tee with plain body and feet from a seed.
+    ///
+    /// Colors are picked from `palette` if given, otherwise any hue with the saturation
+    /// and lightness range of in-game player colors.
+    pub fn random(
+        seed: u64,
+        palette: Option<&[HSL]>,
+    ) -> Tee {
+        Self::random_with_pattern(seed, palette, false)
+    }
+
+    /// Generates a tee like [Tee::random], with spots of noise on the body if `pattern`
+    /// is set.
+    #[instrument(level = "debug", skip(palette))]
+    pub fn random_with_pattern(
+        seed: u64,
+        palette: Option<&[HSL]>,
+        pattern: bool,
+    ) -> Tee {
+        let mut rng = SplitMix64(seed);
+        let pick_color = |rng: &mut SplitMix64| match palette {
+            Some(palette) if !palette.is_empty() => {
+                palette[rng.below(palette.len() as u64) as usize]
+            }
+            _ => (rng.unit(), 0.3 + rng.unit() * 0.6, 0.55 + rng.unit() * 0.4),
+        };
+        let body_color = pick_color(&mut rng);
+        let feet_color = pick_color(&mut rng);
+        let (r, g, b) = hsl_to_rgb((rng.unit(), 0.6, 0.1 + rng.unit() * 0.15));
+        let pupil = Rgba([(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8, 255]);
+        let spots: Vec<(f32, f32, f32)> = if pattern {
+            (0..3 + rng.below(5))
+                .map(|_| {
+                    (
+                        rng.unit() * 1.2 - 0.6,
+                        rng.unit() * 1.2 - 0.6,
+                        0.1 + rng.unit() * 0.2,
+                    )
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let sheet = draw_sheet(pupil, &spots);
+        let source_hash = SourceHash::of(sheet.as_raw());
+        let mut tee = Tee::from_image(
+            DynamicImage::ImageRgba8(sheet),
+            TEE_UV_LAYOUT,
+            source_hash,
+            &ParseOptions::default(),
+        )
+        .expect("the generated sheet matches the UV layout");
+        tee.apply_hsl_to_parts(body_color, &[TeePart::Body]);
+        tee.apply_hsl_to_parts(feet_color, &[TeePart::Feet, TeePart::Hand]);
+        debug!(
+            ?body_color,
+            ?feet_color,
+            spots = spots.len(),
+            "Generated a random tee"
+        );
+        tee
+    }
+}
+
+/// Small deterministic generator, the output must never change between versions.
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0.0..1.0`.
+    pub(crate) fn unit(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub(crate) fn below(
+        &mut self,
+        n: u64,
+    ) -> u64 {
+        self.next() % n
+    }
+}
+
+/// Draws an untinted sheet in [TEE_UV_LAYOUT].
+fn draw_sheet(
+    pupil: Rgba<u8>,
+    spots: &[(f32, f32, f32)],
+) -> RgbaImage {
+    let uv = TEE_UV_LAYOUT;
+    let mut sheet = RgbaImage::new(uv.container.0, uv.container.1);
+
+    let shaded = |x: f32, y: f32, radius: f32| {
+        let light = 1.0 - 0.2 * (x + y + 2.0 * radius) / (4.0 * radius);
+        edge(radius - (x * x + y * y).sqrt()).map(|alpha| gray(light, alpha))
+    };
+    fill(&mut sheet, uv.body, |x, y| {
+        let darker = spots
+            .iter()
+            .any(|(sx, sy, r)| (x - sx).powi(2) + (y - sy).powi(2) < r * r);
+        shaded(x, y, 0.9).map(|mut pixel| {
+            if darker {
+                pixel.0[..3]
+                    .iter_mut()
+                    .for_each(|c| *c = (*c as f32 * 0.75) as u8);
+            }
+            pixel
+        })
+    });
+    fill(&mut sheet, uv.body_shadow, |x, y| {
+        edge(0.97 - (x * x + y * y).sqrt()).map(|alpha| gray(0.0, alpha))
+    });
+    fill(&mut sheet, uv.feet, |x, y| {
+        let distance = ((x / 0.7).powi(2) + ((y - 0.1) / 0.7).powi(2)).sqrt();
+        edge((1.0 - distance) * 0.7).map(|alpha| gray(1.0 - 0.15 * (y + 0.6), alpha))
+    });
+    fill(&mut sheet, uv.feet_shadow, |x, y| {
+        let distance = ((x / 0.77).powi(2) + ((y - 0.1) / 0.85).powi(2)).sqrt();
+        edge((1.0 - distance) * 0.77).map(|alpha| gray(0.0, alpha))
+    });
+    fill(&mut sheet, uv.hand, |x, y| shaded(x, y, 0.6));
+    fill(&mut sheet, uv.hand_shadow, |x, y| {
+        edge(0.7 - (x * x + y * y).sqrt()).map(|alpha| gray(0.0, alpha))
+    });
+
+    let pupil_at = |inside: bool| inside.then_some(pupil);
+    // Normal
+    fill(&mut sheet, uv.eyes[0], |x, y| {
+        pupil_at((x / 0.35).powi(2) + (y / 0.6).powi(2) < 1.0)
+    });
+    // Angry, cut diagonally from the top
+    fill(&mut sheet, uv.eyes[1], |x, y| {
+        pupil_at((x / 0.35).powi(2) + (y / 0.6).powi(2) < 1.0 && y > x * 0.8 - 0.2)
+    });
+    // Pain, a thin cross
+    fill(&mut sheet, uv.eyes[2], |x, y| {
+        pupil_at(x.abs() < 0.5 && ((x - y).abs() < 0.15 || (x + y).abs() < 0.15))
+    });
+    // Happy, an arc
+    fill(&mut sheet, uv.eyes[3], |x, y| {
+        let r = (x * x + (y - 0.2).powi(2)).sqrt();
+        pupil_at(y < 0.2 && (0.3..0.5).contains(&r))
+    });
+    // Empty, a dot
+    fill(&mut sheet, uv.eyes[4], |x, y| {
+        pupil_at(x * x + y * y < 0.04)
+    });
+    // Surprise, a ring
+    fill(&mut sheet, uv.eyes[5], |x, y| {
+        pupil_at((0.3..0.5).contains(&(x * x + y * y).sqrt()))
+    });
+    sheet
+}
+
+/// Fills a part, `shape` gets coordinates from `-1.0` to `1.0` across the part.
+fn fill(
+    sheet: &mut RgbaImage,
+    part: UvPart,
+    shape: impl Fn(f32, f32) -> Option<Rgba<u8>>,
+) {
+    for py in 0..part.h {
+        for px in 0..part.w {
+            let x = (px as f32 + 0.5) / part.w as f32 * 2.0 - 1.0;
+            let y = (py as f32 + 0.5) / part.h as f32 * 2.0 - 1.0;
+            if let Some(pixel) = shape(x, y) {
+                sheet.put_pixel(part.x + px, part.y + py, pixel);
+            }
+        }
+    }
+}
+
+/// Alpha of an anti-aliased edge, `inside` is the distance to the border.
+fn edge(inside: f32) -> Option<f32> {
+    let alpha = (inside * 30.0).clamp(0.0, 1.0);
+    (alpha > 0.0).then_some(alpha)
+}
+
+fn gray(
+    light: f32,
+    alpha: f32,
+) -> Rgba<u8> {
+    let value = (light.clamp(0.0, 1.0) * 255.0) as u8;
+    Rgba([value, value, value, (alpha * 255.0) as u8])
+}
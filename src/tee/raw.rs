@@ -0,0 +1,493 @@
+//! # Module with low-level helpers
+//!
+//! The building blocks [Tee](crate::tee::Tee) is parsed and composed with, for custom
+//! extraction flows such as body-only crops. Every helper checks bounds and
+//! dimensions the same way [Tee::new](crate::tee::Tee::new) does.
+
+use std::io::Cursor;
+
+use bytes::Bytes;
+use image::{
+    AnimationDecoder, DynamicImage, GenericImageView, ImageDecoder, ImageFormat, ImageReader,
+    RgbaImage,
+    codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+    metadata::Orientation,
+};
+use moxcms::{ColorProfile, Layout, TransformOptions};
+use tracing::{debug, error, instrument, trace, warn};
+
+use crate::{
+    error::{Result, TeeError},
+    tee::{
+        imaging::{Backend, Filter, Imaging},
+        options::ParseOptions,
+        parts::{EyeTypeData, WithShadow},
+        pool::BufferPool,
+        uv::UvPart,
+    },
+};
+
+/// What happens when a part reaches past the edge of the source image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ExtractPolicy {
+    /// Fail with `TeeError::OutOfBounds`, and reject sheets of the wrong size
+    #[default]
+    Strict,
+    /// Take what is inside the image and pad the rest with transparency, for slightly
+    /// truncated sheets found in the wild
+    Clamp,
+}
+
+/// Extracts a rectangular part from a source image.
+///
+/// # Arguments
+///
+/// * `img` - A reference to the source `DynamicImage`.
+/// * `part` - A [UvPart] defining the coordinates (`x`, `y`) and dimensions (`w`, `h`) of the area to extract.
+///
+/// # Returns
+///
+/// A `Result` which is `Ok(RgbaImage)` containing the extracted part, or `Err(TeeError::OutOfBounds)` if the part's dimensions fall outside the source image.
+///
+/// # Errors
+///
+/// Returns `TeeError::OutOfBounds` if the dimensions provided fall out of bounds.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use tee_morphosis::tee::{raw::extract_part, uv::UvPart};
+///
+/// let img = image::open("source.png")?;
+/// let part = UvPart { x: 10, y: 10, w: 50, h: 50 };
+/// let extracted = extract_part(&img, part)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[instrument(level = "debug", skip(img), fields(part = ?part))]
+pub fn extract_part(
+    img: &DynamicImage,
+    part: UvPart,
+) -> Result<RgbaImage> {
+    check_bounds(img, part)?;
+
+    trace!(
+        "Extracting part at position ({}, {}) with size ({}, {})",
+        part.x, part.y, part.w, part.h
+    );
+    let cropped_image = Backend::crop(img, part.x, part.y, part.w, part.h);
+    Ok(cropped_image)
+}
+
+/// Extracts a rectangular part like [extract_part], padding whatever lies outside the
+/// source image with transparency instead of failing.
+#[instrument(level = "debug", skip(img), fields(part = ?part))]
+pub fn extract_part_clamped(
+    img: &DynamicImage,
+    part: UvPart,
+) -> RgbaImage {
+    let (img_width, img_height) = img.dimensions();
+    let mut padded = RgbaImage::new(part.w, part.h);
+    let w = part.w.min(img_width.saturating_sub(part.x));
+    let h = part.h.min(img_height.saturating_sub(part.y));
+    if w < part.w || h < part.h {
+        debug!(
+            image_width = img_width,
+            image_height = img_height,
+            "Part is clamped to the image bounds."
+        );
+    }
+    if w > 0 && h > 0 {
+        Backend::replace(&mut padded, &Backend::crop(img, part.x, part.y, w, h), 0, 0);
+    }
+    padded
+}
+
+/// Fails with `TeeError::OutOfBounds` if `part` reaches past the edge of `img`.
+fn check_bounds(
+    img: &DynamicImage,
+    part: UvPart,
+) -> Result<()> {
+    let (img_width, img_height) = img.dimensions();
+    let fits = |start: u32, length: u32, limit: u32| {
+        start.checked_add(length).is_some_and(|end| end <= limit)
+    };
+    if !fits(part.x, part.w, img_width) || !fits(part.y, part.h, img_height) {
+        error!(
+            image_width = img_width,
+            image_height = img_height,
+            "Failed to extract part: out of bounds."
+        );
+        return Err(TeeError::OutOfBounds {
+            part,
+            width: img_width,
+            height: img_height,
+        });
+    }
+    Ok(())
+}
+
+/// Extracts a rectangular part like [extract_part_with_policy], into a buffer taken from
+/// `pool`.
+#[instrument(level = "trace", skip(img, pool), fields(part = ?part))]
+pub fn extract_part_pooled(
+    img: &DynamicImage,
+    part: UvPart,
+    policy: ExtractPolicy,
+    pool: &BufferPool,
+) -> Result<RgbaImage> {
+    if policy == ExtractPolicy::Strict {
+        check_bounds(img, part)?;
+    }
+    let (img_width, img_height) = img.dimensions();
+    let w = part.w.min(img_width.saturating_sub(part.x));
+    let h = part.h.min(img_height.saturating_sub(part.y));
+    let mut buffer = pool.take(part.w, part.h);
+    match img {
+        DynamicImage::ImageRgba8(source) => {
+            let (stride, row) = (img_width as usize * 4, w as usize * 4);
+            for y in 0..h as usize {
+                let start = (part.y as usize + y) * stride + part.x as usize * 4;
+                let target = y * part.w as usize * 4;
+                buffer.as_mut()[target..target + row]
+                    .copy_from_slice(&source.as_raw()[start..start + row]);
+            }
+        }
+        _ => {
+            for y in 0..h {
+                for x in 0..w {
+                    buffer.put_pixel(x, y, img.get_pixel(part.x + x, part.y + y));
+                }
+            }
+        }
+    }
+    Ok(buffer)
+}
+
+/// Extracts a rectangular part with [extract_part] or [extract_part_clamped], depending
+/// on `policy`.
+pub fn extract_part_with_policy(
+    img: &DynamicImage,
+    part: UvPart,
+    policy: ExtractPolicy,
+) -> Result<RgbaImage> {
+    match policy {
+        ExtractPolicy::Strict => extract_part(img, part),
+        ExtractPolicy::Clamp => Ok(extract_part_clamped(img, part)),
+    }
+}
+
+/// Decodes image data from bytes while enforcing decode limits.
+///
+/// Images are normalized to 8-bit RGBA with [normalize]. Still images are turned upright
+/// according to their EXIF orientation and converted to sRGB when they embed an ICC
+/// profile.
+///
+/// # Arguments
+///
+/// * `data` - The raw bytes of the image.
+/// * `format` - The format of the image data, guessed from the content if `None`.
+/// * `options` - The limits to enforce and the frame taken from animated images.
+///
+/// # Returns
+///
+/// A `Result` which is `Ok(DynamicImage)` on successful decoding, or `Err(TeeError)` on
+/// failure, including exceeded limits.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use tee_morphosis::tee::{options::ParseOptions, raw::{decode_image, extract_part}, uv::TEE_UV_LAYOUT};
+///
+/// // Body-only crop, without parsing the rest of the skin
+/// let img = decode_image(std::fs::read("skin.png")?.into(), None, &ParseOptions::default())?;
+/// let body = extract_part(&img, TEE_UV_LAYOUT.body)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[instrument(level = "debug", skip(data), fields(format = ?format, data_size = data.len()))]
+pub fn decode_image(
+    data: Bytes,
+    format: Option<ImageFormat>,
+    options: &ParseOptions,
+) -> Result<DynamicImage> {
+    let format = match format {
+        Some(format) => format,
+        None => image::guess_format(&data)?,
+    };
+    let limits: image::Limits = options.limits.into();
+
+    let decoded = match format {
+        ImageFormat::Gif => {
+            let mut decoder = GifDecoder::new(Cursor::new(data))?;
+            decoder.set_limits(limits)?;
+            decoder.into_frames()
+        }
+        ImageFormat::Png => {
+            let decoder = PngDecoder::with_limits(Cursor::new(data.clone()), limits.clone())?;
+            if !decoder.is_apng()? {
+                return decode_still(data, format, limits, options.frame);
+            }
+            decoder.apng()?.into_frames()
+        }
+        ImageFormat::WebP => {
+            let mut decoder = WebPDecoder::new(Cursor::new(data.clone()))?;
+            if !decoder.has_animation() {
+                return decode_still(data, format, limits, options.frame);
+            }
+            decoder.set_limits(limits)?;
+            decoder.into_frames()
+        }
+        _ => return decode_still(data, format, limits, options.frame),
+    };
+
+    trace!(frame = options.frame, "Decoding frame of animated image");
+    let mut frames = 0;
+    for frame in decoded {
+        let frame = frame?;
+        if frames == options.frame {
+            return Ok(DynamicImage::ImageRgba8(frame.into_buffer()));
+        }
+        frames += 1;
+    }
+    error!(
+        frame = options.frame,
+        frames, "Requested frame is out of range."
+    );
+    Err(TeeError::FrameOutOfRange {
+        index: options.frame,
+        frames,
+    })
+}
+
+/// Decodes a still image, which only has the frame `0`.
+fn decode_still(
+    data: Bytes,
+    format: ImageFormat,
+    limits: image::Limits,
+    frame: usize,
+) -> Result<DynamicImage> {
+    if frame != 0 {
+        error!(frame, "Requested frame of a still image.");
+        return Err(TeeError::FrameOutOfRange {
+            index: frame,
+            frames: 1,
+        });
+    }
+    let mut reader = ImageReader::new(Cursor::new(data));
+    reader.set_format(format);
+    reader.limits(limits);
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let icc_profile = decoder.icc_profile()?;
+
+    let mut img = normalize(DynamicImage::from_decoder(decoder)?);
+    if orientation != Orientation::NoTransforms {
+        trace!(?orientation, "Applying EXIF orientation");
+        img.apply_orientation(orientation);
+    }
+    match icc_profile {
+        Some(icc_profile) => Ok(convert_to_srgb(img, &icc_profile)),
+        None => Ok(img),
+    }
+}
+
+/// Converts any decoded color type to 8-bit RGBA.
+///
+/// 16-bit channels are rounded to the nearest 8-bit value instead of relying on the
+/// conversion defaults of `image`. Paletted and grayscale sources are already expanded
+/// by the decoders and only gain an alpha channel here.
+pub fn normalize(img: DynamicImage) -> DynamicImage {
+    let to_u8 = |value: u16| ((value as u32 * 255 + 32767) / 65535) as u8;
+    let rgba16 = match img {
+        DynamicImage::ImageRgba8(_) => return img,
+        DynamicImage::ImageRgba16(img) => img,
+        DynamicImage::ImageRgb16(_)
+        | DynamicImage::ImageLuma16(_)
+        | DynamicImage::ImageLumaA16(_) => img.into_rgba16(),
+        other => {
+            trace!(color = ?other.color(), "Expanding to 8-bit RGBA");
+            return DynamicImage::ImageRgba8(other.into_rgba8());
+        }
+    };
+    trace!("Rounding 16-bit channels to 8-bit RGBA");
+    let (width, height) = rgba16.dimensions();
+    let raw = rgba16.into_raw().into_iter().map(to_u8).collect();
+    DynamicImage::ImageRgba8(
+        RgbaImage::from_raw(width, height, raw).expect("buffer has the size of the image"),
+    )
+}
+
+/// Converts an image tagged with an ICC profile to sRGB, so wide gamut sources do not
+/// render washed out.
+///
+/// Profiles that can not be parsed or applied are ignored with a warning, and the
+/// pixels are kept as they are.
+fn convert_to_srgb(
+    img: DynamicImage,
+    icc_profile: &[u8],
+) -> DynamicImage {
+    let source = match ColorProfile::new_from_slice(icc_profile) {
+        Ok(profile) => profile,
+        Err(e) => {
+            warn!(error = %e, "Ignoring unreadable ICC profile.");
+            return img;
+        }
+    };
+    let transform = match source.create_transform_8bit(
+        Layout::Rgba,
+        &ColorProfile::new_srgb(),
+        Layout::Rgba,
+        TransformOptions::default(),
+    ) {
+        Ok(transform) => transform,
+        Err(e) => {
+            warn!(error = %e, "Ignoring unsupported ICC profile.");
+            return img;
+        }
+    };
+
+    trace!("Converting ICC tagged image to sRGB");
+    let source = img.to_rgba8();
+    let mut converted = RgbaImage::new(source.width(), source.height());
+    if let Err(e) = transform.transform(source.as_raw(), &mut converted) {
+        warn!(error = %e, "Failed to apply ICC profile.");
+        return DynamicImage::ImageRgba8(source);
+    }
+    DynamicImage::ImageRgba8(converted)
+}
+
+/// Encodes an image into bytes with the specified format.
+///
+/// # Arguments
+///
+/// * `img` - The image to encode.
+/// * `format` - The desired output format.
+///
+/// # Returns
+///
+/// A `Result` which is `Ok(Bytes)` on successful encoding, or `Err(TeeError)` on failure.
+#[instrument(level = "debug", skip(img), fields(format = ?format))]
+pub fn encode_image(
+    img: &RgbaImage,
+    format: ImageFormat,
+) -> Result<Bytes> {
+    debug!("Writing image to buffer in format: {:?}", format);
+    Backend::encode(img, format)
+}
+
+/// Validates that the image dimensions match the expected container dimensions.
+///
+/// # Arguments
+///
+/// * `actual` - The actual dimensions of the image.
+/// * `expected` - The expected dimensions of the image.
+///
+/// # Returns
+///
+/// A `Result` which is `Ok(())` if the dimensions match, or `Err(TeeError::InvalidDimensions)` if they don't.
+#[instrument(level = "debug", fields(actual = ?actual, expected = ?expected))]
+pub fn validate_image_dimensions(
+    actual: (u32, u32),
+    expected: (u32, u32),
+) -> Result<()> {
+    if actual != expected {
+        error!(
+            expected = ?expected,
+            found = ?actual,
+            "Invalid image dimensions."
+        );
+        return Err(TeeError::InvalidDimensions {
+            expected,
+            found: actual,
+        });
+    }
+    Ok(())
+}
+
+/// Extracts a part and its shadow from the source image.
+///
+/// # Arguments
+///
+/// * `img` - The source image.
+/// * `part` - The part to extract.
+/// * `shadow_part` - The shadow part to extract.
+///
+/// # Returns
+///
+/// A `Result` which is `Ok(WithShadow)` containing both the part and its shadow.
+#[instrument(level = "debug", skip(img), fields(part = ?part, shadow_part = ?shadow_part))]
+pub fn extract_with_shadow(
+    img: &DynamicImage,
+    part: UvPart,
+    shadow_part: UvPart,
+) -> Result<WithShadow> {
+    trace!("Extracting part and its shadow");
+    let value = extract_part(img, part)?;
+    let shadow = extract_part(img, shadow_part)?;
+    Ok(WithShadow {
+        value,
+        shadow,
+    })
+}
+
+/// Extracts all eye types from the source image.
+///
+/// # Arguments
+///
+/// * `img` - The source image.
+/// * `eye_parts` - An array of parts for each eye type.
+///
+/// # Returns
+///
+/// A `Result` which is `Ok([EyeTypeData; 6])` containing all eye types read from the
+/// source, see [synthesize_blink] for the blink.
+#[instrument(level = "debug", skip(img, eye_parts))]
+pub fn extract_all_eyes(
+    img: &DynamicImage,
+    eye_parts: &[UvPart; 6],
+) -> Result<[EyeTypeData; 6]> {
+    trace!("Extracting all eye types");
+    let eyes = [
+        EyeTypeData::Normal(extract_part(img, eye_parts[0])?),
+        EyeTypeData::Angry(extract_part(img, eye_parts[1])?),
+        EyeTypeData::Pain(extract_part(img, eye_parts[2])?),
+        EyeTypeData::Happy(extract_part(img, eye_parts[3])?),
+        EyeTypeData::Empty(extract_part(img, eye_parts[4])?),
+        EyeTypeData::Surprise(extract_part(img, eye_parts[5])?),
+    ];
+    Ok(eyes)
+}
+
+/// Squashes the normal eye to a quarter of its height, centered vertically.
+///
+/// This is how [EyeType::Blink](crate::tee::parts::EyeType::Blink) is built while parsing.
+pub fn synthesize_blink(normal: &RgbaImage) -> RgbaImage {
+    let (width, height) = normal.dimensions();
+    let squashed_height = (height / 4).max(1);
+    let squashed = Backend::resize(normal, width, squashed_height, Filter::Triangle);
+
+    let mut blink = RgbaImage::new(width, height);
+    Backend::replace(
+        &mut blink,
+        &squashed,
+        0,
+        ((height - squashed_height) / 2) as i64,
+    );
+    blink
+}
+
+/// Returns the smallest rectangle holding every visible pixel of `img` as
+/// `(x, y, width, height)`, `None` if the image is fully transparent.
+pub fn opaque_bounds(img: &RgbaImage) -> Option<(u32, u32, u32, u32)> {
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+    for (x, y, pixel) in img.enumerate_pixels() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        let (left, top, right, bottom) = bounds.get_or_insert((x, y, x, y));
+        *left = (*left).min(x);
+        *top = (*top).min(y);
+        *right = (*right).max(x);
+        *bottom = (*bottom).max(y);
+    }
+    bounds.map(|(left, top, right, bottom)| (left, top, right - left + 1, bottom - top + 1))
+}
@@ -0,0 +1,176 @@
+//! # Module with CSS spritesheets
+//!
+//! Packs every part of a [Tee] into one sheet and describes it with CSS classes, for web
+//! UIs drawing tees with DOM elements instead of a canvas.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! let sprite = tee.to_css_sprite("tee")?;
+//! std::fs::write("tee.png", &sprite.image)?;
+//! std::fs::write("tee.css", &sprite.css)?;
+//! // <div class="tee tee-eye-happy"></div>
+//! ```
+
+use std::fmt::Write;
+
+use bytes::Bytes;
+use image::{ImageFormat, RgbaImage, imageops};
+use tracing::{debug, instrument};
+
+use crate::{
+    error::Result,
+    tee::{Tee, parts::EyeType, raw::encode_image},
+};
+
+/// Sheets are at least this wide, wider parts widen them.
+const MIN_SHEET_WIDTH: u32 = 256;
+/// Space between packed parts, so scaled sprites do not bleed into each other.
+const GAP: u32 = 1;
+
+/// Where a part lies on a [CssSprite] sheet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpriteRect {
+    /// Name of the part, used as class suffix
+    pub name: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A packed PNG sheet of all parts of a tee and the stylesheet addressing them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssSprite {
+    /// Class prefix
+    pub prefix: String,
+    /// The sheet, PNG encoded
+    pub image: Bytes,
+    /// Size of the sheet
+    pub size: (u32, u32),
+    /// Every part on the sheet
+    pub parts: Vec<SpriteRect>,
+    /// Stylesheet loading the sheet from `{prefix}.png`, see [CssSprite::css]
+    pub css: String,
+}
+
+impl CssSprite {
+    /// Returns the stylesheet loading the sheet from `image_url`.
+    ///
+    /// The `.{prefix}` class sets the sheet as background and every part gets a
+    /// `.{prefix}-{part}` class with its size and `background-position`. Body and feet
+    /// parts are named like `body` and `feet-shadow`, eyes like `eye-happy` and custom
+    /// eyes like `eye-custom-{name}`.
+    pub fn css(
+        &self,
+        image_url: &str,
+    ) -> String {
+        let mut css = format!(
+            ".{} {{ background-image: url(\"{}\"); background-repeat: no-repeat; display: inline-block; }}\n",
+            self.prefix,
+            image_url.replace('"', "%22"),
+        );
+        for part in &self.parts {
+            let _ = writeln!(
+                css,
+                ".{}-{} {{ width: {}px; height: {}px; background-position: -{}px -{}px; }}",
+                self.prefix, part.name, part.width, part.height, part.x, part.y,
+            );
+        }
+        css
+    }
+}
+
+impl Tee {
+    /// Packs all parts and eyes, including custom ones, into a [CssSprite].
+    ///
+    /// Parts are stored at their source resolution, characters of `prefix` and of custom
+    /// eye names that are not valid in class names are replaced with `-`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(CssSprite)`, or `Err(TeeError)` if the sheet could not be
+    /// encoded.
+    #[instrument(level = "debug", skip(self))]
+    pub fn to_css_sprite(
+        &self,
+        prefix: &str,
+    ) -> Result<CssSprite> {
+        let mut parts: Vec<(String, &RgbaImage)> = vec![
+            ("body".into(), &self.body.value),
+            ("body-shadow".into(), &self.body.shadow),
+            ("feet".into(), &self.feet.value),
+            ("feet-shadow".into(), &self.feet.shadow),
+            ("hand".into(), &self.hand.value),
+            ("hand-shadow".into(), &self.hand.shadow),
+        ];
+        parts.extend(
+            EyeType::ALL
+                .into_iter()
+                .map(|eye_type| (format!("eye-{}", eye_type.name()), self.get_eye(eye_type))),
+        );
+        parts.extend(
+            self.custom_eyes
+                .iter()
+                .map(|(name, image)| (format!("eye-custom-{}", class_name(name)), image)),
+        );
+
+        let (size, rects) = pack(&parts);
+        let mut sheet = RgbaImage::new(size.0, size.1);
+        for ((_, image), rect) in parts.iter().zip(&rects) {
+            imageops::replace(&mut sheet, *image, rect.x as i64, rect.y as i64);
+        }
+
+        let mut sprite = CssSprite {
+            prefix: class_name(prefix),
+            image: encode_image(&sheet, ImageFormat::Png)?,
+            size,
+            parts: rects,
+            css: String::new(),
+        };
+        sprite.css = sprite.css(&format!("{}.png", sprite.prefix));
+        debug!(?size, parts = sprite.parts.len(), "Packed CSS sprite");
+        Ok(sprite)
+    }
+}
+
+/// Shelf packs parts, tallest first, and returns the sheet size and the rects in the
+/// order of `parts`.
+fn pack(parts: &[(String, &RgbaImage)]) -> ((u32, u32), Vec<SpriteRect>) {
+    let width = parts
+        .iter()
+        .map(|(_, image)| image.width())
+        .max()
+        .unwrap_or(0)
+        .max(MIN_SHEET_WIDTH);
+
+    let mut order: Vec<usize> = (0..parts.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(parts[index].1.height()));
+
+    let mut rects = vec![None; parts.len()];
+    let (mut x, mut y, mut shelf) = (0, 0, 0);
+    for index in order {
+        let (name, image) = &parts[index];
+        if x + image.width() > width {
+            (x, y, shelf) = (0, y + shelf + GAP, 0);
+        }
+        rects[index] = Some(SpriteRect {
+            name: name.clone(),
+            x,
+            y,
+            width: image.width(),
+            height: image.height(),
+        });
+        x += image.width() + GAP;
+        shelf = shelf.max(image.height());
+    }
+    let rects: Vec<SpriteRect> = rects.into_iter().flatten().collect();
+    ((width, y + shelf), rects)
+}
+
+/// Replaces characters not allowed in CSS class names.
+fn class_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect()
+}
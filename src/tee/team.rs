@@ -0,0 +1,27 @@
+//! # Module with team colors
+
+use crate::tee::hsl::{HSL, ddnet_color_to_hsl};
+
+/// Teams of team based modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TeamColor {
+    Red,
+    Blue,
+}
+
+impl TeamColor {
+    /// Returns the packed color the game uses for this team.
+    ///
+    /// Matches `TeamColors` of the DDNet client, applied to the body and the feet.
+    pub const fn ddnet_color(&self) -> u32 {
+        match self {
+            TeamColor::Red => 65461,
+            TeamColor::Blue => 10223541,
+        }
+    }
+
+    /// Returns the tint of this team.
+    pub fn to_hsl(&self) -> HSL {
+        ddnet_color_to_hsl(self.ddnet_color())
+    }
+}
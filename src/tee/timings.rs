@@ -0,0 +1,69 @@
+//! # Module with compose timings
+
+use std::time::Duration;
+
+/// Time spent in each stage of [`Tee::compose_timed`](crate::tee::Tee::compose_timed).
+///
+/// Useful to regression-test the performance of a pipeline configuration without
+/// setting up a tracing subscriber.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComposeTimings {
+    /// Time spent resizing and overlaying all layers
+    pub layers: Duration,
+    /// Time spent encoding the canvas
+    pub encode: Duration,
+    /// Size of the encoded output in bytes
+    pub output_size: usize,
+}
+
+impl ComposeTimings {
+    /// Returns the total time spent composing.
+    pub fn total(&self) -> Duration {
+        self.layers + self.encode
+    }
+}
+
+/// Time spent on a single layer, see [ComposeReport].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayerTiming {
+    /// Name of the layer, see [Layer::name](crate::tee::layer::Layer::name), extra layers
+    /// are named `extra`
+    pub name: &'static str,
+    /// Time spent resizing the part to its size on the skin
+    pub resize: Duration,
+    /// Time spent blending the layer onto the canvas
+    ///
+    /// The parallel backend blends in bands on several threads, its times are summed
+    /// over all bands.
+    pub overlay: Duration,
+}
+
+/// Per-layer breakdown of a render, see
+/// [`Tee::compose_with_report`](crate::tee::Tee::compose_with_report).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComposeReport {
+    /// Every layer in the order it was drawn
+    pub layers: Vec<LayerTiming>,
+    /// Time spent encoding the canvas
+    pub encode: Duration,
+    /// Size of the encoded output in bytes
+    pub output_size: usize,
+}
+
+impl ComposeReport {
+    /// Returns the time spent on all layers and encoding.
+    pub fn total(&self) -> Duration {
+        self.layers
+            .iter()
+            .map(|layer| layer.resize + layer.overlay)
+            .sum::<Duration>()
+            + self.encode
+    }
+
+    /// Returns the layer that took the longest, if any.
+    pub fn slowest_layer(&self) -> Option<&LayerTiming> {
+        self.layers
+            .iter()
+            .max_by_key(|layer| layer.resize + layer.overlay)
+    }
+}
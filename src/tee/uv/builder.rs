@@ -0,0 +1,285 @@
+//! # Module with UV layout builder
+//!
+//! ## Example
+//!
+//! ```rust
+//! use tee_morphosis::tee::uv::{TEE_UV_LAYOUT, builder::UvBuilder};
+//!
+//! let uv = UvBuilder::new()
+//!     .body_at(0, 0)
+//!     .hand_at(192, 0)
+//!     .feet_at(192, 32)
+//!     .eyes_row(64, 96)
+//!     .validate()?;
+//! assert_eq!(uv, TEE_UV_LAYOUT);
+//! # Ok::<(), tee_morphosis::error::TeeError>(())
+//! ```
+//!
+//! Layouts can also be kept in preset files, see [parse_preset]:
+//!
+//! ```text
+//! # 512x256 sheet
+//! container 512 256
+//! body 0 0 192 192
+//! ...
+//! ```
+
+use std::fmt::Write;
+
+use tracing::error;
+
+use crate::{
+    error::{Result, TeeError},
+    tee::uv::{BODY_SIZE, ContentSize, EYE_SIZE, FEET_SIZE, HAND_SIZE, UV, UvPart},
+};
+
+/// Places the parts of a [UV] layout one group at a time.
+///
+/// Parts get the size of the matching `*_SIZE` const, shadows are placed next to
+/// their part the way the default sheet does it. Single parts can still be moved
+/// afterwards, e.g. with [UvBuilder::body_shadow_at].
+#[derive(Debug, Clone, PartialEq)]
+pub struct UvBuilder {
+    container: ContentSize,
+    body: Option<UvPart>,
+    body_shadow: Option<UvPart>,
+    feet: Option<UvPart>,
+    feet_shadow: Option<UvPart>,
+    hand: Option<UvPart>,
+    hand_shadow: Option<UvPart>,
+    eyes: Option<[UvPart; 6]>,
+}
+
+impl Default for UvBuilder {
+    fn default() -> Self {
+        Self {
+            container: (256, 128),
+            body: None,
+            body_shadow: None,
+            feet: None,
+            feet_shadow: None,
+            hand: None,
+            hand_shadow: None,
+            eyes: None,
+        }
+    }
+}
+
+impl UvBuilder {
+    /// Creates a builder for a 256x128 sheet without any parts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the size of the whole sheet.
+    pub fn container(
+        mut self,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        self.container = (width, height);
+        self
+    }
+
+    /// Places the body at `(x, y)` and its shadow right of it.
+    pub fn body_at(
+        mut self,
+        x: u32,
+        y: u32,
+    ) -> Self {
+        let body = UvPart::new(x, y, BODY_SIZE);
+        self.body = Some(body);
+        self.body_shadow = Some(UvPart::new(body.right(), y, BODY_SIZE));
+        self
+    }
+
+    /// Moves the body shadow to `(x, y)`.
+    pub fn body_shadow_at(
+        mut self,
+        x: u32,
+        y: u32,
+    ) -> Self {
+        self.body_shadow = Some(UvPart::new(x, y, BODY_SIZE));
+        self
+    }
+
+    /// Places the feet at `(x, y)` and their shadow below them.
+    pub fn feet_at(
+        mut self,
+        x: u32,
+        y: u32,
+    ) -> Self {
+        let feet = UvPart::new(x, y, FEET_SIZE);
+        self.feet = Some(feet);
+        self.feet_shadow = Some(UvPart::new(x, feet.bottom(), FEET_SIZE));
+        self
+    }
+
+    /// Moves the feet shadow to `(x, y)`.
+    pub fn feet_shadow_at(
+        mut self,
+        x: u32,
+        y: u32,
+    ) -> Self {
+        self.feet_shadow = Some(UvPart::new(x, y, FEET_SIZE));
+        self
+    }
+
+    /// Places the hand at `(x, y)` and its shadow right of it.
+    pub fn hand_at(
+        mut self,
+        x: u32,
+        y: u32,
+    ) -> Self {
+        let hand = UvPart::new(x, y, HAND_SIZE);
+        self.hand = Some(hand);
+        self.hand_shadow = Some(UvPart::new(hand.right(), y, HAND_SIZE));
+        self
+    }
+
+    /// Moves the hand shadow to `(x, y)`.
+    pub fn hand_shadow_at(
+        mut self,
+        x: u32,
+        y: u32,
+    ) -> Self {
+        self.hand_shadow = Some(UvPart::new(x, y, HAND_SIZE));
+        self
+    }
+
+    /// Places the six eyes in a row starting at `(x, y)`.
+    ///
+    /// Ordered as [Normal, Angry, Pain, Happy, Empty, Surprise].
+    pub fn eyes_row(
+        mut self,
+        x: u32,
+        y: u32,
+    ) -> Self {
+        self.eyes = Some(std::array::from_fn(|index| {
+            UvPart::new(x + EYE_SIZE.0 * index as u32, y, EYE_SIZE)
+        }));
+        self
+    }
+
+    /// Checks that every part is placed inside the container without overlapping
+    /// another part, and returns the layout.
+    pub fn validate(self) -> Result<UV> {
+        let uv = UV {
+            body: required(self.body, "body")?,
+            body_shadow: required(self.body_shadow, "body_shadow")?,
+            feet: required(self.feet, "feet")?,
+            feet_shadow: required(self.feet_shadow, "feet_shadow")?,
+            hand: required(self.hand, "hand")?,
+            hand_shadow: required(self.hand_shadow, "hand_shadow")?,
+            eyes: required(self.eyes, "eyes")?,
+            container: self.container,
+        };
+
+        let container = UvPart::new(0, 0, uv.container);
+        let parts = uv.parts();
+        for (index, (name, part)) in parts.iter().enumerate() {
+            if !container.contains(part) {
+                error!(part = name, "UV part is outside the container.");
+                return Err(TeeError::OutOfBounds {
+                    part: *part,
+                    width: uv.container.0,
+                    height: uv.container.1,
+                });
+            }
+            if let Some((other, _)) = parts[index + 1..]
+                .iter()
+                .find(|(_, other)| part.overlaps(other))
+            {
+                error!(first = name, second = other, "UV parts overlap.");
+                return Err(TeeError::OverlappingUvParts {
+                    first: name,
+                    second: other,
+                });
+            }
+        }
+        Ok(uv)
+    }
+}
+
+fn required<T>(
+    part: Option<T>,
+    name: &'static str,
+) -> Result<T> {
+    part.ok_or_else(|| {
+        error!(part = name, "UV part was not placed.");
+        TeeError::MissingUvPart(name)
+    })
+}
+
+/// Parses a UV preset into a validated layout.
+///
+/// Every part is a line with its name from [UV::PART_NAMES] and `x y width height`, the
+/// sheet size a `container width height` line, 256x128 if it is missing. Lines starting
+/// with `#` and empty lines are ignored.
+///
+/// # Errors
+///
+/// Returns [TeeError::MalformedUvPreset] for unknown names and lines without the right
+/// numbers, and the errors of [UvBuilder::validate].
+pub fn parse_preset(preset: &str) -> Result<UV> {
+    let mut builder = UvBuilder::new();
+    let mut eyes = [None; 6];
+    for (number, line) in preset
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+    {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let malformed = |reason| TeeError::MalformedUvPreset {
+            line: number,
+            reason,
+        };
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let numbers = words
+            .map(str::parse)
+            .collect::<std::result::Result<Vec<u32>, _>>()
+            .map_err(|_| malformed("invalid number"))?;
+        if name == "container" {
+            let [width, height] = numbers[..] else {
+                return Err(malformed("expected width and height"));
+            };
+            builder = builder.container(width, height);
+            continue;
+        }
+        let [x, y, w, h] = numbers[..] else {
+            return Err(malformed("expected x, y, width and height"));
+        };
+        let part = Some(UvPart { x, y, w, h });
+        match name {
+            "body" => builder.body = part,
+            "body_shadow" => builder.body_shadow = part,
+            "feet" => builder.feet = part,
+            "feet_shadow" => builder.feet_shadow = part,
+            "hand" => builder.hand = part,
+            "hand_shadow" => builder.hand_shadow = part,
+            _ => match UV::PART_NAMES[6..].iter().position(|eye| *eye == name) {
+                Some(index) => eyes[index] = part,
+                None => return Err(malformed("unknown part")),
+            },
+        }
+    }
+    let mut placed = [UvPart::new(0, 0, EYE_SIZE); 6];
+    for (index, eye) in eyes.into_iter().enumerate() {
+        placed[index] = required(eye, UV::PART_NAMES[6 + index])?;
+    }
+    builder.eyes = Some(placed);
+    builder.validate()
+}
+
+/// Formats `uv` as a preset read by [parse_preset].
+pub fn format_preset(uv: &UV) -> String {
+    let mut preset = format!("container {} {}\n", uv.container.0, uv.container.1);
+    for (name, part) in uv.parts() {
+        // Writing into a String can not fail
+        let _ = writeln!(preset, "{name} {} {} {} {}", part.x, part.y, part.w, part.h);
+    }
+    preset
+}
@@ -0,0 +1,143 @@
+//! # Module with skin variations
+//!
+//! Generates hue shifted, jittered and mirrored copies of a skin, e.g. for "color
+//! family" previews in skin editors or to seed test data.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::tee::variation::VariationParams;
+//!
+//! let family = tee.variations(8, VariationParams::new().with_hue_spread(0.5));
+//! ```
+
+use image::imageops;
+use tracing::{debug, instrument};
+
+use crate::tee::{Tee, hsl::img_hsl_shift, random::SplitMix64};
+
+/// How [Tee::variations] differ from the source skin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VariationParams {
+    /// Part of the hue circle the variants are spread over, `1.0` spreads them around the
+    /// whole circle
+    pub hue_spread: f32,
+    /// Largest random change of saturation
+    pub saturation_jitter: f32,
+    /// Largest random change of lightness
+    pub lightness_jitter: f32,
+    /// Mirror every second variant
+    pub mirror: bool,
+    /// Seed of the jitter, the same seed gives the same variants
+    pub seed: u64,
+}
+
+impl Default for VariationParams {
+    fn default() -> Self {
+        Self {
+            hue_spread: 1.0,
+            saturation_jitter: 0.0,
+            lightness_jitter: 0.0,
+            mirror: false,
+            seed: 0,
+        }
+    }
+}
+
+impl VariationParams {
+    /// Creates params spreading the variants around the whole hue circle, without
+    /// jitter or mirroring.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the part of the hue circle the variants are spread over.
+    pub fn with_hue_spread(
+        mut self,
+        spread: f32,
+    ) -> Self {
+        self.hue_spread = spread;
+        self
+    }
+
+    /// Sets the largest random changes of saturation and lightness.
+    pub fn with_jitter(
+        mut self,
+        saturation: f32,
+        lightness: f32,
+    ) -> Self {
+        self.saturation_jitter = saturation;
+        self.lightness_jitter = lightness;
+        self
+    }
+
+    /// Mirrors every second variant.
+    pub fn with_mirror(
+        mut self,
+        mirror: bool,
+    ) -> Self {
+        self.mirror = mirror;
+        self
+    }
+
+    /// Sets the seed of the jitter.
+    pub fn with_seed(
+        mut self,
+        seed: u64,
+    ) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+impl Tee {
+    /// Returns `n` variants of this Tee.
+    ///
+    /// Variant `i` has its body, feet and hands hue shifted by `hue_spread * i / n`, so
+    /// the first one keeps the original hue unless jitter is set. Eyes keep their colors.
+    #[instrument(level = "debug", skip(self))]
+    pub fn variations(
+        &self,
+        n: usize,
+        params: VariationParams,
+    ) -> Vec<Tee> {
+        let mut rng = SplitMix64(params.seed);
+        let variants: Vec<Tee> = (0..n)
+            .map(|index| {
+                let mut jitter = |max: f32| (rng.unit() * 2.0 - 1.0) * max;
+                let shift = (
+                    params.hue_spread * index as f32 / n as f32,
+                    jitter(params.saturation_jitter),
+                    jitter(params.lightness_jitter),
+                );
+
+                let mut tee = self.clone();
+                for part in [&mut tee.body, &mut tee.feet, &mut tee.hand] {
+                    img_hsl_shift(&mut part.value, shift);
+                    img_hsl_shift(&mut part.shadow, shift);
+                }
+                if params.mirror && index % 2 == 1 {
+                    tee.mirror();
+                }
+                tee
+            })
+            .collect();
+        debug!(variants = variants.len(), "Generated variations");
+        variants
+    }
+
+    /// Flips every part and eye horizontally.
+    fn mirror(&mut self) {
+        for part in [&mut self.body, &mut self.feet, &mut self.hand] {
+            imageops::flip_horizontal_in_place(&mut part.value);
+            imageops::flip_horizontal_in_place(&mut part.shadow);
+        }
+        for eye in &mut self.eye {
+            imageops::flip_horizontal_in_place(eye.image_mut());
+        }
+        imageops::flip_horizontal_in_place(&mut self.blink);
+        for eye in self.custom_eyes.values_mut() {
+            imageops::flip_horizontal_in_place(eye);
+        }
+    }
+}
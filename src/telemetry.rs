@@ -0,0 +1,95 @@
+//! # Telemetry module
+//!
+//! The crate emits its spans and events at `debug` level and below, so it stays quiet
+//! under the usual `info` filter of high-throughput services. Enable
+//! `tee_morphosis=debug` (or `trace`) in your subscriber filter to see them.
+//!
+//! URLs recorded in spans may carry tokens or private hosts. They can be redacted
+//! crate-wide with [`configure`], which also covers the URLs in the messages of
+//! [`TeeError`](crate::error::TeeError):
+//!
+//! ```rust
+//! use tee_morphosis::telemetry::{self, TelemetryConfig};
+//!
+//! telemetry::configure(TelemetryConfig { redact_urls: true });
+//! assert_eq!(
+//!     telemetry::url("https://cdn.example/skins/a.png?token=secret").to_string(),
+//!     "https://cdn.example/<redacted>"
+//! );
+//! ```
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+static REDACT_URLS: AtomicBool = AtomicBool::new(false);
+
+/// Crate-wide telemetry settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TelemetryConfig {
+    /// Record only the scheme and host of URLs in spans and events
+    pub redact_urls: bool,
+}
+
+/// Applies telemetry settings to the whole crate.
+pub fn configure(config: TelemetryConfig) {
+    REDACT_URLS.store(config.redact_urls, Ordering::Relaxed);
+}
+
+/// Returns the current telemetry settings.
+pub fn config() -> TelemetryConfig {
+    TelemetryConfig {
+        redact_urls: REDACT_URLS.load(Ordering::Relaxed),
+    }
+}
+
+/// Wraps a URL for recording, honoring [`TelemetryConfig::redact_urls`].
+pub fn url(url: &str) -> TelemetryUrl<'_> {
+    TelemetryUrl(url)
+}
+
+/// A URL as it should appear in spans and events, see [`url`].
+///
+/// `Debug` quotes the same text as `Display`, so both redact.
+#[derive(Clone, Copy)]
+pub struct TelemetryUrl<'a>(&'a str);
+
+impl fmt::Debug for TelemetryUrl<'_> {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), f)
+    }
+}
+
+impl fmt::Display for TelemetryUrl<'_> {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        if !REDACT_URLS.load(Ordering::Relaxed) {
+            return f.write_str(self.0);
+        }
+        match self.0.split_once("://") {
+            Some((scheme, rest)) => {
+                let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+                // Drop credentials from `user:password@host`
+                let host = authority.rsplit('@').next().unwrap_or_default();
+                write!(f, "{scheme}://{host}/<redacted>")
+            }
+            None => f.write_str("<redacted>"),
+        }
+    }
+}
+
+/// Formats a request error, redacting the URL it names.
+#[cfg(feature = "net")]
+pub(crate) fn request_error(error: &reqwest::Error) -> String {
+    let message = error.to_string();
+    match error.url() {
+        Some(failed) => message.replace(failed.as_str(), &url(failed.as_str()).to_string()),
+        None => message,
+    }
+}
@@ -0,0 +1,226 @@
+//! # Testing module
+//!
+//! Invariant checks and [proptest] strategies for crates fuzzing their own layouts. The
+//! checks panic with a message naming the offending part, so they drop straight into
+//! tests and property tests.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::testing::{assert_uv_roundtrip, proptest::prelude::*, strategy};
+//!
+//! proptest! {
+//!     #[test]
+//!     fn custom_layouts_roundtrip(uv in strategy::uv()) {
+//!         let tee = Tee::new(skin_data(), ImageFormat::Png).unwrap();
+//!         assert_uv_roundtrip(&tee, uv);
+//!     }
+//! }
+//! ```
+
+use image::{ImageFormat, RgbaImage, imageops};
+pub use proptest;
+
+use crate::tee::{
+    Tee,
+    imaging::{Backend, Imaging},
+    parts::EyeType,
+    skin::Skin,
+    uv::UV,
+};
+
+/// Returns the twelve sprites of `tee` in the order of [UV::PART_NAMES].
+fn parts(tee: &Tee) -> [&RgbaImage; 12] {
+    [
+        &tee.body.value,
+        &tee.body.shadow,
+        &tee.feet.value,
+        &tee.feet.shadow,
+        &tee.hand.value,
+        &tee.hand.shadow,
+        tee.eye[0].image(),
+        tee.eye[1].image(),
+        tee.eye[2].image(),
+        tee.eye[3].image(),
+        tee.eye[4].image(),
+        tee.eye[5].image(),
+    ]
+}
+
+/// Draws the sprites of `tee` onto an empty sheet laid out as `uv`.
+///
+/// # Panics
+///
+/// Panics if a part of `uv` has another size than the sprite drawn into it.
+pub fn layout_sheet(
+    tee: &Tee,
+    uv: &UV,
+) -> RgbaImage {
+    let mut sheet = RgbaImage::new(uv.container.0, uv.container.1);
+    for ((name, part), sprite) in uv.parts().into_iter().zip(parts(tee)) {
+        assert_eq!(
+            part.size(),
+            sprite.dimensions(),
+            "part {name} of the UV does not fit its sprite"
+        );
+        imageops::replace(&mut sheet, sprite, part.x as i64, part.y as i64);
+    }
+    sheet
+}
+
+/// Asserts that laying `tee` out as `uv` and parsing the sheet back yields the same
+/// sprites.
+///
+/// # Panics
+///
+/// Panics naming the first part that changed, or if the sheet does not parse.
+pub fn assert_uv_roundtrip(
+    tee: &Tee,
+    uv: UV,
+) {
+    let sheet = layout_sheet(tee, &uv);
+    let data = Backend::encode(&sheet, ImageFormat::Png).expect("sheet encodes as PNG");
+    let parsed = Tee::new_with_uv(data, uv, ImageFormat::Png)
+        .unwrap_or_else(|e| panic!("sheet laid out as {uv:?} does not parse: {e}"));
+    for (name, (before, after)) in UV::PART_NAMES
+        .iter()
+        .zip(parts(tee).into_iter().zip(parts(&parsed)))
+    {
+        assert!(before == after, "part {name} changed in the roundtrip");
+    }
+}
+
+/// Asserts that composing `tee` on `skin` fills exactly [Skin::output_size] and that
+/// every part lies on the canvas.
+///
+/// # Panics
+///
+/// Panics naming the first part that reaches past the canvas.
+pub fn assert_compose_within_bounds(
+    tee: &Tee,
+    skin: Skin,
+) {
+    let (width, height) = skin.output_size();
+    let image = tee.compose_image(skin, EyeType::Normal);
+    assert_eq!(image.dimensions(), (width, height), "composed image size");
+
+    let uv = &tee.used_uv;
+    let eye = uv.eyes[0].size();
+    let placements = [
+        ("body", skin.body, uv.body.size()),
+        ("feet", skin.feet, uv.feet.size()),
+        ("feet_back", skin.feet_back, uv.feet.size()),
+        ("first_eyes", skin.first_eyes, eye),
+        ("second_eyes", skin.second_eyes, eye),
+    ];
+    for (name, placement, size) in placements {
+        let ((x, y), (w, h)) = skin.place(placement, size);
+        assert!(
+            x >= 0 && y >= 0 && x + w as i64 <= width as i64 && y + h as i64 <= height as i64,
+            "part {name} at {:?} with size {:?} reaches past the {width}x{height} canvas",
+            (x, y),
+            (w, h)
+        );
+    }
+}
+
+/// Strategies generating valid layouts.
+pub mod strategy {
+    use proptest::prelude::*;
+
+    use crate::tee::{
+        skin::{Skin, SkinPS, TEE_SKIN_LAYOUT},
+        uv::{BODY_SIZE, EYE_SIZE, FEET_SIZE, HAND_SIZE, UV, UvPart},
+    };
+
+    /// UV layouts with the parts of the default sheet at random, non overlapping
+    /// positions.
+    ///
+    /// The body, feet, hand and eye groups are stacked in a random order with random
+    /// indents and gaps, so every generated layout passes
+    /// [UvBuilder::validate](crate::tee::uv::builder::UvBuilder::validate).
+    pub fn uv() -> impl Strategy<Value = UV> {
+        (
+            Just([0usize, 1, 2, 3]).prop_shuffle(),
+            prop::array::uniform4((0u32..64, 0u32..16)),
+            0u32..64,
+            any::<bool>(),
+        )
+            .prop_map(|(order, spacing, margin, shadows_below)| {
+                let mut groups: [Vec<UvPart>; 4] = Default::default();
+                let mut top = 0;
+                let mut right = 0;
+                for (group, (indent, gap)) in order.into_iter().zip(spacing) {
+                    let parts = group_parts(group, indent, top + gap, shadows_below);
+                    top = parts.iter().map(UvPart::bottom).max().unwrap_or(top);
+                    right = parts
+                        .iter()
+                        .map(UvPart::right)
+                        .max()
+                        .unwrap_or(right)
+                        .max(right);
+                    groups[group] = parts;
+                }
+                let [body, feet, hand, eyes] = groups;
+                UV {
+                    body: body[0],
+                    body_shadow: body[1],
+                    feet: feet[0],
+                    feet_shadow: feet[1],
+                    hand: hand[0],
+                    hand_shadow: hand[1],
+                    eyes: std::array::from_fn(|index| eyes[index]),
+                    container: (right + margin, top + margin),
+                }
+            })
+    }
+
+    /// Places group `group` (body, feet, hand, eyes) with its top left corner at
+    /// `(x, y)`.
+    fn group_parts(
+        group: usize,
+        x: u32,
+        y: u32,
+        shadows_below: bool,
+    ) -> Vec<UvPart> {
+        let pair = |size: (u32, u32)| {
+            let part = UvPart::new(x, y, size);
+            let shadow = if shadows_below {
+                UvPart::new(x, part.bottom(), size)
+            } else {
+                UvPart::new(part.right(), y, size)
+            };
+            vec![part, shadow]
+        };
+        match group {
+            0 => pair(BODY_SIZE),
+            1 => pair(FEET_SIZE),
+            2 => pair(HAND_SIZE),
+            _ => (0..6)
+                .map(|index| UvPart::new(x + EYE_SIZE.0 * index, y, EYE_SIZE))
+                .collect(),
+        }
+    }
+
+    /// Skin layouts at scales from `0.5` to `4.0` with the parts of [TEE_SKIN_LAYOUT]
+    /// moved by up to 8 units, on a container large enough for them.
+    pub fn skin() -> impl Strategy<Value = Skin> {
+        let offset = || (-8f32..=8., -8f32..=8.);
+        (prop::array::uniform5(offset()), 0.5f32..=4.).prop_map(|(offsets, scale)| {
+            let base = TEE_SKIN_LAYOUT;
+            // Moved away from the top left edge by 8, so negative offsets stay inside
+            let shift = |((x, y), part_scale): SkinPS, (dx, dy): (f32, f32)| {
+                ((x + dx + 8., y + dy + 8.), part_scale)
+            };
+            Skin {
+                body: shift(base.body, offsets[0]),
+                feet: shift(base.feet, offsets[1]),
+                feet_back: shift(base.feet_back, offsets[2]),
+                first_eyes: shift(base.first_eyes, offsets[3]),
+                second_eyes: shift(base.second_eyes, offsets[4]),
+                container: (base.container.0 + 16, base.container.1 + 16),
+                scale,
+            }
+        })
+    }
+}
@@ -0,0 +1,469 @@
+//! # Watch module
+//!
+//! Polls an asset directory and reports files that were added, changed or removed, so
+//! long running services can reload their assets without a restart. Assets are sorted
+//! by the top level directory they are in:
+//!
+//! | Directory | Kind |
+//! |---|---|
+//! | `uv/` | [AssetKind::UvPreset] |
+//! | `skins/` | [AssetKind::Skin] |
+//! | `fonts/` | [AssetKind::Font] |
+//! | `templates/` | [AssetKind::Template] |
+//!
+//! Other files are ignored. Polling only relies on file sizes and modification times, so
+//! it works on every platform and on network mounts, but a rewrite keeping the size within
+//! the timestamp resolution of the file system goes unnoticed.
+//!
+//! [`AssetStore`] keeps the parsed assets and swaps each one in as its file changes.
+//! UV presets are read with [parse_preset], fonts need the `text` feature and card
+//! templates, read with [`CardLayout::from_json`](crate::cards::layout::CardLayout::from_json),
+//! the `templates` feature.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::watch::{AssetStore, AssetWatcher};
+//!
+//! let watcher = AssetWatcher::new("assets")?;
+//! let store = AssetStore::new();
+//! store.apply(&watcher.snapshot());
+//! let handle = watcher.spawn(Duration::from_secs(2), {
+//!     let store = store.clone();
+//!     move |changes| store.apply(changes)
+//! });
+//! let tee = store.skin("santa_limekitty");
+//! ```
+//!
+//! Changes can also be handled directly:
+//!
+//! ```rust,ignore
+//! use tee_morphosis::watch::{AssetKind, AssetWatcher};
+//!
+//! let skins = Arc::new(RwLock::new(HashMap::new()));
+//! let handle = AssetWatcher::new("assets")?.spawn(Duration::from_secs(2), {
+//!     let skins = skins.clone();
+//!     move |changes| {
+//!         for change in changes.iter().filter(|change| change.kind == AssetKind::Skin) {
+//!             let mut skins = skins.write().unwrap();
+//!             match change.data.clone().map(|data| Tee::new(data, ImageFormat::Png)) {
+//!                 Some(Ok(tee)) => skins.insert(change.name(), tee),
+//!                 Some(Err(_)) | None => skins.remove(&change.name()),
+//!             };
+//!         }
+//!     }
+//! });
+//! ```
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, RwLock, RwLockReadGuard,
+        mpsc::{self, RecvTimeoutError, Sender},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+use bytes::Bytes;
+use tracing::{debug, instrument, warn};
+
+#[cfg(feature = "templates")]
+use crate::cards::layout::CardLayout;
+#[cfg(feature = "text")]
+use crate::scene::text::FontArc;
+use crate::{
+    error::{Result, TeeError},
+    tee::{
+        Tee,
+        uv::{UV, builder::parse_preset},
+    },
+};
+
+/// What an asset file holds, by the directory it is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    /// A UV layout of skins
+    UvPreset,
+    /// A skin of a skin pack
+    Skin,
+    /// A font
+    Font,
+    /// A card template
+    Template,
+}
+
+impl AssetKind {
+    /// Every kind of asset.
+    pub const ALL: [AssetKind; 4] = [
+        AssetKind::UvPreset,
+        AssetKind::Skin,
+        AssetKind::Font,
+        AssetKind::Template,
+    ];
+
+    /// Returns the top level directory of the kind, e.g. `"skins"`.
+    pub const fn directory(&self) -> &'static str {
+        match self {
+            AssetKind::UvPreset => "uv",
+            AssetKind::Skin => "skins",
+            AssetKind::Font => "fonts",
+            AssetKind::Template => "templates",
+        }
+    }
+}
+
+/// How an asset file changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// The file is new
+    Added,
+    /// The size or modification time of the file changed
+    Modified,
+    /// The file is gone
+    Removed,
+}
+
+/// A changed asset file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetChange {
+    /// Path of the file
+    pub path: PathBuf,
+    /// What the file holds
+    pub kind: AssetKind,
+    /// How the file changed
+    pub change: ChangeKind,
+    /// Content of added and modified files, `None` for removed ones
+    pub data: Option<Bytes>,
+}
+
+impl AssetChange {
+    /// Returns the file name without extension, e.g. `"santa_limekitty"` for a skin.
+    pub fn name(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// Size and modification time of a file, compared between polls.
+type Stamp = (u64, Option<SystemTime>);
+
+/// Polls an asset directory for changes, see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct AssetWatcher {
+    root: PathBuf,
+    known: HashMap<PathBuf, (AssetKind, Stamp)>,
+}
+
+impl AssetWatcher {
+    /// Watches `root`, the files present now are known and not reported.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TeeError::Watch] if `root` is not a readable directory.
+    #[instrument(level = "debug", skip(root), fields(root = %root.as_ref().display()))]
+    pub fn new(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        fs::read_dir(&root).map_err(TeeError::Watch)?;
+        let known = scan(&root);
+        debug!(files = known.len(), "Watching assets");
+        Ok(Self {
+            root,
+            known,
+        })
+    }
+
+    /// Returns the watched directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns every known file as added, to load the assets present before the first
+    /// poll, e.g. with [AssetStore::apply].
+    ///
+    /// Files that can not be read are skipped.
+    pub fn snapshot(&self) -> Vec<AssetChange> {
+        self.known
+            .iter()
+            .filter_map(|(path, &(kind, _))| match fs::read(path) {
+                Ok(data) => Some(AssetChange {
+                    path: path.clone(),
+                    kind,
+                    change: ChangeKind::Added,
+                    data: Some(Bytes::from(data)),
+                }),
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "Failed to read asset.");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Scans the directory once and returns what changed since the last scan.
+    ///
+    /// Files that can not be read are reported on the next poll they can be read in.
+    #[instrument(level = "trace", skip(self))]
+    pub fn poll(&mut self) -> Vec<AssetChange> {
+        let current = scan(&self.root);
+        let mut changes = Vec::new();
+
+        for (path, &(kind, stamp)) in &current {
+            let change = match self.known.get(path) {
+                None => ChangeKind::Added,
+                Some(&(_, known)) if known != stamp => ChangeKind::Modified,
+                Some(_) => continue,
+            };
+            match fs::read(path) {
+                Ok(data) => changes.push(AssetChange {
+                    path: path.clone(),
+                    kind,
+                    change,
+                    data: Some(Bytes::from(data)),
+                }),
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "Failed to read changed asset.");
+                    continue;
+                }
+            }
+            self.known.insert(path.clone(), (kind, stamp));
+        }
+
+        let removed: Vec<PathBuf> = self
+            .known
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .cloned()
+            .collect();
+        for path in removed {
+            if let Some((kind, _)) = self.known.remove(&path) {
+                changes.push(AssetChange {
+                    path,
+                    kind,
+                    change: ChangeKind::Removed,
+                    data: None,
+                });
+            }
+        }
+
+        if !changes.is_empty() {
+            debug!(changes = changes.len(), "Assets changed");
+        }
+        changes
+    }
+
+    /// Polls every `interval` on a background thread, calling `on_change` with every
+    /// non empty set of changes.
+    ///
+    /// The thread stops when the returned handle is dropped.
+    pub fn spawn<F>(
+        mut self,
+        interval: Duration,
+        mut on_change: F,
+    ) -> WatchHandle
+    where
+        F: FnMut(&[AssetChange]) + Send + 'static,
+    {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::Builder::new()
+            .name("tee-asset-watcher".to_string())
+            .spawn(move || {
+                // Sleeps until the interval passed or the handle is dropped
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let changes = self.poll();
+                    if !changes.is_empty() {
+                        on_change(&changes);
+                    }
+                }
+            })
+            .expect("failed to spawn the asset watcher thread");
+        WatchHandle {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Keeps the thread of [AssetWatcher::spawn] running, dropping it stops the thread.
+#[derive(Debug)]
+pub struct WatchHandle {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(Err(_)) = self.thread.take().map(JoinHandle::join) {
+            warn!("Asset watcher thread panicked");
+        }
+    }
+}
+
+/// Parsed assets by name, shared between the clones of a store.
+type Shelf<T> = Arc<RwLock<HashMap<String, T>>>;
+
+/// Parsed assets of a watched directory, see the [module docs](self).
+///
+/// Assets are stored by file name without extension, see [AssetChange::name]. Each one
+/// is replaced as a whole, readers keep the value they got until they ask again. Clones
+/// share the assets.
+#[derive(Debug, Clone, Default)]
+pub struct AssetStore {
+    uv_presets: Shelf<UV>,
+    skins: Shelf<Arc<Tee>>,
+    #[cfg(feature = "text")]
+    fonts: Shelf<FontArc>,
+    #[cfg(feature = "templates")]
+    templates: Shelf<Arc<CardLayout>>,
+}
+
+impl AssetStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses added and modified assets and swaps them in, and drops removed ones.
+    ///
+    /// An asset that fails to parse keeps its previous value and the error is logged.
+    /// Fonts are applied before templates, which are parsed with the fonts stored at
+    /// that time. Assets of kinds whose feature is disabled are ignored.
+    #[instrument(level = "debug", skip_all, fields(changes = changes.len()))]
+    pub fn apply(
+        &self,
+        changes: &[AssetChange],
+    ) {
+        let mut changes: Vec<&AssetChange> = changes.iter().collect();
+        changes.sort_by_key(|change| AssetKind::ALL.iter().position(|kind| *kind == change.kind));
+        for change in changes {
+            match change.kind {
+                AssetKind::UvPreset => swap(&self.uv_presets, change, |data| {
+                    parse_preset(&String::from_utf8_lossy(data))
+                }),
+                AssetKind::Skin => swap(&self.skins, change, |data| {
+                    let format = image::guess_format(data)?;
+                    Tee::new(data.clone(), format).map(Arc::new)
+                }),
+                #[cfg(feature = "text")]
+                AssetKind::Font => swap(&self.fonts, change, |data| {
+                    FontArc::try_from_vec(data.to_vec()).map_err(|_| TeeError::InvalidFont)
+                }),
+                #[cfg(feature = "templates")]
+                AssetKind::Template => {
+                    let fonts = self.fonts();
+                    swap(&self.templates, change, |data| {
+                        CardLayout::from_json(&String::from_utf8_lossy(data), &fonts).map(Arc::new)
+                    })
+                }
+                #[cfg(not(feature = "text"))]
+                AssetKind::Font => {}
+                #[cfg(not(feature = "templates"))]
+                AssetKind::Template => {}
+            }
+        }
+    }
+
+    /// Returns the UV preset named `name`.
+    pub fn uv_preset(
+        &self,
+        name: &str,
+    ) -> Option<UV> {
+        read(&self.uv_presets).get(name).copied()
+    }
+
+    /// Returns the skin named `name`.
+    pub fn skin(
+        &self,
+        name: &str,
+    ) -> Option<Arc<Tee>> {
+        read(&self.skins).get(name).cloned()
+    }
+
+    /// Returns the font named `name`.
+    #[cfg(feature = "text")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+    pub fn font(
+        &self,
+        name: &str,
+    ) -> Option<FontArc> {
+        read(&self.fonts).get(name).cloned()
+    }
+
+    /// Returns every font by name, e.g. for [CardLayout::from_json].
+    #[cfg(feature = "text")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+    pub fn fonts(&self) -> HashMap<String, FontArc> {
+        read(&self.fonts).clone()
+    }
+
+    /// Returns the card template named `name`.
+    #[cfg(feature = "templates")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "templates")))]
+    pub fn template(
+        &self,
+        name: &str,
+    ) -> Option<Arc<CardLayout>> {
+        read(&self.templates).get(name).cloned()
+    }
+}
+
+/// Locks `shelf` for reading.
+fn read<T>(shelf: &Shelf<T>) -> RwLockReadGuard<'_, HashMap<String, T>> {
+    shelf.read().expect("asset store poisoned")
+}
+
+/// Parses the content of `change` into `shelf`, keeping the stored value on errors.
+fn swap<T>(
+    shelf: &Shelf<T>,
+    change: &AssetChange,
+    parse: impl FnOnce(&Bytes) -> Result<T>,
+) {
+    let name = change.name();
+    let Some(data) = &change.data else {
+        shelf.write().expect("asset store poisoned").remove(&name);
+        debug!(name, kind = ?change.kind, "Dropped removed asset");
+        return;
+    };
+    match parse(data) {
+        Ok(value) => {
+            shelf
+                .write()
+                .expect("asset store poisoned")
+                .insert(name.clone(), value);
+            debug!(name, kind = ?change.kind, "Reloaded asset");
+        }
+        Err(e) => {
+            warn!(path = %change.path.display(), error = %e, "Failed to parse asset, keeping the previous one.");
+        }
+    }
+}
+
+/// Lists the files of every asset directory below `root` with their stamps.
+fn scan(root: &Path) -> HashMap<PathBuf, (AssetKind, Stamp)> {
+    let mut files = HashMap::new();
+    for kind in AssetKind::ALL {
+        let mut pending = vec![root.join(kind.directory())];
+        while let Some(directory) = pending.pop() {
+            let Ok(entries) = fs::read_dir(&directory) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    pending.push(entry.path());
+                } else if metadata.is_file() {
+                    let stamp = (metadata.len(), metadata.modified().ok());
+                    files.insert(entry.path(), (kind, stamp));
+                }
+            }
+        }
+    }
+    files
+}
@@ -0,0 +1,179 @@
+//! # Watermark module
+//!
+//! Brands composed previews with an image or a line of text, see
+//! [`ComposeOptions::watermark`](crate::tee::options::ComposeOptions::watermark).
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::{
+//!     tee::options::ComposeOptions,
+//!     watermark::{Anchor, Watermark},
+//! };
+//!
+//! let logo = image::open("logo.png")?.to_rgba8();
+//! let options = ComposeOptions::new().with_watermark(
+//!     Watermark::image(logo).with_anchor(Anchor::BottomRight).with_opacity(0.5),
+//! );
+//! let bytes = tee.compose_with_options(TEE_SKIN_LAYOUT, EyeType::Normal, ImageFormat::Png, &options)?;
+//! ```
+
+use image::{RgbaImage, imageops};
+
+use crate::etag::ETagHasher;
+
+#[cfg(feature = "text")]
+use crate::scene::text::{TextStyle, render_text};
+
+/// What a [Watermark] draws.
+#[derive(Debug, Clone)]
+pub enum WatermarkContent {
+    /// An image, drawn at its own size
+    Image(RgbaImage),
+    /// A single line of text
+    #[cfg(feature = "text")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+    Text { text: String, style: TextStyle },
+}
+
+/// Corner or center of the canvas a [Watermark] is placed at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    Center,
+}
+
+/// A mark drawn over the composed image.
+#[derive(Debug, Clone)]
+pub struct Watermark {
+    /// What to draw
+    pub content: WatermarkContent,
+    /// Where to draw it
+    pub anchor: Anchor,
+    /// Distance to the edges of the canvas in pixels, ignored for [Anchor::Center]
+    pub margin: u32,
+    /// Opacity from `0.0` (invisible) to `1.0`
+    pub opacity: f32,
+}
+
+impl Watermark {
+    /// Creates an opaque image watermark in the bottom right corner.
+    pub fn image(image: RgbaImage) -> Self {
+        Self::new(WatermarkContent::Image(image))
+    }
+
+    /// Creates an opaque text watermark in the bottom right corner.
+    #[cfg(feature = "text")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+    pub fn text(
+        text: impl Into<String>,
+        style: TextStyle,
+    ) -> Self {
+        Self::new(WatermarkContent::Text {
+            text: text.into(),
+            style,
+        })
+    }
+
+    fn new(content: WatermarkContent) -> Self {
+        Self {
+            content,
+            anchor: Anchor::default(),
+            margin: 2,
+            opacity: 1.0,
+        }
+    }
+
+    /// Sets where the watermark is placed.
+    pub fn with_anchor(
+        mut self,
+        anchor: Anchor,
+    ) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Sets the distance to the edges of the canvas.
+    pub fn with_margin(
+        mut self,
+        margin: u32,
+    ) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Sets the opacity, clamped to `0.0..=1.0`.
+    pub fn with_opacity(
+        mut self,
+        opacity: f32,
+    ) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Feeds everything that changes the drawn mark into an entity tag.
+    pub(crate) fn hash_into(
+        &self,
+        hasher: &mut ETagHasher,
+    ) {
+        match &self.content {
+            WatermarkContent::Image(image) => {
+                hasher.field(b"image").image(image);
+            }
+            #[cfg(feature = "text")]
+            WatermarkContent::Text {
+                text,
+                style,
+            } => {
+                use ab_glyph::Font;
+
+                hasher
+                    .field(b"text")
+                    .field(text.as_bytes())
+                    .field(style.font.font_data())
+                    .field(&style.size.to_le_bytes())
+                    .field(&style.color.0);
+            }
+        }
+        hasher
+            .field(format!("{:?}", self.anchor).as_bytes())
+            .field(&self.margin.to_le_bytes())
+            .field(&self.opacity.to_le_bytes());
+    }
+
+    /// Draws the watermark onto a canvas.
+    pub fn apply(
+        &self,
+        canvas: &mut RgbaImage,
+    ) {
+        let mut mark = match &self.content {
+            WatermarkContent::Image(image) => image.clone(),
+            #[cfg(feature = "text")]
+            WatermarkContent::Text {
+                text,
+                style,
+            } => render_text(text, style),
+        };
+        if self.opacity < 1.0 {
+            for pixel in mark.pixels_mut() {
+                pixel.0[3] = (pixel.0[3] as f32 * self.opacity).round() as u8;
+            }
+        }
+
+        let (canvas_w, canvas_h) = (canvas.width() as i64, canvas.height() as i64);
+        let (mark_w, mark_h) = (mark.width() as i64, mark.height() as i64);
+        let margin = self.margin as i64;
+        let (x, y) = match self.anchor {
+            Anchor::TopLeft => (margin, margin),
+            Anchor::TopRight => (canvas_w - mark_w - margin, margin),
+            Anchor::BottomLeft => (margin, canvas_h - mark_h - margin),
+            Anchor::BottomRight => (canvas_w - mark_w - margin, canvas_h - mark_h - margin),
+            Anchor::Center => ((canvas_w - mark_w) / 2, (canvas_h - mark_h) / 2),
+        };
+        imageops::overlay(canvas, &mark, x, y);
+    }
+}
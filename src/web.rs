@@ -0,0 +1,245 @@
+//! # Web module
+//!
+//! Reads skin uploads of `multipart/form-data` requests, independent of the web
+//! framework: pass the `Content-Type` header and the body, get a parsed [Tee]. The
+//! body size is checked before parsing and the image is decoded with
+//! [DecodeLimits::UNTRUSTED] by default.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::web::SkinUpload;
+//!
+//! async fn upload(headers: HeaderMap, body: Bytes) -> Result<Vec<u8>, AppError> {
+//!     let content_type = headers[CONTENT_TYPE].to_str()?;
+//!     let tee = SkinUpload::new().read(content_type, body)?;
+//!     Ok(tee.compose_png(TEE_SKIN_LAYOUT, EyeType::Happy)?.to_vec())
+//! }
+//! ```
+
+use std::borrow::Cow;
+
+use bytes::Bytes;
+use image::ImageFormat;
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    error::{Result, TeeError},
+    tee::{Tee, limits::DecodeLimits, options::ParseOptions, uv::TEE_UV_LAYOUT},
+};
+
+/// A field of a `multipart/form-data` body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormField {
+    /// Name of the field
+    pub name: String,
+    /// Name of the uploaded file, `None` for plain fields
+    pub filename: Option<String>,
+    /// Content type of the field, lowercase
+    pub content_type: Option<String>,
+    /// Content of the field
+    pub data: Bytes,
+}
+
+/// Splits a `multipart/form-data` body into its fields, `content_type` is the value of
+/// the `Content-Type` header holding the boundary.
+///
+/// # Errors
+///
+/// Returns [TeeError::MalformedUpload] if the header is not multipart or the body does
+/// not follow its boundary.
+#[instrument(level = "debug", skip(body), fields(body_size = body.len()))]
+pub fn parse_multipart(
+    content_type: &str,
+    body: Bytes,
+) -> Result<Vec<FormField>> {
+    let boundary = boundary(content_type)?;
+    let delimiter = format!("--{boundary}").into_bytes();
+    let mut close = b"\r\n".to_vec();
+    close.extend_from_slice(&delimiter);
+
+    let mut position = find(&body, &delimiter, 0)
+        .ok_or(TeeError::MalformedUpload("missing boundary"))?
+        + delimiter.len();
+    let mut fields = Vec::new();
+    loop {
+        let rest = &body[position..];
+        if rest.starts_with(b"--") {
+            break;
+        }
+        if !rest.starts_with(b"\r\n") {
+            return Err(TeeError::MalformedUpload(
+                "missing line break after boundary",
+            ));
+        }
+        let headers_start = position + 2;
+        let headers_end = find(&body, b"\r\n\r\n", headers_start)
+            .ok_or(TeeError::MalformedUpload("unterminated field headers"))?;
+        let data_start = headers_end + 4;
+        let data_end = find(&body, &close, data_start)
+            .ok_or(TeeError::MalformedUpload("unterminated field"))?;
+        let headers = std::str::from_utf8(&body[headers_start..headers_end])
+            .map_err(|_| TeeError::MalformedUpload("field headers are not UTF-8"))?;
+        fields.push(field(headers, body.slice(data_start..data_end))?);
+        position = data_end + close.len();
+    }
+    debug!(fields = fields.len(), "Parsed multipart body");
+    Ok(fields)
+}
+
+/// Reads a skin uploaded as a field of a `multipart/form-data` request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkinUpload {
+    /// Name of the field holding the skin
+    pub field: Cow<'static, str>,
+    /// Largest accepted body in bytes
+    pub max_body_size: usize,
+    /// Limits enforced while decoding the skin
+    pub limits: DecodeLimits,
+}
+
+impl Default for SkinUpload {
+    fn default() -> Self {
+        Self {
+            field: Cow::Borrowed("skin"),
+            max_body_size: 4 * 1024 * 1024,
+            limits: DecodeLimits::UNTRUSTED,
+        }
+    }
+}
+
+impl SkinUpload {
+    /// Reads the field `skin` of bodies up to 4 MiB with [DecodeLimits::UNTRUSTED].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name of the field holding the skin.
+    pub fn with_field(
+        mut self,
+        field: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        self.field = field.into();
+        self
+    }
+
+    /// Sets the largest accepted body in bytes.
+    pub fn with_max_body_size(
+        mut self,
+        max_body_size: usize,
+    ) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Sets the limits enforced while decoding the skin.
+    pub fn with_limits(
+        mut self,
+        limits: DecodeLimits,
+    ) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Parses the skin of a request with the `Content-Type` header `content_type`.
+    ///
+    /// The format is taken from the content type of the field, or guessed from the
+    /// content if it is not an image type.
+    ///
+    /// # Errors
+    ///
+    /// Returns [TeeError::UploadTooLarge] for bodies above the limit,
+    /// [TeeError::MissingUploadField] if the body has no such field, and the errors of
+    /// [parse_multipart] and [Tee::new_with_options].
+    #[instrument(level = "debug", skip(self, body), fields(field = %self.field, body_size = body.len()))]
+    pub fn read(
+        &self,
+        content_type: &str,
+        body: Bytes,
+    ) -> Result<Tee> {
+        if body.len() > self.max_body_size {
+            warn!(max = self.max_body_size, "Upload is too large");
+            return Err(TeeError::UploadTooLarge {
+                size: body.len(),
+                max: self.max_body_size,
+            });
+        }
+        let field = parse_multipart(content_type, body)?
+            .into_iter()
+            .find(|field| field.name == self.field)
+            .ok_or_else(|| TeeError::MissingUploadField(self.field.to_string()))?;
+        let options = ParseOptions::new().with_limits(self.limits);
+        match field
+            .content_type
+            .as_deref()
+            .and_then(ImageFormat::from_mime_type)
+        {
+            Some(format) => Tee::new_with_options(field.data, TEE_UV_LAYOUT, format, options),
+            None => Tee::new_untrusted(field.data, self.limits),
+        }
+    }
+}
+
+/// Returns the boundary parameter of a `multipart/form-data` content type.
+fn boundary(content_type: &str) -> Result<&str> {
+    let mut params = content_type.split(';');
+    let media_type = params.next().unwrap_or_default().trim();
+    if !media_type.eq_ignore_ascii_case("multipart/form-data") {
+        return Err(TeeError::MalformedUpload(
+            "content type is not multipart/form-data",
+        ));
+    }
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim().trim_matches('"'))
+        .filter(|boundary| !boundary.is_empty())
+        .ok_or(TeeError::MalformedUpload("missing boundary parameter"))
+}
+
+/// Builds a field from its header block and content.
+fn field(
+    headers: &str,
+    data: Bytes,
+) -> Result<FormField> {
+    let mut name = None;
+    let mut filename = None;
+    let mut content_type = None;
+    for line in headers.split("\r\n") {
+        let Some((header, value)) = line.split_once(':') else {
+            continue;
+        };
+        let header = header.trim();
+        if header.eq_ignore_ascii_case("content-disposition") {
+            for (key, value) in value.split(';').skip(1).filter_map(|p| p.split_once('=')) {
+                let value = value.trim().trim_matches('"').to_string();
+                match key.trim().to_ascii_lowercase().as_str() {
+                    "name" => name = Some(value),
+                    "filename" => filename = Some(value),
+                    _ => {}
+                }
+            }
+        } else if header.eq_ignore_ascii_case("content-type") {
+            content_type = Some(value.trim().to_ascii_lowercase());
+        }
+    }
+    Ok(FormField {
+        name: name.ok_or(TeeError::MalformedUpload("field without name"))?,
+        filename,
+        content_type,
+        data,
+    })
+}
+
+/// Returns the index of the first `needle` in `haystack` at or after `from`.
+fn find(
+    haystack: &[u8],
+    needle: &[u8],
+    from: usize,
+) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|index| index + from)
+}
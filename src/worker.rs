@@ -0,0 +1,282 @@
+//! # Worker module
+//!
+//! A fixed pool of CPU threads rendering [`RenderRecipe`]s from a bounded queue. Producers
+//! block once the queue is full, so a burst of requests can not grow memory without
+//! bound, and results are reported over a channel as soon as a render finishes.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use tee_morphosis::worker::WorkerPool;
+//!
+//! let pool = WorkerPool::with_resolver(4, 64, TEE_SKIN_LAYOUT, move |skin| tees.get(skin).cloned());
+//! let queue = pool.queue();
+//! std::thread::spawn(move || {
+//!     for recipe in recipes {
+//!         queue.submit(recipe).unwrap();
+//!     }
+//! });
+//! for result in pool.results().iter().take(count) {
+//!     std::fs::write(render_filename(&result.recipe), result.result?)?;
+//! }
+//! ```
+
+use std::{
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+    },
+    thread::{self, JoinHandle},
+};
+
+use bytes::Bytes;
+use image::{ImageFormat, RgbaImage, imageops};
+use tracing::{debug, instrument, trace, warn};
+
+use crate::{
+    error::{Result, TeeError},
+    meta::RenderRecipe,
+    skin_name,
+    tee::{
+        Tee,
+        hsl::ddnet_color_to_hsl,
+        parts::{EyeSelection, EyeType, TeePart},
+        raw::encode_image,
+        skin::Skin,
+    },
+};
+
+/// Outcome of a recipe rendered by a [WorkerPool].
+#[derive(Debug)]
+pub struct RenderResult {
+    /// The rendered recipe
+    pub recipe: RenderRecipe,
+    /// The encoded image, or why rendering failed
+    pub result: Result<Bytes>,
+}
+
+/// Producer side of a [WorkerPool] queue, clones feed the same queue.
+#[derive(Debug, Clone)]
+pub struct RenderQueue {
+    sender: SyncSender<RenderRecipe>,
+}
+
+impl RenderQueue {
+    /// Queues a recipe, waiting while the queue is full.
+    ///
+    /// Returns the recipe back if the pool was shut down.
+    pub fn submit(
+        &self,
+        recipe: RenderRecipe,
+    ) -> std::result::Result<(), RenderRecipe> {
+        self.sender.send(recipe).map_err(|error| error.0)
+    }
+
+    /// Queues a recipe without waiting.
+    ///
+    /// Returns the recipe back if the queue is full or the pool was shut down.
+    pub fn try_submit(
+        &self,
+        recipe: RenderRecipe,
+    ) -> std::result::Result<(), RenderRecipe> {
+        self.sender.try_send(recipe).map_err(|error| match error {
+            TrySendError::Full(recipe) | TrySendError::Disconnected(recipe) => recipe,
+        })
+    }
+}
+
+/// Fixed pool of threads rendering recipes from a bounded queue.
+///
+/// Results are sent over an unbounded channel, so producers and the consumer of
+/// [WorkerPool::results] may run on the same thread. Dropping the pool closes the queue
+/// and waits for queued recipes to finish, so drop every [RenderQueue] first.
+#[derive(Debug)]
+pub struct WorkerPool {
+    queue: Option<RenderQueue>,
+    results: Receiver<RenderResult>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    /// Starts `workers` threads rendering with `render`, with room for `capacity` queued
+    /// recipes.
+    #[instrument(level = "debug", skip(render))]
+    pub fn new<F>(
+        workers: usize,
+        capacity: usize,
+        render: F,
+    ) -> Self
+    where
+        F: Fn(&RenderRecipe) -> Result<Bytes> + Send + Sync + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel::<RenderRecipe>(capacity);
+        let (result_sender, results) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let render = Arc::new(render);
+
+        let workers = (0..workers.max(1))
+            .map(|index| {
+                let receiver = receiver.clone();
+                let result_sender = result_sender.clone();
+                let render = render.clone();
+                thread::Builder::new()
+                    .name(format!("tee-worker-{index}"))
+                    .spawn(move || {
+                        loop {
+                            // the lock is released before rendering
+                            let recipe = match receiver.lock() {
+                                Ok(receiver) => receiver.recv(),
+                                Err(_) => break,
+                            };
+                            let Ok(recipe) = recipe else {
+                                break;
+                            };
+                            trace!(worker = index, skin = %recipe.skin, "Rendering recipe");
+                            let result = render(&recipe);
+                            if result_sender
+                                .send(RenderResult {
+                                    recipe,
+                                    result,
+                                })
+                                .is_err()
+                            {
+                                warn!(worker = index, "Results receiver dropped");
+                                break;
+                            }
+                        }
+                    })
+                    .expect("failed to spawn a worker thread")
+            })
+            .collect();
+
+        Self {
+            queue: Some(RenderQueue { sender }),
+            results,
+            workers,
+        }
+    }
+
+    /// Starts a pool rendering recipes with [render_recipe], looking skins up by the
+    /// [normalized](skin_name::normalize) [RenderRecipe::skin] with `resolve`.
+    ///
+    /// Recipes of invalid names and of skins `resolve` does not know fail with
+    /// [TeeError::UnknownSkin].
+    pub fn with_resolver<R>(
+        workers: usize,
+        capacity: usize,
+        skin: Skin,
+        resolve: R,
+    ) -> Self
+    where
+        R: Fn(&str) -> Option<Arc<Tee>> + Send + Sync + 'static,
+    {
+        Self::new(workers, capacity, move |recipe| {
+            let tee = skin_name::normalize(&recipe.skin)
+                .and_then(|name| resolve(&name))
+                .ok_or_else(|| TeeError::UnknownSkin(recipe.skin.clone()))?;
+            render_recipe(&tee, skin, recipe)
+        })
+    }
+
+    /// Returns a producer handle of the queue.
+    pub fn queue(&self) -> RenderQueue {
+        self.queue
+            .clone()
+            .expect("the queue is only closed when the pool is dropped")
+    }
+
+    /// Queues a recipe, see [RenderQueue::submit].
+    pub fn submit(
+        &self,
+        recipe: RenderRecipe,
+    ) -> std::result::Result<(), RenderRecipe> {
+        self.queue().submit(recipe)
+    }
+
+    /// Returns the channel every finished render is reported on.
+    pub fn results(&self) -> &Receiver<RenderResult> {
+        &self.results
+    }
+
+    /// Closes the queue, waits for queued recipes and returns the results not received
+    /// yet.
+    ///
+    /// Producer handles still alive keep the workers running until they are dropped.
+    pub fn finish(mut self) -> Vec<RenderResult> {
+        self.shutdown();
+        self.results.try_iter().collect()
+    }
+
+    fn shutdown(&mut self) {
+        self.queue = None;
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                warn!("A worker thread panicked");
+            }
+        }
+        debug!("Worker pool shut down");
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Renders a recipe of `tee` onto `skin`.
+///
+/// Colors are applied like player colors in game, the eye is looked up by
+/// [EyeType::name] or as custom eye, and the render is scaled to fit
+/// [RenderRecipe::size] and centered on a transparent image of that size. Recipes without
+/// a format are encoded as PNG.
+pub fn render_recipe(
+    tee: &Tee,
+    skin: Skin,
+    recipe: &RenderRecipe,
+) -> Result<Bytes> {
+    let recolored;
+    let tee = match (recipe.body_color, recipe.feet_color) {
+        (None, None) => tee,
+        (body, feet) => {
+            let mut tee = tee.clone();
+            if let Some(body) = body {
+                tee.apply_hsl_to_parts(
+                    ddnet_color_to_hsl(body),
+                    &[
+                        TeePart::Body,
+                        TeePart::BodyShadow,
+                        TeePart::Hand,
+                        TeePart::HandShadow,
+                    ],
+                );
+            }
+            if let Some(feet) = feet {
+                tee.apply_hsl_to_parts(
+                    ddnet_color_to_hsl(feet),
+                    &[TeePart::Feet, TeePart::FeetShadow],
+                );
+            }
+            recolored = tee;
+            &recolored
+        }
+    };
+    let eye = EyeType::ALL
+        .into_iter()
+        .find(|eye| eye.name() == recipe.eye)
+        .map_or(EyeSelection::Custom(&recipe.eye), EyeSelection::Standard);
+
+    let (width, height) = (recipe.size.0.max(1), recipe.size.1.max(1));
+    let (output_w, output_h) = skin.output_size();
+    let factor =
+        (width as f32 / output_w.max(1) as f32).min(height as f32 / output_h.max(1) as f32);
+    let mut canvas = tee.compose_image(skin.scaled(factor), eye);
+    if canvas.dimensions() != (width, height) {
+        let mut fitted = RgbaImage::new(width, height);
+        let x = (width as i64 - canvas.width() as i64) / 2;
+        let y = (height as i64 - canvas.height() as i64) / 2;
+        imageops::overlay(&mut fitted, &canvas, x, y);
+        canvas = fitted;
+    }
+    encode_image(&canvas, recipe.format.unwrap_or(ImageFormat::Png))
+}
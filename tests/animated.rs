@@ -0,0 +1,146 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, time::Duration};
+
+    use bytes::Bytes;
+    use image::{
+        AnimationDecoder, Delay, Frame, ImageFormat, RgbaImage,
+        codecs::gif::{GifDecoder, GifEncoder},
+        imageops,
+    };
+    use tee_morphosis::{
+        animation,
+        error::TeeError,
+        tee::{
+            Tee, options::ParseOptions, parts::EyeType, skin::TEE_SKIN_LAYOUT, uv::TEE_UV_LAYOUT,
+        },
+    };
+
+    use crate::common::skin_bytes;
+
+    /// Two frame GIF: the test skin followed by an empty frame.
+    fn animated_gif() -> Bytes {
+        let skin = image::load_from_memory(&skin_bytes()).unwrap().to_rgba8();
+        let empty = RgbaImage::new(skin.width(), skin.height());
+
+        let mut buf = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(Cursor::new(&mut buf));
+            let delay = Delay::from_numer_denom_ms(100, 1);
+            encoder
+                .encode_frames([
+                    Frame::from_parts(skin, 0, 0, delay),
+                    Frame::from_parts(empty, 0, 0, delay),
+                ])
+                .unwrap();
+        }
+        Bytes::from(buf)
+    }
+
+    #[test]
+    fn gif_first_frame_is_parsed() {
+        let tee = Tee::new(animated_gif(), ImageFormat::Gif).unwrap();
+        assert!(tee.body.value.pixels().any(|p| p.0[3] != 0));
+    }
+
+    #[test]
+    fn gif_chosen_frame_is_parsed() {
+        let options = ParseOptions::new().with_frame(1);
+        let tee = Tee::new_with_options(animated_gif(), TEE_UV_LAYOUT, ImageFormat::Gif, options)
+            .unwrap();
+        assert!(tee.body.value.pixels().all(|p| p.0[3] == 0));
+    }
+
+    #[test]
+    fn frame_out_of_range() {
+        let options = ParseOptions::new().with_frame(5);
+        let result =
+            Tee::new_with_options(animated_gif(), TEE_UV_LAYOUT, ImageFormat::Gif, options);
+        assert!(matches!(
+            result,
+            Err(TeeError::FrameOutOfRange {
+                index: 5,
+                frames: 2
+            })
+        ));
+
+        let options = ParseOptions::new().with_frame(1);
+        let result = Tee::new_with_options(skin_bytes(), TEE_UV_LAYOUT, ImageFormat::Png, options);
+        assert!(matches!(
+            result,
+            Err(TeeError::FrameOutOfRange {
+                index: 1,
+                frames: 1
+            })
+        ));
+    }
+
+    /// Sheet of three frames: skin, empty, skin.
+    fn sheet() -> Bytes {
+        let skin = image::load_from_memory(&skin_bytes()).unwrap().to_rgba8();
+        let (width, height) = skin.dimensions();
+        let mut sheet = RgbaImage::new(width, height * 3);
+        imageops::replace(&mut sheet, &skin, 0, 0);
+        imageops::replace(&mut sheet, &skin, 0, (height * 2) as i64);
+
+        let mut buf = Vec::new();
+        sheet
+            .write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)
+            .unwrap();
+        Bytes::from(buf)
+    }
+
+    #[test]
+    fn sheet_is_split_into_frames() {
+        let tees = Tee::new_animated(sheet(), TEE_UV_LAYOUT, 3).unwrap();
+        assert_eq!(tees.len(), 3);
+
+        let single = Tee::new(skin_bytes(), ImageFormat::Png).unwrap();
+        assert_eq!(tees[0].body, single.body);
+        assert_eq!(tees[2].eye, single.eye);
+        assert!(tees[1].body.value.pixels().all(|p| p.0[3] == 0));
+    }
+
+    #[test]
+    fn sheet_with_wrong_frame_count() {
+        let result = Tee::new_animated(sheet(), TEE_UV_LAYOUT, 2);
+        assert!(matches!(
+            result,
+            Err(TeeError::InvalidDimensions {
+                expected: (256, 256),
+                found: (256, 384)
+            })
+        ));
+
+        // A sheet that tall can not exist
+        let result = Tee::new_animated(sheet(), TEE_UV_LAYOUT, u32::MAX);
+        assert!(matches!(
+            result,
+            Err(TeeError::FrameOutOfRange {
+                frames: 3,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn sheet_to_gif() {
+        let tees = Tee::new_animated(sheet(), TEE_UV_LAYOUT, 3).unwrap();
+        let frames = animation::compose_frames(&tees, TEE_SKIN_LAYOUT, EyeType::Happy);
+        let gif = animation::encode_gif(&frames, Duration::from_millis(100)).unwrap();
+
+        let decoded = GifDecoder::new(Cursor::new(gif))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[0].buffer().dimensions(), TEE_SKIN_LAYOUT.container);
+        assert!(matches!(
+            animation::encode_gif(&[], Duration::from_millis(100)),
+            Err(TeeError::EmptyAnimation)
+        ));
+    }
+}
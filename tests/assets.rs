@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::Bytes;
+    use image::{ImageFormat, Rgba, RgbaImage};
+    use tee_morphosis::{
+        assets::{
+            countryflags::{CountryFlags, parse_index},
+            emoticons::{EMOTICONS_SIZE, Emoticon, EmoticonSheet},
+            extras::{EXTRAS_SIZE, Extra, ExtrasSheet},
+            game::{Flag, GAME_SIZE, GAME_SPRITES, GameSheet, Pickup, Weapon},
+            particles::{PARTICLES_SIZE, Particle, ParticleSheet},
+        },
+        error::TeeError,
+        scene::Scene,
+    };
+
+    /// Sheet whose pixels hold the grid cell they belong to in red and green.
+    fn grid_sheet(
+        (width, height): (u32, u32),
+        cell: u32,
+    ) -> Bytes {
+        let img = RgbaImage::from_fn(width, height, |x, y| {
+            Rgba([(x / cell) as u8, (y / cell) as u8, 0, 255])
+        });
+        let mut buf = Vec::new();
+        img.write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)
+            .unwrap();
+        Bytes::from(buf)
+    }
+
+    /// Returns the grid cell of the top left pixel of a sprite.
+    fn origin(sprite: &RgbaImage) -> (u8, u8) {
+        let pixel = sprite.get_pixel(0, 0);
+        (pixel.0[0], pixel.0[1])
+    }
+
+    #[test]
+    fn game_sheet_sprites() {
+        let game = GameSheet::parse(grid_sheet(GAME_SIZE, 32)).unwrap();
+        assert_eq!(game.sprites().len(), GAME_SPRITES.len());
+
+        let shotgun = game.weapon(Weapon::Shotgun);
+        assert_eq!(shotgun.body.dimensions(), (256, 64));
+        assert_eq!(origin(shotgun.body), (2, 6));
+        assert_eq!(shotgun.muzzles.len(), 3);
+
+        let hammer = game.weapon(Weapon::Hammer);
+        assert!(hammer.projectile.is_none());
+        assert!(hammer.muzzles.is_empty());
+
+        assert_eq!(origin(game.pickup(Pickup::Armor)), (12, 2));
+        assert_eq!(origin(game.flag(Flag::Red)), (16, 8));
+        assert_eq!(origin(game.hook_head()), (3, 0));
+        assert_eq!(origin(game.star(4)), (17, 0));
+    }
+
+    #[test]
+    fn game_sheet_hd() {
+        let game = GameSheet::parse(grid_sheet((2048, 1024), 64)).unwrap();
+        let laser = game.weapon(Weapon::Laser);
+        assert_eq!(laser.body.dimensions(), (448, 192));
+        assert_eq!(origin(laser.body), (2, 12));
+    }
+
+    #[test]
+    fn game_sheet_wrong_size() {
+        let result = GameSheet::parse(grid_sheet((1024, 1024), 32));
+        assert!(matches!(
+            result,
+            Err(TeeError::InvalidDimensions {
+                expected: GAME_SIZE,
+                found: (1024, 1024)
+            })
+        ));
+    }
+
+    #[test]
+    fn emoticons_by_network_id() {
+        let emoticons = EmoticonSheet::parse(grid_sheet(EMOTICONS_SIZE, 128)).unwrap();
+        for (emoticon, sprite) in emoticons.iter() {
+            assert_eq!(Emoticon::from_id(emoticon.id()), Some(emoticon));
+            let id = emoticon.id();
+            assert_eq!(origin(sprite), (id % 4, id / 4));
+        }
+        assert_eq!(origin(emoticons.get(Emoticon::Ghost)), (3, 1));
+        assert_eq!(Emoticon::from_id(16), None);
+    }
+
+    #[test]
+    fn emoticon_above_tee() {
+        let emoticons = EmoticonSheet::parse(grid_sheet(EMOTICONS_SIZE, 128)).unwrap();
+        let mut scene = Scene::new((200, 200));
+        let id = scene.add_emoticon(emoticons.get(Emoticon::Hearts), (50, 100), (96, 64));
+
+        let item = scene.item(id).unwrap();
+        assert_eq!(item.image.dimensions(), (64, 64));
+        assert_eq!(item.position, (66, 45));
+    }
+
+    #[test]
+    fn particles_and_extras() {
+        let particles = ParticleSheet::parse(grid_sheet(PARTICLES_SIZE, 32)).unwrap();
+        assert_eq!(particles.iter().count(), Particle::ALL.len());
+        let explosion = particles.get(Particle::Explosion);
+        assert_eq!(explosion.dimensions(), (128, 128));
+        assert_eq!(origin(explosion), (0, 4));
+        assert_eq!(origin(particles.get(Particle::Hit)), (4, 1));
+
+        let extras = ExtrasSheet::parse(grid_sheet(EXTRAS_SIZE, 32)).unwrap();
+        assert_eq!(extras.get(Extra::Sparkle).dimensions(), (64, 64));
+        assert_eq!(origin(extras.get(Extra::Hectagon)), (6, 0));
+    }
+
+    #[test]
+    fn country_flags_from_index() {
+        let index =
+            "##### custom #####\n\ndefault\n== -1\n\n# ISO 3166-1\nDE\n  == 276\nRU\n== 643\n";
+        assert_eq!(
+            parse_index(index).unwrap(),
+            vec![
+                ("default".to_string(), -1),
+                ("DE".to_string(), 276),
+                ("RU".to_string(), 643)
+            ]
+        );
+        assert!(matches!(
+            parse_index("DE\nRU\n== 643"),
+            Err(TeeError::MalformedFlagIndex {
+                line: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_index("DE\n== germany"),
+            Err(TeeError::MalformedFlagIndex {
+                line: 2,
+                ..
+            })
+        ));
+
+        // RU has no image and is skipped
+        let flags = CountryFlags::from_index(index, |name| {
+            (name != "RU").then(|| grid_sheet((64, 32), 32))
+        })
+        .unwrap();
+        assert_eq!(flags.len(), 2);
+        assert_eq!(flags.code_of("de"), Some(276));
+        assert_eq!(flags.get(276).unwrap().image.dimensions(), (64, 32));
+        assert!(flags.get(643).is_none());
+        assert_eq!(flags.get_or_default(643).unwrap().name, "default");
+    }
+
+    #[test]
+    fn country_flags_from_sheet() {
+        let flags = CountryFlags::from_sheet(
+            grid_sheet((128, 64), 32),
+            (2, 2),
+            &[(276, "DE"), (40, "AT"), (-1, "default")],
+        )
+        .unwrap();
+        assert_eq!(flags.len(), 3);
+        let austria = &flags.get(40).unwrap().image;
+        assert_eq!(austria.dimensions(), (64, 32));
+        assert_eq!(origin(austria), (2, 0));
+        assert_eq!(origin(&flags.get(-1).unwrap().image), (0, 1));
+        assert!(
+            CountryFlags::from_sheet(grid_sheet((128, 64), 32), (1, 1), &[(1, "A"), (2, "B")])
+                .is_err()
+        );
+
+        let mut scene = Scene::new((100, 100));
+        let id = scene.add_country_flag(&flags, 276, (10, 10), 16).unwrap();
+        assert_eq!(scene.item(id).unwrap().image.dimensions(), (32, 16));
+        assert!(
+            scene
+                .add_country_flag(&CountryFlags::new(), 276, (0, 0), 16)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,120 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread, time::Duration};
+
+    use bytes::Bytes;
+    use image::ImageFormat;
+    use tee_morphosis::{
+        cache::{DedupStore, RenderCache},
+        tee::{
+            Tee, hash::SourceHash, options::ComposeOptions, parts::EyeType, skin::TEE_SKIN_LAYOUT,
+        },
+    };
+
+    use crate::common::skin_bytes;
+
+    #[test]
+    fn identical_sources_are_stored_once() {
+        let store = DedupStore::new();
+        let data = skin_bytes();
+
+        let a = store
+            .get_or_parse("https://a.example/skin.png", data.clone(), ImageFormat::Png)
+            .unwrap();
+        let b = store
+            .get_or_parse("https://b.example/skin.png", data.clone(), ImageFormat::Png)
+            .unwrap();
+
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(a.source_hash(), SourceHash::of(&data));
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.keys_len(), 2);
+
+        assert!(store.remove("https://a.example/skin.png"));
+        assert_eq!(store.len(), 1);
+        assert!(store.remove("https://b.example/skin.png"));
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn hash_is_hex_encoded() {
+        assert_eq!(
+            SourceHash::of(b"").to_hex(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn identical_renders_are_cached() {
+        let tee = Tee::new(skin_bytes(), ImageFormat::Png).unwrap();
+        let cache = RenderCache::new(1 << 20, Duration::from_secs(60));
+        let options = ComposeOptions::default();
+
+        let first = cache
+            .get_or_render(
+                &tee,
+                TEE_SKIN_LAYOUT,
+                EyeType::Happy,
+                ImageFormat::Png,
+                &options,
+            )
+            .unwrap();
+        let second = cache
+            .get_or_render(
+                &tee,
+                TEE_SKIN_LAYOUT,
+                EyeType::Happy,
+                ImageFormat::Png,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(first.as_ptr(), second.as_ptr());
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.bytes(), first.len());
+
+        let key = tee.compose_etag(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png, &options);
+        assert_eq!(cache.get(&key), Some(first));
+
+        cache
+            .get_or_render(
+                &tee,
+                TEE_SKIN_LAYOUT,
+                EyeType::Angry,
+                ImageFormat::Png,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn least_recently_used_renders_are_evicted() {
+        let cache = RenderCache::new(10, Duration::from_secs(60));
+        cache.insert("a", Bytes::from_static(b"aaaa"));
+        cache.insert("b", Bytes::from_static(b"bbbb"));
+        assert!(cache.get("a").is_some());
+        cache.insert("c", Bytes::from_static(b"cccc"));
+
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+        assert_eq!(cache.bytes(), 8);
+
+        cache.insert("huge", Bytes::from_static(b"way too large"));
+        assert!(cache.get("huge").is_none());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn renders_expire() {
+        let cache = RenderCache::new(1024, Duration::from_millis(20));
+        cache.insert("a", Bytes::from_static(b"aaaa"));
+        assert!(cache.get("a").is_some());
+        thread::sleep(Duration::from_millis(40));
+        assert!(cache.get("a").is_none());
+        assert!(cache.is_empty());
+        assert_eq!(cache.bytes(), 0);
+    }
+}
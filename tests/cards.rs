@@ -0,0 +1,115 @@
+mod common;
+
+#[cfg(feature = "text")]
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tee_morphosis::cards::rank::{format_race_time, medal};
+
+    #[test]
+    fn race_times_match_ddnet() {
+        assert_eq!(format_race_time(Duration::from_millis(83_456)), "01:23.45");
+        assert_eq!(format_race_time(Duration::from_millis(999)), "00:00.99");
+        assert_eq!(
+            format_race_time(Duration::from_millis(4_000_450)),
+            "1:06:40.45"
+        );
+    }
+
+    #[test]
+    fn only_top_three_get_medals() {
+        for rank in 1..=3 {
+            let medal = medal(rank, 40).unwrap();
+            assert_eq!(medal.dimensions(), (40, 60));
+            // The middle of the coin is opaque, the corners next to the ribbon are not
+            assert_eq!(medal.get_pixel(20, 40)[3], 255);
+            assert_eq!(medal.get_pixel(0, 0)[3], 0);
+        }
+        assert_ne!(medal(1, 40), medal(2, 40));
+        assert!(medal(0, 40).is_none());
+        assert!(medal(4, 40).is_none());
+    }
+}
+
+#[cfg(feature = "templates")]
+#[cfg(test)]
+mod layout_tests {
+    use std::collections::HashMap;
+
+    use image::Rgba;
+    use tee_morphosis::{
+        cards::layout::{CardData, CardLayout},
+        error::TeeError,
+    };
+
+    use crate::common::tee;
+
+    const LAYOUT: &str = r##"{
+        "size": [200, 100],
+        "background": { "color": "#102030" },
+        "elements": [
+            { "type": "rect", "x": 0, "y": 90, "size": [200, 10], "color": "#ff000080" },
+            { "type": "tee", "x": 0, "y": 0, "height": 64, "eye": "{eye}" },
+            { "type": "medal", "x": 150, "y": 0, "size": 20, "rank": "{rank}", "when": "rank" }
+        ]
+    }"##;
+
+    #[test]
+    fn layout_binds_values() {
+        let layout = CardLayout::from_json(LAYOUT, &HashMap::new()).unwrap();
+        assert_eq!(layout.elements.len(), 3);
+        let tee = tee();
+
+        let data = CardData::new().with_tee(&tee).with("eye", "happy");
+        let scene = layout.scene(&data);
+        // Background, rect and tee, the medal waits for its rank
+        assert_eq!(scene.items().count(), 3);
+        let image = scene.render();
+        assert_eq!(image.dimensions(), (200, 100));
+        assert_eq!(*image.get_pixel(100, 50), Rgba([16, 32, 48, 255]));
+
+        let scene = layout.scene(&data.clone().with("rank", 1));
+        assert_eq!(scene.items().count(), 4);
+        // Ranks without a medal draw nothing
+        assert_eq!(layout.scene(&data.with("rank", 12)).items().count(), 3);
+
+        assert_eq!(
+            CardData::new()
+                .with("map", "Kobra 4")
+                .bind("on {map}{missing}!"),
+            "on Kobra 4!"
+        );
+    }
+
+    #[test]
+    fn malformed_layouts_are_rejected() {
+        let fonts = HashMap::new();
+        let bad_color = LAYOUT.replace("#102030", "#1020");
+        assert!(matches!(
+            CardLayout::from_json(&bad_color, &fonts),
+            Err(TeeError::Json(_))
+        ));
+
+        let unknown_font = r##"{
+            "size": [10, 10],
+            "background": { "color": "#000000" },
+            "styles": { "title": { "font": "missing", "size": 12, "color": "#ffffff" } },
+            "elements": []
+        }"##;
+        assert!(matches!(
+            CardLayout::from_json(unknown_font, &fonts),
+            Err(TeeError::Template(_))
+        ));
+
+        let unknown_style = r##"{
+            "size": [10, 10],
+            "background": { "color": "#000000" },
+            "elements": [{ "type": "text", "x": 0, "y": 0, "style": "title", "text": "hi" }]
+        }"##;
+        assert!(matches!(
+            CardLayout::from_json(unknown_style, &fonts),
+            Err(TeeError::Template(_))
+        ));
+    }
+}
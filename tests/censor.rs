@@ -0,0 +1,42 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+    use tee_morphosis::tee::{censor::Censor, parts::TeePart};
+
+    use crate::common::tee;
+
+    fn alpha(img: &RgbaImage) -> Vec<u8> {
+        img.pixels().map(|pixel| pixel.0[3]).collect()
+    }
+
+    #[test]
+    fn pixelate_blocks() {
+        let mut img = RgbaImage::new(4, 2);
+        img.put_pixel(0, 0, Rgba([200, 0, 0, 255]));
+        img.put_pixel(1, 0, Rgba([0, 200, 0, 255]));
+        img.put_pixel(0, 1, Rgba([0, 0, 255, 0]));
+        img.put_pixel(3, 1, Rgba([10, 20, 30, 255]));
+        Censor::Pixelate(2).apply(&mut img);
+
+        // the transparent pixel takes the color but not the weight
+        assert_eq!(img.get_pixel(0, 1), &Rgba([100, 100, 0, 0]));
+        assert_eq!(img.get_pixel(1, 1), &Rgba([100, 100, 0, 0]));
+        assert_eq!(img.get_pixel(0, 0), &Rgba([100, 100, 0, 255]));
+        assert_eq!(img.get_pixel(2, 0), &Rgba([10, 20, 30, 0]));
+    }
+
+    #[test]
+    fn censor_keeps_silhouette() {
+        let tee = tee();
+        for censor in [Censor::Pixelate(8), Censor::Blur(4.0)] {
+            let mut censored = tee.clone();
+            censored.censor_part(TeePart::Body, censor);
+            assert_ne!(censored.body.value, tee.body.value, "{censor:?}");
+            assert_eq!(alpha(&censored.body.value), alpha(&tee.body.value));
+            assert_eq!(censored.body.shadow, tee.body.shadow);
+            assert_eq!(censored.feet.value, tee.feet.value);
+        }
+    }
+}
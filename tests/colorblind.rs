@@ -0,0 +1,63 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+    use tee_morphosis::{
+        colorblind::ColorBlindness,
+        tee::{options::ComposeOptions, parts::EyeType, skin::TEE_SKIN_LAYOUT},
+    };
+
+    use crate::common::tee;
+
+    fn distance(
+        a: &Rgba<u8>,
+        b: &Rgba<u8>,
+    ) -> u32 {
+        a.0.iter()
+            .zip(b.0)
+            .take(3)
+            .map(|(a, b)| a.abs_diff(b) as u32)
+            .sum()
+    }
+
+    #[test]
+    fn grays_and_alpha_are_kept() {
+        let mut img = RgbaImage::from_pixel(1, 2, Rgba([128, 128, 128, 77]));
+        img.put_pixel(0, 1, Rgba([255, 255, 255, 255]));
+        for kind in ColorBlindness::ALL {
+            let simulated = kind.simulate(&img);
+            assert!(distance(simulated.get_pixel(0, 0), &Rgba([128, 128, 128, 77])) <= 3);
+            assert_eq!(simulated.get_pixel(0, 0).0[3], 77);
+            assert!(distance(simulated.get_pixel(0, 1), &Rgba([255, 255, 255, 255])) <= 3);
+        }
+    }
+
+    #[test]
+    fn red_and_green_merge_for_deuteranopia() {
+        let red = Rgba([200, 60, 40, 255]);
+        let green = Rgba([60, 160, 40, 255]);
+        let mut img = RgbaImage::new(2, 1);
+        img.put_pixel(0, 0, red);
+        img.put_pixel(1, 0, green);
+
+        let simulated = ColorBlindness::Deuteranopia.simulate(&img);
+        let before = distance(&red, &green);
+        let after = distance(simulated.get_pixel(0, 0), simulated.get_pixel(1, 0));
+        assert!(after < before / 2, "{after} >= {before} / 2");
+
+        // blue cones do not help telling red from green, tritanopia keeps them apart
+        let simulated = ColorBlindness::Tritanopia.simulate(&img);
+        assert!(distance(simulated.get_pixel(0, 0), simulated.get_pixel(1, 0)) > before / 2);
+    }
+
+    #[test]
+    fn compose_options_apply_filter() {
+        let tee = tee();
+
+        let plain = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        let options = ComposeOptions::new().with_color_blindness(ColorBlindness::Protanopia);
+        let filtered = tee.compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Normal, &options);
+        assert_eq!(filtered, ColorBlindness::Protanopia.simulate(&plain));
+    }
+}
@@ -0,0 +1,28 @@
+//! Fixtures shared by the integration tests.
+//!
+//! Every test binary compiles its own copy and uses only some of them.
+#![allow(dead_code)]
+
+use std::{fs, path::PathBuf};
+
+use bytes::Bytes;
+use image::ImageFormat;
+use tee_morphosis::tee::Tee;
+
+/// Returns the path of the skin fixture.
+pub fn skin_path() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push(".ref");
+    path.push("test_skin.png");
+    path
+}
+
+/// Returns the PNG bytes of the skin fixture.
+pub fn skin_bytes() -> Bytes {
+    Bytes::from(fs::read(skin_path()).expect("Failed to read fixture file"))
+}
+
+/// Returns the skin fixture, parsed with the default UV layout.
+pub fn tee() -> Tee {
+    Tee::new(skin_bytes(), ImageFormat::Png).unwrap()
+}
@@ -0,0 +1,200 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::ImageFormat;
+    use tee_morphosis::tee::{
+        compositor::CompositorBackend,
+        layer::{Layer, ZOrder},
+        options::{CompatMode, ComposeOptions},
+        parts::EyeType,
+        skin::{Skin, TEE_SKIN_LAYOUT},
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn auto_picks_by_area() {
+        assert_eq!(CompositorBackend::auto((96, 64)), CompositorBackend::Simple);
+        assert_eq!(
+            CompositorBackend::auto((960, 640)),
+            CompositorBackend::Tiled
+        );
+        assert_eq!(
+            CompositorBackend::auto((2048, 1365)),
+            CompositorBackend::Parallel
+        );
+    }
+
+    #[test]
+    fn backends_produce_identical_pixels() {
+        let tee = tee();
+        for skin in [TEE_SKIN_LAYOUT, TEE_SKIN_LAYOUT.scaled(7.5)] {
+            let simple = tee.compose_image_with_backend(
+                skin,
+                EyeType::Happy.into(),
+                CompositorBackend::Simple,
+            );
+            for backend in [CompositorBackend::Tiled, CompositorBackend::Parallel] {
+                assert_eq!(
+                    tee.compose_image_with_backend(skin, EyeType::Happy.into(), backend),
+                    simple,
+                    "{backend:?}"
+                );
+            }
+            assert_eq!(tee.compose_image(skin, EyeType::Happy), simple);
+        }
+    }
+
+    #[test]
+    fn gpu_matches_simple() {
+        use tee_morphosis::diff::compare_images;
+
+        let tee = tee();
+        // Without a GPU or the feature both blend on the CPU and must be identical
+        let tolerance = u8::from(CompositorBackend::gpu_available());
+        for skin in [TEE_SKIN_LAYOUT, TEE_SKIN_LAYOUT.scaled(7.5)] {
+            let simple = tee.compose_image_with_backend(
+                skin,
+                EyeType::Happy.into(),
+                CompositorBackend::Simple,
+            );
+            let gpu =
+                tee.compose_image_with_backend(skin, EyeType::Happy.into(), CompositorBackend::Gpu);
+            let diff = compare_images(&simple, &gpu);
+            assert!(
+                diff.within(tolerance),
+                "{} pixels differ",
+                diff.differing_pixels
+            );
+        }
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    fn gpu_scene_matches_render() {
+        use image::Rgba;
+        use tee_morphosis::{diff::compare_images, scene::Scene};
+
+        let tee = tee();
+        let tolerance = u8::from(CompositorBackend::gpu_available());
+        let mut scene = Scene::new((300, 96)).with_background(Rgba([30, 30, 40, 200]));
+        for (index, x) in [-40, 30, 100, 170, 260].into_iter().enumerate() {
+            scene.add_tee(
+                &tee,
+                TEE_SKIN_LAYOUT,
+                EyeType::Normal,
+                (x, index as i64 * 4 - 8),
+            );
+        }
+        let diff = compare_images(&scene.render(), &scene.render_gpu());
+        assert!(
+            diff.within(tolerance),
+            "{} pixels differ",
+            diff.differing_pixels
+        );
+    }
+
+    #[test]
+    fn fractional_positions_are_resampled() {
+        let tee = tee();
+        // Center of mass along x, weighted by alpha
+        let center = |x: f32| {
+            let skin = Skin {
+                body: ((x, 0.), 0.66),
+                ..TEE_SKIN_LAYOUT
+            };
+            let image = tee.compose_image(skin, EyeType::Normal);
+            let (sum, weight) =
+                image
+                    .enumerate_pixels()
+                    .fold((0.0, 0.0), |(sum, weight), (px, _, pixel)| {
+                        let alpha = pixel.0[3] as f64;
+                        (sum + px as f64 * alpha, weight + alpha)
+                    });
+            (image, sum / weight)
+        };
+        let (whole, left) = center(16.0);
+        let (half, middle) = center(16.5);
+        let (next, right) = center(17.0);
+        assert_ne!(half, whole);
+        assert_ne!(half, next);
+        assert!(left < middle && middle < right, "{left} {middle} {right}");
+    }
+
+    #[test]
+    fn options_force_backend() {
+        let tee = tee();
+        let options = ComposeOptions::new().with_backend(CompositorBackend::Parallel);
+        assert_eq!(
+            tee.compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Normal, &options),
+            tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal)
+        );
+    }
+
+    #[test]
+    fn report_names_every_layer() {
+        let tee = tee();
+        let extra = image::RgbaImage::new(8, 8);
+        for backend in [
+            CompositorBackend::Simple,
+            CompositorBackend::Tiled,
+            CompositorBackend::Parallel,
+        ] {
+            let options = ComposeOptions::new()
+                .with_backend(backend)
+                .with_extra_layer(extra.clone(), TEE_SKIN_LAYOUT.body, ZOrder::Topmost);
+            let (data, report) = tee
+                .compose_with_report(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png, &options)
+                .unwrap();
+
+            let names: Vec<_> = report.layers.iter().map(|layer| layer.name).collect();
+            let mut expected: Vec<_> = Layer::DEFAULT_ORDER.iter().map(Layer::name).collect();
+            expected.push("extra");
+            assert_eq!(names, expected, "{backend:?}");
+            assert_eq!(report.output_size, data.len());
+            assert!(report.slowest_layer().is_some());
+            assert!(report.total() >= report.encode);
+            assert_eq!(
+                data,
+                tee.compose_with_options(
+                    TEE_SKIN_LAYOUT,
+                    EyeType::Happy,
+                    ImageFormat::Png,
+                    &options
+                )
+                .unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn discord_bot_compat_ignores_backend() {
+        let tee = tee();
+        let skin = TEE_SKIN_LAYOUT.scaled(4.);
+        let compose = |options: ComposeOptions| {
+            tee.compose_image_with_options(
+                skin,
+                EyeType::Normal,
+                &options.with_compat(CompatMode::DiscordBotPy),
+            )
+        };
+        let simple = compose(ComposeOptions::new().with_backend(CompositorBackend::Simple));
+        let parallel = compose(ComposeOptions::new().with_backend(CompositorBackend::Parallel));
+        assert_eq!(simple, parallel);
+
+        let native = tee.compose_image(skin, EyeType::Normal);
+        assert_eq!(simple.dimensions(), native.dimensions());
+        assert_ne!(simple, native);
+        // Only the resampling differs, the same pixels are covered
+        let covered = simple
+            .pixels()
+            .zip(native.pixels())
+            .filter(|(a, b)| (a[3] > 0) != (b[3] > 0))
+            .count();
+        assert!(
+            covered < (simple.width() * simple.height() / 50) as usize,
+            "{covered}"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use image::ImageFormat;
+    use tee_morphosis::{config::Config, error::TeeError};
+
+    #[test]
+    fn reads_prefixed_vars() {
+        let config = Config::from_vars([
+            ("PATH", "/usr/bin"),
+            ("TEE_MORPHOSIS_CACHE_DIR", "/var/cache/tees"),
+            ("TEE_MORPHOSIS_TIMEOUT_SECS", "2.5"),
+            ("TEE_MORPHOSIS_DEFAULT_FORMAT", "webp"),
+            ("TEE_MORPHOSIS_MAX_WIDTH", "1024"),
+            ("TEE_MORPHOSIS_USER_AGENT", "skin-bot/1.0"),
+            ("TEE_MORPHOSIS_QUEUE", "0"),
+            ("TEE_MORPHOSIS_SOMETHING_ELSE", "ignored"),
+        ])
+        .unwrap();
+        assert_eq!(config.cache_dir, Some(PathBuf::from("/var/cache/tees")));
+        assert_eq!(config.timeout, Duration::from_millis(2500));
+        assert_eq!(config.connect_timeout, Config::default().connect_timeout);
+        assert_eq!(config.default_format, ImageFormat::WebP);
+        assert_eq!(config.limits.max_width, 1024);
+        assert_eq!(config.limits.max_height, 4096);
+        assert_eq!(config.user_agent, "skin-bot/1.0");
+        assert_eq!(config.queue, Some(0));
+        assert_eq!(config.concurrency, None);
+        assert_eq!(config.parse_options().limits, config.limits);
+        assert!(config.allowed_hosts.is_empty());
+        assert!(!config.allow_private_addresses);
+    }
+
+    #[test]
+    fn reads_host_lists() {
+        let config = Config::from_vars([
+            ("TEE_MORPHOSIS_ALLOWED_HOSTS", " teedata.net, ,ddnet.org "),
+            ("TEE_MORPHOSIS_DENIED_HOSTS", "evil.teedata.net"),
+            ("TEE_MORPHOSIS_ALLOW_PRIVATE_ADDRESSES", "Yes"),
+        ])
+        .unwrap();
+        assert_eq!(config.allowed_hosts, ["teedata.net", "ddnet.org"]);
+        assert_eq!(config.denied_hosts, ["evil.teedata.net"]);
+        assert!(config.allow_private_addresses);
+    }
+
+    #[test]
+    fn rejects_malformed_values() {
+        for (key, value) in [
+            ("TEE_MORPHOSIS_TIMEOUT_SECS", "soon"),
+            ("TEE_MORPHOSIS_TIMEOUT_SECS", "-1"),
+            ("TEE_MORPHOSIS_DEFAULT_FORMAT", "jpeg"),
+            ("TEE_MORPHOSIS_MAX_HEIGHT", "0"),
+            ("TEE_MORPHOSIS_CONCURRENCY", "many"),
+            ("TEE_MORPHOSIS_USER_AGENT", ""),
+            ("TEE_MORPHOSIS_ALLOW_PRIVATE_ADDRESSES", "maybe"),
+        ] {
+            match Config::from_vars([(key, value)]) {
+                Err(TeeError::Config {
+                    key: name, ..
+                }) => assert_eq!(name, key),
+                other => panic!("{key}={value} gave {other:?}"),
+            }
+        }
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn builds_services() {
+        let config = Config::from_vars([("TEE_MORPHOSIS_DEFAULT_FORMAT", "gif")]).unwrap();
+        assert_eq!(
+            config.request("https://example.com/a.png").format,
+            ImageFormat::Gif
+        );
+        config.render_service().unwrap();
+
+        let config = Config::from_vars([("TEE_MORPHOSIS_ALLOWED_HOSTS", "teedata.net")]).unwrap();
+        let policy = config.fetcher().unwrap().url_policy().clone();
+        assert_eq!(policy, config.url_policy());
+        assert_eq!(policy.allowed_hosts(), ["teedata.net"]);
+        assert!(!policy.allows_private_addresses());
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn reads_network_options() {
+        use tee_morphosis::net::network::IpPreference;
+
+        let config = Config::from_vars([
+            ("TEE_MORPHOSIS_PROXY", "http://proxy.internal:3128"),
+            (
+                "TEE_MORPHOSIS_DNS_OVERRIDES",
+                "skins.test=203.0.113.7, skins.test=2001:db8::7",
+            ),
+            ("TEE_MORPHOSIS_IP_PREFERENCE", "IPv4"),
+        ])
+        .unwrap();
+        assert_eq!(config.network.proxy(), Some("http://proxy.internal:3128"));
+        assert_eq!(config.network.dns_override("skins.test").unwrap().len(), 2);
+        assert_eq!(config.network.ip_preference(), IpPreference::PreferV4);
+        assert_eq!(config.fetcher().unwrap().network(), &config.network);
+
+        let direct = Config::from_vars([("TEE_MORPHOSIS_PROXY", "none")]).unwrap();
+        assert!(direct.network.is_direct());
+
+        for (key, value) in [
+            ("TEE_MORPHOSIS_PROXY", "not a proxy"),
+            ("TEE_MORPHOSIS_DNS_OVERRIDES", "skins.test"),
+            ("TEE_MORPHOSIS_DNS_OVERRIDES", "=1.1.1.1"),
+            ("TEE_MORPHOSIS_IP_PREFERENCE", "ipv5"),
+        ] {
+            assert!(Config::from_vars([(key, value)]).is_err(), "{key}={value}");
+        }
+    }
+}
@@ -0,0 +1,49 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageFormat, Rgb, Rgba, RgbaImage};
+    use tee_morphosis::{
+        contrast::{LOW_CONTRAST, contrast_score},
+        tee::{options::ComposeOptions, parts::EyeType, skin::TEE_SKIN_LAYOUT},
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn extremes() {
+        let black = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255]));
+        let score = contrast_score(&black, Rgb([255, 255, 255]));
+        assert!((score - 21.0).abs() < 0.01);
+
+        let gray = RgbaImage::from_pixel(4, 4, Rgba([120, 120, 120, 255]));
+        assert!((contrast_score(&gray, Rgb([120, 120, 120])) - 1.0).abs() < 0.001);
+        assert_eq!(contrast_score(&RgbaImage::new(4, 4), Rgb([0, 0, 0])), 1.0);
+
+        // half transparent black over white blends to gray
+        let faded = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 128]));
+        let score = contrast_score(&faded, Rgb([255, 255, 255]));
+        assert!(score > 1.5 && score < 21.0);
+    }
+
+    #[test]
+    fn composed_image_score() {
+        let tee = tee();
+
+        let image = tee
+            .compose_tagged(
+                TEE_SKIN_LAYOUT,
+                EyeType::Normal,
+                ImageFormat::Png,
+                &ComposeOptions::new(),
+            )
+            .unwrap();
+        let canvas = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        let background = Rgb([135, 165, 195]);
+        assert_eq!(
+            image.contrast_score(background).unwrap(),
+            contrast_score(&canvas, background)
+        );
+        assert!(image.contrast_score(Rgb([0, 0, 0])).unwrap() > LOW_CONTRAST);
+    }
+}
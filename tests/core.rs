@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::{
+        core::{
+            color::{ddnet_color_to_hsl, hsl_to_rgb, rgb_to_hsl},
+            skin::TEE_SKIN_LAYOUT,
+            uv::{TEE_UV_LAYOUT, UvPart},
+        },
+        tee,
+    };
+
+    #[test]
+    fn tee_modules_reexport_core() {
+        let part: tee::uv::UvPart = UvPart::new(1, 2, (3, 4));
+        assert_eq!(part, tee::uv::UvPart::new(1, 2, (3, 4)));
+        assert_eq!(tee::uv::TEE_UV_LAYOUT, TEE_UV_LAYOUT);
+        assert_eq!(
+            tee::skin::TEE_SKIN_LAYOUT.output_size(),
+            TEE_SKIN_LAYOUT.output_size()
+        );
+        assert_eq!(
+            tee::hsl::ddnet_color_to_hsl(65408),
+            ddnet_color_to_hsl(65408)
+        );
+    }
+
+    #[test]
+    fn rounding_matches_std() {
+        for factor in [0.5, 1.25, 1.5, 2.5, 3.3, 7.5] {
+            let part = UvPart::new(13, 27, (33, 65)).scaled(factor);
+            let expected = |value: u32| (value as f32 * factor).round() as u32;
+            assert_eq!(
+                part,
+                UvPart::new(expected(13), expected(27), (expected(33), expected(65))),
+                "{factor}"
+            );
+
+            let skin = TEE_SKIN_LAYOUT.scaled(factor);
+            let ((x, y), _) = skin.first_eyes;
+            assert_eq!(
+                skin.place(skin.first_eyes, (32, 32)).0,
+                ((x * factor).round() as i64, (y * factor).round() as i64)
+            );
+        }
+    }
+
+    #[test]
+    fn color_round_trip() {
+        for color in [0, 65408, 1900500, 10223541, 16777215] {
+            let hsl = ddnet_color_to_hsl(color);
+            let (r, g, b) = hsl_to_rgb(hsl);
+            let (h, s, l) = rgb_to_hsl((r, g, b));
+            let (r2, g2, b2) = hsl_to_rgb((h, s, l));
+            for (a, b) in [(r, r2), (g, g2), (b, b2)] {
+                assert!((a - b).abs() < 1e-4, "{color}: {a} {b}");
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use image::ImageFormat;
+    use tee_morphosis::{
+        error::TeeError,
+        tee::{
+            Tee,
+            data_url::{DataUrl, decode_base64, encode_base64},
+        },
+    };
+
+    use crate::common::skin_bytes;
+
+    #[test]
+    fn base64_roundtrips() {
+        for data in [&b""[..], b"f", b"fo", b"foo", b"foob", b"\xff\xfe\x00"] {
+            let encoded = encode_base64(data);
+            assert_eq!(decode_base64(&encoded).unwrap(), data, "{encoded}");
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(decode_base64(unpadded).unwrap(), data, "{unpadded}");
+        }
+        assert_eq!(
+            decode_base64("_-8=\n").unwrap(),
+            Bytes::from_static(b"\xff\xef")
+        );
+        assert!(decode_base64("Zm9v!").is_err());
+        assert!(decode_base64("Z").is_err());
+        assert!(decode_base64("Zg==Zg").is_err());
+    }
+
+    #[test]
+    fn parses_media_type_and_payload() {
+        let url = DataUrl::parse("DATA:Image/PNG;charset=x;base64,Zm9v").unwrap();
+        assert_eq!(url.media_type, "image/png");
+        assert_eq!(url.format(), Some(ImageFormat::Png));
+        assert_eq!(url.data, Bytes::from_static(b"foo"));
+
+        let plain = DataUrl::parse("data:,a%20b").unwrap();
+        assert_eq!(plain.media_type, "");
+        assert_eq!(plain.data, Bytes::from_static(b"a b"));
+
+        for url in [
+            "image/png;base64,Zm9v",
+            "data:image/png;base64",
+            "data:,%zz",
+        ] {
+            assert!(
+                matches!(DataUrl::parse(url), Err(TeeError::MalformedDataUrl(_))),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn tee_from_data_url_matches_bytes() {
+        let data = skin_bytes().to_vec();
+        let expected = Tee::new(Bytes::from(data.clone()), ImageFormat::Png).unwrap();
+        let encoded = encode_base64(&data);
+
+        let tee = Tee::new_from_data_url(&format!("data:image/png;base64,{encoded}")).unwrap();
+        assert_eq!(tee.body, expected.body);
+        assert_eq!(tee.source_hash(), expected.source_hash());
+
+        let guessed = Tee::new_from_data_url(&format!("data:;base64,{encoded}")).unwrap();
+        assert_eq!(guessed.body, expected.body);
+        assert_eq!(Tee::new_from_base64(&encoded).unwrap().body, expected.body);
+
+        let text = Tee::new_from_data_url(&format!("data:text/plain;base64,{encoded}"));
+        assert!(matches!(text, Err(TeeError::MalformedDataUrl(_))));
+    }
+}
@@ -0,0 +1,220 @@
+mod common;
+
+#[cfg(feature = "net")]
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread::{self, JoinHandle},
+        time::Duration,
+    };
+
+    use tee_morphosis::{
+        db::{SkinDbClient, SkinDbSource},
+        error::TeeError,
+        net::{Fetcher, policy::UrlPolicy},
+    };
+
+    use crate::common::skin_bytes;
+
+    /// Nothing listens there, requests fail right away.
+    const DOWN: &str = "http://127.0.0.1:1/{name}.png";
+
+    const INDEX: &str = r#"{"skins": [
+        {"name": "default", "creator": "Teeworlds", "license": "cc-by-sa-3.0"},
+        {"name": "bluestripe", "creator": "Teeworlds"},
+        {"name": "glow_rainbow"}
+    ]}"#;
+
+    #[test]
+    fn parse_wrapped_index() {
+        let client = SkinDbClient::new();
+        let entries = client.entries_from_json(INDEX.as_bytes()).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].name, "default");
+        assert_eq!(entries[0].url, "https://ddnet.org/skins/skin/default.png");
+        assert_eq!(entries[1].license, "");
+        assert_eq!(entries[2].creator, "");
+    }
+
+    #[test]
+    fn parse_flat_index_with_custom_source() {
+        let client = SkinDbClient::with_source(SkinDbSource::custom(
+            "https://example.com/index.json",
+            "https://example.com/{name}/{name}.png",
+        ));
+        let entries = client
+            .entries_from_json(br#"[{"name": "x_ninja"}]"#)
+            .unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].url, "https://example.com/x_ninja/x_ninja.png");
+    }
+
+    #[test]
+    fn urls_use_normalized_names() {
+        let source = SkinDbSource::TEEDATA;
+        assert_eq!(
+            source.url_for("  santa default.png ").unwrap(),
+            "https://teedata.net/databasev2/skins/santa%20default/santa%20default.png"
+        );
+        for name in [".", "..", " .. ", ""] {
+            assert!(matches!(
+                source.url_for(name),
+                Err(TeeError::UnknownSkin(_))
+            ));
+        }
+        let long = "a".repeat(100);
+        assert!(!source.url_for(&long).unwrap().contains(&long));
+
+        let client = SkinDbClient::new();
+        let entries = client
+            .entries_from_json(br#"[{"name": ".."}, {"name": "default"}]"#)
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "default");
+    }
+
+    fn response(
+        status: &str,
+        content_type: &str,
+        body: &[u8],
+    ) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    fn skin() -> Vec<u8> {
+        response("200 OK", "image/png", &skin_bytes())
+    }
+
+    /// Serves one request per response, returning the base URL and the request lines.
+    fn serve(responses: Vec<Vec<u8>>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = String::new();
+                    let mut reader = BufReader::new(&stream);
+                    reader.read_line(&mut request).unwrap();
+                    for line in reader.lines() {
+                        if line.unwrap().is_empty() {
+                            break;
+                        }
+                    }
+                    stream.write_all(&response).unwrap();
+                    request.trim().to_string()
+                })
+                .collect()
+        });
+        (url, server)
+    }
+
+    fn local(source: SkinDbSource) -> SkinDbClient {
+        let fetcher = Fetcher::new().with_url_policy(UrlPolicy::new().with_private_addresses(true));
+        SkinDbClient::with_source(source).with_fetcher(fetcher)
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_mirrors() {
+        let (mirror, server) = serve(vec![skin(), skin(), skin()]);
+        let client = local(SkinDbSource::from_asset_url(DOWN))
+            .with_mirror(SkinDbSource::from_asset_url(format!(
+                "{mirror}/mirror/{{name}}.png"
+            )))
+            .with_mirror_health(2, Duration::from_secs(60));
+
+        for failures in [1, 2, 2] {
+            client.fetch("default").await.unwrap();
+            let status = client.mirror_status();
+            assert_eq!(status[0].consecutive_failures, failures);
+            assert_eq!(status[0].healthy, failures < 2);
+            assert_eq!(status[1].consecutive_failures, 0);
+            assert!(status[1].healthy);
+        }
+        assert_eq!(
+            server.join().unwrap(),
+            ["GET /mirror/default.png HTTP/1.1"; 3]
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_skins_do_not_count_against_sources() {
+        let (primary, primary_server) =
+            serve(vec![response("404 Not Found", "text/html", b"gone")]);
+        let (mirror, mirror_server) = serve(vec![skin()]);
+        let client = local(SkinDbSource::from_asset_url(format!(
+            "{primary}/{{name}}.png"
+        )))
+        .with_mirror(SkinDbSource::from_asset_url(format!(
+            "{mirror}/{{name}}.png"
+        )));
+
+        client.fetch("santa default").await.unwrap();
+        assert!(
+            client
+                .mirror_status()
+                .iter()
+                .all(|status| status.consecutive_failures == 0)
+        );
+        assert_eq!(
+            primary_server.join().unwrap(),
+            ["GET /santa%20default.png HTTP/1.1"]
+        );
+        mirror_server.join().unwrap();
+
+        let err = local(SkinDbSource::from_asset_url(DOWN))
+            .fetch("default")
+            .await;
+        assert!(err.is_err());
+    }
+
+    #[tokio::test]
+    async fn dot_names_fail_without_a_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let client = local(SkinDbSource::from_asset_url(format!("{url}/{{name}}.png")));
+
+        for name in ["..", "."] {
+            let err = client.fetch(name).await;
+            assert!(matches!(err, Err(TeeError::UnknownSkin(_))), "{err:?}");
+            assert!(client.download_url(name).is_err());
+        }
+        assert!(listener.accept().is_err());
+        assert_eq!(client.mirror_status()[0].consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn reads_index_of_mirrors() {
+        let (mirror, server) = serve(vec![response(
+            "200 OK",
+            "application/json",
+            INDEX.as_bytes(),
+        )]);
+        let client = local(SkinDbSource::custom("http://127.0.0.1:1/index.json", DOWN))
+            .with_mirror(SkinDbSource::from_asset_url(
+                "https://assets.example.com/{name}.png",
+            ))
+            .with_mirror(SkinDbSource::custom(
+                format!("{mirror}/index.json"),
+                format!("{mirror}/{{name}}.png"),
+            ));
+
+        let entries = client.entries().await.unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].url, format!("{mirror}/default.png"));
+        assert_eq!(server.join().unwrap(), ["GET /index.json HTTP/1.1"]);
+        assert_eq!(client.mirror_status()[0].consecutive_failures, 1);
+    }
+}
@@ -0,0 +1,60 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use image::ImageFormat;
+    use tee_morphosis::{
+        error::TeeError,
+        tee::{
+            compositor::CompositorBackend, options::ComposeOptions, parts::EyeType,
+            skin::TEE_SKIN_LAYOUT,
+        },
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn finishes_before_the_deadline() {
+        let tee = tee();
+        let options = ComposeOptions::default();
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let data = tee
+            .compose_with_deadline(
+                TEE_SKIN_LAYOUT,
+                EyeType::Happy,
+                ImageFormat::Png,
+                &options,
+                deadline,
+            )
+            .unwrap();
+        let expected = tee
+            .compose_with_options(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png, &options)
+            .unwrap();
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn passed_deadline_fails_with_every_backend() {
+        let tee = tee();
+        for backend in [
+            CompositorBackend::Simple,
+            CompositorBackend::Tiled,
+            CompositorBackend::Parallel,
+        ] {
+            let options = ComposeOptions::new().with_backend(backend);
+            let result = tee.compose_with_deadline(
+                TEE_SKIN_LAYOUT,
+                EyeType::Normal,
+                ImageFormat::Png,
+                &options,
+                Instant::now(),
+            );
+            assert!(
+                matches!(result, Err(TeeError::DeadlineExceeded { .. })),
+                "{backend:?}"
+            );
+        }
+    }
+}
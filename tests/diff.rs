@@ -0,0 +1,67 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageFormat, Rgba, RgbaImage};
+    use tee_morphosis::{
+        diff::compare_images,
+        tee::{parts::EyeType, skin::TEE_SKIN_LAYOUT},
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn identical_renders() {
+        let tee = tee();
+        let a = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        let b = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+
+        let diff = compare_images(&a, &b);
+        assert!(diff.is_identical());
+        assert_eq!(diff.max_delta, 0);
+        assert_eq!(diff.diff_image.dimensions(), a.dimensions());
+        assert!(diff.diff_image.pixels().all(|pixel| pixel[0] == pixel[1]));
+    }
+
+    #[test]
+    fn counts_and_marks_changes() {
+        let tee = tee();
+        let normal = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        let happy = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy);
+
+        let diff = compare_images(&normal, &happy);
+        assert!(diff.differing_pixels > 0);
+        assert!(!diff.within(0));
+        let red = diff
+            .diff_image
+            .pixels()
+            .filter(|pixel| pixel[0] == 255 && pixel[1] == 0 && pixel[2] == 0)
+            .count();
+        assert_eq!(red, diff.differing_pixels);
+
+        let png = diff.diff_png().unwrap();
+        let decoded = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+        assert_eq!(decoded.to_rgba8(), diff.diff_image);
+    }
+
+    #[test]
+    fn small_and_invisible_changes() {
+        let a = RgbaImage::from_pixel(4, 4, Rgba([100, 100, 100, 255]));
+        let mut b = a.clone();
+        b.put_pixel(1, 1, Rgba([103, 100, 100, 255]));
+        let diff = compare_images(&a, &b);
+        assert_eq!(diff.differing_pixels, 1);
+        assert_eq!(diff.max_delta, 3);
+        assert!(diff.within(3));
+
+        // transparent pixels differing only in color look the same
+        let clear = RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 0]));
+        assert!(compare_images(&clear, &RgbaImage::new(2, 2)).is_identical());
+
+        // pixels outside the smaller image count as transparent
+        let diff = compare_images(&a, &RgbaImage::from_pixel(2, 4, Rgba([100, 100, 100, 255])));
+        assert_eq!(diff.diff_image.dimensions(), (4, 4));
+        assert_eq!(diff.differing_pixels, 8);
+        assert_eq!(diff.max_delta, 255);
+    }
+}
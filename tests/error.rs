@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use image::{
+        ImageError, ImageFormat,
+        error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind},
+    };
+    use tee_morphosis::{error::TeeError, locale::Locale, tee::Tee};
+
+    #[test]
+    fn dimensions_suggest_by_shape() {
+        let hint = |found| {
+            TeeError::InvalidDimensions {
+                expected: (256, 128),
+                found,
+            }
+            .suggestion()
+            .unwrap()
+        };
+        assert!(hint((512, 256)).contains("high resolution"));
+        assert!(hint((128, 256)).contains("upright"));
+        assert!(hint((300, 100)).contains("256x128"));
+    }
+
+    #[test]
+    fn jpeg_suggests_png() {
+        let error = TeeError::Image(ImageError::Unsupported(
+            UnsupportedError::from_format_and_kind(
+                ImageFormatHint::Exact(ImageFormat::Jpeg),
+                UnsupportedErrorKind::Format(ImageFormatHint::Exact(ImageFormat::Jpeg)),
+            ),
+        ));
+        assert!(error.suggestion().unwrap().contains("transparency"));
+    }
+
+    #[test]
+    fn parse_errors_carry_suggestions() {
+        let error = Tee::new(Bytes::from_static(b"not an image"), ImageFormat::Png).unwrap_err();
+        assert!(error.suggestion().is_some(), "{error:?}");
+        assert_eq!(TeeError::SelfTest("x".to_string()).suggestion(), None);
+    }
+
+    #[test]
+    fn locales_parse_from_tags() {
+        assert_eq!(Locale::from_tag("ru"), Some(Locale::Russian));
+        assert_eq!(Locale::from_tag("de-AT"), Some(Locale::German));
+        assert_eq!(Locale::from_tag("EN_us"), Some(Locale::English));
+        assert_eq!(Locale::from_tag("fr"), None);
+        for locale in Locale::ALL {
+            assert_eq!(Locale::from_tag(locale.tag()), Some(locale));
+        }
+    }
+
+    #[test]
+    fn messages_are_localized() {
+        let error = TeeError::InvalidDimensions {
+            expected: (256, 128),
+            found: (512, 256),
+        };
+        assert_eq!(error.localized(Locale::English), error.to_string());
+        assert_eq!(error.suggestion_in(Locale::English), error.suggestion());
+
+        let russian = error.localized(Locale::Russian);
+        let german = error.localized(Locale::German);
+        assert!(russian.contains("512x256"), "{russian}");
+        assert!(german.starts_with("Ungültige Bildgröße"), "{german}");
+        assert_ne!(
+            error.suggestion_in(Locale::Russian),
+            error.suggestion_in(Locale::German)
+        );
+    }
+}
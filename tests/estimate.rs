@@ -0,0 +1,48 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageFormat, imageops};
+    use tee_morphosis::{
+        estimate::estimate_encoded_size,
+        meta::RenderMeta,
+        tee::{options::ComposeOptions, parts::EyeType, raw::encode_image, skin::TEE_SKIN_LAYOUT},
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn close_to_real_size() {
+        let render = tee().compose_image(TEE_SKIN_LAYOUT, EyeType::Happy);
+        let large = imageops::resize(&render, 384, 256, imageops::FilterType::Triangle);
+        let options = ComposeOptions::new();
+        for img in [&render, &large] {
+            for format in [ImageFormat::Png, ImageFormat::WebP, ImageFormat::Gif] {
+                let real = encode_image(img, format).unwrap().len() as f64;
+                let estimate = estimate_encoded_size(img, format, &options) as f64;
+                let ratio = real / estimate;
+                assert!(
+                    (0.5..2.0).contains(&ratio),
+                    "{format:?}: {real} vs {estimate}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn metadata_and_raw_formats() {
+        let render = tee().compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        let plain = ComposeOptions::new();
+        let meta = ComposeOptions::new().with_metadata(RenderMeta::new().with_skin_name("x"));
+        for format in [ImageFormat::Png, ImageFormat::WebP] {
+            assert!(
+                estimate_encoded_size(&render, format, &meta)
+                    > estimate_encoded_size(&render, format, &plain)
+            );
+        }
+        assert_eq!(
+            estimate_encoded_size(&render, ImageFormat::Bmp, &plain),
+            96 * 64 * 4
+        );
+    }
+}
@@ -0,0 +1,91 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{GenericImageView, ImageFormat, Rgba, RgbaImage};
+    use tee_morphosis::{
+        etag::is_not_modified,
+        tee::{options::ComposeOptions, parts::EyeType, skin::TEE_SKIN_LAYOUT},
+        watermark::Watermark,
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn tee_etag_follows_parts() {
+        let mut tee = tee();
+        let etag = tee.etag();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(etag, self::tee().etag());
+
+        tee.apply_hsl_to_all((0.5, 0.5, 0.5));
+        assert_ne!(tee.etag(), etag);
+    }
+
+    #[test]
+    fn composed_etag_matches_precomputed() {
+        let tee = tee();
+        let options = ComposeOptions::new();
+        let image = tee
+            .compose_tagged(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::WebP, &options)
+            .unwrap();
+        assert_eq!(
+            image.etag(),
+            tee.compose_etag(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::WebP, &options)
+        );
+        assert_eq!(image.content_type(), "image/webp");
+
+        let other_eye =
+            tee.compose_etag(TEE_SKIN_LAYOUT, EyeType::Pain, ImageFormat::WebP, &options);
+        let other_format =
+            tee.compose_etag(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png, &options);
+        let watermarked = tee.compose_etag(
+            TEE_SKIN_LAYOUT,
+            EyeType::Happy,
+            ImageFormat::WebP,
+            &ComposeOptions::new().with_watermark(Watermark::image(RgbaImage::from_pixel(
+                2,
+                2,
+                Rgba([255; 4]),
+            ))),
+        );
+        for etag in [other_eye, other_format, watermarked] {
+            assert_ne!(etag, image.etag());
+        }
+    }
+
+    #[test]
+    fn conditional_requests() {
+        let etag = "\"abc\"";
+        assert!(is_not_modified(Some("\"abc\""), etag));
+        assert!(is_not_modified(Some("W/\"abc\""), etag));
+        assert!(is_not_modified(Some("\"x\", \"abc\""), etag));
+        assert!(is_not_modified(Some("*"), etag));
+        assert!(!is_not_modified(Some("\"abcd\""), etag));
+        assert!(!is_not_modified(None::<&str>, etag));
+    }
+
+    #[test]
+    fn encode_all_formats() {
+        let tee = tee();
+        let options = ComposeOptions::new()
+            .with_metadata(tee_morphosis::meta::RenderMeta::new().with_skin_name("x"));
+        let image = tee
+            .compose_tagged(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png, &options)
+            .unwrap();
+        let formats = [ImageFormat::Png, ImageFormat::WebP, ImageFormat::Gif];
+        let encoded = image.encode_all(&formats).unwrap();
+        assert_eq!(encoded.len(), 3);
+        assert_eq!(encoded[&ImageFormat::Png], image.data);
+
+        let webp = tee
+            .compose_with_options(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::WebP, &options)
+            .unwrap();
+        assert_eq!(encoded[&ImageFormat::WebP], webp);
+        for format in formats {
+            let decoded = image::load_from_memory_with_format(&encoded[&format], format).unwrap();
+            assert_eq!(decoded.dimensions(), image.canvas().dimensions());
+        }
+        assert!(image.encode_all(&[]).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::ImageFormat;
+    use tee_morphosis::tee::{
+        expression::Expression,
+        options::ComposeOptions,
+        parts::{EyePair, EyeType},
+        skin::TEE_SKIN_LAYOUT,
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn wink_is_the_eye_pair() {
+        let tee = tee();
+        let png = tee
+            .compose_expression(TEE_SKIN_LAYOUT, Expression::Wink, ImageFormat::Png)
+            .unwrap();
+        let expected = tee
+            .compose(TEE_SKIN_LAYOUT, EyePair::WINK, ImageFormat::Png)
+            .unwrap();
+        assert_eq!(png, expected);
+    }
+
+    #[test]
+    fn cry_adds_tears_and_lowers_eyes() {
+        let (skin, options) = Expression::Cry.apply(TEE_SKIN_LAYOUT, ComposeOptions::new());
+        assert_eq!(options.extra_layers.len(), 2);
+        assert!(skin.first_eyes.0.1 > TEE_SKIN_LAYOUT.first_eyes.0.1);
+        assert_eq!(skin.body, TEE_SKIN_LAYOUT.body);
+        assert_eq!(
+            Expression::Cry.eyes(),
+            EyePair::new(EyeType::Pain, EyeType::Pain)
+        );
+
+        let tee = tee();
+        let crying = tee.compose_image_with_options(skin, Expression::Cry.eyes(), &options);
+        let plain = tee.compose_image(skin, Expression::Cry.eyes());
+        // Tears are light blue
+        let blue = crying
+            .pixels()
+            .zip(plain.pixels())
+            .filter(|(a, b)| a != b)
+            .any(|(pixel, _)| pixel.0[2] as i32 > pixel.0[0] as i32 + 40);
+        assert!(blue);
+    }
+
+    #[test]
+    fn every_expression_renders() {
+        let tee = tee();
+        for expression in Expression::ALL {
+            let png = tee
+                .compose_expression(TEE_SKIN_LAYOUT, expression, ImageFormat::Png)
+                .unwrap();
+            assert!(!png.is_empty(), "{}", expression.name());
+        }
+    }
+}
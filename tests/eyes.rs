@@ -0,0 +1,186 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use image::{ImageFormat, Rgba, RgbaImage};
+    use tee_morphosis::{
+        error::TeeError,
+        tee::{
+            Tee,
+            options::ComposeOptions,
+            parts::{EyePair, EyeSelection, EyeType},
+            skin::TEE_SKIN_LAYOUT,
+            uv::TEE_UV_LAYOUT,
+        },
+    };
+
+    use crate::common::{skin_path, tee};
+
+    /// Returns the first and last rows holding a visible pixel.
+    fn visible_rows(img: &image::RgbaImage) -> (u32, u32) {
+        let rows: Vec<u32> = img
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p.0[3] != 0)
+            .map(|(_, y, _)| y)
+            .collect();
+        (*rows.iter().min().unwrap(), *rows.iter().max().unwrap())
+    }
+
+    #[test]
+    fn blink_is_squashed_normal_eye() {
+        let tee = tee();
+        let normal = tee.get_eye(EyeType::Normal);
+        let blink = tee.get_eye(EyeType::Blink);
+        assert_eq!(normal.dimensions(), blink.dimensions());
+
+        let (normal_top, normal_bottom) = visible_rows(normal);
+        let (blink_top, blink_bottom) = visible_rows(blink);
+        assert!(blink_bottom - blink_top < normal_bottom - normal_top);
+        assert!(blink_top >= normal.height() / 4);
+        assert!(tee.eye.iter().all(|eye| eye.image() != blink));
+    }
+
+    #[test]
+    fn every_eye_type_is_available() {
+        let tee = tee();
+        assert_eq!(tee.get_all_eyes().len(), EyeType::ALL.len());
+        for eye_type in EyeType::ALL {
+            assert_eq!(EyeType::ALL[eye_type.index()], eye_type);
+        }
+        let blink = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Blink);
+        let normal = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        assert_ne!(blink, normal);
+    }
+
+    #[test]
+    fn custom_eye_is_composed() {
+        let mut tee = tee();
+        let dead = RgbaImage::from_pixel(32, 32, Rgba([255, 0, 0, 255]));
+        tee.add_custom_eye("dead", dead.clone()).unwrap();
+
+        assert_eq!(tee.select_eye("dead"), Some(&dead));
+        assert_eq!(tee.custom_eye_names().collect::<Vec<_>>(), ["dead"]);
+
+        let custom = tee.compose_image(TEE_SKIN_LAYOUT, EyeSelection::Custom("dead"));
+        let normal = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        assert_ne!(custom, normal);
+    }
+
+    #[test]
+    fn unknown_custom_eye_falls_back_to_normal() {
+        let tee = tee();
+        assert_eq!(tee.select_eye("x_eyes"), None);
+        assert_eq!(
+            tee.compose_image(TEE_SKIN_LAYOUT, "x_eyes"),
+            tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal)
+        );
+    }
+
+    #[test]
+    fn custom_eye_with_wrong_size() {
+        let mut tee = tee();
+        let result = tee.add_custom_eye("big", RgbaImage::new(64, 64));
+        assert!(matches!(
+            result,
+            Err(TeeError::InvalidDimensions {
+                expected: (32, 32),
+                found: (64, 64)
+            })
+        ));
+        assert!(tee.remove_custom_eye("big").is_none());
+    }
+
+    #[test]
+    fn blank_eye_falls_back_to_normal() {
+        let mut sheet = image::open(skin_path()).unwrap().to_rgba8();
+        let pain = TEE_UV_LAYOUT.eyes[EyeType::Pain.index()];
+        for y in pain.y..pain.y + pain.h {
+            for x in pain.x..pain.x + pain.w {
+                sheet.put_pixel(x, y, Rgba([0; 4]));
+            }
+        }
+        let mut png = Vec::new();
+        sheet
+            .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        let tee = Tee::new(Bytes::from(png), ImageFormat::Png).unwrap();
+
+        assert!(tee.is_eye_blank(EyeType::Pain));
+        assert!(!tee.is_eye_blank(EyeType::Normal));
+        assert!(!self::tee().is_eye_blank(EyeType::Pain));
+        let mut edited = self::tee();
+        edited.get_mut(EyeType::Angry).fill(0);
+        assert!(edited.is_eye_blank(EyeType::Angry));
+
+        let normal = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        let blank = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Pain);
+        assert_ne!(blank, normal);
+        assert_eq!(
+            tee.compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Pain, &ComposeOptions::new()),
+            blank
+        );
+        let options = ComposeOptions::new().with_blank_eye_fallback(true);
+        assert_eq!(
+            tee.compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Pain, &options),
+            normal
+        );
+    }
+
+    #[test]
+    fn eye_color_paints_only_dark_pixels() {
+        let mut tee = Tee::random(7, None);
+        let mut highlight = RgbaImage::from_pixel(
+            tee.get_eye(EyeType::Normal).width(),
+            tee.get_eye(EyeType::Normal).height(),
+            Rgba([10, 10, 10, 255]),
+        );
+        highlight.put_pixel(0, 0, Rgba([250, 250, 250, 255]));
+        tee.add_custom_eye("glint", highlight).unwrap();
+        let before = tee.get_eye(EyeType::Angry).clone();
+
+        tee.set_eye_color((0.0, 1.0, 0.4));
+
+        let after = tee.get_eye(EyeType::Angry);
+        for (old, new) in before.pixels().zip(after.pixels()) {
+            assert_eq!(old.0[3], new.0[3]);
+            if old.0[3] != 0 {
+                assert!(new.0[0] > new.0[1] && new.0[0] > new.0[2], "{new:?}");
+            }
+        }
+        let glint = tee.custom_eye("glint").unwrap();
+        assert_eq!(glint.get_pixel(0, 0), &Rgba([250, 250, 250, 255]));
+        assert!(glint.get_pixel(1, 1).0[0] > 150);
+    }
+
+    #[test]
+    fn eye_pair_draws_different_eyes() {
+        let tee = tee();
+        let same = tee.compose_image(
+            TEE_SKIN_LAYOUT,
+            EyePair::new(EyeType::Angry, EyeType::Angry),
+        );
+        assert_eq!(same, tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Angry));
+
+        let wink = tee.compose_image(TEE_SKIN_LAYOUT, EyePair::WINK);
+        assert_ne!(wink, tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy));
+        assert_ne!(wink, tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Empty));
+
+        let selection = EyeSelection::from(EyePair::WINK);
+        assert_eq!(selection.name(), "happy+empty");
+        assert_ne!(
+            tee.compose_etag(
+                TEE_SKIN_LAYOUT,
+                selection,
+                ImageFormat::Png,
+                &ComposeOptions::new()
+            ),
+            tee.compose_etag(
+                TEE_SKIN_LAYOUT,
+                EyeType::Happy,
+                ImageFormat::Png,
+                &ComposeOptions::new()
+            )
+        );
+    }
+}
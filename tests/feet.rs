@@ -0,0 +1,64 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::ImageFormat;
+    use tee_morphosis::tee::{
+        options::{ComposeOptions, FeetStyle},
+        parts::EyeType,
+        skin::TEE_SKIN_LAYOUT,
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn default_keeps_feet_equal() {
+        let tee = tee();
+        assert_eq!(
+            tee.compose_image_with_options(
+                TEE_SKIN_LAYOUT,
+                EyeType::Normal,
+                &ComposeOptions::new()
+            ),
+            tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal)
+        );
+    }
+
+    #[test]
+    fn darkens_only_the_back_foot() {
+        let tee = tee();
+        let plain = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        let options = ComposeOptions::new().with_feet(FeetStyle::new(0.3));
+        let shaded = tee.compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Normal, &options);
+        assert_eq!(shaded.dimensions(), plain.dimensions());
+
+        let brightness = |x: u32, y: u32| {
+            let [r, g, b, _] = shaded.get_pixel(x, y).0;
+            r as u32 + g as u32 + b as u32
+        };
+        let plain_brightness = |x: u32, y: u32| {
+            let [r, g, b, _] = plain.get_pixel(x, y).0;
+            r as u32 + g as u32 + b as u32
+        };
+        // The back foot sticks out left of the front foot
+        assert!(brightness(30, 46) < plain_brightness(30, 46));
+        // The front foot is drawn on top
+        assert_eq!(shaded.get_pixel(60, 46), plain.get_pixel(60, 46));
+
+        assert_ne!(
+            tee.compose_etag(TEE_SKIN_LAYOUT, EyeType::Normal, ImageFormat::Png, &options),
+            tee.compose_etag(
+                TEE_SKIN_LAYOUT,
+                EyeType::Normal,
+                ImageFormat::Png,
+                &ComposeOptions::new()
+            )
+        );
+    }
+
+    #[test]
+    fn darkening_is_clamped() {
+        assert_eq!(FeetStyle::new(2.0).darken_back, 1.0);
+        assert_eq!(FeetStyle::new(-1.0).darken_back, 0.0);
+    }
+}
@@ -0,0 +1,58 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::ImageFormat;
+    use tee_morphosis::{
+        scene::gallery::{GalleryItem, GalleryStyle, gallery_scenes, render_gallery},
+        tee::{parts::EyeType, skin::TEE_SKIN_LAYOUT},
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn pages_and_grid() {
+        let tees = vec![tee(); 23];
+        let pages = render_gallery(&tees, 5, (48, 32), ImageFormat::Png).unwrap();
+        // 5 columns x 4 rows per page
+        assert_eq!(pages.len(), 2);
+
+        let first = image::load_from_memory(&pages[0]).unwrap();
+        assert_eq!(first.width(), 8 + 5 * (48 + 8));
+        assert_eq!(first.height(), 8 + 4 * (32 + 8));
+        // the last page holds 3 tees in a single row
+        let last = image::load_from_memory(&pages[1]).unwrap();
+        assert_eq!((last.width(), last.height()), (8 + 3 * 56, 8 + 40));
+
+        assert!(
+            render_gallery(&[], 5, (48, 32), ImageFormat::Png)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn cells_hold_scaled_tees_in_order() {
+        let tee = tee();
+        let items: Vec<GalleryItem> = vec![(&tee, "a").into(), (&tee).into()];
+        let style = GalleryStyle {
+            columns: 2,
+            rows: 1,
+            cell_size: (96, 64),
+            eye: EyeType::Happy,
+            ..GalleryStyle::default()
+        };
+        let scenes = gallery_scenes(&items, &style);
+        assert_eq!(scenes.len(), 1);
+
+        let expected = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy);
+        let positions: Vec<_> = scenes[0]
+            .items()
+            .map(|(_, item)| {
+                assert_eq!(item.image, expected);
+                item.position
+            })
+            .collect();
+        assert_eq!(positions, vec![(8, 8), (112, 8)]);
+    }
+}
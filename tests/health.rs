@@ -0,0 +1,13 @@
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::health::SELF_TEST_FORMATS;
+
+    #[test]
+    fn self_test_passes() {
+        let report = tee_morphosis::self_test().unwrap();
+        let formats: Vec<_> = report.encode.iter().map(|(format, _)| *format).collect();
+        assert_eq!(formats, SELF_TEST_FORMATS);
+        assert!(report.threads >= 1);
+        assert!(report.total() >= report.parse + report.compose);
+    }
+}
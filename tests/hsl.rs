@@ -35,7 +35,7 @@ mod tests {
         let s_byte = (s * 255.0).clamp(0.0, 255.0) as u32;
         let l_byte = (l_clamped * 255.0).clamp(0.0, 255.0) as u32;
 
-        (h_byte << 16) | (s_byte << 8) | l_byte + 1
+        (h_byte << 16) | (s_byte << 8) | (l_byte + 1)
     }
 
     /// Конвертирует HSL в RGB
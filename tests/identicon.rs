@@ -0,0 +1,43 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::ImageFormat;
+    use tee_morphosis::tee::{Tee, identicon::IDENTICON_EYES, skin::TEE_SKIN_LAYOUT};
+
+    use crate::common::tee;
+
+    #[test]
+    fn stable_per_name() {
+        let base = tee();
+        let first = Tee::identicon("nameless tee", &base);
+        let again = Tee::identicon("nameless tee", &base);
+        assert_eq!(first.body_color, again.body_color);
+        assert_eq!(first.feet_color, again.feet_color);
+        assert_eq!(first.eye, again.eye);
+        assert_eq!(first.tee.etag(), again.tee.etag());
+        assert_eq!(
+            first.compose(TEE_SKIN_LAYOUT, ImageFormat::Png).unwrap(),
+            again.compose(TEE_SKIN_LAYOUT, ImageFormat::Png).unwrap()
+        );
+        assert_ne!(first.tee.etag(), base.etag());
+
+        let other = Tee::identicon("brainless tee", &base);
+        assert_ne!(
+            (first.body_color, first.feet_color),
+            (other.body_color, other.feet_color)
+        );
+    }
+
+    #[test]
+    fn colors_are_saturated() {
+        let base = tee();
+        for name in ["a", "b", "c", "d", "e", "f", "g", "h"] {
+            let identicon = Tee::identicon(name, &base);
+            for color in [identicon.body_color, identicon.feet_color] {
+                assert!((color >> 8) & 0xFF >= 128);
+            }
+            assert!(IDENTICON_EYES.contains(&identicon.eye));
+        }
+    }
+}
@@ -0,0 +1,58 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage, imageops};
+    use tee_morphosis::{
+        identify::{SkinIndex, identify_from_render},
+        tee::{Tee, parts::EyeType, skin::TEE_SKIN_LAYOUT, team::TeamColor},
+    };
+
+    use crate::common::tee;
+
+    /// The tee rendered twice as large onto a dark map background.
+    fn screenshot(tee: &Tee) -> RgbaImage {
+        let render = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        let (width, height) = render.dimensions();
+        let render = imageops::resize(
+            &render,
+            width * 2,
+            height * 2,
+            imageops::FilterType::Triangle,
+        );
+
+        let mut crop =
+            RgbaImage::from_pixel(width * 2 + 40, height * 2 + 40, Rgba([20, 30, 40, 255]));
+        imageops::overlay(&mut crop, &render, 20, 20);
+        crop
+    }
+
+    #[test]
+    fn identifies_skin_from_screenshot() {
+        let mut red = tee();
+        red.apply_team_color(TeamColor::Red);
+        let mut blue = tee();
+        blue.apply_team_color(TeamColor::Blue);
+
+        let mut index = SkinIndex::new();
+        index.insert("red", &red);
+        index.insert("original", &tee());
+        index.insert("blue", &blue);
+
+        let candidates = identify_from_render(&screenshot(&tee()), &index);
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates[0].name, "original");
+        assert!(candidates[0].score > candidates[1].score);
+
+        let candidates = identify_from_render(&screenshot(&blue), &index);
+        assert_eq!(candidates[0].name, "blue");
+    }
+
+    #[test]
+    fn empty_crop() {
+        let mut index = SkinIndex::new();
+        index.insert("original", &tee());
+        let candidates = identify_from_render(&RgbaImage::new(0, 0), &index);
+        assert_eq!(candidates.len(), 1);
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageFormat, Rgba, RgbaImage};
+    use tee_morphosis::tee::{
+        layer::{Layer, ZOrder},
+        options::ComposeOptions,
+        parts::EyeType,
+        skin::TEE_SKIN_LAYOUT,
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn default_order_matches_compose() {
+        let tee = tee();
+        let options = ComposeOptions::new().with_layer_order(Layer::DEFAULT_ORDER);
+        assert_eq!(ComposeOptions::new().layer_order, Layer::DEFAULT_ORDER);
+        assert_eq!(
+            tee.compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Angry, &options),
+            tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Angry)
+        );
+    }
+
+    #[test]
+    fn eyes_under_body() {
+        let tee = tee();
+        let mut order = Layer::DEFAULT_ORDER.to_vec();
+        order.retain(|layer| !layer.is_eye());
+        order.splice(0..0, [Layer::FirstEye, Layer::SecondEye]);
+        let options = ComposeOptions::new().with_layer_order(order.clone());
+
+        let hidden = tee.compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Angry, &options);
+        assert_ne!(hidden, tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Angry));
+        // The opaque body covers the eyes completely
+        let without_eyes = ComposeOptions::new().with_layer_order(order[2..].to_vec());
+        assert_eq!(
+            hidden,
+            tee.compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Angry, &without_eyes)
+        );
+        assert_ne!(
+            tee.compose_etag(TEE_SKIN_LAYOUT, EyeType::Angry, ImageFormat::Png, &options),
+            tee.compose_etag(
+                TEE_SKIN_LAYOUT,
+                EyeType::Angry,
+                ImageFormat::Png,
+                &ComposeOptions::new()
+            )
+        );
+    }
+
+    #[test]
+    fn empty_order_is_transparent() {
+        let options = ComposeOptions::new().with_layer_order([]);
+        let image = tee().compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Normal, &options);
+        assert_eq!(image.dimensions(), TEE_SKIN_LAYOUT.output_size());
+        assert!(image.pixels().all(|pixel| pixel.0[3] == 0));
+    }
+
+    fn red() -> RgbaImage {
+        RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 255]))
+    }
+
+    #[test]
+    fn extra_layers_follow_z_order() {
+        let tee = tee();
+        let plain = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        let compose = |options: ComposeOptions| {
+            tee.compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Normal, &options)
+        };
+
+        // Under the opaque body
+        let hidden = compose(ComposeOptions::new().with_extra_layer(
+            red(),
+            ((44., 20.), 1.),
+            ZOrder::BeforeBody,
+        ));
+        assert_eq!(hidden, plain);
+
+        let on_eyes = compose(ComposeOptions::new().with_extra_layer(
+            red(),
+            ((44., 20.), 1.),
+            ZOrder::AfterEyes,
+        ));
+        assert_eq!(on_eyes.get_pixel(45, 21), &Rgba([255, 0, 0, 255]));
+
+        // Scaled like a part
+        let top =
+            compose(ComposeOptions::new().with_extra_layer(red(), ((0., 0.), 2.), ZOrder::Topmost));
+        assert_eq!(top.get_pixel(7, 7), &Rgba([255, 0, 0, 255]));
+        assert_ne!(top.get_pixel(8, 8), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn extra_layers_without_anchor_are_drawn_on_top() {
+        let mut order = Layer::DEFAULT_ORDER.to_vec();
+        order.retain(|layer| *layer != Layer::Body);
+        let options = ComposeOptions::new()
+            .with_layer_order(order)
+            .with_extra_layer(red(), ((44., 40.), 1.), ZOrder::BeforeBody);
+        let image = tee().compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Normal, &options);
+        assert_eq!(image.get_pixel(45, 41), &Rgba([255, 0, 0, 255]));
+    }
+}
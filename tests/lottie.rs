@@ -0,0 +1,51 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::{
+        lottie::{LottieOptions, to_lottie},
+        tee::{parts::EyeType, skin::TEE_SKIN_LAYOUT},
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn still_export() {
+        let json = to_lottie(&tee(), TEE_SKIN_LAYOUT, &LottieOptions::new()).unwrap();
+        assert!(json.starts_with(r#"{"v":"5.7.4","fr":30,"ip":0,"op":60,"w":96,"h":64"#));
+        // 5 body and feet layers, 2 eyes, front feet
+        assert_eq!(json.matches(r#""ty":2"#).count(), 8);
+        assert_eq!(json.matches("data:image/png;base64,iVBORw0KGgo").count(), 8);
+        // the front feet are drawn last, so they come first
+        assert!(json.contains(r#""layers":[{"ddd":0,"ind":1,"ty":2,"nm":"feet""#));
+        assert!(!json.contains(r#""a":1"#));
+    }
+
+    #[test]
+    fn eye_swap_and_bounce() {
+        let options = LottieOptions::new()
+            .with_frames(90)
+            .with_eyes([EyeType::Normal, EyeType::Blink, EyeType::Happy])
+            .with_foot_bounce(3);
+        let json = to_lottie(&tee(), TEE_SKIN_LAYOUT, &options).unwrap();
+
+        assert!(json.contains(r#""nm":"eye_blink_1_first""#));
+        assert!(json.contains(r#""ip":30,"op":60"#));
+        assert!(json.contains(r#""ip":60,"op":90"#));
+        // both feet and their shadows bounce
+        assert_eq!(json.matches(r#""a":1"#).count(), 4);
+        assert!(json.contains(r#"{"t":45,"s":[24,27,0]"#));
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn valid_json() {
+        let options = LottieOptions::new()
+            .with_eyes([EyeType::Angry, EyeType::Pain])
+            .with_foot_bounce(2);
+        let json = to_lottie(&tee(), TEE_SKIN_LAYOUT, &options).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["layers"].as_array().unwrap().len(), 10);
+        assert_eq!(value["assets"].as_array().unwrap().len(), 10);
+    }
+}
@@ -40,9 +40,7 @@ mod tests {
             fixture
         );
         let skin_data = fs::read(&fixture).expect("Failed to read fixture file");
-        let tee = Tee::new(Bytes::from(skin_data), image::ImageFormat::Png)
-            .expect("Failed to parse TeeRaw");
-        tee
+        Tee::new(Bytes::from(skin_data), image::ImageFormat::Png).expect("Failed to parse TeeRaw")
     }
 
     #[test]
@@ -177,7 +175,7 @@ mod tests {
             EyeType::Surprise,
         ] {
             let image_bytes = tee
-                .compose(TEE_SKIN_LAYOUT, eye_type.clone(), image::ImageFormat::WebP)
+                .compose(TEE_SKIN_LAYOUT, eye_type, image::ImageFormat::WebP)
                 .expect("Failed to compose image");
             let filename = format!("composed_{:?}.webp", eye_type).to_lowercase();
             fs::write(output_dir.join(filename), image_bytes.as_bytes())
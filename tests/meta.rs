@@ -0,0 +1,222 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use image::ImageFormat;
+    use tee_morphosis::{
+        RENDER_VERSION,
+        error::TeeError,
+        meta::{GENERATOR, RenderMeta, RenderRecipe, render_filename},
+        tee::{options::ComposeOptions, parts::EyeType, skin::TEE_SKIN_LAYOUT},
+    };
+
+    use crate::common::tee;
+
+    fn contains(
+        haystack: &[u8],
+        needle: &[u8],
+    ) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    fn options() -> ComposeOptions {
+        ComposeOptions::new().with_metadata(
+            RenderMeta::new()
+                .with_skin_name("nameless tee")
+                .with_source_url("https://example.com/a&b.png")
+                .with_colors(65408, 10223541),
+        )
+    }
+
+    #[test]
+    fn embeds_png_text_chunks() {
+        let tee = tee();
+        let bytes = tee
+            .compose_with_options(
+                TEE_SKIN_LAYOUT,
+                EyeType::Happy,
+                ImageFormat::Png,
+                &options(),
+            )
+            .unwrap();
+
+        assert!(contains(
+            &bytes,
+            format!("tEXtSoftware\0{GENERATOR}").as_bytes()
+        ));
+        assert!(contains(&bytes, b"tEXttee_morphosis:skin\0nameless tee"));
+        assert!(contains(&bytes, b"tee_morphosis:eye\0happy"));
+        assert!(contains(&bytes, b"tee_morphosis:body_color\x0065408"));
+        assert!(contains(
+            &bytes,
+            format!("tee_morphosis:render_version\0{RENDER_VERSION}").as_bytes()
+        ));
+        assert!(contains(&bytes, tee.source_hash().to_hex().as_bytes()));
+
+        let plain = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy);
+        let decoded = image::load_from_memory_with_format(&bytes, ImageFormat::Png)
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(decoded, plain);
+    }
+
+    #[test]
+    fn embeds_png_itxt_for_unicode() {
+        let meta = RenderMeta::new().with_skin_name("тии");
+        let bytes = tee()
+            .compose_with_options(
+                TEE_SKIN_LAYOUT,
+                EyeType::Normal,
+                ImageFormat::Png,
+                &ComposeOptions::new().with_metadata(meta),
+            )
+            .unwrap();
+        assert!(contains(
+            &bytes,
+            "iTXttee_morphosis:skin\0\0\0\0\0тии".as_bytes()
+        ));
+        image::load_from_memory_with_format(&bytes, ImageFormat::Png).unwrap();
+    }
+
+    #[test]
+    fn embeds_webp_xmp() {
+        let tee = tee();
+        let bytes = tee
+            .compose_with_options(
+                TEE_SKIN_LAYOUT,
+                EyeType::Normal,
+                ImageFormat::WebP,
+                &options(),
+            )
+            .unwrap();
+
+        assert_eq!(&bytes[12..16], b"VP8X");
+        assert_eq!(bytes[20] & 0x04, 0x04);
+        assert_eq!(
+            u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize,
+            bytes.len() - 8
+        );
+        assert!(contains(&bytes, b"XMP "));
+        assert!(contains(&bytes, b"tm:skin=\"nameless tee\""));
+        assert!(contains(
+            &bytes,
+            b"tm:source_url=\"https://example.com/a&amp;b.png\""
+        ));
+        assert!(contains(&bytes, b"tm:eye=\"normal\""));
+
+        let plain = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        let decoded = image::load_from_memory_with_format(&bytes, ImageFormat::WebP)
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(decoded, plain);
+    }
+
+    #[test]
+    fn reads_back_png_metadata() {
+        let tee = tee();
+        let meta = RenderMeta::new()
+            .with_skin_name("тии")
+            .with_source_url("https://example.com/skin.png")
+            .with_colors(65408, 10223541);
+        let bytes = tee
+            .compose_with_options(
+                TEE_SKIN_LAYOUT,
+                EyeType::Pain,
+                ImageFormat::Png,
+                &ComposeOptions::new().with_metadata(meta.clone()),
+            )
+            .unwrap();
+
+        let read = RenderMeta::from_png(&bytes).unwrap().unwrap();
+        assert_eq!(read.render_version, Some(RENDER_VERSION));
+        let plain = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Pain);
+        assert_eq!(
+            read,
+            meta.with_eye("pain")
+                .with_size(plain.width(), plain.height())
+                .with_source_hash(tee.source_hash())
+        );
+    }
+
+    #[test]
+    fn reads_nothing_from_plain_png() {
+        let bytes = tee()
+            .compose(TEE_SKIN_LAYOUT, EyeType::Normal, ImageFormat::Png)
+            .unwrap();
+        assert_eq!(RenderMeta::from_png(&bytes).unwrap(), None);
+        assert!(RenderMeta::from_png(b"not a png").is_err());
+    }
+
+    #[test]
+    fn filenames_are_stable() {
+        let recipe = RenderRecipe::new("nameless tee", "happy", (96, 64));
+        let name = render_filename(&recipe);
+        assert_eq!(name.len(), 32);
+        assert!(name.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(name, render_filename(&recipe.clone()));
+
+        let colored = recipe.clone().with_colors(65408, 10223541);
+        assert_ne!(render_filename(&colored), name);
+        assert_ne!(
+            render_filename(&RenderRecipe::new("nameless te", "ehappy", (96, 64))),
+            name
+        );
+        assert_eq!(
+            render_filename(&recipe.clone().with_format(ImageFormat::WebP)),
+            format!("{name}.webp")
+        );
+    }
+
+    #[test]
+    fn recipe_from_metadata() {
+        let meta = RenderMeta::new()
+            .with_skin_name("nameless tee")
+            .with_eye("happy")
+            .with_size(96, 64);
+        assert_eq!(
+            RenderRecipe::from(&meta),
+            RenderRecipe::new("nameless tee", "happy", (96, 64))
+        );
+    }
+
+    #[test]
+    fn other_formats_are_untouched() {
+        let data = Bytes::from_static(b"GIF89a");
+        let embedded = RenderMeta::new()
+            .embed(data.clone(), ImageFormat::Gif)
+            .unwrap();
+        assert_eq!(embedded, data);
+        assert!(RenderMeta::new().embed(data, ImageFormat::Png).is_err());
+    }
+
+    /// Builds a RIFF WebP container from `(kind, payload)` chunks.
+    fn riff(chunks: &[(&[u8; 4], &[u8])]) -> Bytes {
+        let mut body = b"WEBP".to_vec();
+        for (kind, payload) in chunks {
+            body.extend_from_slice(*kind);
+            body.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            body.extend_from_slice(payload);
+            if payload.len() % 2 == 1 {
+                body.push(0);
+            }
+        }
+        let mut out = b"RIFF".to_vec();
+        out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        out.extend_from_slice(&body);
+        Bytes::from(out)
+    }
+
+    #[test]
+    fn malformed_webp_is_rejected() {
+        // VP8 key frame header with a width and height of 0
+        let sizeless = [0x10, 0x02, 0x00, 0x9d, 0x01, 0x2a, 0, 0, 0, 0];
+        let truncated_vp8x = riff(&[(b"VP8X", &[]), (b"VP8 ", &sizeless)]);
+        for data in [riff(&[(b"VP8 ", &sizeless)]), truncated_vp8x] {
+            assert!(matches!(
+                RenderMeta::new().embed(data, ImageFormat::WebP),
+                Err(TeeError::MalformedContainer(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,83 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use image::{ImageFormat, RgbaImage};
+    use tee_morphosis::{
+        error::TeeError,
+        moderation::{ContentChecker, Verdict},
+        tee::{Tee, options::ParseOptions, uv::TEE_UV_LAYOUT},
+    };
+
+    use crate::common::skin_bytes;
+
+    #[derive(Debug, Default)]
+    struct Counting {
+        calls: AtomicUsize,
+        reject: bool,
+    }
+
+    impl ContentChecker for Counting {
+        fn check(
+            &self,
+            body: &RgbaImage,
+        ) -> Verdict {
+            assert_eq!(body.dimensions(), (96, 96));
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            if self.reject { Verdict::Reject("no".into()) } else { Verdict::Accept }
+        }
+    }
+
+    #[test]
+    fn checker_is_invoked() {
+        let accepting = Arc::new(Counting::default());
+        let options = ParseOptions::new().with_content_checker(accepting.clone());
+        assert_eq!(options, options.clone());
+        assert_ne!(options, ParseOptions::new());
+        Tee::new_with_options(skin_bytes(), TEE_UV_LAYOUT, ImageFormat::Png, options).unwrap();
+        assert_eq!(accepting.calls.load(Ordering::Relaxed), 1);
+
+        let rejecting = Arc::new(Counting {
+            reject: true,
+            ..Counting::default()
+        });
+        let options = ParseOptions::new().with_content_checker(rejecting);
+        assert!(matches!(
+            Tee::new_with_options(skin_bytes(), TEE_UV_LAYOUT, ImageFormat::Png, options),
+            Err(TeeError::ContentRejected(reason)) if reason == "no"
+        ));
+    }
+
+    #[cfg(feature = "moderation")]
+    #[test]
+    fn skin_tone_heuristic() {
+        use image::Rgba;
+        use tee_morphosis::moderation::SkinToneHeuristic;
+
+        let heuristic = SkinToneHeuristic::default();
+        let peach = RgbaImage::from_pixel(8, 8, Rgba([224, 172, 138, 255]));
+        assert_eq!(SkinToneHeuristic::ratio(&peach), 1.0);
+        assert!(matches!(heuristic.check(&peach), Verdict::Reject(_)));
+
+        let mut mixed = RgbaImage::from_pixel(8, 8, Rgba([40, 90, 200, 255]));
+        for x in 0..4 {
+            for y in 0..8 {
+                mixed.put_pixel(x, y, Rgba([224, 172, 138, 255]));
+            }
+        }
+        assert_eq!(SkinToneHeuristic::ratio(&mixed), 0.5);
+        assert_eq!(heuristic.check(&mixed), Verdict::Accept);
+        assert_eq!(SkinToneHeuristic::ratio(&RgbaImage::new(2, 2)), 0.0);
+
+        // the gray fixture passes
+        let options = ParseOptions::new().with_content_checker(Arc::new(heuristic));
+        assert!(
+            Tee::new_with_options(skin_bytes(), TEE_UV_LAYOUT, ImageFormat::Png, options).is_ok()
+        );
+    }
+}
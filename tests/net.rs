@@ -0,0 +1,440 @@
+mod common;
+
+#[cfg(feature = "net")]
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread::{self, JoinHandle},
+        time::{Duration, Instant},
+    };
+
+    use image::{ImageFormat, RgbaImage};
+    use tee_morphosis::{
+        error::TeeError,
+        net::{
+            FetchOutcome, Fetcher, RateLimit, RateLimiter, Validators,
+            network::{IpPreference, NetworkOptions},
+            policy::{UrlPolicy, is_public_address},
+        },
+        tee::{options::ComposeOptions, parts::EyeType, skin::TEE_SKIN_LAYOUT},
+    };
+
+    use crate::common::{skin_bytes, tee};
+
+    const EMPTY_PNG: &[u8] =
+        b"HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: 0\r\n\r\n";
+
+    /// Serves one request with `response`, returning its header lines.
+    fn serve_once(response: &'static [u8]) -> (String, JoinHandle<Vec<String>>) {
+        let (url, server) = serve(vec![response.to_vec()]);
+        (url, thread::spawn(move || server.join().unwrap().remove(0)))
+    }
+
+    /// Serves one request per response on its own connection, returning the header
+    /// lines of every request.
+    fn serve(responses: Vec<Vec<u8>>) -> (String, JoinHandle<Vec<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/skin.png", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let headers: Vec<String> = BufReader::new(&stream)
+                        .lines()
+                        .map(Result::unwrap)
+                        .take_while(|line| !line.is_empty())
+                        .collect();
+                    stream.write_all(&response).unwrap();
+                    headers
+                })
+                .collect()
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn rate_limit_must_be_positive() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                RateLimit::new(2, rate),
+                Err(TeeError::InvalidRateLimit(_))
+            ));
+        }
+        let limit = RateLimit::new(0, 0.5).unwrap();
+        assert_eq!((limit.burst(), limit.per_second()), (1, 0.5));
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_waits_after_burst() {
+        let limiter =
+            RateLimiter::new().with_host_limit("teedata.net", RateLimit::new(2, 20.0).unwrap());
+
+        let start = Instant::now();
+        for _ in 0..4 {
+            limiter.acquire("teedata.net").await;
+        }
+        // Two requests fit into the burst, the other two wait ~50ms each
+        assert!(start.elapsed() >= Duration::from_millis(90));
+
+        // Other hosts have no limit
+        let start = Instant::now();
+        for _ in 0..100 {
+            limiter.acquire("ddnet.org").await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn compose_async_matches_compose() {
+        let mut tee = tee();
+
+        let bytes = tee
+            .compose_async(
+                TEE_SKIN_LAYOUT,
+                EyeType::Happy,
+                ImageFormat::Png,
+                ComposeOptions::new(),
+            )
+            .await
+            .unwrap();
+        let expected = tee
+            .compose(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png)
+            .unwrap();
+        assert_eq!(bytes, expected);
+
+        let size = tee.get_eye(EyeType::Normal).dimensions();
+        tee.add_custom_eye("wink", RgbaImage::new(size.0, size.1))
+            .unwrap();
+        let bytes = tee
+            .compose_async(
+                TEE_SKIN_LAYOUT,
+                "wink",
+                ImageFormat::Png,
+                ComposeOptions::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            bytes,
+            tee.compose(TEE_SKIN_LAYOUT, "wink", ImageFormat::Png)
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn request_hook_attaches_headers() {
+        let (url, server) = serve_once(EMPTY_PNG);
+        let seen = Arc::new(AtomicUsize::new(0));
+        let policy = UrlPolicy::new().with_private_addresses(true);
+        let fetcher = Fetcher::new().with_url_policy(policy).with_request_hook({
+            let seen = seen.clone();
+            move |url, request| {
+                assert_eq!(url.path(), "/skin.png");
+                seen.fetch_add(1, Ordering::SeqCst);
+                Ok(request.bearer_auth("secret"))
+            }
+        });
+
+        let (bytes, format) = fetcher.fetch_image(&url).await.unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(format, ImageFormat::Png);
+        assert_eq!(seen.load(Ordering::SeqCst), 1);
+        let headers = server.join().unwrap();
+        assert!(
+            headers
+                .iter()
+                .any(|line| line.eq_ignore_ascii_case("authorization: Bearer secret")),
+            "{headers:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn request_hook_refuses_urls() {
+        let fetcher = Fetcher::new().with_request_hook(|url, request| {
+            if url.host_str() == Some("teedata.net") {
+                Ok(request)
+            } else {
+                Err(TeeError::UrlNotAllowed(url.to_string()))
+            }
+        });
+
+        let err = fetcher
+            .fetch_tee("https://example.com/skin.png")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TeeError::UrlNotAllowed(ref url) if url.contains("example.com")));
+        assert!(err.suggestion().is_some());
+        assert!(matches!(
+            fetcher.get("not a url").await,
+            Err(TeeError::UrlNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn url_policy_checks_hosts() {
+        let check = |policy: &UrlPolicy, url: &str| policy.check_url(&url.parse().unwrap());
+        let open = UrlPolicy::new();
+        assert!(check(&open, "https://teedata.net/skin.png").is_ok());
+        assert!(check(&open, "ftp://teedata.net/skin.png").is_err());
+        assert!(check(&open, "file:///etc/passwd").is_err());
+        for url in [
+            "http://127.0.0.1/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]:8080/",
+            "http://[::ffff:10.0.0.1]/",
+            "http://[::127.0.0.1]/",
+            "http://[2002:7f00:1::]/",
+            "http://0x7f000001/",
+        ] {
+            assert!(
+                matches!(check(&open, url), Err(TeeError::AddressNotAllowed { .. })),
+                "{url}"
+            );
+        }
+        assert!(
+            check(
+                &open.clone().with_private_addresses(true),
+                "http://127.0.0.1/"
+            )
+            .is_ok()
+        );
+
+        let listed = UrlPolicy::new()
+            .with_allowed_host("TeeData.net.")
+            .with_denied_host("evil.teedata.net");
+        assert!(check(&listed, "https://teedata.net/a.png").is_ok());
+        assert!(check(&listed, "https://skins.teedata.net/a.png").is_ok());
+        assert!(check(&listed, "https://evil.teedata.net/a.png").is_err());
+        assert!(check(&listed, "https://a.evil.teedata.net/a.png").is_err());
+        assert!(check(&listed, "https://notteedata.net/a.png").is_err());
+        assert!(check(&listed, "https://ddnet.org/a.png").is_err());
+    }
+
+    #[test]
+    fn public_addresses() {
+        for address in [
+            "1.1.1.1",
+            "2606:4700::1111",
+            "64:ff9b::808:808",
+            "2002:808:808::1",
+        ] {
+            assert!(is_public_address(address.parse().unwrap()), "{address}");
+        }
+        for address in [
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "fc00::1",
+            "fe80::1",
+            "::",
+            "::ffff:192.168.0.1",
+            "64:ff9b::a00:1",
+            "::127.0.0.1",
+            "::a9fe:a9fe",
+            "2002:7f00:1::",
+            "2002:c0a8:101::1",
+        ] {
+            assert!(!is_public_address(address.parse().unwrap()), "{address}");
+            // The resolver refuses hosts resolving to them
+            assert!(
+                matches!(
+                    UrlPolicy::new().check_address("skins.example", address.parse().unwrap()),
+                    Err(TeeError::AddressNotAllowed { .. })
+                ),
+                "{address}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn fetcher_refuses_private_addresses() {
+        let (url, _server) = serve_once(EMPTY_PNG);
+        let err = Fetcher::new().fetch_image(&url).await.unwrap_err();
+        assert!(matches!(err, TeeError::AddressNotAllowed { .. }), "{err:?}");
+
+        // Checked by the resolver, after the URL passed
+        let port = url.split(':').nth(2).unwrap();
+        let err = Fetcher::new()
+            .fetch_image(&format!("http://localhost:{port}"))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, TeeError::AddressNotAllowed { ref host, .. } if host == "localhost"),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn fetcher_refuses_redirects() {
+        let (url, server) = serve_once(
+            b"HTTP/1.1 302 Found\r\nLocation: http://evil.test/skin.png\r\nContent-Length: 0\r\n\r\n",
+        );
+        let policy = UrlPolicy::new()
+            .with_private_addresses(true)
+            .with_denied_host("evil.test");
+        let err = Fetcher::new()
+            .with_url_policy(policy)
+            .fetch_image(&url)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, TeeError::UrlNotAllowed(ref url) if url.contains("evil.test")),
+            "{err:?}"
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn ip_preference_arranges_addresses() {
+        let addresses: Vec<std::net::IpAddr> =
+            ["2001:4860::1", "8.8.8.8", "2606:4700::1", "1.1.1.1"]
+                .iter()
+                .map(|address| address.parse().unwrap())
+                .collect();
+        let arranged = |preference: IpPreference| -> Vec<String> {
+            preference
+                .arrange(addresses.clone())
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        };
+        assert_eq!(
+            arranged(IpPreference::Any),
+            ["2001:4860::1", "8.8.8.8", "2606:4700::1", "1.1.1.1"]
+        );
+        assert_eq!(
+            arranged(IpPreference::PreferV4),
+            ["8.8.8.8", "1.1.1.1", "2001:4860::1", "2606:4700::1"]
+        );
+        assert_eq!(
+            arranged(IpPreference::PreferV6),
+            ["2001:4860::1", "2606:4700::1", "8.8.8.8", "1.1.1.1"]
+        );
+        assert_eq!(arranged(IpPreference::OnlyV4), ["8.8.8.8", "1.1.1.1"]);
+        assert_eq!(
+            arranged(IpPreference::OnlyV6),
+            ["2001:4860::1", "2606:4700::1"]
+        );
+        for preference in IpPreference::ALL {
+            assert_eq!(IpPreference::from_name(preference.name()), Some(preference));
+        }
+    }
+
+    #[test]
+    fn network_options_validate_proxies() {
+        let network = NetworkOptions::new()
+            .with_proxy("http://proxy.internal:3128")
+            .unwrap();
+        assert_eq!(network.proxy(), Some("http://proxy.internal:3128"));
+        assert!(!network.is_direct());
+        assert!(NetworkOptions::new().without_proxy().is_direct());
+        assert!(NetworkOptions::new().with_proxy("not a proxy").is_err());
+        Fetcher::new().with_network(network.clone());
+
+        // Proxies resolve hosts, so the system ones are skipped by default while private
+        // addresses are refused
+        let strict = UrlPolicy::new();
+        let private = UrlPolicy::new().with_private_addresses(true);
+        assert!(!NetworkOptions::new().uses_system_proxy(&strict));
+        assert!(NetworkOptions::new().uses_system_proxy(&private));
+        let system = NetworkOptions::new().with_system_proxy();
+        assert!(system.uses_system_proxy(&strict));
+        assert!(!network.uses_system_proxy(&private));
+    }
+
+    #[tokio::test]
+    async fn dns_overrides_pass_through_the_policy() {
+        let (url, server) = serve_once(EMPTY_PNG);
+        let port = url.split(':').nth(2).unwrap();
+        let url = format!("http://skins.test:{port}");
+        let network = NetworkOptions::new()
+            .without_proxy()
+            .with_dns_override("Skins.Test", "127.0.0.1".parse().unwrap());
+        assert_eq!(network.dns_override("skins.test").unwrap().len(), 1);
+
+        let err = Fetcher::new()
+            .with_network(network.clone())
+            .fetch_image(&url)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, TeeError::AddressNotAllowed { ref host, .. } if host == "skins.test"),
+            "{err:?}"
+        );
+
+        let local = Fetcher::new()
+            .with_network(network.clone())
+            .with_url_policy(UrlPolicy::new().with_private_addresses(true));
+        assert!(
+            local
+                .clone()
+                .with_network(network.clone().with_ip_preference(IpPreference::OnlyV6))
+                .fetch_image(&url)
+                .await
+                .is_err()
+        );
+        let (_, format) = local.fetch_image(&url).await.unwrap();
+        assert_eq!(format, ImageFormat::Png);
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn fetch_if_modified_skips_unchanged_sources() {
+        let skin = skin_bytes();
+        let mut changed = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nETag: \"v1\"\r\nLast-Modified: Wed, 14 Oct 2026 10:00:00 GMT\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            skin.len()
+        )
+        .into_bytes();
+        changed.extend_from_slice(&skin);
+        let unchanged =
+            b"HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n".to_vec();
+        let (url, server) = serve(vec![changed, unchanged]);
+        let fetcher = Fetcher::new().with_url_policy(UrlPolicy::new().with_private_addresses(true));
+
+        let FetchOutcome::New {
+            tee,
+            validators,
+        } = fetcher
+            .fetch_if_modified(&url, &Validators::new())
+            .await
+            .unwrap()
+        else {
+            panic!("unconditional fetch was not modified");
+        };
+        assert_eq!(tee.body.value.dimensions(), (96, 96));
+        assert_eq!(
+            validators,
+            Validators::new()
+                .with_etag("\"v1\"")
+                .with_last_modified("Wed, 14 Oct 2026 10:00:00 GMT")
+        );
+
+        let outcome = fetcher.fetch_if_modified(&url, &validators).await.unwrap();
+        assert!(matches!(outcome, FetchOutcome::NotModified));
+
+        let requests = server.join().unwrap();
+        let has = |request: &[String], header: &str| {
+            request
+                .iter()
+                .any(|line| line.to_ascii_lowercase().starts_with(header))
+        };
+        assert!(!has(&requests[0], "if-none-match"));
+        assert!(
+            requests[1]
+                .iter()
+                .any(|line| line.eq_ignore_ascii_case("if-none-match: \"v1\""))
+        );
+        assert!(has(&requests[1], "if-modified-since: wed, 14 oct 2026"));
+    }
+}
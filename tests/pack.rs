@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::{pack::SkinPack, tee::hash::SourceHash};
+
+    #[test]
+    fn diff_sorts_skins_into_buckets() {
+        let mut old = SkinPack::new();
+        old.insert_bytes("default", b"v1");
+        old.insert_bytes("bomb", b"bomb");
+        old.insert_bytes("santa", b"santa");
+        let mut new = old.clone();
+        new.insert_bytes("default", b"v2");
+        new.remove("bomb");
+        new.insert_bytes("zebra", b"zebra");
+        new.insert_bytes("apple", b"apple");
+
+        let diff = SkinPack::diff(&old, &new);
+        assert_eq!(diff.added, ["apple", "zebra"]);
+        assert_eq!(diff.removed, ["bomb"]);
+        assert_eq!(diff.changed, ["default"]);
+        assert_eq!(
+            diff.outdated().collect::<Vec<_>>(),
+            ["apple", "zebra", "default"]
+        );
+        assert_eq!(diff.to_string(), "+ apple\n+ zebra\n- bomb\n~ default\n");
+
+        let unchanged = SkinPack::diff(&new, &new);
+        assert!(unchanged.is_empty());
+        assert_eq!(unchanged.to_string(), "");
+    }
+
+    #[test]
+    fn collects_from_hashes() {
+        let pack: SkinPack = [("b", SourceHash::of(b"b")), ("a", SourceHash::of(b"a"))]
+            .into_iter()
+            .collect();
+        assert_eq!(pack.len(), 2);
+        assert_eq!(pack.get("a"), Some(SourceHash::of(b"a")));
+        assert_eq!(
+            pack.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::tee::{
+        parts::{AnyPart, EyeType, TeePart},
+        uv::UV,
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn names_match_the_uv() {
+        for (part, name) in AnyPart::ALL.iter().zip(UV::PART_NAMES) {
+            assert_eq!(part.name(), name);
+        }
+        assert_eq!(AnyPart::Eye(EyeType::Blink).name(), "eye_blink");
+    }
+
+    #[test]
+    fn get_covers_parts_and_eyes() {
+        let tee = tee();
+        assert_eq!(tee.get(TeePart::FeetShadow), &tee.feet.shadow);
+        assert_eq!(tee.get(EyeType::Happy), tee.get_eye(EyeType::Happy));
+        for part in AnyPart::ALL {
+            assert!(tee.get(part).width() > 0, "{}", part.name());
+        }
+    }
+
+    #[test]
+    fn apply_hsl_changes_only_that_part() {
+        let original = tee();
+        let mut tee = original.clone();
+        tee.apply_hsl(EyeType::Angry, (0.5, 1.0, 0.5));
+        for part in AnyPart::ALL {
+            let changed = tee.get(part) != original.get(part);
+            assert_eq!(
+                changed,
+                part == AnyPart::Eye(EyeType::Angry),
+                "{}",
+                part.name()
+            );
+        }
+
+        let mut parts = original.clone();
+        parts.apply_hsl_to_parts((0.5, 1.0, 0.5), &[TeePart::Body]);
+        let mut any = original.clone();
+        any.apply_hsl(TeePart::Body, (0.5, 1.0, 0.5));
+        assert_eq!(parts, any);
+    }
+}
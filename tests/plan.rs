@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::{
+        meta::{RenderRecipe, render_filename},
+        pack::SkinPack,
+        plan::{JobReason, RenderPlan},
+        tee::hash::SourceHash,
+    };
+
+    fn recipes() -> Vec<RenderRecipe> {
+        ["apple", "default", "bomb", "santa"]
+            .into_iter()
+            .flat_map(|skin| {
+                [
+                    RenderRecipe::new(skin, "normal", (96, 64)),
+                    RenderRecipe::new(skin, "happy", (96, 64)),
+                ]
+            })
+            .collect()
+    }
+
+    fn plan() -> RenderPlan {
+        let old: SkinPack = [("default", b"v1"), ("bomb", b"v1"), ("santa", b"v1")]
+            .into_iter()
+            .map(|(name, data)| (name, SourceHash::of(data)))
+            .collect();
+        let mut new = old.clone();
+        new.insert_bytes("default", b"v2");
+        new.insert_bytes("apple", b"v1");
+        new.remove("bomb");
+
+        let mut recipes = recipes();
+        // duplicates are rendered once
+        recipes.push(RenderRecipe::new("default", "normal", (96, 64)));
+        RenderPlan::from_diff(&SkinPack::diff(&old, &new), &recipes)
+    }
+
+    #[test]
+    fn plans_minimal_jobs_by_priority() {
+        let plan = plan();
+        let jobs: Vec<_> = plan
+            .jobs()
+            .iter()
+            .map(|job| {
+                (
+                    job.recipe.skin.as_str(),
+                    job.recipe.eye.as_str(),
+                    job.reason,
+                )
+            })
+            .collect();
+        assert_eq!(
+            jobs,
+            [
+                ("default", "normal", JobReason::Changed),
+                ("default", "happy", JobReason::Changed),
+                ("apple", "normal", JobReason::Added),
+                ("apple", "happy", JobReason::Added),
+            ]
+        );
+        assert_eq!(
+            plan.stale(),
+            [
+                render_filename(&RenderRecipe::new("bomb", "normal", (96, 64))),
+                render_filename(&RenderRecipe::new("bomb", "happy", (96, 64))),
+            ]
+        );
+    }
+
+    #[test]
+    fn resumes_from_checkpoint() {
+        let mut plan = plan();
+        let first = plan.jobs()[0].filename.clone();
+        assert!(plan.complete(&first));
+        assert!(!plan.complete(&first));
+        assert!(!plan.complete("unknown.png"));
+        assert_eq!(plan.pending().count(), 3);
+
+        let checkpoint = plan.checkpoint();
+        assert_eq!(checkpoint, format!("{first}\n"));
+        let mut resumed = self::plan().resume(&checkpoint);
+        assert_eq!(resumed.pending().count(), 3);
+
+        let pending: Vec<String> = resumed.pending().map(|job| job.filename.clone()).collect();
+        for filename in pending {
+            resumed.complete(&filename);
+        }
+        assert!(resumed.is_finished());
+    }
+}
@@ -0,0 +1,78 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::ImageFormat;
+    use tee_morphosis::tee::{
+        Tee,
+        options::ParseOptions,
+        parts::EyeType,
+        pool::BufferPool,
+        raw::{ExtractPolicy, decode_image, extract_part_clamped, extract_part_pooled},
+        skin::TEE_SKIN_LAYOUT,
+        uv::{TEE_UV_LAYOUT, UvPart},
+    };
+
+    use crate::common::skin_bytes;
+
+    #[test]
+    fn pooled_parses_reuse_buffers() {
+        let plain = Tee::new(skin_bytes(), ImageFormat::Png).unwrap();
+        let pool = BufferPool::new(8);
+        let options = ParseOptions::new().with_buffer_pool(pool.clone());
+
+        let first = Tee::new_with_options(
+            skin_bytes(),
+            TEE_UV_LAYOUT,
+            ImageFormat::Png,
+            options.clone(),
+        )
+        .unwrap();
+        let (hits, misses) = pool.stats();
+        assert_eq!(hits, 0);
+        assert!(misses > 0);
+        pool.recycle(first);
+        assert!(!pool.is_empty());
+
+        let second =
+            Tee::new_with_options(skin_bytes(), TEE_UV_LAYOUT, ImageFormat::Png, options).unwrap();
+        assert_eq!(pool.stats(), (misses, misses));
+        assert_eq!(second.body.value, plain.body.value);
+        assert_eq!(
+            second.get_eye(EyeType::Happy),
+            plain.get_eye(EyeType::Happy)
+        );
+        assert_eq!(
+            second.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal),
+            plain.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal)
+        );
+    }
+
+    #[test]
+    fn pooled_extraction_pads_like_clamped() {
+        let img = decode_image(skin_bytes(), None, &ParseOptions::default()).unwrap();
+        let pool = BufferPool::new(1);
+        // Dirty a buffer of the same size, it must come back transparent
+        pool.give(image::RgbaImage::from_pixel(40, 40, image::Rgba([255; 4])));
+
+        let part = UvPart {
+            x: 230,
+            y: 100,
+            w: 40,
+            h: 40,
+        };
+        let pooled = extract_part_pooled(&img, part, ExtractPolicy::Clamp, &pool).unwrap();
+        assert_eq!(pool.stats(), (1, 0));
+        assert_eq!(pooled, extract_part_clamped(&img, part));
+        assert!(extract_part_pooled(&img, part, ExtractPolicy::Strict, &pool).is_err());
+    }
+
+    #[test]
+    fn full_pool_drops_buffers() {
+        let pool = BufferPool::new(1);
+        pool.give(image::RgbaImage::new(4, 4));
+        pool.give(image::RgbaImage::new(4, 4));
+        pool.give(image::RgbaImage::new(2, 2));
+        assert_eq!(pool.len(), 2);
+    }
+}
@@ -0,0 +1,67 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageFormat, Rgba};
+    use tee_morphosis::tee::{
+        parts::{AnyPart, EyeType},
+        raw::opaque_bounds,
+        skin::TEE_SKIN_LAYOUT,
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn portrait_is_a_tight_square() {
+        let tee = tee();
+        for size in [16, 32, 128] {
+            let portrait = tee.compose_portrait_image(EyeType::Happy, size);
+            assert_eq!(portrait.dimensions(), (size, size));
+            // The head touches two opposite edges and is centered on the other axis
+            let (x, y, width, height) = opaque_bounds(&portrait).unwrap();
+            assert!(
+                width >= size - 1 || height >= size - 1,
+                "{size}: {width}x{height}"
+            );
+            assert!(
+                x.abs_diff(size - x - width) <= 1,
+                "{size}: x {x}, width {width}"
+            );
+            assert!(
+                y.abs_diff(size - y - height) <= 1,
+                "{size}: y {y}, height {height}"
+            );
+        }
+    }
+
+    #[test]
+    fn portrait_has_no_feet() {
+        let tee = tee();
+        let portrait = tee.compose_portrait_image(EyeType::Normal, 64);
+        let mut painted = tee.clone();
+        for part in [AnyPart::Feet, AnyPart::FeetShadow] {
+            for pixel in painted.get_mut(part).pixels_mut() {
+                *pixel = Rgba([255, 0, 0, 255]);
+            }
+        }
+        assert_eq!(
+            painted.compose_portrait_image(EyeType::Normal, 64),
+            portrait
+        );
+        assert_ne!(
+            painted.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal),
+            tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal)
+        );
+
+        let png = tee
+            .compose_portrait(EyeType::Normal, 64, ImageFormat::Png)
+            .unwrap();
+        let decoded = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+        assert_eq!(decoded.to_rgba8(), portrait);
+        assert_ne!(
+            portrait,
+            tee.compose_portrait_image(EyeType::Angry, 64),
+            "eyes are drawn"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageFormat, imageops};
+    use tee_morphosis::tee::{
+        options::ComposeOptions, parts::EyeType, pose::Pose, skin::TEE_SKIN_LAYOUT,
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn front_is_the_layout() {
+        let tee = tee();
+        assert_eq!(
+            tee.compose_pose_image(TEE_SKIN_LAYOUT, Pose::Front, EyeType::Happy),
+            tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy)
+        );
+        assert!(matches!(
+            tee.posed(Pose::Front),
+            std::borrow::Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn back_mirrors_the_body_and_hides_the_eyes() {
+        let tee = tee();
+        let posed = tee.posed(Pose::Back);
+        assert_eq!(posed.body.value, imageops::flip_horizontal(&tee.body.value));
+        assert_eq!(
+            posed.body.shadow,
+            imageops::flip_horizontal(&tee.body.shadow)
+        );
+
+        let back = tee.compose_pose_image(TEE_SKIN_LAYOUT, Pose::Back, EyeType::Normal);
+        assert_eq!(
+            back,
+            tee.compose_pose_image(TEE_SKIN_LAYOUT, Pose::Back, EyeType::Angry),
+            "eyes are not drawn"
+        );
+        assert_ne!(back, tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal));
+
+        let (_, options) = Pose::Back.apply(TEE_SKIN_LAYOUT, ComposeOptions::new());
+        assert!(options.layer_order.iter().all(|layer| !layer.is_eye()));
+    }
+
+    #[test]
+    fn three_quarter_moves_eyes_and_far_foot() {
+        let tee = tee();
+        let (skin, _) = Pose::ThreeQuarter.apply(TEE_SKIN_LAYOUT, ComposeOptions::new());
+        let base = TEE_SKIN_LAYOUT;
+        assert!(skin.first_eyes.0.0 > base.first_eyes.0.0);
+        // The eyes close up
+        assert!(
+            skin.second_eyes.0.0 - skin.first_eyes.0.0 < base.second_eyes.0.0 - base.first_eyes.0.0
+        );
+        assert!(skin.feet_back.1 < base.feet_back.1);
+        assert_eq!(skin.feet, base.feet);
+
+        let png = tee
+            .compose_pose(
+                TEE_SKIN_LAYOUT,
+                Pose::ThreeQuarter,
+                EyeType::Normal,
+                ImageFormat::Png,
+            )
+            .unwrap();
+        let decoded = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+        assert_eq!(
+            decoded.to_rgba8(),
+            tee.compose_pose_image(TEE_SKIN_LAYOUT, Pose::ThreeQuarter, EyeType::Normal)
+        );
+        assert_eq!(
+            Pose::ALL.map(|pose| pose.name()),
+            ["front", "back", "three-quarter"]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageFormat, Rgba};
+    use tee_morphosis::tee::{
+        parts::AnyPart,
+        preview::{PartPreview, PreviewPart},
+        raw::opaque_bounds,
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn parts_are_centered_and_padded() {
+        let tee = tee();
+        for part in PreviewPart::ALL {
+            let preview = PartPreview::new(part).with_size(96).with_padding(8);
+            let image = tee.compose_part_preview_image(preview);
+            assert_eq!(image.dimensions(), (96, 96), "{}", part.name());
+
+            let (x, y, width, height) = opaque_bounds(&image).unwrap();
+            let (right, bottom) = (96 - x - width, 96 - y - height);
+            // The longer side touches the padding, the part is centered on both axes
+            assert!(x.min(y) >= 8, "{}: at {x}, {y}", part.name());
+            assert!(x.min(y) <= 9, "{}: at {x}, {y}", part.name());
+            assert!(x.abs_diff(right) <= 1, "{}: {x} and {right}", part.name());
+            assert!(y.abs_diff(bottom) <= 1, "{}: {y} and {bottom}", part.name());
+        }
+    }
+
+    #[test]
+    fn presets_draw_their_part() {
+        let tee = tee();
+        let feet = tee.compose_part_preview_image(PartPreview::FEET);
+        let hand = tee.compose_part_preview_image(PartPreview::HAND);
+        assert_eq!(feet.dimensions(), (64, 64));
+        assert_ne!(feet, hand);
+
+        // Only the feet and their shadow are drawn
+        let mut painted = tee.clone();
+        for pixel in painted.get_mut(AnyPart::Hand).pixels_mut() {
+            pixel[0] = 255 - pixel[0];
+        }
+        assert_eq!(painted.compose_part_preview_image(PartPreview::FEET), feet);
+        assert_ne!(painted.compose_part_preview_image(PartPreview::HAND), hand);
+
+        let png = tee
+            .compose_part_preview(PartPreview::FEET, ImageFormat::Png)
+            .unwrap();
+        let decoded = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+        assert_eq!(decoded.to_rgba8(), feet);
+    }
+
+    #[test]
+    fn empty_part_gives_empty_square() {
+        let mut tee = tee();
+        for part in [AnyPart::Hand, AnyPart::HandShadow] {
+            for pixel in tee.get_mut(part).pixels_mut() {
+                *pixel = Rgba([0, 0, 0, 0]);
+            }
+        }
+        let image = tee.compose_part_preview_image(PartPreview::HAND.with_size(16));
+        assert_eq!(image.dimensions(), (16, 16));
+        assert!(opaque_bounds(&image).is_none());
+    }
+}
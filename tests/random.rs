@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::tee::{Tee, hsl::ddnet_color_to_hsl, parts::EyeType, skin::TEE_SKIN_LAYOUT};
+
+    #[test]
+    fn same_seed_same_tee() {
+        let first = Tee::random(7, None);
+        let again = Tee::random(7, None);
+        assert_eq!(first.etag(), again.etag());
+        assert_eq!(
+            first.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy),
+            again.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy)
+        );
+        assert_ne!(first.etag(), Tee::random(8, None).etag());
+
+        let patterned = Tee::random_with_pattern(7, None, true);
+        assert_ne!(patterned.body.value, first.body.value);
+        assert!(!EyeType::ALL.into_iter().any(|eye| first.is_eye_blank(eye)));
+    }
+
+    #[test]
+    fn palette_colors() {
+        let red = ddnet_color_to_hsl(65461);
+        for seed in 0..8 {
+            let tee = Tee::random(seed, Some(&[red]));
+            // red tint: the opaque center of the body has no green or blue left over red
+            let center = tee.body.value.get_pixel(48, 48);
+            assert!(
+                center.0[0] > center.0[1] && center.0[0] > center.0[2],
+                "{center:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,275 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use image::ImageFormat;
+    use tee_morphosis::{
+        error::TeeError,
+        tee::{
+            Tee,
+            options::ParseOptions,
+            raw::{
+                ExtractPolicy, decode_image, extract_part, extract_part_clamped,
+                extract_with_shadow, normalize,
+            },
+            uv::{TEE_UV_LAYOUT, UvPart},
+        },
+    };
+
+    use crate::common::skin_bytes;
+
+    #[test]
+    fn body_only_crop_matches_parsed_tee() {
+        let img = decode_image(skin_bytes(), None, &ParseOptions::default()).unwrap();
+        let body =
+            extract_with_shadow(&img, TEE_UV_LAYOUT.body, TEE_UV_LAYOUT.body_shadow).unwrap();
+
+        let tee = Tee::new(skin_bytes(), ImageFormat::Png).unwrap();
+        assert_eq!(body, tee.body);
+    }
+
+    #[test]
+    fn extract_part_out_of_bounds() {
+        let img = decode_image(skin_bytes(), None, &ParseOptions::default()).unwrap();
+        let part = UvPart {
+            x: 200,
+            y: 100,
+            w: 96,
+            h: 96,
+        };
+        assert!(matches!(
+            extract_part(&img, part),
+            Err(TeeError::OutOfBounds {
+                width: 256,
+                height: 128,
+                ..
+            })
+        ));
+
+        // Edges past u32::MAX are out of bounds too
+        let overflowing = UvPart::new(u32::MAX - 8, 0, (96, 96));
+        assert!(matches!(
+            extract_part(&img, overflowing),
+            Err(TeeError::OutOfBounds { .. })
+        ));
+        assert_eq!(
+            extract_part_clamped(&img, overflowing).dimensions(),
+            (96, 96)
+        );
+    }
+
+    #[test]
+    fn clamped_extraction_pads_with_transparency() {
+        let img = decode_image(skin_bytes(), None, &ParseOptions::default()).unwrap();
+        let part = UvPart::new(240, 120, (32, 32));
+        assert!(extract_part(&img, part).is_err());
+
+        let clamped = extract_part_clamped(&img, part);
+        assert_eq!(clamped.dimensions(), (32, 32));
+        assert_eq!(
+            clamped.get_pixel(3, 3),
+            &img.to_rgba8().get_pixel(243, 123).clone()
+        );
+        assert_eq!(clamped.get_pixel(20, 3).0[3], 0);
+        assert_eq!(clamped.get_pixel(3, 20).0[3], 0);
+
+        let inside = UvPart::new(0, 0, (32, 32));
+        assert_eq!(
+            extract_part_clamped(&img, inside),
+            extract_part(&img, inside).unwrap()
+        );
+    }
+
+    #[test]
+    fn clamp_policy_parses_truncated_sheets() {
+        let img = decode_image(skin_bytes(), None, &ParseOptions::default()).unwrap();
+        let mut truncated = Vec::new();
+        img.crop_imm(0, 0, 256, 120)
+            .write_to(&mut std::io::Cursor::new(&mut truncated), ImageFormat::Png)
+            .unwrap();
+        let truncated = Bytes::from(truncated);
+
+        let strict = Tee::new_with_options(
+            truncated.clone(),
+            TEE_UV_LAYOUT,
+            ImageFormat::Png,
+            ParseOptions::new(),
+        );
+        assert!(matches!(strict, Err(TeeError::InvalidDimensions { .. })));
+
+        let options = ParseOptions::new().with_extract_policy(ExtractPolicy::Clamp);
+        let tee =
+            Tee::new_with_options(truncated, TEE_UV_LAYOUT, ImageFormat::Png, options.clone())
+                .unwrap();
+        let full = Tee::new(skin_bytes(), ImageFormat::Png).unwrap();
+        assert_eq!(tee.body, full.body);
+
+        let mut larger = Vec::new();
+        image::DynamicImage::new_rgba8(300, 128)
+            .write_to(&mut std::io::Cursor::new(&mut larger), ImageFormat::Png)
+            .unwrap();
+        let larger = Tee::new_with_options(larger.into(), TEE_UV_LAYOUT, ImageFormat::Png, options);
+        assert!(matches!(larger, Err(TeeError::InvalidDimensions { .. })));
+    }
+
+    fn encode_png_with(
+        img: &image::RgbaImage,
+        icc_profile: Option<Vec<u8>>,
+        exif: Option<Vec<u8>>,
+    ) -> Bytes {
+        use image::{ImageEncoder, codecs::png::PngEncoder};
+
+        let mut png = Vec::new();
+        let mut encoder = PngEncoder::new(&mut png);
+        if let Some(icc_profile) = icc_profile {
+            encoder.set_icc_profile(icc_profile).unwrap();
+        }
+        if let Some(exif) = exif {
+            encoder.set_exif_metadata(exif).unwrap();
+        }
+        encoder
+            .write_image(
+                img.as_raw(),
+                img.width(),
+                img.height(),
+                image::ExtendedColorType::Rgba8,
+            )
+            .unwrap();
+        Bytes::from(png)
+    }
+
+    #[test]
+    fn wide_gamut_sources_are_converted_to_srgb() {
+        let pixel = image::Rgba([40, 200, 60, 255]);
+        let img = image::RgbaImage::from_pixel(2, 2, pixel);
+
+        let srgb = encode_png_with(
+            &img,
+            Some(moxcms::ColorProfile::new_srgb().encode().unwrap()),
+            None,
+        );
+        let decoded = decode_image(srgb, None, &ParseOptions::default())
+            .unwrap()
+            .to_rgba8();
+        for (a, b) in decoded.get_pixel(0, 0).0.iter().zip(pixel.0) {
+            assert!(a.abs_diff(b) <= 1);
+        }
+
+        let p3 = encode_png_with(
+            &img,
+            Some(moxcms::ColorProfile::new_display_p3().encode().unwrap()),
+            None,
+        );
+        let decoded = decode_image(p3, None, &ParseOptions::default())
+            .unwrap()
+            .to_rgba8();
+        let converted = decoded.get_pixel(0, 0);
+        // display p3 green lies outside of sRGB, the red channel has to drop
+        assert!(converted.0[0] < pixel.0[0]);
+        assert_eq!(converted.0[3], 255);
+    }
+
+    #[test]
+    fn exif_orientation_is_applied() {
+        let mut img = image::RgbaImage::new(2, 1);
+        img.put_pixel(0, 0, image::Rgba([255, 0, 0, 255]));
+        img.put_pixel(1, 0, image::Rgba([0, 0, 255, 255]));
+
+        // big endian TIFF header, one IFD entry: orientation (0x0112), SHORT, 6 (rotate 90 cw)
+        let exif = vec![
+            b'M', b'M', 0, 42, 0, 0, 0, 8, 0, 1, 0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0, 0, 0, 0,
+            0,
+        ];
+        let png = encode_png_with(&img, None, Some(exif));
+        let decoded = decode_image(png, None, &ParseOptions::default())
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(decoded.dimensions(), (1, 2));
+        assert_eq!(decoded.get_pixel(0, 0), &image::Rgba([255, 0, 0, 255]));
+        assert_eq!(decoded.get_pixel(0, 1), &image::Rgba([0, 0, 255, 255]));
+    }
+
+    /// Builds a PNG by hand, with the image data in a single stored deflate block.
+    fn raw_png(
+        header: [u8; 13],
+        chunks: &[(&[u8; 4], Vec<u8>)],
+        scanlines: &[u8],
+    ) -> Bytes {
+        fn chunk(
+            png: &mut Vec<u8>,
+            kind: &[u8; 4],
+            data: &[u8],
+        ) {
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            png.extend_from_slice(kind);
+            png.extend_from_slice(data);
+            let mut crc = crc32fast::Hasher::new();
+            crc.update(kind);
+            crc.update(data);
+            png.extend_from_slice(&crc.finalize().to_be_bytes());
+        }
+
+        let len = scanlines.len() as u16;
+        let mut zlib = vec![0x78, 0x01, 0x01];
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(scanlines);
+        let (mut a, mut b) = (1u32, 0u32);
+        for byte in scanlines {
+            a = (a + *byte as u32) % 65521;
+            b = (b + a) % 65521;
+        }
+        zlib.extend_from_slice(&((b << 16) | a).to_be_bytes());
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut png, b"IHDR", &header);
+        for (kind, data) in chunks {
+            chunk(&mut png, kind, data);
+        }
+        chunk(&mut png, b"IDAT", &zlib);
+        chunk(&mut png, b"IEND", &[]);
+        Bytes::from(png)
+    }
+
+    #[test]
+    fn sixteen_bit_sources_are_rounded() {
+        // 1x1, 16 bits per channel, RGBA
+        let header = [0, 0, 0, 1, 0, 0, 0, 1, 16, 6, 0, 0, 0];
+        // 128 and 129 of 65535 sit on both sides of half an 8-bit step
+        let scanline = [0, 0x00, 0x80, 0x00, 0x81, 0xff, 0xff, 0x80, 0x80];
+        let png = raw_png(header, &[], &scanline);
+
+        let decoded = decode_image(png, None, &ParseOptions::default()).unwrap();
+        let rgba = decoded.as_rgba8().expect("normalized to 8-bit RGBA");
+        assert_eq!(rgba.get_pixel(0, 0).0, [0, 1, 255, 128]);
+    }
+
+    #[test]
+    fn paletted_sources_keep_transparency() {
+        // 2x1, 8-bit palette indices
+        let header = [0, 0, 0, 2, 0, 0, 0, 1, 8, 3, 0, 0, 0];
+        let palette = vec![255, 0, 0, 0, 0, 255];
+        let transparency = vec![255, 0];
+        let png = raw_png(
+            header,
+            &[(b"PLTE", palette), (b"tRNS", transparency)],
+            &[0, 0, 1],
+        );
+
+        let decoded = decode_image(png, None, &ParseOptions::default()).unwrap();
+        let rgba = decoded.as_rgba8().expect("normalized to 8-bit RGBA");
+        assert_eq!(rgba.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(rgba.get_pixel(1, 0).0, [0, 0, 255, 0]);
+    }
+
+    #[test]
+    fn normalize_expands_grayscale() {
+        let gray =
+            image::DynamicImage::ImageLuma8(image::GrayImage::from_pixel(1, 1, image::Luma([7])));
+        assert_eq!(
+            normalize(gray).as_rgba8().unwrap().get_pixel(0, 0).0,
+            [7, 7, 7, 255]
+        );
+    }
+}
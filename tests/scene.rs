@@ -0,0 +1,165 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::Bytes;
+    use image::{ImageFormat, Rgba, RgbaImage};
+    use tee_morphosis::{
+        assets::game::{Flag, GAME_SIZE, GameSheet},
+        scene::Scene,
+        tee::{parts::EyeType, skin::TEE_SKIN_LAYOUT},
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn items_are_drawn_in_order() {
+        let mut scene = Scene::new((4, 4)).with_background(Rgba([0, 0, 0, 255]));
+        scene.add_image(RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255])), (0, 0));
+        scene.add_image(RgbaImage::from_pixel(2, 2, Rgba([0, 255, 0, 255])), (1, 1));
+
+        let canvas = scene.render();
+        assert_eq!(canvas.dimensions(), (4, 4));
+        assert_eq!(canvas.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(1, 1), &Rgba([0, 255, 0, 255]));
+        assert_eq!(canvas.get_pixel(3, 3), &Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn tee_matches_compose_image() {
+        let tee = tee();
+        let mut scene = Scene::new(TEE_SKIN_LAYOUT.container);
+        let id = scene.add_tee(&tee, TEE_SKIN_LAYOUT, EyeType::Happy, (0, 0));
+
+        let expected = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy);
+        assert_eq!(scene.item(id).unwrap().image, expected);
+        assert_eq!(scene.render().dimensions(), expected.dimensions());
+    }
+
+    /// Opaque white game sheet, so every sprite is visible.
+    fn get_game_sheet() -> GameSheet {
+        let img = RgbaImage::from_pixel(GAME_SIZE.0, GAME_SIZE.1, Rgba([255, 255, 255, 255]));
+        let mut buf = Vec::new();
+        img.write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)
+            .unwrap();
+        GameSheet::parse(Bytes::from(buf)).unwrap()
+    }
+
+    #[test]
+    fn hook_is_drawn_below_the_tee() {
+        let game = get_game_sheet();
+        let mut scene = Scene::new((400, 100));
+        let tee = scene.add_image(RgbaImage::new(96, 64), (0, 0));
+        let hook = scene.draw_hook(&game, tee, (300, 32)).unwrap();
+
+        let order: Vec<_> = scene.items().map(|(id, _)| id).collect();
+        assert_eq!(order, [hook, tee]);
+
+        let canvas = scene.render();
+        // Head, chain in the middle, nothing behind the head
+        assert_eq!(canvas.get_pixel(300, 32).0[3], 255);
+        assert_eq!(canvas.get_pixel(150, 32).0[3], 255);
+        assert_eq!(canvas.get_pixel(330, 32).0[3], 0);
+        assert_eq!(canvas.get_pixel(150, 60).0[3], 0);
+    }
+
+    #[test]
+    fn hook_from_unknown_item() {
+        let game = get_game_sheet();
+        let mut other = Scene::new((10, 10));
+        let foreign = other.add_image(RgbaImage::new(1, 1), (0, 0));
+
+        let mut scene = Scene::new((10, 10));
+        assert!(scene.draw_hook(&game, foreign, (5, 5)).is_none());
+        assert_eq!(scene.items().count(), 0);
+    }
+
+    #[test]
+    fn flag_is_drawn_behind_the_tee() {
+        let game = get_game_sheet();
+        let tee = tee();
+        let mut scene = Scene::new((200, 200));
+        let (flag, tee) = scene.add_tee_with_flag(
+            &tee,
+            TEE_SKIN_LAYOUT,
+            EyeType::Normal,
+            (50, 100),
+            &game,
+            Flag::Red,
+        );
+
+        let order: Vec<_> = scene.items().map(|(id, _)| id).collect();
+        assert_eq!(order, [flag, tee]);
+
+        // 63 pixel body centered at (97, 131)
+        let flag = scene.item(flag).unwrap();
+        assert_eq!(flag.image.dimensions(), (41, 82));
+        assert_eq!(flag.position, (77, 60));
+    }
+
+    #[test]
+    fn tiles_cover_the_scene() {
+        let tee = tee();
+        let mut scene = Scene::new((300, 130)).with_background(Rgba([10, 20, 30, 255]));
+        scene.add_tee(&tee, TEE_SKIN_LAYOUT, EyeType::Normal, (70, 40));
+        scene.add_tee(&tee, TEE_SKIN_LAYOUT, EyeType::Angry, (200, 10));
+
+        let tiles = scene.tiles((128, 100));
+        // 3 columns of 100px and 2 rows of 65px
+        assert_eq!(tiles.len(), 6);
+        assert!(tiles.iter().all(|tile| tile.size() == (100, 65)));
+
+        let whole = scene.render();
+        for (index, tile) in tiles.iter().enumerate() {
+            let (x, y) = ((index % 3) as u32 * 100, (index / 3) as u32 * 65);
+            let part = image::imageops::crop_imm(&whole, x, y, 100, 65).to_image();
+            assert_eq!(tile.render(), part, "tile {index}");
+        }
+
+        assert_eq!(scene.tiles((512, 512)), vec![scene.clone()]);
+        assert_eq!(
+            scene
+                .encode_tiles((128, 100), ImageFormat::Png)
+                .unwrap()
+                .len(),
+            6
+        );
+    }
+
+    #[test]
+    fn updates_redraw_dirty_regions() {
+        let tee = tee();
+        let mut scene = Scene::new((320, 160)).with_background(Rgba([10, 20, 30, 255]));
+        let first = scene.add_tee(&tee, TEE_SKIN_LAYOUT, EyeType::Normal, (0, 0));
+        let second = scene.add_tee(&tee, TEE_SKIN_LAYOUT, EyeType::Angry, (150, 40));
+        let third = scene.add_tee(&tee, TEE_SKIN_LAYOUT, EyeType::Pain, (260, 100));
+
+        let mut canvas = RgbaImage::new(1, 1);
+        let full = scene.update(&mut canvas);
+        assert_eq!(full.len(), 1);
+        assert_eq!(canvas, scene.render());
+        assert!(scene.dirty_regions().is_empty());
+
+        assert!(scene.move_item(first, (40, 60)));
+        assert!(scene.remove_item(third).is_some());
+        assert!(scene.replace_image(second, tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Happy)));
+        scene.add_image(
+            RgbaImage::from_pixel(8, 8, Rgba([255, 0, 0, 255])),
+            (-4, -4),
+        );
+        assert!(!scene.move_item(third, (0, 0)));
+
+        let regions = scene.update(&mut canvas);
+        assert!(!regions.is_empty());
+        assert_eq!(canvas, scene.render());
+        // the third tee was partly outside, so nothing past the canvas is dirty
+        assert!(
+            regions
+                .iter()
+                .all(|r| r.x + r.width <= 320 && r.y + r.height <= 160)
+        );
+        assert!(scene.update(&mut canvas).is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+mod common;
+
+#[cfg(feature = "net")]
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+        time::Duration,
+    };
+
+    use bytes::Bytes;
+    use image::ImageFormat;
+    use tee_morphosis::{
+        cache::DedupStore,
+        db::{SkinDbClient, SkinDbSource},
+        error::TeeError,
+        net::{Fetcher, policy::UrlPolicy},
+        scene::scoreboard::PlayerColors,
+        service::{Fallback, FallbackPolicy, Provenance, RenderRequest, RenderService},
+        tee::{Tee, parts::EyeType, skin::TEE_SKIN_LAYOUT},
+    };
+    use tokio::task::JoinSet;
+
+    use crate::common::skin_path;
+
+    const URL: &str = "https://example.com/default.png";
+
+    fn store() -> DedupStore {
+        let store = DedupStore::new();
+        store
+            .get_or_parse(
+                URL,
+                Bytes::from(fs::read(skin_path()).unwrap()),
+                ImageFormat::Png,
+            )
+            .unwrap();
+        store
+    }
+
+    #[tokio::test]
+    async fn renders_stored_skins() {
+        let store = store();
+        let service = RenderService::new().with_store(store.clone());
+
+        let rendered = service
+            .render(RenderRequest::new(URL).with_eye(EyeType::Happy))
+            .await
+            .unwrap();
+        assert_eq!(rendered.source, Provenance::Skin(URL.to_string()));
+        assert_eq!(rendered.content_type(), "image/png");
+        assert!(rendered.cache_hit);
+        assert_eq!(rendered.timings.fetch, Duration::ZERO);
+        let tee: &Tee = &store.get(URL).unwrap();
+        assert_eq!(
+            rendered.bytes,
+            tee.compose(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png)
+                .unwrap()
+        );
+
+        let colored = service
+            .render(RenderRequest::new(URL).with_colors(PlayerColors {
+                body: 1900500,
+                feet: 65280,
+            }))
+            .await
+            .unwrap();
+        assert_ne!(colored.bytes, rendered.bytes);
+        assert_eq!(service.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_when_queue_is_full() {
+        // accepts connections but never answers, so the first render holds its slot
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/skin.png", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                connections.push(socket);
+            }
+        });
+
+        let local = UrlPolicy::new().with_private_addresses(true);
+        let service = RenderService::new()
+            .with_fetcher(Fetcher::new().with_url_policy(local))
+            .with_store(store())
+            .with_concurrency(1)
+            .with_queue(0);
+        let stalled = tokio::spawn({
+            let service = service.clone();
+            async move { service.render(RenderRequest::new(url)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(service.pending(), 1);
+
+        let result = service.render(RenderRequest::new(URL)).await;
+        assert!(matches!(result, Err(TeeError::ServiceOverloaded)));
+
+        stalled.abort();
+        let _ = stalled.await;
+        assert_eq!(service.pending(), 0);
+        service.render(RenderRequest::new(URL)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn degrades_through_fallbacks() {
+        // nothing listens there, fetching fails right away
+        let url = "http://127.0.0.1:1/skin.png";
+        let source = store();
+        let default_skin = source.get(URL).unwrap();
+        let stored = DedupStore::new();
+        stored
+            .get_or_parse(
+                url,
+                Bytes::from(fs::read(skin_path()).unwrap()),
+                ImageFormat::Png,
+            )
+            .unwrap();
+        let service = RenderService::new()
+            .with_store(stored)
+            .with_fallback(FallbackPolicy::new(default_skin.clone()));
+
+        let good = service
+            .render_or_fallback(RenderRequest::new(url))
+            .await
+            .unwrap();
+        assert_eq!(good.fallback(), None);
+
+        let offline = service.clone().with_store(DedupStore::new());
+        let last_good = offline
+            .render_or_fallback(RenderRequest::new(url))
+            .await
+            .unwrap();
+        assert_eq!(last_good.fallback(), Some(Fallback::LastGood));
+        assert_eq!(last_good.source.name(), "last-good");
+        assert_eq!(last_good.bytes, good.bytes);
+        assert!(last_good.cache_hit);
+
+        let default = offline
+            .render_or_fallback(RenderRequest::new(url).with_eye(EyeType::Happy))
+            .await
+            .unwrap();
+        assert_eq!(default.source, Provenance::Fallback(Fallback::DefaultSkin));
+        assert!(!default.cache_hit);
+        assert_eq!(
+            default.bytes,
+            default_skin
+                .compose(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png)
+                .unwrap()
+        );
+
+        let identicon = offline
+            .clone()
+            .with_fallback(
+                FallbackPolicy::new(default_skin.clone()).with_steps([Fallback::Identicon]),
+            )
+            .render_or_fallback(RenderRequest::new(url))
+            .await
+            .unwrap();
+        assert_eq!(identicon.fallback(), Some(Fallback::Identicon));
+        assert!(!identicon.cache_hit);
+
+        let unprotected = RenderService::new().with_store(DedupStore::new());
+        assert!(
+            unprotected
+                .render_or_fallback(RenderRequest::new(url))
+                .await
+                .is_err()
+        );
+    }
+
+    /// Serves the test skin `count` times, returning the base URL.
+    fn serve_skin(count: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let skin = fs::read(skin_path()).unwrap();
+        thread::spawn(move || {
+            for _ in 0..count {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(&stream);
+                for line in (&mut reader).lines() {
+                    if line.unwrap().is_empty() {
+                        break;
+                    }
+                }
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    skin.len()
+                );
+                stream.write_all(head.as_bytes()).unwrap();
+                stream.write_all(&skin).unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn concurrent_misses_share_one_fetch() {
+        // The server answers a single request, a second fetch would fail
+        let url = format!("{}/skin.png", serve_skin(1));
+        let fetcher = Fetcher::new().with_url_policy(UrlPolicy::new().with_private_addresses(true));
+        let service = RenderService::new()
+            .with_fetcher(fetcher)
+            .with_store(store())
+            .with_concurrency(8);
+
+        let mut renders = JoinSet::new();
+        for _ in 0..8 {
+            let service = service.clone();
+            let url = url.clone();
+            renders.spawn(async move { service.render(RenderRequest::new(url)).await });
+        }
+        let mut fetched = 0;
+        while let Some(rendered) = renders.join_next().await {
+            fetched += usize::from(!rendered.unwrap().unwrap().cache_hit);
+        }
+        assert_eq!(fetched, 1);
+    }
+
+    #[tokio::test]
+    async fn warms_up_names_and_urls() {
+        let base = serve_skin(2);
+        let fetcher = Fetcher::new().with_url_policy(UrlPolicy::new().with_private_addresses(true));
+        let skin_db = SkinDbClient::with_source(SkinDbSource::from_asset_url(format!(
+            "{base}/skins/{{name}}.png"
+        )))
+        .with_fetcher(fetcher.clone());
+        let store = store();
+        let service = RenderService::new()
+            .with_fetcher(fetcher)
+            .with_store(store.clone())
+            .with_skin_db(skin_db)
+            .with_concurrency(2);
+
+        let direct = format!("{base}/direct.png");
+        let report = service
+            .warmup([
+                URL,
+                direct.as_str(),
+                "santa default.png",
+                " \"/ ",
+                "http://127.0.0.1:1/x.png",
+            ])
+            .await;
+        assert_eq!(report.loaded, 2);
+        assert_eq!(report.stored, 1);
+        let mut failed: Vec<_> = report
+            .failed
+            .iter()
+            .map(|(skin, _)| skin.as_str())
+            .collect();
+        failed.sort();
+        assert_eq!(failed, [" \"/ ", "http://127.0.0.1:1/x.png"]);
+
+        let named = service.skin_url("santa default").unwrap();
+        assert_eq!(named, format!("{base}/skins/santa%20default.png"));
+        assert!(store.get(&named).is_some());
+        assert!(store.get(&direct).is_some());
+        // both are stored now, rendering them does not fetch again
+        for url in [named, direct] {
+            let rendered = service.render(RenderRequest::new(url)).await.unwrap();
+            assert!(rendered.cache_hit);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::ImageFormat;
+    use tee_morphosis::{
+        error::TeeError,
+        sheet::{Sheet, SheetLayout},
+        tee::{
+            Tee,
+            options::ParseOptions,
+            uv::{TEE_UV_LAYOUT, UV, UvPart},
+        },
+    };
+
+    use crate::common::skin_bytes;
+
+    #[test]
+    fn uv_sheet_matches_tee() {
+        let sheet = Sheet::new(
+            skin_bytes(),
+            SheetLayout::from(TEE_UV_LAYOUT),
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let parts = sheet.extract_all().unwrap();
+        assert_eq!(parts.len(), UV::PART_NAMES.len());
+
+        let tee = Tee::new(skin_bytes(), ImageFormat::Png).unwrap();
+        assert_eq!(parts["body"], tee.body.value);
+        assert_eq!(parts["feet_shadow"], tee.feet.shadow);
+    }
+
+    #[test]
+    fn custom_layout() {
+        let layout = SheetLayout::new((256, 128))
+            .with_part("left", UvPart::new(0, 0, (128, 128)))
+            .with_part("right", UvPart::new(128, 0, (128, 128)));
+        let sheet = Sheet::new(skin_bytes(), layout, &ParseOptions::default()).unwrap();
+
+        assert_eq!(sheet.extract("left").unwrap().dimensions(), (128, 128));
+        assert!(matches!(
+            sheet.extract("middle"),
+            Err(TeeError::UnknownSheetPart(name)) if name == "middle"
+        ));
+    }
+
+    #[test]
+    fn layout_size_is_checked() {
+        let result = Sheet::new(
+            skin_bytes(),
+            SheetLayout::from(TEE_UV_LAYOUT).scaled(2.0),
+            &ParseOptions::default(),
+        );
+        assert!(matches!(
+            result,
+            Err(TeeError::InvalidDimensions {
+                expected: (512, 256),
+                found: (256, 128)
+            })
+        ));
+    }
+}
@@ -0,0 +1,50 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{GenericImageView, ImageFormat};
+    use tee_morphosis::tee::{parts::EyeType, skin::TEE_SKIN_LAYOUT};
+
+    use crate::common::tee;
+
+    #[test]
+    fn every_size_keeps_aspect() {
+        let sizes = [32, 64, 96, 128, 256];
+        let outputs = tee()
+            .compose_sizes(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png, &sizes)
+            .unwrap();
+        assert_eq!(outputs.len(), sizes.len());
+        for size in sizes {
+            let image = image::load_from_memory(&outputs[&size]).unwrap();
+            assert_eq!(image.dimensions(), (size, size * 2 / 3), "{size}");
+        }
+        assert!(
+            tee()
+                .compose_sizes(TEE_SKIN_LAYOUT, EyeType::Happy, ImageFormat::Png, &[])
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn scaled_layout() {
+        let skin = TEE_SKIN_LAYOUT.scaled(2.0);
+        assert_eq!(skin.container, (96, 64));
+        assert_eq!(skin.output_size(), (192, 128));
+        assert_eq!(skin.place(skin.body, (96, 96)), ((32, 0), (126, 126)));
+        assert_eq!(skin.place(skin.first_eyes, (32, 32)), ((78, 36), (51, 51)));
+    }
+
+    #[test]
+    fn scaling_does_not_drift() {
+        let direct = TEE_SKIN_LAYOUT.scaled(4.0);
+        let chained = TEE_SKIN_LAYOUT.scaled(0.5).scaled(8.0);
+        for part in [direct.first_eyes, direct.second_eyes, direct.body] {
+            assert_eq!(direct.place(part, (32, 32)), chained.place(part, (32, 32)));
+        }
+        assert_eq!(direct.place(direct.first_eyes, (32, 32)).0, (156, 72));
+
+        let image = tee().compose_image(chained, EyeType::Happy);
+        assert_eq!(image, tee().compose_image(direct, EyeType::Happy));
+    }
+}
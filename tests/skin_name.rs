@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::skin_name::{MAX_LENGTH, encode, is_valid, normalize};
+
+    #[test]
+    fn valid_names_stay_unchanged() {
+        for name in ["default", "santa_limekitty", "Nameless Tee", "кот"] {
+            assert!(is_valid(name), "{name}");
+            assert_eq!(normalize(name).as_deref(), Some(name));
+        }
+    }
+
+    #[test]
+    fn normalize_applies_the_game_rules() {
+        assert_eq!(normalize(" x_ninja.PNG\n").as_deref(), Some("x_ninja"));
+        assert_eq!(normalize("a/b\\c\"d").as_deref(), Some("abcd"));
+        assert_eq!(normalize("\t/\"\u{7}"), None);
+        assert_eq!(normalize(".png"), None);
+
+        // Relative path segments would leave the skin directory of database URLs
+        for dots in [".", "..", "...png", " .. "] {
+            assert_eq!(normalize(dots), None, "{dots}");
+        }
+        assert!(!is_valid(".."));
+        assert_eq!(normalize("..x").as_deref(), Some("..x"));
+
+        let long = "ä".repeat(20);
+        let cut = normalize(&long).unwrap();
+        assert!(cut.len() <= MAX_LENGTH);
+        assert_eq!(cut, "ä".repeat(MAX_LENGTH / 2));
+        assert!(!is_valid(&long));
+    }
+
+    #[test]
+    fn encode_escapes_reserved_bytes() {
+        assert_eq!(encode("nameless tee"), "nameless%20tee");
+        assert_eq!(encode("a+b?c#d"), "a%2Bb%3Fc%23d");
+        assert_eq!(encode("ä"), "%C3%A4");
+        assert_eq!(encode("x_ninja-1.0~"), "x_ninja-1.0~");
+    }
+}
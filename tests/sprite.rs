@@ -0,0 +1,51 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::tee::parts::EyeType;
+
+    use crate::common::tee;
+
+    #[test]
+    fn sheet_holds_every_part() {
+        let mut tee = tee();
+        let wink = tee.get_eye(EyeType::Happy).clone();
+        tee.add_custom_eye("big wink", wink).unwrap();
+
+        let sprite = tee.to_css_sprite("my tee").unwrap();
+        assert_eq!(sprite.prefix, "my-tee");
+        // 6 parts, 7 eyes, 1 custom eye
+        assert_eq!(sprite.parts.len(), 14);
+
+        let sheet = image::load_from_memory(&sprite.image).unwrap().to_rgba8();
+        assert_eq!(sheet.dimensions(), sprite.size);
+        for part in &sprite.parts {
+            assert!(part.x + part.width <= sprite.size.0 && part.y + part.height <= sprite.size.1);
+            for other in &sprite.parts {
+                let overlaps = part.name != other.name
+                    && part.x < other.x + other.width
+                    && other.x < part.x + part.width
+                    && part.y < other.y + other.height
+                    && other.y < part.y + part.height;
+                assert!(!overlaps, "{} overlaps {}", part.name, other.name);
+            }
+        }
+
+        let body = sprite.parts.iter().find(|p| p.name == "body").unwrap();
+        let packed = image::imageops::crop_imm(&sheet, body.x, body.y, body.width, body.height);
+        assert_eq!(packed.to_image(), tee.body.value);
+
+        let happy = sprite.parts.iter().find(|p| p.name == "eye-happy").unwrap();
+        assert!(sprite.css.contains(&format!(
+            ".my-tee-eye-happy {{ width: {}px; height: {}px; background-position: -{}px -{}px; }}",
+            happy.width, happy.height, happy.x, happy.y
+        )));
+        assert!(sprite.css.contains(".my-tee-eye-custom-big-wink {"));
+        assert!(
+            sprite
+                .css
+                .starts_with(".my-tee { background-image: url(\"my-tee.png\");")
+        );
+        assert!(sprite.css("/cdn/t.png").contains("url(\"/cdn/t.png\")"));
+    }
+}
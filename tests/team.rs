@@ -0,0 +1,28 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::tee::{hsl::ddnet_color_to_hsl, team::TeamColor};
+
+    use crate::common::tee;
+
+    #[test]
+    fn team_hues() {
+        let (red_hue, red_saturation, _) = TeamColor::Red.to_hsl();
+        assert_eq!((red_hue, red_saturation), (0.0, 1.0));
+
+        let (blue_hue, _, _) = TeamColor::Blue.to_hsl();
+        assert!((0.55..0.7).contains(&blue_hue));
+    }
+
+    #[test]
+    fn team_color_matches_packed_color() {
+        let mut team = tee();
+        team.apply_team_color(TeamColor::Blue);
+
+        let mut custom = tee();
+        custom.apply_hsl_to_all(ddnet_color_to_hsl(10223541));
+        assert_eq!(team, custom);
+        assert_ne!(team.body, tee().body);
+    }
+}
@@ -0,0 +1,58 @@
+mod common;
+
+#[cfg(feature = "testing")]
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::{
+        tee::{
+            skin::TEE_SKIN_LAYOUT,
+            uv::{TEE_UV_LAYOUT, builder::UvBuilder},
+        },
+        testing::{
+            assert_compose_within_bounds, assert_uv_roundtrip, proptest::prelude::*, strategy,
+        },
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn default_layouts_hold() {
+        let tee = tee();
+        assert_uv_roundtrip(&tee, TEE_UV_LAYOUT);
+        assert_compose_within_bounds(&tee, TEE_SKIN_LAYOUT);
+        assert_compose_within_bounds(&tee, TEE_SKIN_LAYOUT.scaled(3.));
+    }
+
+    #[test]
+    #[should_panic(expected = "part body")]
+    fn part_past_the_canvas_panics() {
+        let mut skin = TEE_SKIN_LAYOUT;
+        skin.body.0.0 = 60.;
+        assert_compose_within_bounds(&tee(), skin);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn generated_uvs_roundtrip(uv in strategy::uv()) {
+            let validated = UvBuilder::new()
+                .container(uv.container.0, uv.container.1)
+                .body_at(uv.body.x, uv.body.y)
+                .body_shadow_at(uv.body_shadow.x, uv.body_shadow.y)
+                .feet_at(uv.feet.x, uv.feet.y)
+                .feet_shadow_at(uv.feet_shadow.x, uv.feet_shadow.y)
+                .hand_at(uv.hand.x, uv.hand.y)
+                .hand_shadow_at(uv.hand_shadow.x, uv.hand_shadow.y)
+                .eyes_row(uv.eyes[0].x, uv.eyes[0].y)
+                .validate();
+            prop_assert_eq!(validated.unwrap(), uv);
+            assert_uv_roundtrip(&tee(), uv);
+        }
+
+        #[test]
+        fn generated_skins_stay_on_the_canvas(skin in strategy::skin()) {
+            assert_compose_within_bounds(&tee(), skin);
+        }
+    }
+}
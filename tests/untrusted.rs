@@ -0,0 +1,72 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::Bytes;
+    use image::{ImageError, ImageFormat, RgbaImage};
+    use tee_morphosis::{
+        error::TeeError,
+        tee::{Tee, limits::DecodeLimits, options::ParseOptions, uv::TEE_UV_LAYOUT},
+    };
+
+    use crate::common::skin_bytes;
+
+    fn png_of_size(
+        width: u32,
+        height: u32,
+    ) -> Bytes {
+        let mut buf = Vec::new();
+        RgbaImage::new(width, height)
+            .write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)
+            .unwrap();
+        Bytes::from(buf)
+    }
+
+    #[test]
+    fn untrusted_accepts_regular_skin() {
+        let data = skin_bytes();
+
+        let trusted = Tee::new(data.clone(), ImageFormat::Png).unwrap();
+        let untrusted = Tee::new_untrusted(data, DecodeLimits::UNTRUSTED).unwrap();
+        assert_eq!(trusted, untrusted);
+    }
+
+    #[test]
+    fn untrusted_rejects_oversized_image() {
+        let result = Tee::new_untrusted(png_of_size(4096, 16), DecodeLimits::UNTRUSTED);
+        assert!(matches!(
+            result,
+            Err(TeeError::Image(ImageError::Limits(_)))
+        ));
+    }
+
+    #[test]
+    fn parse_options_limits_apply_to_trusted_parse() {
+        let options = ParseOptions::new().with_limits(DecodeLimits {
+            max_width: 64,
+            max_height: 64,
+            max_alloc: 1024 * 1024,
+        });
+        let result = Tee::new_with_options(
+            png_of_size(256, 128),
+            TEE_UV_LAYOUT,
+            ImageFormat::Png,
+            options,
+        );
+        assert!(matches!(
+            result,
+            Err(TeeError::Image(ImageError::Limits(_)))
+        ));
+    }
+
+    #[test]
+    fn default_limits_reject_huge_image() {
+        let result = Tee::new(png_of_size(8192, 16), ImageFormat::Png);
+        assert!(matches!(
+            result,
+            Err(TeeError::Image(ImageError::Limits(_)))
+        ));
+    }
+}
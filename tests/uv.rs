@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::{
+        error::TeeError,
+        tee::uv::{
+            BODY_SIZE, TEE_UV_LAYOUT, UvPart,
+            builder::{UvBuilder, format_preset, parse_preset},
+        },
+    };
+
+    #[test]
+    fn part_edges() {
+        let body = TEE_UV_LAYOUT.body;
+        assert_eq!(body, UvPart::new(0, 0, BODY_SIZE));
+        assert_eq!((body.right(), body.bottom()), (96, 96));
+        assert_eq!(body.size(), BODY_SIZE);
+    }
+
+    #[test]
+    fn part_contains_and_overlaps() {
+        let container = UvPart::new(0, 0, TEE_UV_LAYOUT.container);
+        assert!(container.contains(&TEE_UV_LAYOUT.body));
+        assert!(container.contains(&TEE_UV_LAYOUT.eyes[5]));
+        assert!(!TEE_UV_LAYOUT.body.contains(&container));
+
+        assert!(!TEE_UV_LAYOUT.body.overlaps(&TEE_UV_LAYOUT.body_shadow));
+        assert!(!TEE_UV_LAYOUT.body.overlaps(&TEE_UV_LAYOUT.eyes[0]));
+        assert!(TEE_UV_LAYOUT.hand.overlaps(&UvPart::new(220, 20, (16, 16))));
+    }
+
+    #[test]
+    fn part_translated_and_scaled() {
+        let part = UvPart::new(10, 20, (32, 32));
+        assert_eq!(part.translated(5, -30), UvPart::new(15, 0, (32, 32)));
+        assert_eq!(part.scaled(2.0), UvPart::new(20, 40, (64, 64)));
+    }
+
+    #[test]
+    fn part_edges_saturate() {
+        let far = UvPart::new(10, 20, (32, 32)).translated(i64::MAX, i64::MAX);
+        assert_eq!((far.right(), far.bottom()), (u32::MAX, u32::MAX));
+        let huge = UvPart::new(1, 1, (u32::MAX, u32::MAX));
+        // Both reach the end of the coordinate space
+        assert!(huge.contains(&far));
+        assert!(!TEE_UV_LAYOUT.body.overlaps(&far));
+    }
+
+    fn default_builder() -> UvBuilder {
+        UvBuilder::new()
+            .body_at(0, 0)
+            .hand_at(192, 0)
+            .feet_at(192, 32)
+            .eyes_row(64, 96)
+    }
+
+    #[test]
+    fn builder_matches_default_layout() {
+        assert_eq!(default_builder().validate().unwrap(), TEE_UV_LAYOUT);
+    }
+
+    #[test]
+    fn builder_rejects_missing_part() {
+        let result = UvBuilder::new().body_at(0, 0).validate();
+        assert!(matches!(result, Err(TeeError::MissingUvPart("feet"))));
+    }
+
+    #[test]
+    fn builder_rejects_overlap_and_out_of_bounds() {
+        let result = default_builder().hand_shadow_at(200, 40).validate();
+        assert!(matches!(
+            result,
+            Err(TeeError::OverlappingUvParts {
+                first: "feet",
+                second: "hand_shadow"
+            })
+        ));
+
+        let result = default_builder().container(128, 128).validate();
+        assert!(matches!(result, Err(TeeError::OutOfBounds { .. })));
+    }
+
+    #[test]
+    fn presets_round_trip() {
+        let preset = format_preset(&TEE_UV_LAYOUT);
+        assert_eq!(parse_preset(&preset).unwrap(), TEE_UV_LAYOUT);
+
+        let commented = format!(
+            "# default sheet\n\n{}",
+            preset.replace("container 256 128\n", "")
+        );
+        assert_eq!(parse_preset(&commented).unwrap(), TEE_UV_LAYOUT);
+
+        let missing_eye = preset.replace("eye_pain", "# eye_pain");
+        assert!(matches!(
+            parse_preset(&missing_eye),
+            Err(TeeError::MissingUvPart("eye_pain"))
+        ));
+        assert!(matches!(
+            parse_preset("container 256 128\nbody 0 0 96"),
+            Err(TeeError::MalformedUvPreset {
+                line: 2,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_preset("tail 0 0 96 96"),
+            Err(TeeError::MalformedUvPreset {
+                line: 1,
+                reason: "unknown part"
+            })
+        ));
+    }
+}
@@ -0,0 +1,63 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::imageops;
+    use tee_morphosis::tee::{hsl::ddnet_color_to_hsl, parts::EyeType, variation::VariationParams};
+
+    use crate::common::tee;
+
+    #[test]
+    fn hue_family() {
+        // the fixture is gray, give it a hue to shift
+        let mut tee = tee();
+        tee.apply_hsl_to_all(ddnet_color_to_hsl(65461));
+        let variants = tee.variations(4, VariationParams::new());
+        assert_eq!(variants.len(), 4);
+
+        // no shift for the first one, up to rounding
+        let diff = variants[0]
+            .body
+            .value
+            .pixels()
+            .zip(tee.body.value.pixels())
+            .flat_map(|(a, b)| a.0.iter().zip(b.0).map(|(a, b)| a.abs_diff(b)))
+            .max()
+            .unwrap();
+        assert!(diff <= 1, "{diff}");
+
+        for (i, a) in variants.iter().enumerate() {
+            assert_eq!(a.get_eye(EyeType::Happy), tee.get_eye(EyeType::Happy));
+            for b in &variants[i + 1..] {
+                assert_ne!(a.body.value, b.body.value);
+            }
+        }
+        assert!(tee.variations(0, VariationParams::new()).is_empty());
+    }
+
+    #[test]
+    fn mirror_and_seed() {
+        let tee = tee();
+        let params = VariationParams::new()
+            .with_hue_spread(0.0)
+            .with_mirror(true)
+            .with_jitter(0.1, 0.1)
+            .with_seed(3);
+        let variants = tee.variations(2, params);
+        assert_eq!(
+            variants[1].get_eye(EyeType::Angry),
+            &imageops::flip_horizontal(tee.get_eye(EyeType::Angry))
+        );
+        assert_eq!(
+            variants[0].get_eye(EyeType::Angry),
+            tee.get_eye(EyeType::Angry)
+        );
+
+        let again = tee.variations(2, params);
+        assert_eq!(variants[1].etag(), again[1].etag());
+        assert_ne!(
+            variants[0].etag(),
+            tee.variations(2, params.with_seed(4))[0].etag()
+        );
+    }
+}
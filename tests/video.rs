@@ -0,0 +1,69 @@
+#[cfg(test)]
+#[cfg(feature = "ffmpeg")]
+mod tests {
+    use std::process::Command;
+
+    use image::{Rgba, RgbaImage};
+    use tee_morphosis::{
+        animation::video::{VideoCodec, VideoOptions, encode_video},
+        error::TeeError,
+    };
+
+    fn frames() -> Vec<RgbaImage> {
+        (0..10u8)
+            .map(|i| RgbaImage::from_pixel(33, 17, Rgba([i * 25, 100, 200, 255])))
+            .collect()
+    }
+
+    fn has_ffmpeg() -> bool {
+        Command::new("ffmpeg").arg("-version").output().is_ok()
+    }
+
+    #[test]
+    fn invalid_frames() {
+        let options = VideoOptions::new(VideoCodec::WebM);
+        assert!(matches!(
+            encode_video(&[], &options),
+            Err(TeeError::EmptyAnimation)
+        ));
+
+        let mut frames = frames();
+        frames.push(RgbaImage::new(4, 4));
+        assert!(matches!(
+            encode_video(&frames, &options),
+            Err(TeeError::InvalidDimensions {
+                expected: (33, 17),
+                found: (4, 4)
+            })
+        ));
+    }
+
+    #[test]
+    fn missing_ffmpeg() {
+        let options = VideoOptions::new(VideoCodec::Mp4).with_ffmpeg("/nonexistent/ffmpeg");
+        assert!(matches!(
+            encode_video(&frames(), &options),
+            Err(TeeError::Process(_))
+        ));
+    }
+
+    #[test]
+    fn encodes_webm_and_mp4() {
+        if !has_ffmpeg() {
+            eprintln!("ffmpeg is not installed, skipping");
+            return;
+        }
+        let webm = encode_video(
+            &frames(),
+            &VideoOptions::new(VideoCodec::WebM)
+                .with_frame_rate(10)
+                .with_bitrate(200),
+        )
+        .unwrap();
+        // EBML header
+        assert_eq!(&webm[..4], &[0x1A, 0x45, 0xDF, 0xA3]);
+
+        let mp4 = encode_video(&frames(), &VideoOptions::new(VideoCodec::Mp4)).unwrap();
+        assert_eq!(&mp4[4..8], b"ftyp");
+    }
+}
@@ -0,0 +1,146 @@
+mod common;
+
+#[cfg(feature = "watch")]
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::PathBuf,
+        sync::{Arc, mpsc},
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    use image::ImageFormat;
+    use tee_morphosis::{
+        error::TeeError,
+        tee::{
+            raw::encode_image,
+            uv::{TEE_UV_LAYOUT, builder::format_preset},
+        },
+        watch::{AssetKind, AssetStore, AssetWatcher, ChangeKind},
+    };
+
+    use crate::common::skin_path;
+
+    /// Creates an empty directory unique to a test.
+    fn asset_dir(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("tee_watch_{name}_{nanos}"));
+        fs::create_dir_all(dir.join("skins")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn poll_reports_changes_by_kind() {
+        let dir = asset_dir("poll");
+        fs::write(dir.join("skins/default.png"), b"v1").unwrap();
+        let mut watcher = AssetWatcher::new(&dir).unwrap();
+        assert!(watcher.poll().is_empty());
+
+        fs::create_dir_all(dir.join("fonts")).unwrap();
+        fs::write(dir.join("fonts/mono.ttf"), b"font").unwrap();
+        fs::write(dir.join("skins/default.png"), b"v2 is longer").unwrap();
+        fs::write(dir.join("readme.txt"), b"ignored").unwrap();
+        let mut changes = watcher.poll();
+        changes.sort_by_key(|change| change.path.clone());
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].kind, AssetKind::Font);
+        assert_eq!(changes[0].change, ChangeKind::Added);
+        assert_eq!(changes[1].name(), "default");
+        assert_eq!(changes[1].change, ChangeKind::Modified);
+        assert_eq!(changes[1].data.as_deref(), Some(&b"v2 is longer"[..]));
+        assert!(watcher.poll().is_empty());
+
+        fs::remove_file(dir.join("skins/default.png")).unwrap();
+        let changes = watcher.poll();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].change, ChangeKind::Removed);
+        assert_eq!(changes[0].data, None);
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(AssetWatcher::new(&dir), Err(TeeError::Watch(_))));
+    }
+
+    #[test]
+    fn store_swaps_reparsed_assets() {
+        let dir = asset_dir("store");
+        fs::copy(skin_path(), dir.join("skins/default.png")).unwrap();
+        fs::create_dir_all(dir.join("uv")).unwrap();
+        fs::write(dir.join("uv/default.txt"), format_preset(&TEE_UV_LAYOUT)).unwrap();
+        let mut watcher = AssetWatcher::new(&dir).unwrap();
+        let store = AssetStore::new();
+        store.apply(&watcher.snapshot());
+        let first = store.skin("default").unwrap();
+        assert_eq!(store.uv_preset("default"), Some(TEE_UV_LAYOUT));
+
+        // A broken file keeps the previous skin
+        fs::write(dir.join("skins/default.png"), b"not a skin").unwrap();
+        store.apply(&watcher.poll());
+        assert!(Arc::ptr_eq(&store.skin("default").unwrap(), &first));
+
+        let mut sheet = image::open(skin_path()).unwrap().to_rgba8();
+        sheet.get_pixel_mut(40, 40).0 = [255, 0, 0, 255];
+        let edited = encode_image(&sheet, ImageFormat::Png).unwrap();
+        fs::write(dir.join("skins/default.png"), &edited).unwrap();
+        let mut wide = TEE_UV_LAYOUT;
+        wide.container = (1024, 128);
+        fs::write(dir.join("uv/default.txt"), format_preset(&wide)).unwrap();
+        store.apply(&watcher.poll());
+        let second = store.skin("default").unwrap();
+        assert!(!Arc::ptr_eq(&second, &first));
+        assert_ne!(second.body, first.body);
+        assert_eq!(store.uv_preset("default"), Some(wide));
+
+        fs::remove_file(dir.join("skins/default.png")).unwrap();
+        store.apply(&watcher.poll());
+        assert!(store.skin("default").is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn store_swaps_reparsed_templates() {
+        let template = |width: u32| {
+            format!(
+                r##"{{ "size": [{width}, 100], "background": {{ "color": "#102030" }}, "elements": [] }}"##
+            )
+        };
+        let dir = asset_dir("templates");
+        fs::create_dir_all(dir.join("templates")).unwrap();
+        fs::write(dir.join("templates/rank.json"), template(200)).unwrap();
+        let mut watcher = AssetWatcher::new(&dir).unwrap();
+        let store = AssetStore::new();
+        store.apply(&watcher.snapshot());
+        assert_eq!(store.template("rank").unwrap().size, (200, 100));
+
+        fs::write(dir.join("templates/rank.json"), "{ broken").unwrap();
+        store.apply(&watcher.poll());
+        assert_eq!(store.template("rank").unwrap().size, (200, 100));
+
+        fs::write(dir.join("templates/rank.json"), template(1280)).unwrap();
+        store.apply(&watcher.poll());
+        assert_eq!(store.template("rank").unwrap().size, (1280, 100));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn spawned_watcher_calls_back() {
+        let dir = asset_dir("spawn");
+        let (sender, receiver) = mpsc::channel();
+        let handle =
+            AssetWatcher::new(&dir)
+                .unwrap()
+                .spawn(Duration::from_millis(10), move |changes| {
+                    sender.send(changes.to_vec()).unwrap();
+                });
+
+        fs::write(dir.join("skins/santa.png"), b"skin").unwrap();
+        let changes = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(changes[0].name(), "santa");
+        drop(handle);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
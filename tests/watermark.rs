@@ -0,0 +1,56 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+    use tee_morphosis::{
+        tee::{options::ComposeOptions, parts::EyeType, skin::TEE_SKIN_LAYOUT},
+        watermark::{Anchor, Watermark},
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn watermark_anchors() {
+        let mark = RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255]));
+        let background = Rgba([0, 0, 0, 255]);
+
+        let mut canvas = RgbaImage::from_pixel(10, 10, background);
+        Watermark::image(mark.clone()).apply(&mut canvas);
+        assert_eq!(canvas.get_pixel(7, 7), &Rgba([255, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(8, 8), &background);
+
+        let mut canvas = RgbaImage::from_pixel(10, 10, background);
+        Watermark::image(mark)
+            .with_anchor(Anchor::TopLeft)
+            .with_margin(0)
+            .with_opacity(0.5)
+            .apply(&mut canvas);
+        let pixel = canvas.get_pixel(0, 0);
+        assert!((120..=135).contains(&pixel.0[0]));
+        assert_eq!(canvas.get_pixel(2, 2), &background);
+    }
+
+    #[test]
+    fn compose_with_watermark() {
+        let tee = tee();
+        let mark = RgbaImage::from_pixel(8, 8, Rgba([0, 255, 0, 255]));
+        let options = ComposeOptions::new().with_watermark(Watermark::image(mark));
+
+        let marked = tee.compose_image_with_options(TEE_SKIN_LAYOUT, EyeType::Normal, &options);
+        let plain = tee.compose_image(TEE_SKIN_LAYOUT, EyeType::Normal);
+        assert_ne!(marked, plain);
+        let (width, height) = marked.dimensions();
+        assert_eq!(
+            marked.get_pixel(width - 3, height - 3),
+            &Rgba([0, 255, 0, 255])
+        );
+
+        let unmarked = tee.compose_image_with_options(
+            TEE_SKIN_LAYOUT,
+            EyeType::Normal,
+            &ComposeOptions::new(),
+        );
+        assert_eq!(unmarked, plain);
+    }
+}
@@ -0,0 +1,136 @@
+mod common;
+
+#[cfg(feature = "web")]
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use tee_morphosis::{
+        error::TeeError,
+        tee::{Tee, limits::DecodeLimits},
+        web::{SkinUpload, parse_multipart},
+    };
+
+    use crate::common::skin_bytes;
+
+    const CONTENT_TYPE: &str = "multipart/form-data; boundary=\"XyZ\"";
+
+    /// Builds a body with the boundary of [CONTENT_TYPE] from `(name, headers, data)`.
+    fn body(fields: &[(&str, &str, &[u8])]) -> Bytes {
+        let mut body = b"preamble\r\n".to_vec();
+        for (name, headers, data) in fields {
+            body.extend_from_slice(
+                format!(
+                    "--XyZ\r\nContent-Disposition: form-data; name=\"{name}\"{headers}\r\n\r\n"
+                )
+                .as_bytes(),
+            );
+            body.extend_from_slice(data);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(b"--XyZ--\r\n");
+        Bytes::from(body)
+    }
+
+    #[test]
+    fn parses_fields() {
+        let fields = parse_multipart(
+            CONTENT_TYPE,
+            body(&[
+                ("title", "", b"santa"),
+                (
+                    "skin",
+                    "; filename=\"santa.png\"\r\nCONTENT-TYPE: Image/PNG",
+                    b"\x89PNG\r\n--XY",
+                ),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].name, "title");
+        assert_eq!(fields[0].filename, None);
+        assert_eq!(fields[0].data, &b"santa"[..]);
+        assert_eq!(fields[1].filename.as_deref(), Some("santa.png"));
+        assert_eq!(fields[1].content_type.as_deref(), Some("image/png"));
+        assert_eq!(fields[1].data, &b"\x89PNG\r\n--XY"[..]);
+    }
+
+    #[test]
+    fn rejects_malformed_bodies() {
+        let malformed = [
+            ("application/json", body(&[])),
+            ("multipart/form-data", body(&[])),
+            (CONTENT_TYPE, Bytes::from_static(b"no boundary here")),
+            (
+                CONTENT_TYPE,
+                Bytes::from_static(
+                    b"--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nabc",
+                ),
+            ),
+            (
+                CONTENT_TYPE,
+                Bytes::from_static(b"--XyZ\r\nContent-Type: text/plain\r\n\r\nabc\r\n--XyZ--"),
+            ),
+        ];
+        for (content_type, body) in malformed {
+            assert!(
+                matches!(
+                    parse_multipart(content_type, body.clone()),
+                    Err(TeeError::MalformedUpload(_))
+                ),
+                "{content_type} {body:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn reads_skin() {
+        let data = skin_bytes().to_vec();
+        let expected = Tee::new(Bytes::from(data.clone()), image::ImageFormat::Png).unwrap();
+        for headers in ["; filename=\"a.png\"\r\nContent-Type: image/png", ""] {
+            let tee = SkinUpload::new()
+                .read(CONTENT_TYPE, body(&[("skin", headers, &data)]))
+                .unwrap();
+            assert!(tee.body.value == expected.body.value);
+        }
+
+        let tee = SkinUpload::new()
+            .with_field("file")
+            .read(
+                CONTENT_TYPE,
+                body(&[("skin", "", b"x"), ("file", "", &data)]),
+            )
+            .unwrap();
+        assert!(tee.body.value == expected.body.value);
+    }
+
+    #[test]
+    fn enforces_limits() {
+        let data = skin_bytes().to_vec();
+        let upload = body(&[("skin", "", &data)]);
+
+        let err = SkinUpload::new()
+            .with_max_body_size(data.len())
+            .read(CONTENT_TYPE, upload.clone())
+            .unwrap_err();
+        assert!(
+            matches!(err, TeeError::UploadTooLarge { size, max } if size == upload.len() && max == data.len())
+        );
+        assert!(err.suggestion().is_some());
+
+        let limits = DecodeLimits {
+            max_width: 64,
+            ..DecodeLimits::UNTRUSTED
+        };
+        assert!(
+            SkinUpload::new()
+                .with_limits(limits)
+                .read(CONTENT_TYPE, upload.clone())
+                .is_err()
+        );
+
+        assert!(matches!(
+            SkinUpload::new().read(CONTENT_TYPE, body(&[("other", "", &data)])),
+            Err(TeeError::MissingUploadField(name)) if name == "skin"
+        ));
+    }
+}
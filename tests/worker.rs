@@ -0,0 +1,90 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    use bytes::Bytes;
+    use image::{GenericImageView, ImageFormat};
+    use tee_morphosis::{
+        error::TeeError, meta::RenderRecipe, tee::skin::TEE_SKIN_LAYOUT, worker::WorkerPool,
+    };
+
+    use crate::common::tee;
+
+    #[test]
+    fn renders_from_many_producers() {
+        let mut tees = HashMap::new();
+        tees.insert("default".to_string(), Arc::new(tee()));
+        let pool =
+            WorkerPool::with_resolver(3, 2, TEE_SKIN_LAYOUT, move |skin| tees.get(skin).cloned());
+
+        let producers: Vec<_> = (0..3)
+            .map(|producer| {
+                let queue = pool.queue();
+                thread::spawn(move || {
+                    for index in 0..4u32 {
+                        let skin = if producer == 2 && index == 0 { "missing" } else { "default" };
+                        let recipe = RenderRecipe::new(skin, "happy", (48 + index * 8, 32))
+                            .with_format(ImageFormat::Png);
+                        queue.submit(recipe).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let results: Vec<_> = pool.results().iter().take(12).collect();
+        assert_eq!(results.len(), 12);
+        for result in results {
+            if result.recipe.skin == "missing" {
+                assert!(matches!(result.result, Err(TeeError::UnknownSkin(_))));
+                continue;
+            }
+            let image = image::load_from_memory(&result.result.unwrap()).unwrap();
+            assert_eq!(image.dimensions(), result.recipe.size);
+        }
+        assert!(pool.finish().is_empty());
+    }
+
+    #[test]
+    fn full_queue_applies_backpressure() {
+        let release = Arc::new(AtomicBool::new(false));
+        let gate = release.clone();
+        let pool = WorkerPool::new(1, 1, move |_| {
+            while !gate.load(Ordering::Acquire) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            Ok(Bytes::new())
+        });
+
+        let queue = pool.queue();
+        let mut accepted = 0;
+        while queue
+            .try_submit(RenderRecipe::new("default", "normal", (96, 64)))
+            .is_ok()
+        {
+            accepted += 1;
+            assert!(
+                accepted <= 2,
+                "the queue holds one recipe and the worker one"
+            );
+        }
+        drop(queue);
+
+        release.store(true, Ordering::Release);
+        let results = pool.finish();
+        assert_eq!(results.len(), accepted);
+        assert!(results.iter().all(|result| result.result.is_ok()));
+    }
+}